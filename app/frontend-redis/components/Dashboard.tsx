@@ -600,7 +600,7 @@ const Dashboard: React.FC<DashboardProps> = ({ user, token, onLogout }) => {
     if (!orgToEdit) return;
     setLoading('edit-org');
     try {
-        await updateOrganization(token, orgToEdit.id, { name: orgToEdit.name, description: orgToEdit.description, slug: orgToEdit.slug });
+        await updateOrganization(token, orgToEdit.id, orgToEdit.updated_at, { name: orgToEdit.name, description: orgToEdit.description, slug: orgToEdit.slug });
         setEditOrgModalOpen(false);
         setOrgToEdit(null);
         fetchDashboardData();
@@ -66,10 +66,10 @@ export const createOrganization = async (token: string, orgData: { name: string;
     return response.data;
 };
 
-export const updateOrganization = async (token: string, orgId: string, orgData: { name: string; description: string; slug: string }) => {
+export const updateOrganization = async (token: string, orgId: string, updatedAt: string, orgData: { name: string; description: string; slug: string }) => {
     const response = await request<Organization>(`/api/organizations/${orgId}`, {
         method: 'PUT',
-        headers: { 'Authorization': `Bearer ${token}` },
+        headers: { 'Authorization': `Bearer ${token}`, 'If-Match': `"${updatedAt}"` },
         body: JSON.stringify(orgData)
     });
     return response.data;
@@ -58,6 +58,7 @@ export interface ApiResponse<T> {
   success: boolean;
   data: T;
   message: string | null;
+  code?: string; // Machine-readable error code, e.g. SLUG_TAKEN
   timestamp: string;
 }
 
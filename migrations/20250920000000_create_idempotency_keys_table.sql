@@ -0,0 +1,16 @@
+-- Create idempotency keys table
+-- Maps a client-supplied Idempotency-Key to the resource created by the original request
+
+CREATE TABLE idempotency_keys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    endpoint VARCHAR(100) NOT NULL, -- create_organization, create_api_key, create_redis_instance
+    idempotency_key VARCHAR(255) NOT NULL,
+    request_hash VARCHAR(64) NOT NULL, -- SHA-256 of the request payload
+    resource_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Keys are scoped per user and endpoint
+CREATE UNIQUE INDEX idx_idempotency_keys_lookup ON idempotency_keys(user_id, endpoint, idempotency_key);
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
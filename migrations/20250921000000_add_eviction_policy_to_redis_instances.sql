@@ -0,0 +1,3 @@
+-- Store the maxmemory eviction policy each instance is deployed with
+-- Existing instances were deployed with allkeys-lru
+ALTER TABLE redis_instances ADD COLUMN eviction_policy VARCHAR(32) DEFAULT 'allkeys-lru';
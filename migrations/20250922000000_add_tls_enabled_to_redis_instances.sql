@@ -0,0 +1,2 @@
+-- Whether the instance only accepts TLS connections
+ALTER TABLE redis_instances ADD COLUMN tls_enabled BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,2 @@
+-- Whether the instance was provisioned with per-scope Redis ACL users
+ALTER TABLE redis_instances ADD COLUMN acl_enabled BOOLEAN NOT NULL DEFAULT false;
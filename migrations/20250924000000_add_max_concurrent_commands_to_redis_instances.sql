@@ -0,0 +1,2 @@
+-- Per-instance cap on in-flight commands; NULL uses the gateway default
+ALTER TABLE redis_instances ADD COLUMN max_concurrent_commands INTEGER;
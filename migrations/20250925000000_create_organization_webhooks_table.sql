@@ -0,0 +1,10 @@
+-- Create organization webhooks table
+-- One status-change webhook per organization, POSTed to when an instance changes status
+
+CREATE TABLE organization_webhooks (
+    organization_id UUID PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(255) NOT NULL, -- HMAC-SHA256 key for the X-RedisGate-Signature header
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
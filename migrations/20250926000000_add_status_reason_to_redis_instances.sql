@@ -0,0 +1,2 @@
+-- Why an instance is in its current status, e.g. the pod event behind a failed deployment
+ALTER TABLE redis_instances ADD COLUMN status_reason TEXT;
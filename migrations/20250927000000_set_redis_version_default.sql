@@ -0,0 +1,2 @@
+-- Match the gateway's default version instead of the mutable `latest` tag
+ALTER TABLE redis_instances ALTER COLUMN redis_version SET DEFAULT '7.2';
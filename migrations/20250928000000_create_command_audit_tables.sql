@@ -0,0 +1,25 @@
+-- Create command audit tables
+-- Organizations can record a sample of the commands sent to their instances through the
+-- Redis HTTP API; an organization without settings does not audit commands
+
+CREATE TABLE organization_command_audit (
+    organization_id UUID PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT false,
+    sample_rate DOUBLE PRECISION NOT NULL DEFAULT 1.0 CHECK (sample_rate > 0 AND sample_rate <= 1), -- Fraction of commands recorded
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE command_audit_logs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    instance_id UUID NOT NULL, -- Kept after the instance is deleted
+    api_key_id UUID NOT NULL, -- Kept after the key is revoked
+    command VARCHAR(64) NOT NULL,
+    key TEXT, -- NULL for commands without a key
+    scope VARCHAR(20), -- Scope the command requires; NULL for pass-through commands
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_command_audit_logs_org_created ON command_audit_logs(organization_id, created_at DESC);
+CREATE INDEX idx_command_audit_logs_instance_id ON command_audit_logs(instance_id);
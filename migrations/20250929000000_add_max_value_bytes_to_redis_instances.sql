@@ -0,0 +1,2 @@
+-- Per-instance cap on values written by SET-family commands; NULL leaves only the gateway's argument limit
+ALTER TABLE redis_instances ADD COLUMN max_value_bytes INTEGER;
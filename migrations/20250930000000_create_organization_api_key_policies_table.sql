@@ -0,0 +1,10 @@
+-- Create organization API key policies table
+-- Scopes given to new API keys that do not request any, and the most the organization's keys
+-- may be granted; an organization without a policy leaves scopes to each request
+
+CREATE TABLE organization_api_key_policies (
+    organization_id UUID PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    default_scopes TEXT[] NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
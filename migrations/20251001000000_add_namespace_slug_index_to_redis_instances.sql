@@ -0,0 +1,3 @@
+-- Instance resources are named after the slug, so a slug can only be used once per namespace,
+-- including a shared namespace all organizations deploy to
+CREATE UNIQUE INDEX idx_redis_instances_namespace_slug ON redis_instances(namespace, slug) WHERE deleted_at IS NULL;
@@ -0,0 +1,2 @@
+-- Service in front of the instance's read replicas; NULL when it has none and reads go to the primary
+ALTER TABLE redis_instances ADD COLUMN replica_service_name VARCHAR(255);
@@ -0,0 +1,5 @@
+-- Every instance is created with its plan's maxclients, so the column no longer needs a default.
+-- Rows without a value were deployed without --maxclients and run with redis-server's default.
+UPDATE redis_instances SET max_connections = 10000 WHERE max_connections IS NULL;
+ALTER TABLE redis_instances ALTER COLUMN max_connections DROP DEFAULT;
+ALTER TABLE redis_instances ALTER COLUMN max_connections SET NOT NULL;
@@ -1,15 +1,68 @@
 // API request and response models
 
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::auth::password_weakness;
+use crate::config::{is_redis_version, PasswordPolicyConfig};
+use crate::k8s_service::PodDiagnostics;
+use crate::models::Plan;
+use crate::redis_pool::PoolStats;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 lazy_static! {
     static ref SLUG_REGEX: Regex = Regex::new(r"^[a-z0-9-]+$").unwrap();
+    static ref EVICTION_POLICY_REGEX: Regex = Regex::new(
+        r"^(noeviction|allkeys-lru|allkeys-lfu|allkeys-random|volatile-lru|volatile-lfu|volatile-random|volatile-ttl)$"
+    )
+    .unwrap();
+    static ref KEY_TYPE_REGEX: Regex = Regex::new(r"^(string|list|set|zset|hash|stream)$").unwrap();
+    static ref PASSWORD_POLICY: PasswordPolicyConfig = PasswordPolicyConfig::from_env();
+}
+
+fn validate_password_strength(password: &str) -> Result<(), ValidationError> {
+    match password_weakness(password, &PASSWORD_POLICY) {
+        Some(reason) => Err(ValidationError::new("password_strength").with_message(reason.into())),
+        None => Ok(()),
+    }
+}
+
+// The version becomes the Redis image tag, so only plain version numbers are accepted
+fn validate_redis_version(version: &str) -> Result<(), ValidationError> {
+    if is_redis_version(version) {
+        return Ok(());
+    }
+    Err(ValidationError::new("redis_version").with_message("must be a Redis version such as 7.2".into()))
+}
+
+// Webhooks are called from inside the cluster's network, so only plain HTTP(S) URLs to public
+// hosts are accepted. Host names are also resolved before the webhook is saved.
+fn validate_webhook_url(url: &str) -> Result<(), ValidationError> {
+    let host = match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => parsed.host_str().map(str::to_string),
+        _ => None,
+    };
+    let Some(host) = host else {
+        return Err(ValidationError::new("url").with_message("must be an http or https URL".into()));
+    };
+    crate::webhooks::check_webhook_host(&host)
+        .map_err(|_| ValidationError::new("url").with_message("must not point at a private or internal host".into()))
+}
+
+// Scopes the gateway enforces on API keys
+const API_KEY_SCOPES: &[&str] = &["read", "write", "admin"];
+
+// Organization scope policies may only name the scopes the gateway enforces
+fn validate_api_key_scopes(scopes: &[String]) -> Result<(), ValidationError> {
+    if scopes.iter().all(|scope| API_KEY_SCOPES.contains(&scope.as_str())) {
+        return Ok(());
+    }
+    Err(ValidationError::new("scopes").with_message("must be read, write or admin".into()))
 }
 
 // User registration request
@@ -19,7 +72,7 @@ pub struct RegisterRequest {
     pub email: String,
     #[validate(length(min = 3, max = 50))]
     pub username: String,
-    #[validate(length(min = 8))]
+    #[validate(custom(function = "validate_password_strength"))]
     pub password: String,
     #[validate(length(min = 1, max = 50))]
     pub first_name: Option<String>,
@@ -56,8 +109,24 @@ pub struct UserResponse {
     pub created_at: DateTime<Utc>,
 }
 
+// Organization membership of the current user
+#[derive(Debug, Serialize)]
+pub struct UserMembershipResponse {
+    pub organization_id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub role: String,
+}
+
+// Current user profile with organization memberships
+#[derive(Debug, Serialize)]
+pub struct CurrentUserResponse {
+    pub user: UserResponse,
+    pub organizations: Vec<UserMembershipResponse>,
+}
+
 // Organization creation request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateOrganizationRequest {
     #[validate(length(min = 1, max = 100))]
     pub name: String,
@@ -83,13 +152,51 @@ pub struct OrganizationResponse {
     pub updated_at: DateTime<Utc>,
 }
 
+// Organization plan change request
+#[derive(Debug, Deserialize)]
+pub struct UpdatePlanRequest {
+    pub plan: Plan,
+}
+
+// Organization ownership transfer request
+#[derive(Debug, Deserialize)]
+pub struct TransferOwnershipRequest {
+    pub new_owner_user_id: Uuid,
+}
+
+// Aggregate resource usage for an organization
+#[derive(Debug, Serialize)]
+pub struct OrganizationUsageResponse {
+    pub organization_id: Uuid,
+    pub instance_count: i64,
+    pub total_max_memory: i64,
+    pub total_current_memory: i64,
+    pub api_key_count: i64,
+    pub active_api_key_count: i64,
+    pub total_connections: i64,
+    pub total_max_connections: i64,
+}
+
+// Organization member with their user details
+#[derive(Debug, Serialize)]
+pub struct OrganizationMemberResponse {
+    pub user_id: Uuid,
+    pub email: String,
+    pub username: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub role: String,
+    pub is_active: bool,
+    pub joined_at: Option<DateTime<Utc>>,
+}
+
 // API key creation request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateApiKeyRequest {
     #[validate(length(min = 1, max = 100))]
     pub name: String,
     pub organization_id: Uuid,
-    pub scopes: Vec<String>,
+    pub scopes: Option<Vec<String>>, // Defaults to the organization's default_api_key_scopes
     pub expires_at: Option<DateTime<Utc>>,
 }
 
@@ -114,8 +221,14 @@ pub struct ApiKeyCreationResponse {
     pub key: String, // Only returned on creation
 }
 
+// Bulk API key revocation result
+#[derive(Debug, Serialize)]
+pub struct RevokeApiKeysResponse {
+    pub revoked: usize,
+}
+
 // Redis instance creation request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateRedisInstanceRequest {
     #[validate(length(min = 1, max = 100))]
     pub name: String,
@@ -124,9 +237,32 @@ pub struct CreateRedisInstanceRequest {
     pub organization_id: Uuid,
     #[validate(range(min = 1048576, max = 17179869184i64))] // 1MB to 16GB
     pub max_memory: i64,
-    pub redis_version: Option<String>,
+    #[validate(custom(function = "validate_redis_version"))]
+    pub redis_version: Option<String>, // Defaults to K8S_DEFAULT_REDIS_VERSION
     pub persistence_enabled: Option<bool>,
     pub backup_enabled: Option<bool>,
+    #[validate(range(min = 1))]
+    pub max_connections: Option<i32>, // Defaults to the plan limit
+    #[validate(regex(path = "*EVICTION_POLICY_REGEX"))]
+    pub eviction_policy: Option<String>, // Defaults to the plan's policy
+    pub tls_enabled: Option<bool>,       // Serve Redis over TLS only; defaults to false
+    #[validate(range(min = 1, max = 1000))]
+    pub max_concurrent_commands: Option<i32>, // Defaults to the gateway's pool size
+    #[validate(range(min = 1))]
+    pub max_value_bytes: Option<i32>, // Largest value SET-family commands may write; defaults to the plan limit
+    #[validate(nested)]
+    pub pod_security: Option<PodSecurityOverrides>, // Defaults to the gateway's K8S_POD_* settings
+}
+
+// Per-instance changes to the pod security context. Running as non-root and dropping
+// capabilities are cluster policy and cannot be turned off per instance.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PodSecurityOverrides {
+    #[validate(range(min = 1))]
+    pub run_as_user: Option<i64>,
+    #[validate(range(min = 0))]
+    pub fs_group: Option<i64>,
+    pub read_only_root_filesystem: Option<bool>,
 }
 
 // Redis instance response
@@ -144,11 +280,17 @@ pub struct RedisInstanceResponse {
     pub redis_version: String,
     pub namespace: String,
     pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_reason: Option<String>, // Set when provisioning failed, with the pod's reason
     pub health_status: String,
     pub cpu_usage_percent: f64,
     pub memory_usage_percent: f64,
     pub connections_count: i32,
     pub max_connections: i32,
+    pub eviction_policy: String,
+    pub tls_enabled: bool,
+    pub max_concurrent_commands: Option<i32>,
+    pub max_value_bytes: Option<i32>,
     pub persistence_enabled: bool,
     pub backup_enabled: bool,
     pub last_backup_at: Option<DateTime<Utc>>,
@@ -156,12 +298,137 @@ pub struct RedisInstanceResponse {
     pub updated_at: DateTime<Utc>,
 }
 
+// Redis instance creation response (includes the generated password)
+#[derive(Debug, Serialize)]
+pub struct RedisInstanceCreationResponse {
+    #[serde(flatten)]
+    pub redis_instance: RedisInstanceResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>, // Replays read it from the instance Secret; simulated instances have none to replay
+}
+
+// Redis instance status, with the pod's conditions and events while it is not running
+#[derive(Debug, Serialize)]
+pub struct RedisInstanceStatusResponse {
+    #[serde(flatten)]
+    pub redis_instance: RedisInstanceResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pod: Option<PodDiagnostics>,
+}
+
+// Soft-deleted Redis instance that is still within the retention window
+#[derive(Debug, Serialize)]
+pub struct DeletedRedisInstanceResponse {
+    #[serde(flatten)]
+    pub redis_instance: RedisInstanceResponse,
+    pub deleted_at: DateTime<Utc>,
+    pub retained_until: DateTime<Utc>, // Gone for good after this
+}
+
+// Redis password rotation response (includes the new password)
+#[derive(Debug, Serialize)]
+pub struct RedisPasswordRotationResponse {
+    pub instance_id: Uuid,
+    pub password: String, // Only returned once
+}
+
+// Result of rebuilding an instance's connection pool
+#[derive(Debug, Serialize)]
+pub struct RedisPoolRefreshResponse {
+    pub instance_id: Uuid,
+    pub health_status: String, // "healthy" if a fresh connection answered PING, otherwise "unhealthy"
+    pub checked_at: DateTime<Utc>,
+}
+
+// Organization status-change webhook registration
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateWebhookRequest {
+    #[validate(length(max = 2048), custom(function = "validate_webhook_url"))]
+    pub url: String,
+    pub rotate_secret: Option<bool>, // Replace the signing secret of an existing webhook
+}
+
+// Organization status-change webhook
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub organization_id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>, // Only returned when a new signing secret was generated
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Organization command auditing settings update
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCommandAuditRequest {
+    pub enabled: bool,
+    #[validate(range(exclusive_min = 0.0, max = 1.0))]
+    pub sample_rate: Option<f64>, // Fraction of commands recorded; defaults to every command
+}
+
+// Organization command auditing settings
+#[derive(Debug, Serialize)]
+pub struct CommandAuditResponse {
+    pub organization_id: Uuid,
+    pub enabled: bool,
+    pub sample_rate: f64,
+    pub updated_at: Option<DateTime<Utc>>, // None until the settings are first changed
+}
+
+// Organization API key scope policy update; null removes the policy
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateApiKeyScopesRequest {
+    #[validate(length(min = 1), custom(function = "validate_api_key_scopes"))]
+    pub default_api_key_scopes: Option<Vec<String>>,
+}
+
+// Organization API key scope policy: the scopes of keys created without any, which are also
+// the most any new key may be granted
+#[derive(Debug, Serialize)]
+pub struct ApiKeyScopesResponse {
+    pub organization_id: Uuid,
+    pub default_api_key_scopes: Option<Vec<String>>, // None when the organization has no policy
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Machine-readable error codes, serialized like `SLUG_TAKEN`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    ValidationError,
+    InvalidRequest,
+    InvalidHeader,
+    InvalidCredentials,
+    AccountInactive,
+    Forbidden,
+    NotFound,
+    SlugTaken,
+    UserExists,
+    QuotaExceeded,
+    ResourceInUse,
+    IdempotencyKeyReused,
+    PreconditionFailed,
+    DatabaseError,
+    KubernetesError,
+    ProvisioningBusy,
+    RedisError,
+    RedisUnavailable,
+    RedisTimeout,
+    RedisCredentialMismatch,
+    InternalError,
+}
+
 // Generic API response wrapper
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>, // Set on errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<BTreeMap<String, Vec<String>>>, // Field-level validation messages
     pub timestamp: DateTime<Utc>,
 }
 
@@ -171,18 +438,321 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             message: None,
+            code: None,
+            errors: None,
             timestamp: Utc::now(),
         }
     }
 
-    pub fn error(message: String) -> ApiResponse<()> {
+    pub fn error(code: ErrorCode, message: String) -> ApiResponse<()> {
         ApiResponse {
             success: false,
             data: None,
             message: Some(message),
+            code: Some(code),
+            errors: None,
             timestamp: Utc::now(),
         }
     }
+
+    // Validation failure with a `{"field": ["message", ...]}` map of what was wrong. Errors of
+    // nested objects and lists are keyed by their path, like `pod_security.run_as_user`.
+    pub fn validation_error(errors: &ValidationErrors) -> ApiResponse<()> {
+        let mut fields = BTreeMap::new();
+        collect_field_errors("", errors, &mut fields);
+
+        ApiResponse {
+            errors: Some(fields),
+            ..ApiResponse::<()>::error(ErrorCode::ValidationError, "Validation failed".to_string())
+        }
+    }
+}
+
+fn collect_field_errors(prefix: &str, errors: &ValidationErrors, fields: &mut BTreeMap<String, Vec<String>>) {
+    for (field, kind) in errors.errors() {
+        let path = format!("{}{}", prefix, field);
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                fields.insert(path, errors.iter().map(validation_message).collect());
+            }
+            ValidationErrorsKind::Struct(errors) => collect_field_errors(&format!("{}.", path), errors, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_field_errors(&format!("{}[{}].", path, index), errors, fields);
+                }
+            }
+        }
+    }
+}
+
+// Human-readable message for a single failed validation rule
+fn validation_message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("length", Some(min), Some(max)) => format!("must be between {} and {} characters", min, max),
+        ("length", Some(min), None) => format!("must be at least {} characters", min),
+        ("length", None, Some(max)) => format!("must be at most {} characters", max),
+        ("range", Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+        ("range", Some(min), None) => format!("must be at least {}", min),
+        ("range", None, Some(max)) => format!("must be at most {}", max),
+        ("email", _, _) => "must be a valid email address".to_string(),
+        ("regex", _, _) => "has an invalid format".to_string(),
+        (code, _, _) => format!("is invalid ({})", code),
+    }
+}
+
+// Bulk key deletion request
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteKeysRequest {
+    #[validate(length(min = 1, max = 1024))]
+    pub pattern: String,
+    pub cursor: Option<u64>, // Resume from a previous response
+    #[validate(range(min = 1, max = 10000))]
+    pub count: Option<usize>,
+    #[serde(rename = "type")]
+    #[validate(regex(path = "*KEY_TYPE_REGEX"))]
+    pub key_type: Option<String>, // Only keys of this type, filtered by SCAN itself
+}
+
+// Bulk key deletion result
+#[derive(Debug, Serialize)]
+pub struct DeleteKeysResponse {
+    pub deleted: u64,
+    pub cursor: u64,
+    pub complete: bool, // false when the safety timeout stopped the scan early
+}
+
+// Filters for the platform-wide instance list
+#[derive(Debug, Deserialize, Validate)]
+pub struct AdminInstanceListParams {
+    #[validate(range(min = 1))]
+    pub page: Option<u32>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<u32>,
+    #[validate(length(min = 1, max = 50))]
+    pub status: Option<String>, // Only instances with this status, e.g. "failed"
+}
+
+// An instance in the platform-wide list, with the organization that owns it
+#[derive(Debug, Serialize)]
+pub struct AdminRedisInstanceResponse {
+    #[serde(flatten)]
+    pub redis_instance: RedisInstanceResponse,
+    pub organization_name: String,
+    pub organization_slug: String,
+}
+
+// Connection pool saturation across all instances
+#[derive(Debug, Serialize)]
+pub struct PoolHealthReport {
+    pub pools: Vec<PoolStats>,
+    pub waiting: usize, // Requests waiting for a connection across all pools
+    pub checked_at: DateTime<Utc>,
+}
+
+// Differences between the redis_instances table and the cluster
+#[derive(Debug, Serialize)]
+pub struct DriftReport {
+    pub missing_deployments: Vec<MissingDeployment>,
+    pub orphaned_deployments: Vec<OrphanedDeployment>,
+    pub checked_at: DateTime<Utc>,
+}
+
+// An instance marked running whose deployment cannot be found
+#[derive(Debug, Serialize)]
+pub struct MissingDeployment {
+    pub instance_id: Uuid,
+    pub organization_id: Uuid,
+    pub slug: String,
+    pub namespace: String,
+}
+
+// A RedisGate deployment with no matching instance
+#[derive(Debug, Serialize, PartialEq)]
+pub struct OrphanedDeployment {
+    pub namespace: String,
+    pub name: String,
+    pub instance_id: Option<Uuid>,
+}
+
+// Bulk TTL lookup request
+#[derive(Debug, Deserialize, Validate)]
+pub struct KeyTtlRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub keys: Vec<String>,
+}
+
+// Remaining TTL in seconds per key: -1 without expiry, -2 when the key does not exist
+#[derive(Debug, Serialize)]
+pub struct KeyTtlResponse {
+    pub ttls: BTreeMap<String, i64>,
+}
+
+// Bulk idle time lookup request
+#[derive(Debug, Deserialize, Validate)]
+pub struct KeyIdleTimeRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub keys: Vec<String>,
+}
+
+// Seconds since each key was last accessed; null when the key does not exist
+#[derive(Debug, Serialize)]
+pub struct KeyIdleTimeResponse {
+    pub idle_seconds: BTreeMap<String, Option<i64>>,
+}
+
+// Bulk TTL update request
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetKeyTtlRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub keys: Vec<String>,
+    #[validate(range(min = 1))]
+    pub seconds: i64,
+}
+
+// Bulk TTL update result
+#[derive(Debug, Serialize)]
+pub struct SetKeyTtlResponse {
+    pub updated: u64, // Keys that existed and now expire
+}
+
+// Command mix and key counts of an instance, from INFO commandstats and INFO keyspace
+#[derive(Debug, Serialize)]
+pub struct InstanceStatsResponse {
+    pub commands: Vec<CommandStats>, // Most-called first
+    pub total_calls: u64,
+    pub keyspace: Vec<DatabaseKeyspace>,
+    pub collected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CommandStats {
+    pub command: String, // Lowercase, with `|` between a container command and its subcommand
+    pub calls: u64,
+    pub usec: u64,
+    pub usec_per_call: f64,
+    pub rejected_calls: Option<u64>, // Reported by Redis 7+
+    pub failed_calls: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DatabaseKeyspace {
+    pub db: i64,
+    pub keys: u64,
+    pub expires: u64, // Keys with a TTL
+    pub avg_ttl_ms: u64,
+}
+
+// Keyspace export options
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExportKeysParams {
+    #[serde(rename = "match")]
+    #[validate(length(min = 1, max = 1024))]
+    pub pattern: Option<String>,
+    pub cursor: Option<u64>, // Resume from the last checkpoint of a previous export
+    #[validate(range(min = 1, max = 10000))]
+    pub count: Option<usize>,
+    #[serde(rename = "type")]
+    #[validate(regex(path = "*KEY_TYPE_REGEX"))]
+    pub key_type: Option<String>, // Only keys of this type, filtered by SCAN itself
+}
+
+// Large-key scan options
+#[derive(Debug, Deserialize, Validate)]
+pub struct BigKeysParams {
+    #[validate(range(min = 1, max = 100))]
+    pub top: Option<usize>,
+    #[validate(range(min = 1, max = 100000))]
+    pub sample: Option<usize>, // Keys to inspect before stopping
+    #[serde(rename = "type")]
+    #[validate(regex(path = "*KEY_TYPE_REGEX"))]
+    pub key_type: Option<String>, // Only keys of this type, filtered by SCAN itself
+}
+
+// Largest keys found by a bounded keyspace scan
+#[derive(Debug, Serialize)]
+pub struct BigKeysResponse {
+    pub keys: Vec<BigKey>, // Largest first
+    pub sampled: usize,
+    pub cursor: u64,
+    pub complete: bool, // true when the whole keyspace was scanned
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BigKey {
+    pub key: String,
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub memory_bytes: u64, // MEMORY USAGE estimate
+    pub length: Option<u64>, // Bytes of a string, elements of other built-in types
+}
+
+// One NDJSON line of a keyspace import, in the format produced by export
+#[derive(Debug, Deserialize)]
+pub struct ImportKeyRecord {
+    pub key: String,
+    pub key_encoding: Option<String>, // "base64" for binary keys
+    #[serde(rename = "type")]
+    pub key_type: Option<String>,
+    pub ttl_ms: Option<i64>,
+    pub ttl: Option<i64>, // Seconds, for hand-written records; ttl_ms wins when both are set
+    #[serde(default)]
+    pub value: serde_json::Value,
+    pub dump: Option<String>, // Base64 DUMP payload, restored with RESTORE instead of typed writes
+    pub encoding: Option<String>, // Encoding of dump; exports always use base64
+}
+
+// A line that could not be imported
+#[derive(Debug, Serialize)]
+pub struct ImportKeyError {
+    pub line: usize,
+    pub error: String,
+}
+
+// Bulk key import result
+#[derive(Debug, Serialize)]
+pub struct ImportKeysResponse {
+    pub imported: u64,
+    pub failed: u64,
+    pub errors: Vec<ImportKeyError>, // Capped; `failed` has the full count
+}
+
+// Runtime config parameters to read, comma separated (defaults to all readable ones)
+#[derive(Debug, Deserialize)]
+pub struct RedisConfigParams {
+    pub parameters: Option<String>,
+}
+
+// Runtime config changes applied with CONFIG SET
+#[derive(Debug, Deserialize)]
+pub struct UpdateRedisConfigRequest {
+    pub parameters: BTreeMap<String, String>,
+}
+
+// Current values of runtime config parameters
+#[derive(Debug, Serialize)]
+pub struct RedisConfigResponse {
+    pub parameters: BTreeMap<String, String>,
+}
+
+// Pod log options
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct InstanceLogsParams {
+    #[validate(range(min = 1, max = 5000))]
+    pub tail_lines: Option<i64>, // Defaults to 100
+    pub follow: Option<bool>,    // Keep the response open and stream new lines
+    pub previous: Option<bool>,  // Logs of the previous container, e.g. after a crash
+}
+
+// Redis instance deletion options
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteRedisInstanceParams {
+    // Also delete persistent volumes and backup jobs instead of keeping them for the restore window
+    pub purge_data: Option<bool>,
 }
 
 // Pagination parameters
@@ -209,4 +779,84 @@ pub struct PaginatedResponse<T> {
     pub page: u32,
     pub limit: u32,
     pub total_pages: u32,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_error_lists_messages_per_field() {
+        let request = RegisterRequest {
+            email: "not-an-email".to_string(),
+            username: "ab".to_string(),
+            password: "correct horse 42".to_string(),
+            first_name: None,
+            last_name: None,
+        };
+        let response = ApiResponse::<()>::validation_error(&request.validate().unwrap_err());
+
+        assert_eq!(response.code, Some(ErrorCode::ValidationError));
+        let errors = response.errors.unwrap();
+        assert_eq!(errors["email"], vec!["must be a valid email address"]);
+        assert_eq!(errors["username"], vec!["must be between 3 and 50 characters"]);
+        assert!(!errors.contains_key("password"));
+    }
+
+    #[test]
+    fn test_validation_error_includes_nested_fields() {
+        let request: CreateRedisInstanceRequest = serde_json::from_value(serde_json::json!({
+            "name": "cache",
+            "slug": "cache",
+            "organization_id": Uuid::nil(),
+            "max_memory": 1048576,
+            "pod_security": {"run_as_user": 0, "fs_group": -1},
+        }))
+        .unwrap();
+        let response = ApiResponse::<()>::validation_error(&request.validate().unwrap_err());
+
+        let errors = response.errors.unwrap();
+        assert_eq!(errors["pod_security.run_as_user"], vec!["must be at least 1"]);
+        assert_eq!(errors["pod_security.fs_group"], vec!["must be at least 0"]);
+    }
+
+    #[test]
+    fn test_register_rejects_weak_password() {
+        let request = RegisterRequest {
+            email: "user@example.com".to_string(),
+            username: "user".to_string(),
+            password: "password123".to_string(),
+            first_name: None,
+            last_name: None,
+        };
+        let response = ApiResponse::<()>::validation_error(&request.validate().unwrap_err());
+
+        assert_eq!(response.errors.unwrap()["password"], vec!["is too common"]);
+    }
+
+    #[test]
+    fn test_webhook_url_must_be_http() {
+        assert!(validate_webhook_url("https://hooks.example.com/redisgate").is_ok());
+        assert!(validate_webhook_url("http://10.0.0.5:8080/events").is_err());
+        assert!(validate_webhook_url("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(validate_webhook_url("ftp://hooks.example.com").is_err());
+        assert!(validate_webhook_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_api_key_scope_policy_names_known_scopes() {
+        let valid = UpdateApiKeyScopesRequest {
+            default_api_key_scopes: Some(vec!["read".to_string(), "write".to_string()]),
+        };
+        assert!(valid.validate().is_ok());
+        assert!(UpdateApiKeyScopesRequest { default_api_key_scopes: None }.validate().is_ok());
+
+        let unknown = UpdateApiKeyScopesRequest {
+            default_api_key_scopes: Some(vec!["superuser".to_string()]),
+        };
+        assert!(unknown.validate().is_err());
+        let empty = UpdateApiKeyScopesRequest {
+            default_api_key_scopes: Some(vec![]),
+        };
+        assert!(empty.validate().is_err());
+    }
+}
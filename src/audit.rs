@@ -0,0 +1,35 @@
+// Audit log recording
+
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+// An action to record in the audit_logs table
+pub struct AuditEvent<'a> {
+    pub user_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub action: &'a str,
+    pub resource_type: &'a str,
+    pub resource_id: Option<Uuid>,
+    pub details: Value,
+}
+
+// Insert an audit log entry; accepts a pool or an open transaction
+pub async fn record<'e, E: PgExecutor<'e>>(executor: E, event: &AuditEvent<'_>) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_logs (user_id, organization_id, action, resource_type, resource_id, details)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        event.user_id,
+        event.organization_id,
+        event.action,
+        event.resource_type,
+        event.resource_id,
+        event.details
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,220 @@
+// Sampled auditing of Redis API commands, for organizations that turn it on
+
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+// How long an organization's settings are used before being read again; changes made through
+// another gateway replica take effect within this time
+const SETTINGS_REFRESH: Duration = Duration::from_secs(30);
+
+/// A command sent through the Redis HTTP API
+pub struct AuditedCommand<'a> {
+    pub organization_id: Uuid,
+    pub instance_id: Uuid,
+    pub api_key_id: Uuid,
+    pub command: &'a str,
+    pub key: Option<&'a str>,
+    pub scope: Option<&'static str>, // Scope the command requires; None for pass-through commands
+}
+
+// Sample rate of an organization that audits commands, or None when auditing is off. Loaded
+// once per refresh, however many commands are waiting for it.
+#[derive(Debug, Clone)]
+struct CachedSettings {
+    sample_rate: Arc<OnceCell<Option<f64>>>,
+    created_at: Instant,
+}
+
+impl CachedSettings {
+    fn new(now: Instant) -> Self {
+        Self {
+            sample_rate: Arc::new(OnceCell::new()),
+            created_at: now,
+        }
+    }
+
+    fn is_fresh(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.created_at) < SETTINGS_REFRESH
+    }
+}
+
+/// Writes a sample of each auditing organization's commands to command_audit_logs
+pub struct CommandAuditor {
+    db_pool: PgPool,
+    settings: Mutex<HashMap<Uuid, CachedSettings>>,
+}
+
+impl CommandAuditor {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            settings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record the command if its organization audits commands and it falls in the sample.
+    ///
+    /// Loading settings and writing the entry run in a spawned task so commands never wait on
+    /// the database; organizations known not to audit cost only a map lookup.
+    pub fn record(self: &Arc<Self>, command: AuditedCommand<'_>) {
+        let now = Instant::now();
+        let pending = match self.cached_sample_rate(command.organization_id, now) {
+            Some(None) => return,
+            Some(Some(rate)) if !sampled(rate, rand::random()) => return,
+            Some(Some(_)) => None,
+            None => Some(self.settings_cell(command.organization_id, now)),
+        };
+
+        let entry = OwnedCommand {
+            organization_id: command.organization_id,
+            instance_id: command.instance_id,
+            api_key_id: command.api_key_id,
+            command: command.command.to_ascii_uppercase(),
+            key: command.key.map(str::to_string),
+            scope: command.scope,
+        };
+        let auditor = self.clone();
+        tokio::spawn(async move {
+            // Settings not cached yet: load them, or wait for the command already loading
+            // them, then sample
+            if let Some(sample_rate) = pending {
+                let loaded = sample_rate
+                    .get_or_try_init(|| auditor.load_sample_rate(entry.organization_id))
+                    .await;
+                match loaded {
+                    Ok(Some(rate)) if sampled(*rate, rand::random()) => {}
+                    Ok(_) => return,
+                    Err(e) => {
+                        tracing::warn!("Failed to load command audit settings for organization {}: {}", entry.organization_id, e);
+                        return;
+                    }
+                }
+            }
+            if let Err(e) = auditor.insert(&entry).await {
+                tracing::warn!("Failed to record command in command audit log: {}", e);
+            }
+        });
+    }
+
+    /// Forget an organization's cached settings, after they were changed through this gateway
+    pub fn invalidate(&self, organization_id: Uuid) {
+        self.settings.lock().unwrap().remove(&organization_id);
+    }
+
+    // Some(rate) while the organization's settings are loaded and fresh in the cache
+    fn cached_sample_rate(&self, organization_id: Uuid, now: Instant) -> Option<Option<f64>> {
+        let settings = self.settings.lock().unwrap();
+        settings
+            .get(&organization_id)
+            .filter(|cached| cached.is_fresh(now))
+            .and_then(|cached| cached.sample_rate.get().copied())
+    }
+
+    // Where the organization's current settings are, or will be once loaded. Every command
+    // that misses the cache before they are loaded gets the same cell.
+    fn settings_cell(&self, organization_id: Uuid, now: Instant) -> Arc<OnceCell<Option<f64>>> {
+        let mut settings = self.settings.lock().unwrap();
+        let cached = settings
+            .entry(organization_id)
+            .or_insert_with(|| CachedSettings::new(now));
+        if !cached.is_fresh(now) {
+            *cached = CachedSettings::new(now);
+        }
+        cached.sample_rate.clone()
+    }
+
+    async fn load_sample_rate(&self, organization_id: Uuid) -> Result<Option<f64>, sqlx::Error> {
+        let settings = sqlx::query!(
+            "SELECT enabled, sample_rate FROM organization_command_audit WHERE organization_id = $1",
+            organization_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+        Ok(settings.and_then(|settings| settings.enabled.then_some(settings.sample_rate)))
+    }
+
+    async fn insert(&self, entry: &OwnedCommand) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO command_audit_logs (organization_id, instance_id, api_key_id, command, key, scope)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            entry.organization_id,
+            entry.instance_id,
+            entry.api_key_id,
+            entry.command,
+            entry.key,
+            entry.scope
+        )
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+}
+
+struct OwnedCommand {
+    organization_id: Uuid,
+    instance_id: Uuid,
+    api_key_id: Uuid,
+    command: String,
+    key: Option<String>,
+    scope: Option<&'static str>,
+}
+
+// Whether a command falls in the sample, given a uniform roll in [0, 1)
+fn sampled(sample_rate: f64, roll: f64) -> bool {
+    roll < sample_rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The pool is lazy and never connected
+    fn auditor() -> CommandAuditor {
+        CommandAuditor::new(PgPool::connect_lazy("postgres://localhost/redisgate_test").expect("static URL parses"))
+    }
+
+    #[test]
+    fn test_sampled() {
+        assert!(sampled(1.0, 0.999));
+        assert!(sampled(0.25, 0.1));
+        assert!(!sampled(0.25, 0.25));
+        assert!(!sampled(0.01, 0.5));
+    }
+
+    #[tokio::test]
+    async fn test_cached_settings_expire() {
+        let auditor = auditor();
+        let organization_id = Uuid::new_v4();
+        let now = Instant::now();
+        assert_eq!(auditor.cached_sample_rate(organization_id, now), None);
+
+        auditor.settings_cell(organization_id, now).set(Some(0.5)).unwrap();
+        assert_eq!(auditor.cached_sample_rate(organization_id, now), Some(Some(0.5)));
+        assert_eq!(auditor.cached_sample_rate(organization_id, now + SETTINGS_REFRESH), None);
+
+        auditor.invalidate(organization_id);
+        assert_eq!(auditor.cached_sample_rate(organization_id, now), None);
+    }
+
+    #[tokio::test]
+    async fn test_settings_loaded_once() {
+        let auditor = auditor();
+        let organization_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        // Commands missing the cache share one load until the settings go stale
+        let first = auditor.settings_cell(organization_id, now);
+        let second = auditor.settings_cell(organization_id, now + Duration::from_secs(1));
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(auditor.cached_sample_rate(organization_id, now), None);
+
+        let refreshed = auditor.settings_cell(organization_id, now + SETTINGS_REFRESH);
+        assert!(!Arc::ptr_eq(&first, &refreshed));
+    }
+}
@@ -0,0 +1,838 @@
+// Runtime configuration loaded from environment variables
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+// Read an environment variable, falling back to a default when unset or unparsable
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+// Read a comma-separated environment variable, falling back to a default list when unset
+fn env_list(name: &str, default: &[&str]) -> Vec<String> {
+    match std::env::var(name) {
+        Ok(value) => comma_list(&value),
+        Err(_) => default.iter().map(|item| item.to_string()).collect(),
+    }
+}
+
+fn comma_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Settings for the Redis HTTP API handlers
+#[derive(Debug, Clone)]
+pub struct RedisHandlerConfig {
+    pub connect_timeout: Duration,
+    pub command_timeout: Duration,
+    pub max_command_timeout: Duration,
+    pub max_db: i64,
+    pub pool_max_size: usize,
+    pub pool_timeout: Duration,
+    pub pool_recycle_timeout: Duration, // How long the health check of a reused connection may take
+    pub pool_idle_timeout: Duration,    // Idle connections older than this are closed instead of reused
+    pub pool_evict_after: Option<Duration>, // Pools not checked out from for this long are dropped; None keeps them
+    pub tcp_keepalive: Option<Duration>, // Idle time before TCP keepalive probes start; None disables them
+    pub breaker_failure_threshold: u32, // Consecutive connection failures that open an instance's circuit breaker; 0 disables it
+    pub breaker_cooldown: Duration,     // How long an open breaker fails requests before probing the instance again
+    pub max_arg_bytes: usize,     // Largest single command argument
+    pub max_command_bytes: usize, // Largest command, name and arguments combined
+    pub max_command_args: usize,  // Most arguments a single command may carry
+    pub max_pipeline_commands: usize, // Most commands a single batch request may pipeline
+    pub max_pipeline_args: usize, // Most arguments all commands of one pipeline may carry together
+    pub global_rate_limit: u32,   // Redis API requests per second across the gateway; 0 disables the limit
+    pub instance_rate_limit: u32, // Redis API requests per second to any one instance; 0 disables the limit
+    pub max_key_bytes: usize,     // Longest key accepted in the path of the per-command routes
+    pub tls_insecure: bool,       // Skip certificate verification for TLS-enabled instances
+    pub acl_secret: Option<String>, // Derives per-instance ACL user passwords; unset disables ACL users
+    pub client_name: Option<String>, // Gateway label in the CLIENT SETNAME of its connections; None leaves them unnamed
+    pub client_setinfo: bool,        // Report the gateway as the client library with CLIENT SETINFO
+}
+
+impl Default for RedisHandlerConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_millis(2000),
+            command_timeout: Duration::from_millis(5000),
+            max_command_timeout: Duration::from_millis(30000),
+            max_db: 15,
+            pool_max_size: 16,
+            pool_timeout: Duration::from_secs(5),
+            pool_recycle_timeout: Duration::from_millis(2000),
+            pool_idle_timeout: Duration::from_secs(300),
+            pool_evict_after: Some(Duration::from_secs(1800)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            breaker_failure_threshold: 5,
+            breaker_cooldown: Duration::from_secs(30),
+            max_arg_bytes: 1024 * 1024,
+            max_command_bytes: 2 * 1024 * 1024,
+            max_command_args: 10_000,
+            max_pipeline_commands: 1000,
+            max_pipeline_args: 100_000,
+            global_rate_limit: 0,
+            instance_rate_limit: 0,
+            max_key_bytes: 1024,
+            tls_insecure: false,
+            acl_secret: None,
+            client_name: Some("redisgate".to_string()),
+            client_setinfo: true,
+        }
+    }
+}
+
+impl RedisHandlerConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let millis = |name: &str, default: Duration| Duration::from_millis(env_or(name, default.as_millis() as u64));
+        let secs = |name: &str, default: Duration| Duration::from_secs(env_or(name, default.as_secs()));
+        // Zero turns these off
+        let optional_secs = |name: &str, default: Option<Duration>| {
+            Some(env_or(name, default.map_or(0, |duration| duration.as_secs())))
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+        };
+        Self {
+            connect_timeout: millis("REDIS_CONNECT_TIMEOUT_MS", defaults.connect_timeout),
+            command_timeout: millis("REDIS_COMMAND_TIMEOUT_MS", defaults.command_timeout),
+            max_command_timeout: millis("REDIS_MAX_COMMAND_TIMEOUT_MS", defaults.max_command_timeout),
+            max_db: env_or("REDIS_MAX_DB", defaults.max_db),
+            pool_max_size: env_or("REDIS_POOL_MAX_SIZE", defaults.pool_max_size),
+            pool_timeout: secs("REDIS_POOL_TIMEOUT_SECONDS", defaults.pool_timeout),
+            pool_recycle_timeout: millis("REDIS_POOL_RECYCLE_TIMEOUT_MS", defaults.pool_recycle_timeout),
+            pool_idle_timeout: secs("REDIS_POOL_IDLE_TIMEOUT_SECS", defaults.pool_idle_timeout),
+            pool_evict_after: optional_secs("REDIS_POOL_EVICT_IDLE_SECS", defaults.pool_evict_after),
+            tcp_keepalive: optional_secs("REDIS_TCP_KEEPALIVE_SECS", defaults.tcp_keepalive),
+            breaker_failure_threshold: env_or("REDIS_BREAKER_FAILURE_THRESHOLD", defaults.breaker_failure_threshold),
+            breaker_cooldown: secs("REDIS_BREAKER_COOLDOWN_SECS", defaults.breaker_cooldown),
+            max_arg_bytes: env_or("REDIS_MAX_ARG_BYTES", defaults.max_arg_bytes),
+            max_command_bytes: env_or("REDIS_MAX_COMMAND_BYTES", defaults.max_command_bytes),
+            max_command_args: env_or("REDIS_MAX_COMMAND_ARGS", defaults.max_command_args),
+            max_pipeline_commands: env_or("REDIS_MAX_PIPELINE_COMMANDS", defaults.max_pipeline_commands),
+            max_pipeline_args: env_or("REDIS_MAX_PIPELINE_ARGS", defaults.max_pipeline_args),
+            global_rate_limit: env_or("REDIS_GLOBAL_RATE_LIMIT", defaults.global_rate_limit),
+            instance_rate_limit: env_or("REDIS_INSTANCE_RATE_LIMIT", defaults.instance_rate_limit),
+            max_key_bytes: env_or("REDIS_MAX_KEY_BYTES", defaults.max_key_bytes),
+            tls_insecure: env_or("REDIS_TLS_INSECURE", defaults.tls_insecure),
+            acl_secret: std::env::var("REDIS_ACL_SECRET").ok().filter(|secret| !secret.is_empty()),
+            // Defaults to the host (pod) name; set REDIS_CLIENT_NAME to an empty string to turn naming off
+            client_name: match std::env::var("REDIS_CLIENT_NAME") {
+                Ok(name) => Some(name).filter(|name| !name.is_empty()),
+                Err(_) => std::env::var("HOSTNAME").ok().or(defaults.client_name),
+            },
+            client_setinfo: env_or("REDIS_CLIENT_SETINFO", defaults.client_setinfo),
+        }
+    }
+}
+
+// Hostname assigned to each Redis instance, built from a template with
+// `{slug}` (instance slug) and `{org}` (organization slug) placeholders
+#[derive(Debug, Clone)]
+pub struct DomainConfig {
+    pub template: String,
+}
+
+impl DomainConfig {
+    pub fn from_env() -> Self {
+        Self {
+            template: std::env::var("REDIS_DOMAIN_TEMPLATE").unwrap_or_else(|_| "{slug}.{org}.redis.local".to_string()),
+        }
+    }
+
+    // Check the template at startup: it must name the instance and yield a valid hostname
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.template.contains("{slug}") {
+            return Err(format!("Domain template '{}' must contain {{slug}}", self.template));
+        }
+        self.render("instance", "organization")
+            .map(|_| ())
+            .ok_or_else(|| format!("Domain template '{}' does not produce a valid hostname", self.template))
+    }
+
+    // Render the domain for an instance, or None when the result is not an RFC 1123 hostname
+    pub fn render(&self, slug: &str, org_slug: &str) -> Option<String> {
+        let domain = self.template.replace("{slug}", slug).replace("{org}", org_slug).to_lowercase();
+        is_valid_hostname(&domain).then_some(domain)
+    }
+}
+
+fn is_valid_hostname(hostname: &str) -> bool {
+    hostname.len() <= 253 && hostname.split('.').all(is_dns_label)
+}
+
+// RFC 1123 label, as required for hostname parts and Kubernetes namespace names
+pub fn is_dns_label(label: &str) -> bool {
+    (1..=63).contains(&label.len())
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+}
+
+// Redis version used when a creation request names none and K8S_DEFAULT_REDIS_VERSION is unset
+pub const DEFAULT_REDIS_VERSION: &str = "7.2";
+
+// Image tags instances may be created with when K8S_SUPPORTED_REDIS_VERSIONS is unset
+const DEFAULT_SUPPORTED_REDIS_VERSIONS: &str = "6.2,7.0,7.2,7.4";
+
+// A Redis image tag of the form `7.2`, `7.2.4` or `7.2-alpine`; anything else (`latest`,
+// registry paths, shell metacharacters) is refused before it reaches an image reference
+pub fn is_redis_version(version: &str) -> bool {
+    let number = version.strip_suffix("-alpine").unwrap_or(version);
+    let parts: Vec<&str> = number.split('.').collect();
+    (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| (1..=4).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+// Where Redis instances are deployed: a namespace per organization (`redis-{org}`), or a
+// single shared namespace for clusters with strict namespace quotas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamespaceMode {
+    PerOrganization,
+    Shared(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct KubernetesConfig {
+    pub namespace_mode: NamespaceMode,
+    pub enabled: bool, // false runs without a cluster (local-only); instances are recorded but not deployed
+    pub connect_initial_backoff: Duration,
+    pub connect_max_backoff: Duration,
+    pub default_redis_version: String,
+    pub supported_redis_versions: Vec<String>, // Image tags instances may be created with
+    pub max_concurrent_provisions: usize, // Instance deployments applied to the cluster at once
+    pub provision_queue_timeout: Duration, // How long a creation waits for a free slot before failing with 503
+    pub pod_security: PodSecurityConfig, // Defaults for new instances; uid, gid and read-only root can be overridden per instance
+    pub tls_issuer: TlsIssuer, // Signs the certificates of TLS-enabled instances
+}
+
+// cert-manager issuer that signs instance certificates
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsIssuer {
+    pub name: String,
+    pub kind: String, // ClusterIssuer, or Issuer for one in the instance namespace
+}
+
+impl Default for TlsIssuer {
+    fn default() -> Self {
+        Self {
+            name: "redisgate-ca".to_string(),
+            kind: "ClusterIssuer".to_string(),
+        }
+    }
+}
+
+impl TlsIssuer {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            name: std::env::var("K8S_TLS_ISSUER").unwrap_or(defaults.name),
+            kind: std::env::var("K8S_TLS_ISSUER_KIND").unwrap_or(defaults.kind),
+        }
+    }
+}
+
+// Security context of Redis pods, for clusters enforcing the restricted PodSecurity standard
+#[derive(Debug, Clone, PartialEq)]
+pub struct PodSecurityConfig {
+    pub run_as_non_root: bool,
+    pub run_as_user: i64, // 999 is the redis user of the official image
+    pub fs_group: i64,
+    pub drop_all_capabilities: bool,
+    pub read_only_root_filesystem: bool, // Redis then writes to an emptyDir mounted at /data
+}
+
+impl Default for PodSecurityConfig {
+    fn default() -> Self {
+        Self {
+            run_as_non_root: true,
+            run_as_user: 999,
+            fs_group: 999,
+            drop_all_capabilities: true,
+            read_only_root_filesystem: true,
+        }
+    }
+}
+
+impl PodSecurityConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            run_as_non_root: env_or("K8S_POD_RUN_AS_NON_ROOT", defaults.run_as_non_root),
+            run_as_user: env_or("K8S_POD_RUN_AS_USER", defaults.run_as_user),
+            fs_group: env_or("K8S_POD_FS_GROUP", defaults.fs_group),
+            drop_all_capabilities: env_or("K8S_POD_DROP_ALL_CAPABILITIES", defaults.drop_all_capabilities),
+            read_only_root_filesystem: env_or("K8S_POD_READ_ONLY_ROOT_FILESYSTEM", defaults.read_only_root_filesystem),
+        }
+    }
+}
+
+impl Default for KubernetesConfig {
+    fn default() -> Self {
+        Self {
+            namespace_mode: NamespaceMode::PerOrganization,
+            enabled: true,
+            connect_initial_backoff: Duration::from_secs(1),
+            connect_max_backoff: Duration::from_secs(60),
+            default_redis_version: DEFAULT_REDIS_VERSION.to_string(),
+            supported_redis_versions: comma_list(DEFAULT_SUPPORTED_REDIS_VERSIONS),
+            max_concurrent_provisions: 4,
+            provision_queue_timeout: Duration::from_secs(30),
+            pod_security: PodSecurityConfig::default(),
+            tls_issuer: TlsIssuer::default(),
+        }
+    }
+}
+
+impl KubernetesConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let namespace_mode = match std::env::var("K8S_NAMESPACE_MODE").as_deref() {
+            Ok("shared") => NamespaceMode::Shared(
+                std::env::var("K8S_SHARED_NAMESPACE").unwrap_or_else(|_| "redisgate-instances".to_string()),
+            ),
+            _ => defaults.namespace_mode,
+        };
+        Self {
+            namespace_mode,
+            enabled: env_or("K8S_ENABLED", defaults.enabled),
+            connect_initial_backoff: Duration::from_secs(
+                env_or("K8S_CONNECT_INITIAL_BACKOFF_SECS", defaults.connect_initial_backoff.as_secs()).max(1),
+            ),
+            connect_max_backoff: Duration::from_secs(
+                env_or("K8S_CONNECT_MAX_BACKOFF_SECS", defaults.connect_max_backoff.as_secs()).max(1),
+            ),
+            default_redis_version: std::env::var("K8S_DEFAULT_REDIS_VERSION").unwrap_or(defaults.default_redis_version),
+            supported_redis_versions: std::env::var("K8S_SUPPORTED_REDIS_VERSIONS")
+                .map(|versions| comma_list(&versions))
+                .unwrap_or(defaults.supported_redis_versions),
+            max_concurrent_provisions: env_or("K8S_MAX_CONCURRENT_PROVISIONS", defaults.max_concurrent_provisions).max(1),
+            provision_queue_timeout: Duration::from_secs(
+                env_or("K8S_PROVISION_QUEUE_TIMEOUT_SECS", defaults.provision_queue_timeout.as_secs()),
+            ),
+            pod_security: PodSecurityConfig::from_env(),
+            tls_issuer: TlsIssuer::from_env(),
+        }
+    }
+
+    // Check the shared namespace and Redis versions at startup rather than on the first instance creation
+    pub fn validate(&self) -> Result<(), String> {
+        if let NamespaceMode::Shared(namespace) = &self.namespace_mode {
+            if !is_dns_label(namespace) {
+                return Err(format!("Shared namespace '{}' is not a valid namespace name", namespace));
+            }
+        }
+        if let Some(version) = self.supported_redis_versions.iter().find(|version| !is_redis_version(version)) {
+            return Err(format!("Supported Redis version '{}' is not a valid version tag", version));
+        }
+        if self.pod_security.run_as_non_root && self.pod_security.run_as_user == 0 {
+            return Err("K8S_POD_RUN_AS_USER cannot be 0 (root) while K8S_POD_RUN_AS_NON_ROOT is on".to_string());
+        }
+        if self.pod_security.run_as_user < 0 || self.pod_security.fs_group < 0 {
+            return Err("K8S_POD_RUN_AS_USER and K8S_POD_FS_GROUP must not be negative".to_string());
+        }
+        if !["ClusterIssuer", "Issuer"].contains(&self.tls_issuer.kind.as_str()) {
+            return Err(format!("K8S_TLS_ISSUER_KIND must be ClusterIssuer or Issuer, not '{}'", self.tls_issuer.kind));
+        }
+        if !self.supports_redis_version(&self.default_redis_version) {
+            return Err(format!(
+                "Default Redis version '{}' is not one of the supported versions",
+                self.default_redis_version
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn supports_redis_version(&self, version: &str) -> bool {
+        self.supported_redis_versions.iter().any(|supported| supported == version)
+    }
+}
+
+// Settings for the background Redis instance health checks
+#[derive(Debug, Clone)]
+pub struct HealthCheckConfig {
+    pub interval: Duration,
+    pub failure_threshold: u32, // Consecutive failed pings before an instance is marked unhealthy
+}
+
+impl HealthCheckConfig {
+    pub fn from_env() -> Self {
+        Self {
+            interval: Duration::from_secs(env_or("HEALTH_CHECK_INTERVAL_SECS", 30).max(1)),
+            failure_threshold: env_or("HEALTH_CHECK_FAILURE_THRESHOLD", 3).max(1),
+        }
+    }
+}
+
+// JWT signing keys. Keyed secrets ("kid:secret") allow several to verify tokens during a
+// rotation while only the current one signs; JWT_SECRET remains for tokens without a kid.
+// RS256/ES256 use a PEM key pair instead, e.g. when an external identity provider issues tokens.
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub algorithm: String,
+    pub keys: Vec<(String, String)>,
+    pub current_kid: Option<String>, // Defaults to the last configured key
+    pub legacy_secret: Option<String>,
+    pub private_key_pem: Option<String>, // Without it, tokens can be verified but not issued
+    pub public_key_pem: Option<String>,
+}
+
+impl JwtConfig {
+    pub fn from_env() -> Self {
+        let keys: Vec<(String, String)> = env_list("JWT_KEYS", &[])
+            .into_iter()
+            .filter_map(|entry| match entry.split_once(':') {
+                Some((kid, secret)) if !kid.is_empty() && !secret.is_empty() => {
+                    Some((kid.to_string(), secret.to_string()))
+                }
+                _ => {
+                    tracing::warn!("Ignoring JWT key entry without a 'kid:secret' pair");
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            algorithm: std::env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string()),
+            current_kid: std::env::var("JWT_CURRENT_KID")
+                .ok()
+                .or_else(|| keys.last().map(|(kid, _)| kid.clone())),
+            keys,
+            legacy_secret: std::env::var("JWT_SECRET").ok(),
+            private_key_pem: read_env_file("JWT_PRIVATE_KEY_PATH"),
+            public_key_pem: read_env_file("JWT_PUBLIC_KEY_PATH"),
+        }
+    }
+}
+
+// Read the file named by an environment variable, if set
+fn read_env_file(name: &str) -> Option<String> {
+    let path = std::env::var(name).ok()?;
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Some(contents),
+        Err(e) => {
+            tracing::warn!("Failed to read {} from '{}': {}", name, path, e);
+            None
+        }
+    }
+}
+
+// Password rules enforced at registration
+#[derive(Debug, Clone)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+}
+
+impl PasswordPolicyConfig {
+    pub fn from_env() -> Self {
+        Self {
+            min_length: env_or("PASSWORD_MIN_LENGTH", 8).max(1),
+            require_digit: env_or("PASSWORD_REQUIRE_DIGIT", true),
+            require_symbol: env_or("PASSWORD_REQUIRE_SYMBOL", false),
+        }
+    }
+}
+
+// Settings for the background check that compares instances with the cluster
+#[derive(Debug, Clone)]
+pub struct DriftCheckConfig {
+    pub interval: Duration,
+}
+
+impl DriftCheckConfig {
+    pub fn from_env() -> Self {
+        Self {
+            interval: Duration::from_secs(env_or("DRIFT_CHECK_INTERVAL_SECS", 300).max(1)),
+        }
+    }
+}
+
+// Settings for the background task that follows new instances until they are ready
+#[derive(Debug, Clone)]
+pub struct ProvisioningConfig {
+    pub poll_interval: Duration,
+    pub creating_timeout: Duration, // Instances still pending after this are marked failed
+}
+
+impl ProvisioningConfig {
+    pub fn from_env() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(env_or("PROVISIONING_POLL_INTERVAL_SECS", 15).max(1)),
+            creating_timeout: Duration::from_secs(env_or("INSTANCE_CREATING_TIMEOUT_SECS", 300).max(1)),
+        }
+    }
+}
+
+// How long soft-deleted instances are kept, and listed, before the purge task removes them
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    pub window: Duration,
+    pub purge_interval: Duration,
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        Self {
+            window: Duration::from_secs(env_or("INSTANCE_RETENTION_DAYS", 7u64).min(3650) * 24 * 60 * 60),
+            purge_interval: Duration::from_secs(env_or("INSTANCE_PURGE_INTERVAL_SECS", 3600).max(1)),
+        }
+    }
+
+    /// When an instance deleted at `deleted_at` leaves the retention window
+    pub fn retained_until(&self, deleted_at: DateTime<Utc>) -> DateTime<Utc> {
+        deleted_at + self.chrono_window()
+    }
+
+    /// Instances deleted at or before this time are past the retention window
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.chrono_window()
+    }
+
+    fn chrono_window(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.window).expect("retention window is at most ten years")
+    }
+}
+
+// Delivery settings for organization status-change webhooks
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub timeout: Duration,
+    pub max_attempts: u32,
+    pub initial_backoff: Duration, // Doubled after each failed attempt, up to max_backoff
+    pub max_backoff: Duration,
+}
+
+impl WebhookConfig {
+    pub fn from_env() -> Self {
+        Self {
+            timeout: Duration::from_secs(env_or("WEBHOOK_TIMEOUT_SECS", 10).max(1)),
+            max_attempts: env_or("WEBHOOK_MAX_ATTEMPTS", 5).max(1),
+            initial_backoff: Duration::from_millis(env_or("WEBHOOK_INITIAL_BACKOFF_MS", 1000)),
+            max_backoff: Duration::from_secs(env_or("WEBHOOK_MAX_BACKOFF_SECS", 60)),
+        }
+    }
+}
+
+// Platform operators allowed to use the /api/admin endpoints
+#[derive(Debug, Clone)]
+pub struct AdminConfig {
+    pub emails: Vec<String>,
+}
+
+impl AdminConfig {
+    pub fn from_env() -> Self {
+        Self {
+            emails: env_list("PLATFORM_ADMIN_EMAILS", &[]),
+        }
+    }
+
+    pub fn is_admin(&self, email: &str) -> bool {
+        self.emails.iter().any(|admin| admin.eq_ignore_ascii_case(email))
+    }
+}
+
+// Settings the application state is built from
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub redis: RedisHandlerConfig,
+    pub domain: DomainConfig,
+    pub admin: AdminConfig,
+    pub kubernetes: KubernetesConfig,
+    pub webhook: WebhookConfig,
+    pub retention: RetentionConfig,
+}
+
+impl AppConfig {
+    pub fn from_env() -> Self {
+        Self {
+            redis: RedisHandlerConfig::from_env(),
+            domain: DomainConfig::from_env(),
+            admin: AdminConfig::from_env(),
+            kubernetes: KubernetesConfig::from_env(),
+            webhook: WebhookConfig::from_env(),
+            retention: RetentionConfig::from_env(),
+        }
+    }
+}
+
+// Log output: human-readable lines, or one JSON object per line for log aggregators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, ()> {
+        match value.to_ascii_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    pub format: LogFormat, // Defaults to pretty in debug builds and JSON in release builds
+}
+
+impl LogConfig {
+    pub fn from_env() -> Self {
+        let default = if cfg!(debug_assertions) { LogFormat::Pretty } else { LogFormat::Json };
+        Self {
+            format: env_or("LOG_FORMAT", default),
+        }
+    }
+}
+
+// Settings for the HTTP server itself
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub shutdown_grace_period: Duration,
+    pub request_timeout: Duration, // Requests still running after this get 408, except streaming routes
+    pub tcp_enabled: bool, // Listen on 0.0.0.0:3000
+    pub unix_socket: Option<PathBuf>, // Also or instead listen here, for sidecars sharing a pod with the app
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            shutdown_grace_period: Duration::from_secs(30),
+            request_timeout: Duration::from_secs(60),
+            tcp_enabled: true,
+            unix_socket: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            shutdown_grace_period: Duration::from_secs(
+                env_or("SHUTDOWN_GRACE_PERIOD_SECS", defaults.shutdown_grace_period.as_secs()),
+            ),
+            request_timeout: Duration::from_secs(env_or("REQUEST_TIMEOUT_SECS", defaults.request_timeout.as_secs()).max(1)),
+            tcp_enabled: env_or("SERVER_TCP_ENABLED", defaults.tcp_enabled),
+            unix_socket: std::env::var("SERVER_UNIX_SOCKET").ok().filter(|path| !path.is_empty()).map(PathBuf::from),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.unix_socket.is_some() && !cfg!(unix) {
+            return Err("SERVER_UNIX_SOCKET is only supported on Unix platforms".to_string());
+        }
+        if !self.tcp_enabled && self.unix_socket.is_none() {
+            return Err("SERVER_TCP_ENABLED=false requires SERVER_UNIX_SOCKET to be set".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Cross-origin request settings
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub origins: Vec<String>, // "*" allows any origin
+    pub methods: Vec<String>,
+    pub headers: Vec<String>,
+}
+
+impl CorsConfig {
+    pub fn from_env() -> Self {
+        Self {
+            origins: env_list("CORS_ORIGINS", &[]),
+            methods: env_list("CORS_METHODS", &["GET", "POST", "PUT", "DELETE", "OPTIONS"]),
+            headers: env_list(
+                "CORS_HEADERS",
+                &["authorization", "content-type", "if-match", "idempotency-key", "x-command-timeout-ms", "x-request-id"],
+            ),
+        }
+    }
+
+    // Build the CORS layer. Without configured origins, debug builds allow any origin
+    // and release builds send no CORS headers, so browsers block cross-origin requests.
+    pub fn layer(&self) -> CorsLayer {
+        if self.origins.is_empty() {
+            return if cfg!(debug_assertions) {
+                CorsLayer::permissive()
+            } else {
+                CorsLayer::new()
+            };
+        }
+
+        let allow_origin = if self.origins.iter().any(|origin| origin == "*") {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(parse_all::<HeaderValue>("origin", &self.origins))
+        };
+
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(parse_all::<Method>("method", &self.methods))
+            .allow_headers(parse_all::<HeaderName>("header", &self.headers))
+    }
+}
+
+// Parse configured values, skipping (and logging) ones that are invalid
+fn parse_all<T: FromStr>(kind: &str, values: &[String]) -> Vec<T> {
+    values
+        .iter()
+        .filter_map(|value| {
+            let parsed = value.parse().ok();
+            if parsed.is_none() {
+                tracing::warn!("Ignoring invalid CORS {} '{}'", kind, value);
+            }
+            parsed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comma_list() {
+        assert_eq!(
+            comma_list(" https://a.example, ,https://b.example "),
+            vec!["https://a.example", "https://b.example"]
+        );
+        assert!(comma_list("").is_empty());
+    }
+
+    #[test]
+    fn test_log_format() {
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert_eq!("Pretty".parse(), Ok(LogFormat::Pretty));
+        assert_eq!("compact".parse::<LogFormat>(), Err(()));
+    }
+
+    #[test]
+    fn test_domain_template() {
+        let config = DomainConfig {
+            template: "{slug}.{org}.redis.example.com".to_string(),
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.render("cache", "acme").as_deref(), Some("cache.acme.redis.example.com"));
+        assert_eq!(config.render("-cache", "acme"), None);
+        assert_eq!(config.render(&"a".repeat(64), "acme"), None);
+
+        let missing_slug = DomainConfig {
+            template: "{org}.redis.example.com".to_string(),
+        };
+        assert!(missing_slug.validate().is_err());
+        let invalid = DomainConfig {
+            template: "{slug}_{org}.redis.example.com".to_string(),
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_shared_namespace_must_be_dns_label() {
+        let shared = |namespace: &str| KubernetesConfig {
+            namespace_mode: NamespaceMode::Shared(namespace.to_string()),
+            ..KubernetesConfig::default()
+        };
+        assert!(shared("redisgate-instances").validate().is_ok());
+        assert!(shared("Redis_Instances").validate().is_err());
+        let per_organization = KubernetesConfig {
+            namespace_mode: NamespaceMode::PerOrganization,
+            ..KubernetesConfig::default()
+        };
+        assert!(per_organization.validate().is_ok());
+    }
+
+    #[test]
+    fn test_is_redis_version() {
+        assert!(is_redis_version("7.2"));
+        assert!(is_redis_version("7.2.4"));
+        assert!(is_redis_version("6.2-alpine"));
+        assert!(!is_redis_version("latest"));
+        assert!(!is_redis_version("7"));
+        assert!(!is_redis_version("7.2@sha256:abc"));
+        assert!(!is_redis_version("7.2; rm -rf /"));
+        assert!(!is_redis_version("evil.io/redis:7.2"));
+    }
+
+    #[test]
+    fn test_redis_versions_are_validated() {
+        let versions = |default: &str, supported: &[&str]| KubernetesConfig {
+            default_redis_version: default.to_string(),
+            supported_redis_versions: supported.iter().map(|version| version.to_string()).collect(),
+            ..KubernetesConfig::default()
+        };
+        assert!(versions("7.2", &["7.0", "7.2"]).validate().is_ok());
+        assert!(versions("7.4", &["7.0", "7.2"]).validate().is_err());
+        assert!(versions("7.2", &["7.2", "latest"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_pod_security_is_validated() {
+        let security = |run_as_non_root: bool, run_as_user: i64| KubernetesConfig {
+            pod_security: PodSecurityConfig {
+                run_as_non_root,
+                run_as_user,
+                ..PodSecurityConfig::default()
+            },
+            ..KubernetesConfig::default()
+        };
+        assert!(security(true, 999).validate().is_ok());
+        assert!(security(true, 0).validate().is_err());
+        assert!(security(false, 0).validate().is_ok());
+        assert!(security(false, -1).validate().is_err());
+    }
+
+    #[test]
+    fn test_tls_issuer_kind_is_validated() {
+        let issuer = |kind: &str| KubernetesConfig {
+            tls_issuer: TlsIssuer {
+                kind: kind.to_string(),
+                ..TlsIssuer::default()
+            },
+            ..KubernetesConfig::default()
+        };
+        assert!(issuer("ClusterIssuer").validate().is_ok());
+        assert!(issuer("Issuer").validate().is_ok());
+        assert!(issuer("SelfSigned").validate().is_err());
+    }
+
+    #[test]
+    fn test_server_listeners_are_validated() {
+        let listeners = |tcp_enabled: bool, unix_socket: Option<&str>| ServerConfig {
+            tcp_enabled,
+            unix_socket: unix_socket.map(PathBuf::from),
+            ..ServerConfig::default()
+        };
+        assert!(listeners(true, None).validate().is_ok());
+        assert!(listeners(false, None).validate().is_err());
+        assert_eq!(listeners(false, Some("/tmp/gateway.sock")).validate().is_ok(), cfg!(unix));
+    }
+
+    #[test]
+    fn test_retained_until() {
+        let config = RetentionConfig {
+            window: Duration::from_secs(2 * 24 * 60 * 60),
+            purge_interval: Duration::from_secs(3600),
+        };
+        let deleted_at = DateTime::parse_from_rfc3339("2025-09-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(config.retained_until(deleted_at).to_rfc3339(), "2025-09-03T12:00:00+00:00");
+        assert_eq!(config.cutoff(config.retained_until(deleted_at)), deleted_at);
+    }
+}
@@ -0,0 +1,161 @@
+// Detection of drift between the redis_instances table and the Kubernetes cluster
+
+use chrono::Utc;
+use futures::StreamExt;
+use kube::Error as KubeError;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api_models::{DriftReport, MissingDeployment, OrphanedDeployment};
+use crate::config::DriftCheckConfig;
+use crate::k8s_service::{K8sRedisService, KubernetesClient, ManagedDeployment};
+
+// Deployment lookups run at the same time
+const MAX_CONCURRENT_LOOKUPS: usize = 16;
+
+#[derive(Debug)]
+pub enum DriftError {
+    Database(sqlx::Error),
+    Kubernetes(KubeError),
+}
+
+impl fmt::Display for DriftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriftError::Database(e) => write!(f, "database error: {}", e),
+            DriftError::Kubernetes(e) => write!(f, "Kubernetes error: {}", e),
+        }
+    }
+}
+
+/// Run the drift check forever at the configured interval, logging what it finds
+pub async fn run(pool: PgPool, kubernetes: Arc<KubernetesClient>, config: DriftCheckConfig) {
+    let mut interval = tokio::time::interval(config.interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        // Without a cluster (e.g. local development) there is nothing to compare
+        let Ok(k8s_service) = kubernetes.service() else {
+            continue;
+        };
+
+        match detect(&pool, &k8s_service).await {
+            Ok(report) => {
+                for missing in &report.missing_deployments {
+                    tracing::warn!(
+                        "Redis instance {} is marked running but has no deployment in namespace {}",
+                        missing.instance_id,
+                        missing.namespace
+                    );
+                }
+                for orphan in &report.orphaned_deployments {
+                    tracing::warn!("Deployment {}/{} has no matching Redis instance", orphan.namespace, orphan.name);
+                }
+            }
+            Err(e) => tracing::warn!("Drift check failed: {}", e),
+        }
+    }
+}
+
+/// Compare instances and cluster deployments.
+///
+/// Instances marked `running` are missing when their deployment cannot be found, and
+/// deployments labeled `created-by=redisgate` are orphaned when no live instance owns them.
+pub async fn detect(pool: &PgPool, k8s_service: &K8sRedisService) -> Result<DriftReport, DriftError> {
+    let running = sqlx::query!(
+        "SELECT id, organization_id, slug, namespace FROM redis_instances WHERE deleted_at IS NULL AND status = 'running'"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DriftError::Database)?;
+
+    let lookups: Vec<_> = running
+        .into_iter()
+        .map(|instance| async move {
+            let found = k8s_service
+                .get_deployment_status(&instance.namespace, &instance.slug)
+                .await
+                .map(|status| status != "failed");
+            (instance, found)
+        })
+        .collect();
+    let lookups: Vec<_> = futures::stream::iter(lookups)
+        .buffer_unordered(MAX_CONCURRENT_LOOKUPS)
+        .collect()
+        .await;
+
+    let mut missing_deployments = Vec::new();
+    for (instance, found) in lookups {
+        if !found.map_err(DriftError::Kubernetes)? {
+            missing_deployments.push(MissingDeployment {
+                instance_id: instance.id,
+                organization_id: instance.organization_id,
+                slug: instance.slug,
+                namespace: instance.namespace,
+            });
+        }
+    }
+
+    let live_ids: HashSet<Uuid> = sqlx::query_scalar!("SELECT id FROM redis_instances WHERE deleted_at IS NULL")
+        .fetch_all(pool)
+        .await
+        .map_err(DriftError::Database)?
+        .into_iter()
+        .collect();
+    let deployments = k8s_service
+        .list_managed_deployments()
+        .await
+        .map_err(DriftError::Kubernetes)?;
+
+    Ok(DriftReport {
+        missing_deployments,
+        orphaned_deployments: orphaned(deployments, &live_ids),
+        checked_at: Utc::now(),
+    })
+}
+
+// Deployments without an instance-id label, or whose instance is gone
+fn orphaned(deployments: Vec<ManagedDeployment>, live_ids: &HashSet<Uuid>) -> Vec<OrphanedDeployment> {
+    deployments
+        .into_iter()
+        .filter(|deployment| !deployment.instance_id.is_some_and(|id| live_ids.contains(&id)))
+        .map(|deployment| OrphanedDeployment {
+            namespace: deployment.namespace,
+            name: deployment.name,
+            instance_id: deployment.instance_id,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orphaned_deployments() {
+        let live = Uuid::new_v4();
+        let deleted = Uuid::new_v4();
+        let deployment = |name: &str, instance_id: Option<Uuid>| ManagedDeployment {
+            namespace: "redis-acme".to_string(),
+            name: name.to_string(),
+            instance_id,
+        };
+
+        let orphans = orphaned(
+            vec![
+                deployment("redis-live", Some(live)),
+                deployment("redis-deleted", Some(deleted)),
+                deployment("redis-unlabeled", None),
+            ],
+            &HashSet::from([live]),
+        );
+
+        let names: Vec<&str> = orphans.iter().map(|orphan| orphan.name.as_str()).collect();
+        assert_eq!(names, vec!["redis-deleted", "redis-unlabeled"]);
+    }
+}
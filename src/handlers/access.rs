@@ -0,0 +1,48 @@
+// Organization access checks shared by the management handlers
+
+use axum::{http::StatusCode, response::Json};
+use uuid::Uuid;
+
+use crate::api_models::{ApiResponse, ErrorCode};
+use crate::middleware::{AppState, CurrentUser};
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+// Check the user is an admin or owner of the organization; `action` completes the 403 message
+pub(crate) async fn require_org_admin(
+    state: &AppState,
+    current_user: &CurrentUser,
+    org_id: Uuid,
+    action: &str,
+) -> Result<(), ErrorResponse> {
+    let org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    if !["admin", "owner"].contains(&org_membership.role.as_str()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, format!("Only organization admins can {}", action))),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// Platform administration handlers, limited to the operators in PLATFORM_ADMIN_EMAILS
+
+use axum::{
+    extract::{Extension, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Utc;
+use sqlx::{FromRow, Row};
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::api_models::{
+    AdminInstanceListParams, AdminRedisInstanceResponse, ApiResponse, DriftReport, ErrorCode, PaginatedResponse, PoolHealthReport,
+};
+use crate::drift::{self, DriftError};
+use crate::handlers::redis_instances::redis_instance_to_response;
+use crate::middleware::{AppState, CurrentUser};
+use crate::models::RedisInstance;
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+fn require_admin(state: &AppState, current_user: &CurrentUser) -> Result<(), ErrorResponse> {
+    if state.admin_config.is_admin(&current_user.email) {
+        return Ok(());
+    }
+    Err((
+        StatusCode::FORBIDDEN,
+        Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Platform admin access required".to_string())),
+    ))
+}
+
+pub async fn get_drift(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<ApiResponse<DriftReport>>, ErrorResponse> {
+    require_admin(&state, &current_user)?;
+
+    let k8s_service = state.kubernetes.service().map_err(|e| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Kubernetes not available: {}", e))),
+        )
+    })?;
+
+    let report = drift::detect(&state.db_pool, &k8s_service).await.map_err(|e| match e {
+        DriftError::Database(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        ),
+        DriftError::Kubernetes(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Kubernetes error: {}", e))),
+        ),
+    })?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+pub async fn get_pool_health(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<ApiResponse<PoolHealthReport>>, ErrorResponse> {
+    require_admin(&state, &current_user)?;
+
+    let pools = state.redis_pools.stats();
+    Ok(Json(ApiResponse::success(PoolHealthReport {
+        waiting: pools.iter().map(|pool| pool.waiting).sum(),
+        pools,
+        checked_at: Utc::now(),
+    })))
+}
+
+// Every live instance across all organizations, newest first
+pub async fn list_all_redis_instances(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<AdminInstanceListParams>,
+) -> Result<Json<ApiResponse<PaginatedResponse<AdminRedisInstanceResponse>>>, ErrorResponse> {
+    require_admin(&state, &current_user)?;
+    if let Err(errors) = params.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let page = params.page.unwrap_or(1);
+    let limit = params.limit.unwrap_or(20);
+    let offset = (page - 1) * limit;
+    let database_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT r.*, o.name AS organization_name, o.slug AS organization_slug
+        FROM redis_instances r
+        JOIN organizations o ON o.id = r.organization_id
+        WHERE r.deleted_at IS NULL AND ($1::TEXT IS NULL OR r.status = $1)
+        ORDER BY r.created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(&params.status)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(database_error)?;
+
+    let total_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM redis_instances WHERE deleted_at IS NULL AND ($1::TEXT IS NULL OR status = $1)",
+    )
+    .bind(&params.status)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(database_error)?;
+
+    let items = rows
+        .iter()
+        .map(|row| {
+            Ok(AdminRedisInstanceResponse {
+                redis_instance: redis_instance_to_response(RedisInstance::from_row(row)?, &state.kubernetes_config.default_redis_version),
+                organization_name: row.try_get("organization_name")?,
+                organization_slug: row.try_get("organization_slug")?,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+        .map_err(database_error)?;
+
+    let total_pages = ((total_count as f64) / (limit as f64)).ceil() as u32;
+
+    Ok(Json(ApiResponse::success(PaginatedResponse {
+        items,
+        total_count,
+        page,
+        limit,
+        total_pages,
+    })))
+}
@@ -2,24 +2,65 @@
 
 use axum::{
     extract::{Extension, Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
 use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::PgExecutor;
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::api_models::{
-    ApiKeyCreationResponse, ApiKeyResponse, ApiResponse, CreateApiKeyRequest, PaginatedResponse,
-    PaginationParams,
+    ApiKeyCreationResponse, ApiKeyResponse, ApiKeyScopesResponse, ApiResponse, CreateApiKeyRequest, ErrorCode,
+    PaginatedResponse, PaginationParams, RevokeApiKeysResponse, UpdateApiKeyScopesRequest,
 };
+use crate::audit::{self, AuditEvent};
 use crate::auth::{ApiKeyClaims};
+use crate::handlers::access::require_org_admin;
+use crate::idempotency::IdempotencyKey;
 use crate::middleware::{AppState, CurrentUser};
-use crate::models::ApiKey;
+use crate::models::{ApiKey, Plan};
 
 type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
 
+/// API keys of an organization to revoke
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RevokedKeys {
+    /// A single key
+    Key(Uuid),
+    /// Every key created by a user
+    CreatedBy(Uuid),
+}
+
+/// Deactivate an organization's active API keys, returning the ids of the keys revoked
+pub(crate) async fn revoke_api_keys<'e, E: PgExecutor<'e>>(
+    executor: E,
+    organization_id: Uuid,
+    keys: RevokedKeys,
+    now: DateTime<Utc>,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let (key_id, user_id) = match keys {
+        RevokedKeys::Key(key_id) => (Some(key_id), None),
+        RevokedKeys::CreatedBy(user_id) => (None, Some(user_id)),
+    };
+    // Exactly one of $3 and $4 is set; comparing against the NULL one matches nothing
+    sqlx::query_scalar!(
+        r#"
+        UPDATE api_keys SET is_active = false, updated_at = $1
+        WHERE organization_id = $2 AND is_active = true AND (id = $3 OR user_id = $4)
+        RETURNING id
+        "#,
+        now,
+        organization_id,
+        key_id,
+        user_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
 // Helper function to convert ApiKey to ApiKeyResponse
 fn api_key_to_response(api_key: ApiKey) -> ApiKeyResponse {
     ApiKeyResponse {
@@ -31,10 +72,61 @@ fn api_key_to_response(api_key: ApiKey) -> ApiKeyResponse {
         last_used_at: api_key.last_used_at,
         is_active: api_key.is_active.unwrap_or(true),
         expires_at: api_key.expires_at,
-        created_at: api_key.created_at.unwrap_or_else(|| Utc::now()),
+        created_at: api_key.created_at.unwrap_or_else(Utc::now),
     }
 }
 
+// How much a scope grants; a higher scope covers every lower one, as the gateway enforces them
+fn scope_rank(scope: &str) -> Option<u8> {
+    match scope {
+        "read" => Some(1),
+        "write" => Some(2),
+        "admin" => Some(3),
+        _ => None,
+    }
+}
+
+// Scopes of a new key: the requested ones, or the organization's defaults when none were
+// requested. With a policy in place, no requested scope may grant more than the policy does.
+fn resolve_api_key_scopes(
+    requested: Option<&[String]>,
+    policy: Option<&[String]>,
+) -> Result<Vec<String>, ErrorResponse> {
+    let Some(requested) = requested else {
+        return policy.map(<[String]>::to_vec).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::ValidationError,
+                    "scopes are required when the organization has no default API key scopes".to_string(),
+                )),
+            )
+        });
+    };
+
+    if let Some(policy) = policy {
+        let ceiling = policy.iter().filter_map(|scope| scope_rank(scope)).max();
+        let exceeding: Vec<&str> = requested
+            .iter()
+            .filter(|scope| match (scope_rank(scope), ceiling) {
+                (Some(rank), Some(ceiling)) => rank > ceiling,
+                _ => !policy.contains(scope),
+            })
+            .map(String::as_str)
+            .collect();
+        if !exceeding.is_empty() {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::Forbidden,
+                    format!("Scopes exceed the organization's API key policy: {}", exceeding.join(", ")),
+                )),
+            ));
+        }
+    }
+    Ok(requested.to_vec())
+}
+
 // Generate a JWT-based API key
 fn generate_api_key_jwt(
     state: &AppState, 
@@ -67,13 +159,14 @@ fn generate_api_key_jwt(
 pub async fn create_api_key(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
+    headers: HeaderMap,
     Json(payload): Json<CreateApiKeyRequest>,
 ) -> Result<Json<ApiResponse<ApiKeyCreationResponse>>, ErrorResponse> {
     // Validate input
     if let Err(errors) = payload.validate() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error(format!("Validation error: {:?}", errors))),
+            Json(ApiResponse::<()>::validation_error(&errors)),
         ));
     }
 
@@ -91,16 +184,44 @@ pub async fn create_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
+    // Return the original key when a request is retried with the same Idempotency-Key
+    let idempotency_key = IdempotencyKey::from_headers(&headers, current_user.id, "create_api_key", &payload)?;
+    if let Some(key) = &idempotency_key {
+        if let Some(api_key_id) = key.replayed_resource(&state.db_pool).await? {
+            let created_key = sqlx::query_as!(
+                ApiKey,
+                r#"SELECT id, name, key_token, key_prefix, user_id, organization_id, scopes, 
+                  last_used_at, last_used_ip, is_active, expires_at, created_at, updated_at 
+           FROM api_keys WHERE id = $1"#,
+                api_key_id
+            )
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch created API key: {}", e))),
+                )
+            })?;
+
+            let key_token = created_key.key_token.clone();
+            return Ok(Json(ApiResponse::success(ApiKeyCreationResponse {
+                api_key: api_key_to_response(created_key),
+                key: key_token,
+            })));
+        }
+    }
+
     // Check if organization has reached API key limit
     let api_key_count = sqlx::query!(
         "SELECT COUNT(*) as count FROM api_keys WHERE organization_id = $1 AND is_active = true",
@@ -111,14 +232,14 @@ pub async fn create_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .count
     .unwrap_or(0);
 
     let org_limits = sqlx::query!(
-        "SELECT max_api_keys FROM organizations WHERE id = $1",
+        "SELECT plan, max_api_keys FROM organizations WHERE id = $1",
         payload.organization_id
     )
     .fetch_one(&state.db_pool)
@@ -126,17 +247,35 @@ pub async fn create_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
-    if api_key_count >= org_limits.max_api_keys.unwrap_or(10) as i64 {
+    let max_api_keys = org_limits
+        .max_api_keys
+        .unwrap_or_else(|| Plan::from_db(org_limits.plan.as_deref()).max_api_keys());
+
+    if api_key_count >= max_api_keys as i64 {
         return Err((
             StatusCode::CONFLICT,
-            Json(ApiResponse::<()>::error("Organization has reached the maximum number of API keys".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::QuotaExceeded, "Organization has reached the maximum number of API keys".to_string())),
         ));
     }
 
+    let policy = sqlx::query_scalar!(
+        "SELECT default_scopes FROM organization_api_key_policies WHERE organization_id = $1",
+        payload.organization_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?;
+    let scopes = resolve_api_key_scopes(payload.scopes.as_deref(), policy.as_deref())?;
+
     // Generate API key JWT token
     let api_key_id = Uuid::new_v4();
     let (api_key_token, key_prefix) = generate_api_key_jwt(
@@ -144,12 +283,12 @@ pub async fn create_api_key(
         api_key_id,
         current_user.id,
         payload.organization_id,
-        payload.scopes.clone(),
+        scopes.clone(),
         payload.expires_at,
     ).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Key generation error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::InternalError, format!("Key generation error: {}", e))),
         )
     })?;
 
@@ -167,7 +306,7 @@ pub async fn create_api_key(
         key_prefix,
         current_user.id,
         payload.organization_id,
-        &payload.scopes,
+        &scopes,
         payload.expires_at,
         now,
         now
@@ -177,7 +316,7 @@ pub async fn create_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to create API key: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to create API key: {}", e))),
         )
     })?;
 
@@ -194,10 +333,16 @@ pub async fn create_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to fetch created API key: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch created API key: {}", e))),
         )
     })?;
 
+    if let Some(key) = &idempotency_key {
+        if let Err(e) = key.store(&state.db_pool, api_key_id).await {
+            tracing::warn!("Failed to store idempotency key: {}", e);
+        }
+    }
+
     let api_key_response = api_key_to_response(created_key);
 
     let creation_response = ApiKeyCreationResponse {
@@ -228,13 +373,13 @@ pub async fn list_api_keys(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
@@ -262,7 +407,7 @@ pub async fn list_api_keys(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
@@ -276,7 +421,7 @@ pub async fn list_api_keys(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .count
@@ -319,13 +464,13 @@ pub async fn get_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
@@ -343,13 +488,13 @@ pub async fn get_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("API key not found".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "API key not found".to_string())),
         )
     })?;
 
@@ -377,13 +522,13 @@ pub async fn revoke_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
@@ -398,13 +543,13 @@ pub async fn revoke_api_key(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("API key not found".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "API key not found".to_string())),
         )
     })?;
 
@@ -412,31 +557,242 @@ pub async fn revoke_api_key(
     if api_key.user_id != current_user.id && !["admin", "owner"].contains(&org_membership.role.as_str()) {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ApiResponse::<()>::error("Insufficient permissions to revoke this API key".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Insufficient permissions to revoke this API key".to_string())),
         ));
     }
 
     let now = Utc::now();
 
     // Revoke API key (soft delete)
-    sqlx::query!(
-        "UPDATE api_keys SET is_active = false, updated_at = $1 WHERE id = $2",
-        now,
-        key_id
+    revoke_api_keys(&state.db_pool, org_id, RevokedKeys::Key(key_id), now)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to revoke API key: {}", e))),
+            )
+        })?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("API key revoked successfully".to_string()),
+        code: None,
+        errors: None,
+        timestamp: Utc::now(),
+    }))
+}
+
+// Revoke every active API key a member owns in the organization, e.g. when offboarding them
+pub async fn revoke_member_api_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<RevokeApiKeysResponse>>, ErrorResponse> {
+    // Check if user has access to the organization
+    let org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
     )
-    .execute(&state.db_pool)
+    .fetch_optional(&state.db_pool)
     .await
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to revoke API key: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
-    Ok(Json(ApiResponse {
-        success: true,
-        data: None,
-        message: Some("API key revoked successfully".to_string()),
-        timestamp: Utc::now(),
-    }))
-}
\ No newline at end of file
+    if !["admin", "owner"].contains(&org_membership.role.as_str()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Insufficient permissions to revoke member API keys".to_string())),
+        ));
+    }
+
+    let db_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to revoke API keys: {}", e))),
+        )
+    };
+
+    let mut tx = state.db_pool.begin().await.map_err(db_error)?;
+
+    // The member may already have left the organization, so only their keys are matched
+    let key_ids = revoke_api_keys(&mut *tx, org_id, RevokedKeys::CreatedBy(user_id), Utc::now())
+        .await
+        .map_err(db_error)?;
+
+    audit::record(
+        &mut *tx,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "revoke_member_api_keys",
+            resource_type: "user",
+            resource_id: Some(user_id),
+            details: json!({ "api_key_ids": key_ids }),
+        },
+    )
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Revoked {} API keys for user {} in organization {}", key_ids.len(), user_id, org_id);
+
+    Ok(Json(ApiResponse::success(RevokeApiKeysResponse { revoked: key_ids.len() })))
+}
+
+// Organizations without a policy leave the scopes of new keys to each request
+pub async fn get_api_key_scopes(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ApiKeyScopesResponse>>, ErrorResponse> {
+    require_org_admin(&state, &current_user, org_id, "manage API key scopes").await?;
+
+    let policy = sqlx::query!(
+        "SELECT default_scopes, updated_at FROM organization_api_key_policies WHERE organization_id = $1",
+        org_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?;
+
+    let (default_api_key_scopes, updated_at) = policy.map(|policy| (policy.default_scopes, policy.updated_at)).unzip();
+    Ok(Json(ApiResponse::success(ApiKeyScopesResponse {
+        organization_id: org_id,
+        default_api_key_scopes,
+        updated_at,
+    })))
+}
+
+// Set the scopes new keys get when they request none, which also cap the scopes any new key
+// may request. Existing keys keep their scopes. Setting null removes the policy.
+pub async fn update_api_key_scopes(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+    Json(payload): Json<UpdateApiKeyScopesRequest>,
+) -> Result<Json<ApiResponse<ApiKeyScopesResponse>>, ErrorResponse> {
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    require_org_admin(&state, &current_user, org_id, "manage API key scopes").await?;
+
+    let db_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    };
+
+    let updated_at = match &payload.default_api_key_scopes {
+        Some(scopes) => {
+            let updated_at = sqlx::query_scalar!(
+                r#"
+                INSERT INTO organization_api_key_policies (organization_id, default_scopes)
+                VALUES ($1, $2)
+                ON CONFLICT (organization_id) DO UPDATE
+                SET default_scopes = EXCLUDED.default_scopes, updated_at = NOW()
+                RETURNING updated_at
+                "#,
+                org_id,
+                scopes
+            )
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(db_error)?;
+            Some(updated_at)
+        }
+        None => {
+            sqlx::query!("DELETE FROM organization_api_key_policies WHERE organization_id = $1", org_id)
+                .execute(&state.db_pool)
+                .await
+                .map_err(db_error)?;
+            None
+        }
+    };
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "update_api_key_scopes",
+            resource_type: "organization",
+            resource_id: Some(org_id),
+            details: json!({ "default_api_key_scopes": payload.default_api_key_scopes }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record API key scope change in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(ApiKeyScopesResponse {
+        organization_id: org_id,
+        default_api_key_scopes: payload.default_api_key_scopes,
+        updated_at,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_requested_scopes_without_policy_are_kept() {
+        let requested = scopes(&["admin", "custom"]);
+        assert_eq!(resolve_api_key_scopes(Some(&requested), None).unwrap(), requested);
+        assert_eq!(resolve_api_key_scopes(None, None).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_policy_supplies_default_scopes() {
+        let policy = scopes(&["read"]);
+        assert_eq!(resolve_api_key_scopes(None, Some(&policy)).unwrap(), policy);
+    }
+
+    #[test]
+    fn test_policy_caps_requested_scopes() {
+        let policy = scopes(&["write"]);
+        assert_eq!(
+            resolve_api_key_scopes(Some(&scopes(&["read", "write"])), Some(&policy)).unwrap(),
+            scopes(&["read", "write"])
+        );
+        assert_eq!(
+            resolve_api_key_scopes(Some(&scopes(&["admin"])), Some(&policy)).unwrap_err().0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            resolve_api_key_scopes(Some(&scopes(&["custom"])), Some(&policy)).unwrap_err().0,
+            StatusCode::FORBIDDEN
+        );
+    }
+}
@@ -1,23 +1,25 @@
 // Authentication handlers (register, login)
 
-use axum::{extract::State, http::StatusCode, response::Json};
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::Json,
+};
 use chrono::Utc;
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::api_models::{ApiResponse, LoginRequest, LoginResponse, RegisterRequest, UserResponse};
+use crate::api_models::{
+    ApiResponse, ErrorCode, CurrentUserResponse, LoginRequest, LoginResponse, RegisterRequest, UserMembershipResponse,
+    UserResponse,
+};
 use crate::auth::{hash_password, verify_password, Claims};
-use crate::middleware::AppState;
+use crate::middleware::{AppState, CurrentUser};
 use crate::models::User;
 
 type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
 
-// Helper function to create error responses
-fn error_response(status: StatusCode, message: String) -> ErrorResponse {
-    (status, Json(ApiResponse::<()>::error(message)))
-}
-
 // Helper function to convert User to UserResponse
 fn user_to_response(user: User) -> UserResponse {
     UserResponse {
@@ -28,7 +30,7 @@ fn user_to_response(user: User) -> UserResponse {
         last_name: user.last_name,
         is_active: user.is_active.unwrap_or(true),
         is_verified: user.is_verified.unwrap_or(false),
-        created_at: user.created_at.unwrap_or_else(|| Utc::now()),
+        created_at: user.created_at.unwrap_or_else(Utc::now),
     }
 }
 
@@ -40,7 +42,7 @@ pub async fn register(
     if let Err(errors) = payload.validate() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error(format!("Validation error: {:?}", errors))),
+            Json(ApiResponse::<()>::validation_error(&errors)),
         ));
     }
 
@@ -55,14 +57,14 @@ pub async fn register(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
     if existing_user.is_some() {
         return Err((
             StatusCode::CONFLICT,
-            Json(ApiResponse::<()>::error("User already exists with this email or username".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::UserExists, "User already exists with this email or username".to_string())),
         ));
     }
 
@@ -70,7 +72,7 @@ pub async fn register(
     let password_hash = hash_password(&payload.password).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Password hashing error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::InternalError, format!("Password hashing error: {}", e))),
         )
     })?;
 
@@ -97,7 +99,7 @@ pub async fn register(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to create user: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to create user: {}", e))),
         )
     })?;
 
@@ -108,7 +110,7 @@ pub async fn register(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(format!("Failed to fetch created user: {}", e))),
+                Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch created user: {}", e))),
             )
         })?;
 
@@ -125,7 +127,7 @@ pub async fn login(
     if let Err(errors) = payload.validate() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error(format!("Validation error: {:?}", errors))),
+            Json(ApiResponse::<()>::validation_error(&errors)),
         ));
     }
 
@@ -140,13 +142,13 @@ pub async fn login(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::UNAUTHORIZED,
-            Json(ApiResponse::<()>::error("Invalid credentials".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidCredentials, "Invalid credentials".to_string())),
         )
     })?;
 
@@ -154,7 +156,7 @@ pub async fn login(
     if !user.is_active.unwrap_or(false) {
         return Err((
             StatusCode::UNAUTHORIZED,
-            Json(ApiResponse::<()>::error("User account is not active".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::AccountInactive, "User account is not active".to_string())),
         ));
     }
 
@@ -162,14 +164,14 @@ pub async fn login(
     let password_valid = verify_password(&payload.password, &user.password_hash).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Password verification error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::InternalError, format!("Password verification error: {}", e))),
         )
     })?;
 
     if !password_valid {
         return Err((
             StatusCode::UNAUTHORIZED,
-            Json(ApiResponse::<()>::error("Invalid credentials".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidCredentials, "Invalid credentials".to_string())),
         ));
     }
 
@@ -183,7 +185,7 @@ pub async fn login(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .map(|row| row.organization_id);
@@ -193,7 +195,7 @@ pub async fn login(
     let token = state.jwt_manager.create_token(&claims).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Token creation failed: {:?}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::InternalError, format!("Token creation failed: {:?}", e))),
         )
     })?;
 
@@ -205,4 +207,60 @@ pub async fn login(
     };
 
     Ok(Json(ApiResponse::success(login_response)))
-}
\ No newline at end of file
+}
+
+pub async fn get_current_user(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<ApiResponse<CurrentUserResponse>>, ErrorResponse> {
+    let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", current_user.id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error(ErrorCode::NotFound, "User not found".to_string())),
+            )
+        })?;
+
+    // Get the organizations the user belongs to along with their role
+    let memberships = sqlx::query!(
+        r#"
+        SELECT o.id, o.name, o.slug, om.role
+        FROM organization_memberships om
+        INNER JOIN organizations o ON o.id = om.organization_id
+        WHERE om.user_id = $1 AND om.is_active = true
+        ORDER BY om.created_at ASC
+        "#,
+        current_user.id
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?;
+
+    let organizations = memberships
+        .into_iter()
+        .map(|row| UserMembershipResponse {
+            organization_id: row.id,
+            name: row.name,
+            slug: row.slug,
+            role: row.role,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(CurrentUserResponse {
+        user: user_to_response(user),
+        organizations,
+    })))
+}
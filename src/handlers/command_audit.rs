@@ -0,0 +1,118 @@
+// Organization command auditing settings handlers
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::api_models::{ApiResponse, CommandAuditResponse, ErrorCode, UpdateCommandAuditRequest};
+use crate::audit::{self, AuditEvent};
+use crate::handlers::access::require_org_admin;
+use crate::middleware::{AppState, CurrentUser};
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+fn database_error(e: sqlx::Error) -> ErrorResponse {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+    )
+}
+
+// Organizations that never changed their settings do not audit commands
+pub async fn get_command_audit(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<CommandAuditResponse>>, ErrorResponse> {
+    require_org_admin(&state, &current_user, org_id, "manage command auditing").await?;
+
+    let settings = sqlx::query!(
+        "SELECT enabled, sample_rate, updated_at FROM organization_command_audit WHERE organization_id = $1",
+        org_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(database_error)?;
+
+    let response = match settings {
+        Some(settings) => CommandAuditResponse {
+            organization_id: org_id,
+            enabled: settings.enabled,
+            sample_rate: settings.sample_rate,
+            updated_at: Some(settings.updated_at),
+        },
+        None => CommandAuditResponse {
+            organization_id: org_id,
+            enabled: false,
+            sample_rate: 1.0,
+            updated_at: None,
+        },
+    };
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Turn command auditing on or off. Sampled commands are written to command_audit_logs; the
+// change reaches other gateway replicas within their settings refresh interval.
+pub async fn update_command_audit(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+    Json(payload): Json<UpdateCommandAuditRequest>,
+) -> Result<Json<ApiResponse<CommandAuditResponse>>, ErrorResponse> {
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    require_org_admin(&state, &current_user, org_id, "manage command auditing").await?;
+
+    let sample_rate = payload.sample_rate.unwrap_or(1.0);
+    let updated_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO organization_command_audit (organization_id, enabled, sample_rate)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (organization_id) DO UPDATE
+        SET enabled = EXCLUDED.enabled, sample_rate = EXCLUDED.sample_rate, updated_at = NOW()
+        RETURNING updated_at
+        "#,
+        org_id,
+        payload.enabled,
+        sample_rate
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(database_error)?;
+
+    state.command_audit.invalidate(org_id);
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "update_command_audit",
+            resource_type: "organization",
+            resource_id: Some(org_id),
+            details: json!({ "enabled": payload.enabled, "sample_rate": sample_rate }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record command audit change in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(CommandAuditResponse {
+        organization_id: org_id,
+        enabled: payload.enabled,
+        sample_rate,
+        updated_at: Some(updated_at),
+    })))
+}
@@ -0,0 +1,211 @@
+// Runtime CONFIG GET/SET for Redis instances, limited to parameters that are safe to tune live
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api_models::{ApiResponse, ErrorCode, RedisConfigParams, RedisConfigResponse, UpdateRedisConfigRequest};
+use crate::audit::{self, AuditEvent};
+use crate::handlers::keyspace::{admin_instance, instance_connection, run_command};
+use crate::middleware::{AppState, CurrentUser};
+use crate::models::Plan;
+use crate::redis_pool::PooledConnection;
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+// Parameters that can be read through the API
+const READABLE_PARAMETERS: &[&str] = &["maxmemory", "maxmemory-policy", "timeout", "save"];
+
+// Parameters that can be changed through the API; persistence is managed by the platform
+const WRITABLE_PARAMETERS: &[&str] = &["maxmemory", "maxmemory-policy", "timeout"];
+
+// Same bounds as instance creation: 1MB to 16GB
+const MIN_MAX_MEMORY: i64 = 1048576;
+const MAX_MAX_MEMORY: i64 = 17179869184;
+
+fn forbidden_parameter(parameter: &str) -> ErrorResponse {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ApiResponse::<()>::error(ErrorCode::Forbidden, format!(
+            "Config parameter '{}' is not available through the API",
+            parameter
+        ))),
+    )
+}
+
+// Read the given parameters from the instance
+async fn read_parameters(
+    state: &AppState,
+    conn: &mut PooledConnection,
+    parameters: &[&str],
+) -> Result<BTreeMap<String, String>, ErrorResponse> {
+    let mut values = BTreeMap::new();
+    for parameter in parameters {
+        // CONFIG GET replies with name/value pairs
+        let pairs: Vec<(String, String)> = run_command(
+            state,
+            redis::cmd("CONFIG").arg("GET").arg(*parameter).query_async(&mut **conn),
+        )
+        .await?;
+        values.extend(pairs);
+    }
+    Ok(values)
+}
+
+pub async fn get_instance_config(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<RedisConfigParams>,
+) -> Result<Json<ApiResponse<RedisConfigResponse>>, ErrorResponse> {
+    let parameters: Vec<&str> = match &params.parameters {
+        Some(list) => list.split(',').map(str::trim).filter(|p| !p.is_empty()).collect(),
+        None => READABLE_PARAMETERS.to_vec(),
+    };
+    if let Some(parameter) = parameters.iter().find(|p| !READABLE_PARAMETERS.contains(p)) {
+        return Err(forbidden_parameter(parameter));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let mut conn = instance_connection(&state, &instance).await?;
+    let parameters = read_parameters(&state, &mut conn, &parameters).await?;
+
+    Ok(Json(ApiResponse::success(RedisConfigResponse { parameters })))
+}
+
+pub async fn update_instance_config(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateRedisConfigRequest>,
+) -> Result<Json<ApiResponse<RedisConfigResponse>>, ErrorResponse> {
+    if payload.parameters.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, "No config parameters given".to_string())),
+        ));
+    }
+    if let Some(parameter) = payload.parameters.keys().find(|p| !WRITABLE_PARAMETERS.contains(&p.as_str())) {
+        return Err(forbidden_parameter(parameter));
+    }
+
+    // maxmemory is mirrored in the database, so only accept a plain byte count
+    let max_memory = match payload.parameters.get("maxmemory") {
+        Some(value) => match value.parse::<i64>() {
+            Ok(bytes) if (MIN_MAX_MEMORY..=MAX_MAX_MEMORY).contains(&bytes) => Some(bytes),
+            _ => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, format!(
+                        "maxmemory must be a byte count between {} and {}",
+                        MIN_MAX_MEMORY, MAX_MAX_MEMORY
+                    ))),
+                ));
+            }
+        },
+        None => None,
+    };
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+
+    let db_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    };
+
+    // The database is updated in the transaction that checked the memory quota, once Redis
+    // has accepted the new values
+    let mut tx = state.db_pool.begin().await.map_err(db_error)?;
+
+    // Raising maxmemory counts against the plan's memory quota like creating an instance does.
+    // The organization row stays locked so concurrent changes cannot exceed it together.
+    if let Some(max_memory) = max_memory {
+        let plan = sqlx::query_scalar!("SELECT plan FROM organizations WHERE id = $1 FOR UPDATE", org_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        let plan = Plan::from_db(plan.as_deref());
+        let other_instances_memory = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(max_memory), 0)::BIGINT AS "total!" FROM redis_instances WHERE organization_id = $1 AND id <> $2 AND deleted_at IS NULL"#,
+            org_id,
+            instance_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        if other_instances_memory + max_memory > plan.max_total_memory() {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(ApiResponse::<()>::error(ErrorCode::QuotaExceeded, format!(
+                    "Organization memory quota exceeded: {} of {} bytes allocated to other instances on the {} plan",
+                    other_instances_memory,
+                    plan.max_total_memory(),
+                    plan.as_str()
+                ))),
+            ));
+        }
+    }
+
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    let mut cmd = redis::cmd("CONFIG");
+    cmd.arg("SET");
+    for (parameter, value) in &payload.parameters {
+        cmd.arg(parameter).arg(value);
+    }
+    // Redis rejects invalid values (e.g. an unknown eviction policy) without applying any of them
+    run_command::<()>(&state, cmd.query_async(&mut *conn))
+        .await
+        .map_err(|(status, body)| match status {
+            StatusCode::INTERNAL_SERVER_ERROR => (StatusCode::BAD_REQUEST, body),
+            _ => (status, body),
+        })?;
+
+    sqlx::query!(
+        r#"
+        UPDATE redis_instances
+        SET max_memory = COALESCE($1, max_memory),
+            eviction_policy = COALESCE($2, eviction_policy),
+            updated_at = NOW()
+        WHERE id = $3
+        "#,
+        max_memory,
+        payload.parameters.get("maxmemory-policy"),
+        instance_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "update_instance_config",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "parameters": payload.parameters }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record config change in audit log: {}", e);
+    }
+
+    let changed: Vec<&str> = payload.parameters.keys().map(String::as_str).collect();
+    let parameters = read_parameters(&state, &mut conn, &changed).await?;
+
+    Ok(Json(ApiResponse::success(RedisConfigResponse { parameters })))
+}
@@ -0,0 +1,998 @@
+// Keyspace maintenance handlers for Redis instances (bulk operations run by org admins)
+
+use axum::{
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use deadpool::managed::PoolError;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::time::Instant;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::api_models::{
+    ApiResponse, BigKey, BigKeysParams, BigKeysResponse, CommandStats, DatabaseKeyspace, ErrorCode, DeleteKeysRequest, DeleteKeysResponse, ExportKeysParams,
+    ImportKeyError, ImportKeyRecord, ImportKeysResponse, InstanceStatsResponse, KeyIdleTimeRequest, KeyIdleTimeResponse,
+    KeyTtlRequest, KeyTtlResponse, SetKeyTtlRequest, SetKeyTtlResponse,
+};
+use crate::audit::{self, AuditEvent};
+use crate::handlers::redis::parse_redis_info;
+use crate::middleware::{AppState, CurrentUser};
+use crate::models::RedisInstance;
+use crate::redis_acl::AclUser;
+use crate::redis_pool::{is_auth_error, CheckoutError, PooledConnection};
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+// Keys requested per SCAN call unless the client asks for a different batch size
+const DEFAULT_SCAN_COUNT: usize = 500;
+
+// One SCAN step; with a type, Redis filters keys server-side (Redis 6+)
+fn scan_command(cursor: u64, pattern: &str, count: usize, key_type: Option<&str>) -> redis::Cmd {
+    let mut cmd = redis::cmd("SCAN");
+    cmd.arg(cursor).arg("MATCH").arg(pattern).arg("COUNT").arg(count);
+    if let Some(key_type) = key_type {
+        cmd.arg("TYPE").arg(key_type);
+    }
+    cmd
+}
+
+// Largest keys reported and keys inspected by a big-keys scan unless the client asks otherwise
+const DEFAULT_BIG_KEYS_TOP: usize = 10;
+const DEFAULT_BIG_KEYS_SAMPLE: usize = 10000;
+
+// Per-line import errors included in the response
+const MAX_IMPORT_ERRORS: usize = 100;
+
+// Load an instance after checking the user is an admin or owner of its organization
+pub(crate) async fn admin_instance(
+    state: &AppState,
+    current_user: &CurrentUser,
+    org_id: Uuid,
+    instance_id: Uuid,
+) -> Result<RedisInstance, ErrorResponse> {
+    let org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    if !["admin", "owner"].contains(&org_membership.role.as_str()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Only organization admins can manage this instance".to_string())),
+        ));
+    }
+
+    sqlx::query_as!(
+        RedisInstance,
+        "SELECT * FROM redis_instances WHERE id = $1 AND organization_id = $2 AND deleted_at IS NULL",
+        instance_id,
+        org_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Redis instance not found".to_string())),
+        )
+    })
+}
+
+// Check out a pooled connection to the instance
+pub(crate) async fn instance_connection(state: &AppState, instance: &RedisInstance) -> Result<PooledConnection, ErrorResponse> {
+    // Management operations are limited to organization admins, who get the unrestricted user
+    state.redis_pools.connection(instance, AclUser::Admin).await.map_err(|e| match e {
+        CheckoutError::Pool(PoolError::Backend(e)) if is_auth_error(&e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::RedisCredentialMismatch,
+                "Redis credential mismatch: the instance rejected the gateway's password".to_string(),
+            )),
+        ),
+        e => {
+            tracing::warn!("Failed to get Redis connection for instance {}: {}", instance.id, e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ApiResponse::<()>::error(ErrorCode::RedisUnavailable, "Redis instance unavailable".to_string())),
+            )
+        }
+    })
+}
+
+// Run a single Redis command under the per-command timeout
+pub(crate) async fn run_command<T>(
+    state: &AppState,
+    command: impl Future<Output = redis::RedisResult<T>>,
+) -> Result<T, ErrorResponse> {
+    tokio::time::timeout(state.redis_config.command_timeout, command)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(ApiResponse::<()>::error(ErrorCode::RedisTimeout, "Redis command timed out".to_string())),
+            )
+        })?
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(ErrorCode::RedisError, format!("Redis command failed: {}", e))),
+            )
+        })
+}
+
+pub async fn delete_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<DeleteKeysRequest>,
+) -> Result<Json<ApiResponse<DeleteKeysResponse>>, ErrorResponse> {
+    // Validate input
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    // Stop after the safety budget and hand the cursor back so the client can resume
+    let deadline = Instant::now() + state.redis_config.max_command_timeout;
+    let count = payload.count.unwrap_or(DEFAULT_SCAN_COUNT);
+    let mut cursor = payload.cursor.unwrap_or(0);
+    let mut deleted: u64 = 0;
+
+    loop {
+        let (next_cursor, keys): (u64, Vec<Vec<u8>>) = run_command(
+            &state,
+            scan_command(cursor, &payload.pattern, count, payload.key_type.as_deref()).query_async(&mut *conn),
+        )
+        .await?;
+
+        if !keys.is_empty() {
+            let removed: u64 = run_command(&state, redis::cmd("UNLINK").arg(&keys).query_async(&mut *conn)).await?;
+            deleted += removed;
+        }
+
+        cursor = next_cursor;
+        if cursor == 0 || Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    tracing::info!(
+        "Deleted {} keys matching '{}' from Redis instance {}",
+        deleted,
+        payload.pattern,
+        instance_id
+    );
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "delete_keys",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "pattern": payload.pattern, "type": payload.key_type, "deleted": deleted, "cursor": cursor }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record key deletion in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(DeleteKeysResponse {
+        deleted,
+        cursor,
+        complete: cursor == 0,
+    })))
+}
+
+// Key as it appears in export records: text when it is UTF-8, base64 otherwise
+fn export_key(key: &[u8]) -> Value {
+    match std::str::from_utf8(key) {
+        Ok(key) => json!({ "key": key }),
+        Err(_) => json!({ "key": STANDARD.encode(key), "key_encoding": "base64" }),
+    }
+}
+
+// Read one key's type, TTL and DUMP payload; None when the key expired or was deleted since SCAN
+// returned it. DUMP keeps binary values and every type (including module types) intact, and
+// import restores it with RESTORE.
+async fn export_record(
+    state: &AppState,
+    conn: &mut PooledConnection,
+    key: &[u8],
+) -> Result<Option<Value>, ErrorResponse> {
+    let (key_type, pttl, dump): (String, i64, Option<Vec<u8>>) = run_command(
+        state,
+        redis::pipe()
+            .cmd("TYPE")
+            .arg(key)
+            .cmd("PTTL")
+            .arg(key)
+            .cmd("DUMP")
+            .arg(key)
+            .query_async(&mut **conn),
+    )
+    .await?;
+    let Some(dump) = dump else {
+        return Ok(None);
+    };
+
+    let mut record = export_key(key);
+    record["type"] = json!(key_type);
+    // PTTL returns -1 for keys without an expiry
+    record["ttl_ms"] = json!(if pttl >= 0 { Some(pttl) } else { None });
+    record["dump"] = json!(STANDARD.encode(dump));
+    record["encoding"] = json!("base64");
+    Ok(Some(record))
+}
+
+// Progress of an export stream between chunks
+struct ExportState {
+    app: Arc<AppState>,
+    conn: PooledConnection,
+    pattern: String,
+    key_type: Option<String>,
+    count: usize,
+    cursor: u64,        // Cursor for the next SCAN call
+    resume_cursor: u64, // Cursor that produced the keys still pending
+    pending: VecDeque<Vec<u8>>,
+    checkpoint_due: bool,
+    done: bool,
+}
+
+// Produce the next NDJSON line: a key record, a checkpoint after each SCAN batch, or an error
+async fn next_export_line(mut export: ExportState) -> Option<(Result<String, Infallible>, ExportState)> {
+    loop {
+        if export.done {
+            return None;
+        }
+
+        if let Some(key) = export.pending.pop_front() {
+            match export_record(&export.app, &mut export.conn, &key).await {
+                Ok(Some(record)) => return Some((Ok(format!("{}\n", record)), export)),
+                Ok(None) => continue,
+                Err((_, Json(response))) => {
+                    export.done = true;
+                    let line = json!({
+                        "error": response.message.unwrap_or_else(|| "Export failed".to_string()),
+                        "cursor": export.resume_cursor,
+                    });
+                    return Some((Ok(format!("{}\n", line)), export));
+                }
+            }
+        }
+
+        if export.checkpoint_due {
+            export.checkpoint_due = false;
+            export.done = export.cursor == 0;
+            let line = json!({ "cursor": export.cursor, "complete": export.done });
+            return Some((Ok(format!("{}\n", line)), export));
+        }
+
+        let scan: Result<(u64, Vec<Vec<u8>>), ErrorResponse> = run_command(
+            &export.app,
+            scan_command(export.cursor, &export.pattern, export.count, export.key_type.as_deref())
+                .query_async(&mut *export.conn),
+        )
+        .await;
+
+        match scan {
+            Ok((next_cursor, keys)) => {
+                export.resume_cursor = export.cursor;
+                export.cursor = next_cursor;
+                export.pending = keys.into();
+                export.checkpoint_due = true;
+            }
+            Err((_, Json(response))) => {
+                export.done = true;
+                let line = json!({
+                    "error": response.message.unwrap_or_else(|| "Export failed".to_string()),
+                    "cursor": export.cursor,
+                });
+                return Some((Ok(format!("{}\n", line)), export));
+            }
+        }
+    }
+}
+
+pub async fn export_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<ExportKeysParams>,
+) -> Result<Response, ErrorResponse> {
+    // Validate input
+    if let Err(errors) = params.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let conn = instance_connection(&state, &instance).await?;
+
+    let pattern = params.pattern.unwrap_or_else(|| "*".to_string());
+    let cursor = params.cursor.unwrap_or(0);
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "export_keys",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "pattern": pattern, "type": params.key_type, "cursor": cursor }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record key export in audit log: {}", e);
+    }
+
+    let export = ExportState {
+        app: state.clone(),
+        conn,
+        pattern,
+        key_type: params.key_type,
+        count: params.count.unwrap_or(DEFAULT_SCAN_COUNT),
+        cursor,
+        resume_cursor: cursor,
+        pending: VecDeque::new(),
+        checkpoint_due: false,
+        done: false,
+    };
+
+    let body = Body::from_stream(futures::stream::unfold(export, next_export_line));
+    Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response())
+}
+
+// Convert a JSON scalar into a Redis argument
+fn scalar_arg(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        other => Err(format!("Expected a string or number, got {}", other)),
+    }
+}
+
+// Non-empty array value of a collection record (Redis cannot store empty collections)
+fn collection_items<'a>(value: &'a Value, key_type: &str) -> Result<&'a [Value], String> {
+    match value {
+        Value::Array(items) if !items.is_empty() => Ok(items),
+        Value::Array(_) => Err(format!("Empty {} values cannot be imported", key_type)),
+        _ => Err(format!("A {} value must be an array", key_type)),
+    }
+}
+
+// Key of an import record, decoding keys exported as base64
+fn import_key(record: &ImportKeyRecord) -> Result<Vec<u8>, String> {
+    match record.key_encoding.as_deref() {
+        None => Ok(record.key.clone().into_bytes()),
+        Some("base64") => STANDARD.decode(&record.key).map_err(|e| format!("Invalid key encoding: {}", e)),
+        Some(other) => Err(format!("Unsupported key encoding '{}'", other)),
+    }
+}
+
+// Expiry of an import record in milliseconds, if it has a positive one
+fn import_ttl_ms(record: &ImportKeyRecord) -> Option<i64> {
+    record
+        .ttl_ms
+        .or_else(|| record.ttl.map(|secs| secs.saturating_mul(1000)))
+        .filter(|ttl| *ttl > 0)
+}
+
+// Build an atomic pipeline that replaces the key with the record's contents
+fn import_pipeline(record: &ImportKeyRecord) -> Result<redis::Pipeline, String> {
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    let ttl = import_ttl_ms(record);
+    let key = import_key(record)?;
+
+    if let Some(dump) = &record.dump {
+        if let Some(encoding) = record.encoding.as_deref().filter(|encoding| *encoding != "base64") {
+            return Err(format!("Unsupported dump encoding '{}'", encoding));
+        }
+        let payload = STANDARD.decode(dump).map_err(|e| format!("Invalid dump encoding: {}", e))?;
+        pipe.cmd("RESTORE")
+            .arg(&key)
+            .arg(ttl.unwrap_or(0))
+            .arg(payload)
+            .arg("REPLACE")
+            .ignore();
+        return Ok(pipe);
+    }
+
+    let key_type = record.key_type.as_deref().ok_or("Missing type")?;
+    pipe.cmd("DEL").arg(&key).ignore();
+
+    match key_type {
+        "string" => {
+            pipe.cmd("SET").arg(&key).arg(scalar_arg(&record.value)?).ignore();
+        }
+        "list" | "set" => {
+            let command = if key_type == "list" { "RPUSH" } else { "SADD" };
+            let cmd = pipe.cmd(command).arg(&key);
+            for item in collection_items(&record.value, key_type)? {
+                cmd.arg(scalar_arg(item)?);
+            }
+            cmd.ignore();
+        }
+        "hash" => {
+            let fields = match &record.value {
+                Value::Object(fields) if !fields.is_empty() => fields,
+                Value::Object(_) => return Err("Empty hash values cannot be imported".to_string()),
+                _ => return Err("A hash value must be an object".to_string()),
+            };
+            let cmd = pipe.cmd("HSET").arg(&key);
+            for (field, value) in fields {
+                cmd.arg(field).arg(scalar_arg(value)?);
+            }
+            cmd.ignore();
+        }
+        "zset" => {
+            let cmd = pipe.cmd("ZADD").arg(&key);
+            for entry in collection_items(&record.value, key_type)? {
+                match entry.as_array().map(Vec::as_slice) {
+                    Some([member, Value::Number(score)]) => {
+                        cmd.arg(score.to_string()).arg(scalar_arg(member)?);
+                    }
+                    _ => return Err("A zset entry must be [member, score]".to_string()),
+                }
+            }
+            cmd.ignore();
+        }
+        "stream" => {
+            for entry in collection_items(&record.value, key_type)? {
+                match entry.as_array().map(Vec::as_slice) {
+                    Some([Value::String(id), Value::Array(fields)]) if !fields.is_empty() && fields.len() % 2 == 0 => {
+                        let cmd = pipe.cmd("XADD").arg(&key).arg(id);
+                        for field in fields {
+                            cmd.arg(scalar_arg(field)?);
+                        }
+                        cmd.ignore();
+                    }
+                    _ => return Err("A stream entry must be [id, [field, value, ...]]".to_string()),
+                }
+            }
+        }
+        other => return Err(format!("Unsupported type '{}'", other)),
+    }
+
+    if let Some(ttl) = ttl {
+        pipe.cmd("PEXPIRE").arg(&key).arg(ttl).ignore();
+    }
+    Ok(pipe)
+}
+
+pub async fn import_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    body: String,
+) -> Result<Json<ApiResponse<ImportKeysResponse>>, ErrorResponse> {
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    let mut imported: u64 = 0;
+    let mut failed: u64 = 0;
+    let mut errors = Vec::new();
+
+    for (index, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let result = match serde_json::from_str::<Value>(line) {
+            // Checkpoint lines from an export carry a cursor but no key
+            Ok(value) if value.get("key").is_none() && value.get("cursor").is_some() => continue,
+            Ok(value) => match serde_json::from_value::<ImportKeyRecord>(value) {
+                Ok(record) => match import_pipeline(&record) {
+                    Ok(pipe) => run_command::<()>(&state, pipe.query_async(&mut *conn))
+                        .await
+                        .map_err(|(_, Json(response))| response.message.unwrap_or_default()),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(format!("Invalid record: {}", e)),
+            },
+            Err(e) => Err(format!("Invalid JSON: {}", e)),
+        };
+
+        match result {
+            Ok(()) => imported += 1,
+            Err(error) => {
+                failed += 1;
+                if errors.len() < MAX_IMPORT_ERRORS {
+                    errors.push(ImportKeyError { line: index + 1, error });
+                }
+            }
+        }
+    }
+
+    tracing::info!(
+        "Imported {} keys into Redis instance {} ({} failed)",
+        imported,
+        instance_id,
+        failed
+    );
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "import_keys",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "imported": imported, "failed": failed }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record key import in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(ImportKeysResponse {
+        imported,
+        failed,
+        errors,
+    })))
+}
+
+pub async fn get_key_ttls(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<KeyTtlRequest>,
+) -> Result<Json<ApiResponse<KeyTtlResponse>>, ErrorResponse> {
+    // Validate input
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    // One round trip for all keys
+    let mut pipe = redis::pipe();
+    for key in &payload.keys {
+        pipe.cmd("TTL").arg(key);
+    }
+    let ttls: Vec<i64> = run_command(&state, pipe.query_async(&mut *conn)).await?;
+
+    Ok(Json(ApiResponse::success(KeyTtlResponse {
+        ttls: payload.keys.into_iter().zip(ttls).collect(),
+    })))
+}
+
+pub async fn get_key_idle_times(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<KeyIdleTimeRequest>,
+) -> Result<Json<ApiResponse<KeyIdleTimeResponse>>, ErrorResponse> {
+    // Validate input
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+
+    // Redis only tracks access frequency, not idle time, under LFU policies
+    if instance.eviction_policy.as_deref().is_some_and(|policy| policy.contains("lfu")) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                ErrorCode::InvalidRequest,
+                "Idle time is not tracked under an LFU eviction policy".to_string(),
+            )),
+        ));
+    }
+
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    // One round trip for all keys; OBJECT IDLETIME does not itself count as an access
+    let mut pipe = redis::pipe();
+    for key in &payload.keys {
+        pipe.cmd("OBJECT").arg("IDLETIME").arg(key);
+    }
+    let idle_times: Vec<Option<i64>> = run_command(&state, pipe.query_async(&mut *conn)).await?;
+
+    Ok(Json(ApiResponse::success(KeyIdleTimeResponse {
+        idle_seconds: payload.keys.into_iter().zip(idle_times).collect(),
+    })))
+}
+
+// Split an INFO value like `calls=21,usec=175,usec_per_call=8.33` into its fields
+fn info_value_fields(value: &str) -> HashMap<&str, &str> {
+    value.split(',').filter_map(|field| field.split_once('=')).collect()
+}
+
+// `cmdstat_<command>` fields of INFO commandstats, most-called first
+fn parse_command_stats(info: &HashMap<String, String>) -> Vec<CommandStats> {
+    let mut commands: Vec<CommandStats> = info
+        .iter()
+        .filter_map(|(field, value)| {
+            let command = field.strip_prefix("cmdstat_")?;
+            let fields = info_value_fields(value);
+            let count = |name: &str| fields.get(name).and_then(|value| value.parse().ok());
+            Some(CommandStats {
+                command: command.to_string(),
+                calls: count("calls")?,
+                usec: count("usec").unwrap_or(0),
+                usec_per_call: fields.get("usec_per_call").and_then(|value| value.parse().ok()).unwrap_or(0.0),
+                rejected_calls: count("rejected_calls"),
+                failed_calls: count("failed_calls"),
+            })
+        })
+        .collect();
+    commands.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.command.cmp(&b.command)));
+    commands
+}
+
+// `db<N>` fields of INFO keyspace, by database number
+fn parse_keyspace_info(info: &HashMap<String, String>) -> Vec<DatabaseKeyspace> {
+    let mut databases: Vec<DatabaseKeyspace> = info
+        .iter()
+        .filter_map(|(field, value)| {
+            let db = field.strip_prefix("db")?.parse().ok()?;
+            let fields = info_value_fields(value);
+            let count = |name: &str| fields.get(name).and_then(|value| value.parse().ok()).unwrap_or(0);
+            Some(DatabaseKeyspace {
+                db,
+                keys: count("keys"),
+                expires: count("expires"),
+                avg_ttl_ms: count("avg_ttl"),
+            })
+        })
+        .collect();
+    databases.sort_by_key(|database| database.db);
+    databases
+}
+
+// INFO commandstats walks every command Redis knows, so this is limited to org admins
+pub async fn get_instance_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<InstanceStatsResponse>>, ErrorResponse> {
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    let mut pipe = redis::pipe();
+    pipe.cmd("INFO").arg("commandstats").cmd("INFO").arg("keyspace");
+    let (command_info, keyspace_info): (String, String) = run_command(&state, pipe.query_async(&mut *conn)).await?;
+
+    let commands = parse_command_stats(&parse_redis_info(&command_info));
+    Ok(Json(ApiResponse::success(InstanceStatsResponse {
+        total_calls: commands.iter().map(|command| command.calls).sum(),
+        commands,
+        keyspace: parse_keyspace_info(&parse_redis_info(&keyspace_info)),
+        collected_at: chrono::Utc::now(),
+    })))
+}
+
+pub async fn set_key_ttls(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<SetKeyTtlRequest>,
+) -> Result<Json<ApiResponse<SetKeyTtlResponse>>, ErrorResponse> {
+    // Validate input
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    let mut pipe = redis::pipe();
+    for key in &payload.keys {
+        pipe.cmd("EXPIRE").arg(key).arg(payload.seconds);
+    }
+    // EXPIRE replies 1 when the timeout was set and 0 when the key does not exist
+    let replies: Vec<u64> = run_command(&state, pipe.query_async(&mut *conn)).await?;
+    let updated = replies.into_iter().sum();
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "set_key_ttls",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "keys": payload.keys.len(), "seconds": payload.seconds, "updated": updated }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record TTL update in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(SetKeyTtlResponse { updated })))
+}
+
+// Command giving the size of a key of a built-in type: bytes for strings, elements otherwise
+fn length_command(key_type: &str) -> Option<&'static str> {
+    match key_type {
+        "string" => Some("STRLEN"),
+        "list" => Some("LLEN"),
+        "hash" => Some("HLEN"),
+        "set" => Some("SCARD"),
+        "zset" => Some("ZCARD"),
+        "stream" => Some("XLEN"),
+        _ => None,
+    }
+}
+
+// Merge candidates into the running top list, keeping the `top` largest by memory
+fn keep_largest(largest: &mut Vec<BigKey>, candidates: Vec<BigKey>, top: usize) {
+    largest.extend(candidates);
+    largest.sort_by(|a, b| b.memory_bytes.cmp(&a.memory_bytes).then_with(|| a.key.cmp(&b.key)));
+    largest.truncate(top);
+}
+
+// Type, memory and length of a batch of scanned keys; keys deleted since SCAN returned them are skipped
+async fn measure_keys(state: &AppState, conn: &mut PooledConnection, keys: &[Vec<u8>]) -> Result<Vec<BigKey>, ErrorResponse> {
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("TYPE").arg(key);
+    }
+    for key in keys {
+        pipe.cmd("MEMORY").arg("USAGE").arg(key);
+    }
+    let replies: Vec<redis::Value> = run_command(state, pipe.query_async(&mut **conn)).await?;
+    let (types, usages) = replies.split_at(keys.len());
+
+    let mut measured = Vec::new();
+    let mut lengths = redis::pipe();
+    for ((key, key_type), usage) in keys.iter().zip(types).zip(usages) {
+        let key_type: String = redis::from_redis_value(key_type).unwrap_or_default();
+        let memory_bytes: Option<u64> = redis::from_redis_value(usage).unwrap_or_default();
+        let Some(memory_bytes) = memory_bytes.filter(|_| key_type != "none") else {
+            continue;
+        };
+        if let Some(command) = length_command(&key_type) {
+            lengths.cmd(command).arg(key);
+        }
+        measured.push(BigKey {
+            key: String::from_utf8_lossy(key).into_owned(),
+            key_type,
+            memory_bytes,
+            length: None,
+        });
+    }
+
+    // Keys of module types have no length command, so the pipeline can be empty
+    let lengths: Vec<u64> = if measured.iter().any(|key| length_command(&key.key_type).is_some()) {
+        run_command(state, lengths.query_async(&mut **conn)).await?
+    } else {
+        Vec::new()
+    };
+    let mut lengths = lengths.into_iter();
+    for key in measured.iter_mut().filter(|key| length_command(&key.key_type).is_some()) {
+        key.length = lengths.next();
+    }
+    Ok(measured)
+}
+
+// Like `redis-cli --bigkeys`: SCAN a bounded sample of the keyspace and report the largest keys.
+// The scan stops at the sample size or the safety budget, whichever comes first.
+pub async fn get_big_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<BigKeysParams>,
+) -> Result<Json<ApiResponse<BigKeysResponse>>, ErrorResponse> {
+    // Validate input
+    if let Err(errors) = params.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let mut conn = instance_connection(&state, &instance).await?;
+
+    let deadline = Instant::now() + state.redis_config.max_command_timeout;
+    let top = params.top.unwrap_or(DEFAULT_BIG_KEYS_TOP);
+    let sample = params.sample.unwrap_or(DEFAULT_BIG_KEYS_SAMPLE);
+    let mut cursor = 0;
+    let mut sampled = 0;
+    let mut largest = Vec::new();
+
+    loop {
+        let count = DEFAULT_SCAN_COUNT.min(sample - sampled);
+        let (next_cursor, mut keys): (u64, Vec<Vec<u8>>) = run_command(
+            &state,
+            scan_command(cursor, "*", count, params.key_type.as_deref()).query_async(&mut *conn),
+        )
+        .await?;
+        // COUNT is only a hint, so a step can return more keys than the sample has room for
+        keys.truncate(sample - sampled);
+        sampled += keys.len();
+
+        if !keys.is_empty() {
+            let measured = measure_keys(&state, &mut conn, &keys).await?;
+            keep_largest(&mut largest, measured, top);
+        }
+
+        cursor = next_cursor;
+        if cursor == 0 || sampled >= sample || Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    Ok(Json(ApiResponse::success(BigKeysResponse {
+        keys: largest,
+        sampled,
+        cursor,
+        complete: cursor == 0,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_stats() {
+        let info = parse_redis_info(
+            "# Commandstats\r\n\
+             cmdstat_get:calls=21,usec=175,usec_per_call=8.33,rejected_calls=0,failed_calls=1\r\n\
+             cmdstat_config|get:calls=2,usec=30,usec_per_call=15.00\r\n\
+             cmdstat_set:calls=40,usec=300,usec_per_call=7.50,rejected_calls=0,failed_calls=0\r\n",
+        );
+        let commands = parse_command_stats(&info);
+
+        let names: Vec<&str> = commands.iter().map(|command| command.command.as_str()).collect();
+        assert_eq!(names, vec!["set", "get", "config|get"]);
+        assert_eq!(commands[1].calls, 21);
+        assert_eq!(commands[1].usec_per_call, 8.33);
+        assert_eq!(commands[1].failed_calls, Some(1));
+        assert_eq!(commands[2].rejected_calls, None);
+    }
+
+    #[test]
+    fn test_parse_keyspace_info() {
+        let info = parse_redis_info("# Keyspace\r\ndb3:keys=5,expires=0,avg_ttl=0\r\ndb0:keys=12,expires=4,avg_ttl=9000,subexpiry=0\r\n");
+
+        assert_eq!(
+            parse_keyspace_info(&info),
+            vec![
+                DatabaseKeyspace { db: 0, keys: 12, expires: 4, avg_ttl_ms: 9000 },
+                DatabaseKeyspace { db: 3, keys: 5, expires: 0, avg_ttl_ms: 0 },
+            ]
+        );
+    }
+
+    fn record(line: &str) -> ImportKeyRecord {
+        serde_json::from_str(line).unwrap()
+    }
+
+    #[test]
+    fn test_export_key_encodes_binary_keys() {
+        assert_eq!(export_key(b"user:1"), json!({ "key": "user:1" }));
+        let binary = export_key(&[0xff, 0x00]);
+        assert_eq!(binary, json!({ "key": "/wA=", "key_encoding": "base64" }));
+        assert_eq!(import_key(&serde_json::from_value(binary).unwrap()).unwrap(), vec![0xff, 0x00]);
+    }
+
+    #[test]
+    fn test_import_pipeline_accepts_export_records() {
+        for line in [
+            r#"{"key":"s","type":"string","ttl_ms":1000,"value":"v"}"#,
+            r#"{"key":"l","type":"list","ttl_ms":null,"value":["a","b"]}"#,
+            r#"{"key":"h","type":"hash","value":{"f":"v"}}"#,
+            r#"{"key":"z","type":"zset","value":[["m",1.5]]}"#,
+            r#"{"key":"x","type":"stream","value":[["1-0",["f","v"]]]}"#,
+            r#"{"key":"d","dump":"AAAA"}"#,
+            r#"{"key":"/w==","key_encoding":"base64","type":"string","ttl_ms":null,"dump":"AAAA","encoding":"base64"}"#,
+        ] {
+            assert!(import_pipeline(&record(line)).is_ok(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_import_ttl_is_in_milliseconds_or_seconds() {
+        assert_eq!(import_ttl_ms(&record(r#"{"key":"k","ttl_ms":1500}"#)), Some(1500));
+        assert_eq!(import_ttl_ms(&record(r#"{"key":"k","ttl":60}"#)), Some(60_000));
+        assert_eq!(import_ttl_ms(&record(r#"{"key":"k","ttl_ms":1500,"ttl":60}"#)), Some(1500));
+        assert_eq!(import_ttl_ms(&record(r#"{"key":"k","ttl_ms":-1}"#)), None);
+        assert_eq!(import_ttl_ms(&record(r#"{"key":"k"}"#)), None);
+    }
+
+    #[test]
+    fn test_import_pipeline_rejects_bad_records() {
+        for line in [
+            r#"{"key":"k","value":"v"}"#,
+            r#"{"key":"k","type":"list","value":[]}"#,
+            r#"{"key":"k","type":"zset","value":[["m"]]}"#,
+            r#"{"key":"k","type":"json","value":{}}"#,
+            r#"{"key":"k","dump":"not base64!"}"#,
+            r#"{"key":"not base64!","key_encoding":"base64","dump":"AAAA"}"#,
+        ] {
+            assert!(import_pipeline(&record(line)).is_err(), "{}", line);
+        }
+    }
+
+    fn big_key(key: &str, memory_bytes: u64) -> BigKey {
+        BigKey {
+            key: key.to_string(),
+            key_type: "string".to_string(),
+            memory_bytes,
+            length: None,
+        }
+    }
+
+    #[test]
+    fn test_keep_largest() {
+        let mut largest = Vec::new();
+        keep_largest(&mut largest, vec![big_key("a", 10), big_key("b", 300), big_key("c", 50)], 2);
+        keep_largest(&mut largest, vec![big_key("d", 100), big_key("e", 5)], 2);
+        assert_eq!(largest, vec![big_key("b", 300), big_key("d", 100)]);
+
+        keep_largest(&mut largest, vec![big_key("a", 100)], 3);
+        let keys: Vec<&str> = largest.iter().map(|key| key.key.as_str()).collect();
+        assert_eq!(keys, vec!["b", "a", "d"]);
+    }
+
+    #[test]
+    fn test_length_command() {
+        assert_eq!(length_command("string"), Some("STRLEN"));
+        assert_eq!(length_command("zset"), Some("ZCARD"));
+        assert_eq!(length_command("ReJSON-RL"), None);
+    }
+}
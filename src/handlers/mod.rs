@@ -1,7 +1,13 @@
 // Handlers module declarations
 
+pub mod access;
+pub mod admin;
 pub mod auth;
 pub mod organizations;
 pub mod api_keys;
 pub mod redis_instances;
-pub mod redis;
\ No newline at end of file
+pub mod keyspace;
+pub mod instance_config;
+pub mod redis;
+pub mod webhooks;
+pub mod command_audit;
\ No newline at end of file
@@ -2,25 +2,30 @@
 
 use axum::{
     extract::{Extension, Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderName, StatusCode},
     response::Json,
 };
-use chrono::Utc;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::api_models::{
-    ApiResponse, CreateOrganizationRequest, OrganizationResponse, PaginatedResponse,
-    PaginationParams,
+    ApiResponse, ErrorCode, CreateOrganizationRequest, OrganizationMemberResponse, OrganizationResponse,
+    OrganizationUsageResponse, PaginatedResponse, PaginationParams, TransferOwnershipRequest, UpdatePlanRequest,
 };
+use crate::audit::{self, AuditEvent};
+use crate::handlers::api_keys::{revoke_api_keys, RevokedKeys};
+use crate::idempotency::IdempotencyKey;
 use crate::middleware::{AppState, CurrentUser};
-use crate::models::Organization;
+use crate::models::{Organization, Plan};
 
 type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
 
 // Helper function to convert Organization to OrganizationResponse
 fn organization_to_response(organization: Organization) -> OrganizationResponse {
+    let plan = Plan::from_db(organization.plan.as_deref());
     OrganizationResponse {
         id: organization.id,
         name: organization.name,
@@ -28,27 +33,78 @@ fn organization_to_response(organization: Organization) -> OrganizationResponse
         description: organization.description,
         owner_id: organization.owner_id,
         is_active: organization.is_active.unwrap_or(true),
-        plan: organization.plan.unwrap_or_else(|| "free".to_string()),
-        max_redis_instances: organization.max_redis_instances.unwrap_or(3),
-        max_api_keys: organization.max_api_keys.unwrap_or(10),
-        created_at: organization.created_at.unwrap_or_else(|| Utc::now()),
-        updated_at: organization.updated_at.unwrap_or_else(|| Utc::now()),
+        plan: plan.as_str().to_string(),
+        max_redis_instances: organization.max_redis_instances.unwrap_or(plan.max_redis_instances()),
+        max_api_keys: organization.max_api_keys.unwrap_or(plan.max_api_keys()),
+        created_at: organization.created_at.unwrap_or_else(Utc::now),
+        updated_at: organization.updated_at.unwrap_or_else(Utc::now),
     }
 }
 
+// ETag for an organization version, derived from its last update time. Organizations never
+// updated are versioned by their creation time, matching COALESCE(updated_at, created_at, 'epoch').
+fn organization_etag(organization: &Organization) -> String {
+    let version = organization.updated_at.or(organization.created_at).unwrap_or_default();
+    format!("\"{}\"", version.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+// Parse the expected organization version from an If-Match header, if one was sent
+fn parse_if_match(headers: &HeaderMap) -> Result<Option<DateTime<Utc>>, ErrorResponse> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .map(|etag| etag.trim().trim_start_matches("W/").trim_matches('"'))
+        .and_then(|version| DateTime::parse_from_rfc3339(version).ok())
+        .map(|version| Some(version.with_timezone(&Utc)))
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(ErrorCode::InvalidHeader, "Invalid If-Match header".to_string())),
+            )
+        })
+}
+
 pub async fn create_organization(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
+    headers: HeaderMap,
     Json(payload): Json<CreateOrganizationRequest>,
 ) -> Result<Json<ApiResponse<OrganizationResponse>>, ErrorResponse> {
     // Validate input
     if let Err(errors) = payload.validate() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error(format!("Validation error: {:?}", errors))),
+            Json(ApiResponse::<()>::validation_error(&errors)),
         ));
     }
 
+    // Return the original organization when a request is retried with the same Idempotency-Key
+    let idempotency_key =
+        IdempotencyKey::from_headers(&headers, current_user.id, "create_organization", &payload)?;
+    if let Some(key) = &idempotency_key {
+        if let Some(org_id) = key.replayed_resource(&state.db_pool).await? {
+            let organization = sqlx::query_as!(
+                Organization,
+                "SELECT * FROM organizations WHERE id = $1",
+                org_id
+            )
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch created organization: {}", e))),
+                )
+            })?;
+
+            return Ok(Json(ApiResponse::success(organization_to_response(organization))));
+        }
+    }
+
     // Check if organization slug is unique
     let existing_org = sqlx::query!(
         "SELECT id FROM organizations WHERE slug = $1",
@@ -59,31 +115,35 @@ pub async fn create_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
     if existing_org.is_some() {
         return Err((
             StatusCode::CONFLICT,
-            Json(ApiResponse::<()>::error("Organization with this slug already exists".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::SlugTaken, "Organization with this slug already exists".to_string())),
         ));
     }
 
     let org_id = Uuid::new_v4();
     let now = Utc::now();
+    let plan = Plan::Free;
 
     // Create organization
     sqlx::query!(
         r#"
-        INSERT INTO organizations (id, name, slug, description, owner_id, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO organizations (id, name, slug, description, owner_id, plan, max_redis_instances, max_api_keys, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         "#,
         org_id,
         payload.name,
         payload.slug,
         payload.description,
         current_user.id,
+        plan.as_str(),
+        plan.max_redis_instances(),
+        plan.max_api_keys(),
         now,
         now
     )
@@ -92,7 +152,7 @@ pub async fn create_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to create organization: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to create organization: {}", e))),
         )
     })?;
 
@@ -115,7 +175,7 @@ pub async fn create_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to create organization membership: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to create organization membership: {}", e))),
         )
     })?;
 
@@ -130,10 +190,16 @@ pub async fn create_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to fetch created organization: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch created organization: {}", e))),
         )
     })?;
 
+    if let Some(key) = &idempotency_key {
+        if let Err(e) = key.store(&state.db_pool, org_id).await {
+            tracing::warn!("Failed to store idempotency key: {}", e);
+        }
+    }
+
     let org_response = organization_to_response(organization);
 
     Ok(Json(ApiResponse::success(org_response)))
@@ -167,7 +233,7 @@ pub async fn list_organizations(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
@@ -185,7 +251,7 @@ pub async fn list_organizations(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .count
@@ -213,7 +279,7 @@ pub async fn get_organization(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
     Path(org_id): Path<Uuid>,
-) -> Result<Json<ApiResponse<OrganizationResponse>>, ErrorResponse> {
+) -> Result<([(HeaderName, String); 1], Json<ApiResponse<OrganizationResponse>>), ErrorResponse> {
     // Check if user has access to this organization
     let organization = sqlx::query_as!(
         Organization,
@@ -230,35 +296,40 @@ pub async fn get_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
+    let etag = organization_etag(&organization);
     let org_response = organization_to_response(organization);
 
-    Ok(Json(ApiResponse::success(org_response)))
+    Ok(([(header::ETAG, etag)], Json(ApiResponse::success(org_response))))
 }
 
 pub async fn update_organization(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
     Path(org_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<CreateOrganizationRequest>, // Reusing the same request struct
-) -> Result<Json<ApiResponse<OrganizationResponse>>, ErrorResponse> {
+) -> Result<([(HeaderName, String); 1], Json<ApiResponse<OrganizationResponse>>), ErrorResponse> {
     // Validate input
     if let Err(errors) = payload.validate() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error(format!("Validation error: {:?}", errors))),
+            Json(ApiResponse::<()>::validation_error(&errors)),
         ));
     }
 
+    // Clients may send the version they last read to avoid overwriting concurrent edits
+    let expected_version = parse_if_match(&headers)?;
+
     // Check if user is owner of this organization
     let org_membership = sqlx::query!(
         r#"
@@ -273,20 +344,20 @@ pub async fn update_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
     if org_membership.role != "owner" {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ApiResponse::<()>::error("Only organization owners can update organization details".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Only organization owners can update organization details".to_string())),
         ));
     }
 
@@ -301,41 +372,49 @@ pub async fn update_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
     if existing_org.is_some() {
         return Err((
             StatusCode::CONFLICT,
-            Json(ApiResponse::<()>::error("Organization with this slug already exists".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::SlugTaken, "Organization with this slug already exists".to_string())),
         ));
     }
 
     let now = Utc::now();
 
-    // Update organization
-    sqlx::query!(
+    // With If-Match, update organization only if nobody changed it since the client read it
+    let updated = sqlx::query!(
         r#"
         UPDATE organizations 
         SET name = $1, slug = $2, description = $3, updated_at = $4
-        WHERE id = $5
+        WHERE id = $5 AND ($6::timestamptz IS NULL OR COALESCE(updated_at, created_at, 'epoch') = $6)
         "#,
         payload.name,
         payload.slug,
         payload.description,
         now,
-        org_id
+        org_id,
+        expected_version
     )
     .execute(&state.db_pool)
     .await
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to update organization: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to update organization: {}", e))),
         )
     })?;
 
+    if updated.rows_affected() == 0 {
+        return Err((
+            StatusCode::PRECONDITION_FAILED,
+            Json(ApiResponse::<()>::error(ErrorCode::PreconditionFailed, "Organization was modified by another request".to_string())),
+        ));
+    }
+
     // Fetch updated organization
     let organization = sqlx::query_as!(
         Organization,
@@ -347,13 +426,14 @@ pub async fn update_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to fetch updated organization: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch updated organization: {}", e))),
         )
     })?;
 
+    let etag = organization_etag(&organization);
     let org_response = organization_to_response(organization);
 
-    Ok(Json(ApiResponse::success(org_response)))
+    Ok(([(header::ETAG, etag)], Json(ApiResponse::success(org_response))))
 }
 
 pub async fn delete_organization(
@@ -375,20 +455,20 @@ pub async fn delete_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
     if org_membership.role != "owner" {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ApiResponse::<()>::error("Only organization owners can delete the organization".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Only organization owners can delete the organization".to_string())),
         ));
     }
 
@@ -402,7 +482,7 @@ pub async fn delete_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .count
@@ -411,7 +491,7 @@ pub async fn delete_organization(
     if active_instances > 0 {
         return Err((
             StatusCode::CONFLICT,
-            Json(ApiResponse::<()>::error("Cannot delete organization with active Redis instances".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::ResourceInUse, "Cannot delete organization with active Redis instances".to_string())),
         ));
     }
 
@@ -427,7 +507,7 @@ pub async fn delete_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to delete organization: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to delete organization: {}", e))),
         )
     })?;
 
@@ -442,7 +522,7 @@ pub async fn delete_organization(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to deactivate memberships: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to deactivate memberships: {}", e))),
         )
     })?;
 
@@ -450,6 +530,538 @@ pub async fn delete_organization(
         success: true,
         data: None,
         message: Some("Organization deleted successfully".to_string()),
+        code: None,
+        errors: None,
+        timestamp: Utc::now(),
+    }))
+}
+pub async fn get_organization_usage(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<OrganizationUsageResponse>>, ErrorResponse> {
+    // Check if user has access to the organization
+    let _org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    // Aggregate Redis instance resources
+    let instance_usage = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) as "instance_count!",
+            COALESCE(SUM(max_memory), 0)::BIGINT as "total_max_memory!",
+            COALESCE(SUM(current_memory), 0)::BIGINT as "total_current_memory!",
+            COALESCE(SUM(connections_count), 0)::BIGINT as "total_connections!",
+            COALESCE(SUM(max_connections), 0)::BIGINT as "total_max_connections!"
+        FROM redis_instances
+        WHERE organization_id = $1 AND deleted_at IS NULL
+        "#,
+        org_id
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?;
+
+    // Count API keys
+    let api_key_usage = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) as "api_key_count!",
+            COUNT(*) FILTER (WHERE is_active = true) as "active_api_key_count!"
+        FROM api_keys
+        WHERE organization_id = $1
+        "#,
+        org_id
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?;
+
+    let usage = OrganizationUsageResponse {
+        organization_id: org_id,
+        instance_count: instance_usage.instance_count,
+        total_max_memory: instance_usage.total_max_memory,
+        total_current_memory: instance_usage.total_current_memory,
+        api_key_count: api_key_usage.api_key_count,
+        active_api_key_count: api_key_usage.active_api_key_count,
+        total_connections: instance_usage.total_connections,
+        total_max_connections: instance_usage.total_max_connections,
+    };
+
+    Ok(Json(ApiResponse::success(usage)))
+}
+
+pub async fn list_organization_members(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<ApiResponse<PaginatedResponse<OrganizationMemberResponse>>>, ErrorResponse> {
+    let page = params.page.unwrap_or(1).max(1);
+    let limit = params.limit.unwrap_or(20).clamp(1, 100); // Max 100 items per page
+    let offset = (page - 1) * limit;
+
+    let db_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    };
+
+    // Only members of the organization can see who else belongs to it
+    sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    let members = sqlx::query!(
+        r#"
+        SELECT u.id, u.email, u.username, u.first_name, u.last_name,
+               om.role, om.is_active, om.joined_at
+        FROM organization_memberships om
+        INNER JOIN users u ON u.id = om.user_id
+        WHERE om.organization_id = $1
+        ORDER BY om.joined_at ASC NULLS LAST, u.username ASC
+        LIMIT $2 OFFSET $3
+        "#,
+        org_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(db_error)?;
+
+    let total_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM organization_memberships WHERE organization_id = $1"#,
+        org_id
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(db_error)?;
+
+    let items = members
+        .into_iter()
+        .map(|member| OrganizationMemberResponse {
+            user_id: member.id,
+            email: member.email,
+            username: member.username,
+            first_name: member.first_name,
+            last_name: member.last_name,
+            role: member.role,
+            is_active: member.is_active.unwrap_or(true),
+            joined_at: member.joined_at,
+        })
+        .collect();
+
+    let total_pages = ((total_count as f64) / (limit as f64)).ceil() as u32;
+
+    Ok(Json(ApiResponse::success(PaginatedResponse {
+        items,
+        total_count,
+        page,
+        limit,
+        total_pages,
+    })))
+}
+
+pub async fn remove_organization_member(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<()>>, ErrorResponse> {
+    // Check if user is an admin or owner of this organization
+    let org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    if !["admin", "owner"].contains(&org_membership.role.as_str()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Insufficient permissions to remove members".to_string())),
+        ));
+    }
+
+    let db_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to remove member: {}", e))),
+        )
+    };
+
+    let mut tx = state.db_pool.begin().await.map_err(db_error)?;
+
+    // Lock the organization's memberships so concurrent removals see a consistent owner count
+    let memberships = sqlx::query!(
+        r#"
+        SELECT user_id, role FROM organization_memberships
+        WHERE organization_id = $1 AND is_active = true
+        FOR UPDATE
+        "#,
+        org_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let target_role = memberships
+        .iter()
+        .find(|membership| membership.user_id == user_id)
+        .map(|membership| membership.role.clone())
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Member not found".to_string())),
+            )
+        })?;
+
+    if target_role == "owner" {
+        if user_id == current_user.id {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::InvalidRequest,
+                    "Owners cannot remove themselves; transfer ownership first".to_string(),
+                )),
+            ));
+        }
+        if org_membership.role != "owner" {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Only organization owners can remove an owner".to_string())),
+            ));
+        }
+        let owner_count = memberships.iter().filter(|membership| membership.role == "owner").count();
+        if owner_count <= 1 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, "Cannot remove the last owner of the organization".to_string())),
+            ));
+        }
+    }
+
+    let now = Utc::now();
+    sqlx::query!(
+        "UPDATE organization_memberships SET is_active = false, updated_at = $1 WHERE organization_id = $2 AND user_id = $3",
+        now,
+        org_id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    // A removed member keeps no access through API keys they created
+    let key_ids = revoke_api_keys(&mut *tx, org_id, RevokedKeys::CreatedBy(user_id), now)
+        .await
+        .map_err(db_error)?;
+
+    audit::record(
+        &mut *tx,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "remove_member",
+            resource_type: "user",
+            resource_id: Some(user_id),
+            details: json!({ "role": target_role, "revoked_api_key_ids": key_ids }),
+        },
+    )
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Removed user {} from organization {} and revoked {} API keys", user_id, org_id, key_ids.len());
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Member removed successfully".to_string()),
+        code: None,
+        errors: None,
         timestamp: Utc::now(),
     }))
-}
\ No newline at end of file
+}
+
+pub async fn transfer_ownership(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+    Json(payload): Json<TransferOwnershipRequest>,
+) -> Result<Json<ApiResponse<OrganizationResponse>>, ErrorResponse> {
+    // Check if user is owner of this organization
+    let org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    if org_membership.role != "owner" {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Only organization owners can transfer ownership".to_string())),
+        ));
+    }
+
+    if payload.new_owner_user_id == current_user.id {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, "You already own this organization".to_string())),
+        ));
+    }
+
+    // The new owner must be an active member
+    let target_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        payload.new_owner_user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, "New owner must be an active member of the organization".to_string())),
+        )
+    })?;
+
+    let now = Utc::now();
+    let db_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to transfer ownership: {}", e))),
+        )
+    };
+
+    let mut tx = state.db_pool.begin().await.map_err(db_error)?;
+
+    // Demote the current owner to admin
+    sqlx::query!(
+        "UPDATE organization_memberships SET role = 'admin', updated_at = $1 WHERE organization_id = $2 AND user_id = $3",
+        now,
+        org_id,
+        current_user.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    // Promote the new owner
+    sqlx::query!(
+        "UPDATE organization_memberships SET role = 'owner', updated_at = $1 WHERE organization_id = $2 AND user_id = $3",
+        now,
+        org_id,
+        payload.new_owner_user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let organization = sqlx::query_as!(
+        Organization,
+        "UPDATE organizations SET owner_id = $1, updated_at = $2 WHERE id = $3 RETURNING *",
+        payload.new_owner_user_id,
+        now,
+        org_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    audit::record(
+        &mut *tx,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "transfer_ownership",
+            resource_type: "organization",
+            resource_id: Some(org_id),
+            details: json!({
+                "previous_owner_id": current_user.id,
+                "new_owner_id": payload.new_owner_user_id,
+                "new_owner_previous_role": target_membership.role,
+            }),
+        },
+    )
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    let org_response = organization_to_response(organization);
+
+    Ok(Json(ApiResponse::success(org_response)))
+}
+
+pub async fn update_plan(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+    Json(payload): Json<UpdatePlanRequest>,
+) -> Result<Json<ApiResponse<OrganizationResponse>>, ErrorResponse> {
+    // Check if user is owner of this organization
+    let org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    if org_membership.role != "owner" {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Only organization owners can change the plan".to_string())),
+        ));
+    }
+
+    let plan = payload.plan;
+
+    // Apply the plan and its quotas
+    let organization = sqlx::query_as!(
+        Organization,
+        r#"
+        UPDATE organizations
+        SET plan = $1, max_redis_instances = $2, max_api_keys = $3, updated_at = $4
+        WHERE id = $5
+        RETURNING *
+        "#,
+        plan.as_str(),
+        plan.max_redis_instances(),
+        plan.max_api_keys(),
+        Utc::now(),
+        org_id
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to update plan: {}", e))),
+        )
+    })?;
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "update_plan",
+            resource_type: "organization",
+            resource_id: Some(org_id),
+            details: json!({ "plan": plan.as_str() }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record plan change in audit log: {}", e);
+    }
+
+    let org_response = organization_to_response(organization);
+
+    Ok(Json(ApiResponse::success(org_response)))
+}
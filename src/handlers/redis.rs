@@ -1,20 +1,37 @@
 // Redis HTTP API handlers
 
 use axum::{
-    extract::{Path, Query, State},
-    http::{StatusCode, HeaderMap},
-    response::Json,
+    async_trait,
+    extract::{Extension, FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, StatusCode, HeaderMap},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json,
+    },
 };
-use redis::{Commands, Connection, Client};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{Stream, StreamExt};
+use deadpool::managed::{PoolError, TimeoutType};
+use redis::aio::ConnectionLike;
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 use tracing::{info, warn, error};
 
 use crate::middleware::AppState;
 use crate::models::RedisInstance;
-use crate::auth::ApiKeyClaims;
+use crate::auth::{ApiKeyClaims, AuthError};
+use crate::command_audit::AuditedCommand;
+use crate::config::RedisHandlerConfig;
+use crate::redis_acl::AclUser;
+use crate::redis_pool::{is_auth_error, CheckoutError, Endpoint, LimitedConnection};
 
 type ErrorResponse = (StatusCode, Json<Value>);
 
@@ -24,14 +41,8 @@ pub struct RedisResponse {
     result: Value,
 }
 
-/// Redis error response format
-#[derive(serde::Serialize)]
-struct RedisErrorResponse {
-    error: String,
-}
-
 /// Extract API key from headers or query parameters
-fn extract_api_key(headers: &HeaderMap, query: &Query<HashMap<String, String>>) -> Option<String> {
+pub(crate) fn extract_api_key(headers: &HeaderMap, query: &HashMap<String, String>) -> Option<String> {
     // First try Authorization header
     if let Some(auth_header) = headers.get("authorization") {
         if let Ok(auth_str) = auth_header.to_str() {
@@ -52,12 +63,8 @@ fn extract_api_key(headers: &HeaderMap, query: &Query<HashMap<String, String>>)
     None
 }
 
-/// Authenticate API key (JWT) and get Redis instance
-async fn authenticate_and_get_instance(
-    state: &AppState,
-    api_key_token: &str,
-    instance_id: Uuid,
-) -> Result<(RedisInstance, ApiKeyClaims), ErrorResponse> {
+/// Authenticate an API key (JWT)
+pub(crate) fn verify_api_key(state: &AppState, api_key_token: &str) -> Result<ApiKeyClaims, ErrorResponse> {
     // Verify JWT token directly (no database lookup needed!)
     let token_data = state.jwt_manager.verify_api_key_token(api_key_token)
         .map_err(|_| {
@@ -70,17 +77,25 @@ async fn authenticate_and_get_instance(
 
     let claims = token_data.claims;
     info!("Authenticated API key: {} for organization: {}", claims.key_prefix, claims.organization_id);
+    Ok(claims)
+}
 
-    // Get Redis instance and verify organization access
+/// Get the Redis instance an authenticated API key is calling, if it belongs to the key's organization
+async fn instance_for_key(
+    state: &AppState,
+    claims: &ApiKeyClaims,
+    instance_id: Uuid,
+) -> Result<RedisInstance, ErrorResponse> {
     let instance = sqlx::query_as!(
         RedisInstance,
         r#"
         SELECT id, name, slug, organization_id, api_key_id,
                port, private_ip_address, public_ip_address, domain,
                max_memory, current_memory, password_hash, redis_version,
-               namespace, pod_name, service_name,
+               namespace, pod_name, service_name, replica_service_name,
                status, last_health_check_at, health_status,
-               cpu_usage_percent, memory_usage_percent, connections_count, max_connections,
+               cpu_usage_percent, memory_usage_percent, connections_count, max_connections, eviction_policy, tls_enabled, acl_enabled, max_concurrent_commands, max_value_bytes,
+               status_reason,
                persistence_enabled, backup_enabled, last_backup_at,
                created_at, updated_at, deleted_at
         FROM redis_instances 
@@ -107,246 +122,1873 @@ async fn authenticate_and_get_instance(
         )
     })?;
 
-    Ok((instance, claims))
+    Ok(instance)
 }
 
-/// Get Redis connection for an instance
-async fn get_redis_connection(_instance: &RedisInstance) -> Result<Connection, ErrorResponse> {
-    // For development, we'll connect to localhost:6379
-    // In production, this would connect to the actual Redis instance
-    let redis_url = "redis://127.0.0.1:6379/";
-    
-    let client = Client::open(redis_url).map_err(|e| {
-        error!("Failed to create Redis client: {}", e);
+/// Verify the request's API key and return its claims, without running a command.
+///
+/// Invalid and expired keys fail with 401 and a `reason` of `invalid` or `expired`. Valid keys
+/// report `active: false` once revoked; revoked keys keep working until they expire, since
+/// the gateway checks only the token.
+pub async fn handle_introspect_key(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ErrorResponse> {
+    let api_key = extract_api_key(&headers, &query).ok_or_else(|| {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Failed to connect to Redis"})),
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "Missing API key"})),
         )
     })?;
 
-    let connection = client.get_connection().map_err(|e| {
-        error!("Failed to get Redis connection: {}", e);
+    let claims = state
+        .jwt_manager
+        .verify_api_key_token(&api_key)
+        .map_err(|e| {
+            let (message, reason) = match e {
+                AuthError::TokenExpired => ("API key has expired", "expired"),
+                _ => ("Invalid API key", "invalid"),
+            };
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"error": message, "reason": reason})),
+            )
+        })?
+        .claims;
+
+    let active: bool = sqlx::query_scalar(
+        "SELECT COALESCE(is_active, true) FROM api_keys WHERE id = $1 AND organization_id = $2",
+    )
+    .bind(claims.api_key_id)
+    .bind(claims.organization_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        error!("Database error looking up API key {}: {}", claims.key_prefix, e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Failed to connect to Redis"})),
+            Json(json!({"error": "Internal server error"})),
         )
-    })?;
+    })?
+    .unwrap_or(false);
 
-    Ok(connection)
+    let timestamp = |seconds: i64| chrono::DateTime::from_timestamp(seconds, 0).map(|time| time.to_rfc3339());
+    Ok(Json(json!({
+        "key_id": claims.api_key_id,
+        "key_prefix": claims.key_prefix,
+        "organization_id": claims.organization_id,
+        "user_id": claims.user_id,
+        "scopes": claims.scopes,
+        "issued_at": timestamp(claims.iat),
+        "expires_at": timestamp(claims.exp),
+        "active": active,
+    })))
 }
 
-/// Convert Redis value to JSON
-fn redis_value_to_json(value: redis::Value) -> Value {
-    match value {
-        redis::Value::Nil => Value::Null,
-        redis::Value::Int(i) => Value::Number(serde_json::Number::from(i)),
-        redis::Value::Data(bytes) => {
-            if let Ok(s) = String::from_utf8(bytes) {
-                Value::String(s)
-            } else {
-                Value::Null
-            }
+/// Get a pooled Redis connection for an instance, authenticated as the ACL user matching
+/// the API key's scopes and switched to the requested logical database.
+///
+/// The connection holds one of the instance's concurrency permits until it is dropped;
+/// when none is free the request fails with 503.
+async fn get_redis_connection(
+    state: &AppState,
+    instance: &RedisInstance,
+    user: AclUser,
+    endpoint: Endpoint,
+    db: i64,
+) -> Result<LimitedConnection, ErrorResponse> {
+    let limit = instance.max_concurrent_commands.map(|limit| limit as usize);
+    let permit = state.instance_limiter.try_acquire(instance.id, limit).ok_or_else(|| {
+        warn!("Concurrency limit reached for instance {}", instance.id);
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Redis instance busy: too many concurrent commands"})),
+        )
+    })?;
+
+    let connect_timeout = state.redis_config.connect_timeout;
+    let mut connection = state.redis_executor.checkout(instance, user, endpoint).await.map_err(|e| match e {
+        CheckoutError::Pool(PoolError::Timeout(TimeoutType::Wait)) => {
+            warn!("No pooled Redis connection available for instance {}", instance.id);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"error": "Redis instance busy: no connection available"})),
+            )
         }
-        redis::Value::Bulk(values) => {
-            let json_values: Vec<Value> = values
-                .into_iter()
-                .map(redis_value_to_json)
-                .collect();
-            Value::Array(json_values)
+        CheckoutError::Pool(PoolError::Timeout(_)) => {
+            warn!("Timed out connecting to Redis after {:?}", connect_timeout);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"error": "Redis instance unavailable: connection timed out"})),
+            )
         }
-        redis::Value::Status(s) => Value::String(s),
-        redis::Value::Okay => Value::String("OK".to_string()),
+        CheckoutError::CircuitOpen => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Redis instance unavailable: too many recent connection failures"})),
+        ),
+        CheckoutError::Pool(PoolError::Backend(e)) if is_auth_error(&e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({"error": "Redis credential mismatch: the instance rejected the gateway's password"})),
+        ),
+        e => {
+            error!("Failed to get Redis connection: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Failed to connect to Redis"})),
+            )
+        }
+    })?;
+
+    if db != 0 {
+        with_timeout(connect_timeout, connection.select_db(db))
+            .await?
+            .map_err(|e| {
+                error!("Redis SELECT {} failed: {}", db, e);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Failed to select database {}", db)})),
+                )
+            })?;
     }
+
+    Ok(LimitedConnection::new(connection, permit))
 }
 
-/// Handle PING command
-pub async fn handle_ping(
-    State(state): State<Arc<AppState>>,
-    Path(instance_id): Path<Uuid>,
-    Query(query): Query<HashMap<String, String>>,
-    headers: HeaderMap,
-) -> Result<Json<RedisResponse>, ErrorResponse> {
-    info!("PING request for instance_id: {}", instance_id);
-    
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+/// Resolve the logical database for a request from the `db` query parameter
+fn logical_db(state: &AppState, query: &HashMap<String, String>) -> Result<i64, ErrorResponse> {
+    let Some(db) = query.get("db") else {
+        return Ok(0);
+    };
+
+    db.parse::<i64>()
+        .ok()
+        .filter(|db| (0..=state.redis_config.max_db).contains(db))
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": format!("Invalid db: must be between 0 and {}", state.redis_config.max_db)
+                })),
+            )
+        })
+}
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+/// Resolve the command timeout for a request.
+///
+/// Clients may lower or raise the default with the `X-Command-Timeout-Ms` header
+/// or the `_timeout_ms` query parameter, capped at the server maximum.
+fn command_timeout(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: &HashMap<String, String>,
+) -> Result<Duration, ErrorResponse> {
+    let requested = headers
+        .get("x-command-timeout-ms")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| query.get("_timeout_ms").map(String::as_str));
 
-    let result: String = redis::cmd("PING").query(&mut conn).map_err(|e| {
-        error!("Redis PING failed: {}", e);
+    let Some(requested) = requested else {
+        return Ok(state.redis_config.command_timeout);
+    };
+
+    let millis: u64 = requested.parse().ok().filter(|ms| *ms > 0).ok_or_else(|| {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Redis command failed"})),
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Invalid command timeout"})),
         )
     })?;
 
-    Ok(Json(RedisResponse {
-        result: Value::String(result),
-    }))
+    Ok(Duration::from_millis(millis).min(state.redis_config.max_command_timeout))
 }
 
-/// Handle SET command
-pub async fn handle_set(
-    State(state): State<Arc<AppState>>,
-    Path((instance_id, key, value)): Path<(Uuid, String, String)>,
-    Query(query): Query<HashMap<String, String>>,
-    headers: HeaderMap,
-) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query.clone())).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+/// Pick the server a command runs on: reads go to the instance's replicas unless the request
+/// asks for `consistency=primary`; writes and pass-through commands always go to the primary
+fn command_endpoint(access: Option<CommandAccess>, query: &HashMap<String, String>) -> Result<Endpoint, ErrorResponse> {
+    let read_endpoint = match query.get("consistency").map(String::as_str) {
+        None | Some("replica") => Endpoint::Replica,
+        Some("primary") => Endpoint::Primary,
+        Some(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid consistency: must be primary or replica"})),
+            ))
+        }
+    };
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+    Ok(match access {
+        Some(CommandAccess::Read) => read_endpoint,
+        Some(CommandAccess::Write) | None => Endpoint::Primary,
+    })
+}
 
-    // Handle optional parameters from query string
-    let result = if let Some(ex) = query.get("EX") {
-        let expire_seconds: u64 = ex.parse().map_err(|_| {
+/// Path parameters of the per-command routes
+#[derive(Debug, serde::Deserialize)]
+pub struct KeyPath {
+    instance_id: Uuid,
+    key: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KeyValuePath {
+    instance_id: Uuid,
+    key: String,
+    value: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FieldPath {
+    instance_id: Uuid,
+    key: String,
+    field: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FieldValuePath {
+    instance_id: Uuid,
+    key: String,
+    field: String,
+    value: String,
+}
+
+pub trait CommandPath {
+    fn key(&self) -> &str;
+
+    fn field(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl CommandPath for KeyPath {
+    fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl CommandPath for KeyValuePath {
+    fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl CommandPath for FieldPath {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn field(&self) -> Option<&str> {
+        Some(&self.field)
+    }
+}
+
+impl CommandPath for FieldValuePath {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn field(&self) -> Option<&str> {
+        Some(&self.field)
+    }
+}
+
+/// Extractor for the per-command routes' path parameters.
+///
+/// Each segment is percent-decoded (`%2F` becomes `/`, `%20` a space) exactly once. Segments
+/// that do not decode to UTF-8, empty keys or fields, and keys longer than the configured
+/// maximum are rejected with 400 before any Redis work is done.
+pub struct CommandPathParams<T>(pub T);
+
+#[async_trait]
+impl<T> FromRequestParts<Arc<AppState>> for CommandPathParams<T>
+where
+    T: CommandPath + DeserializeOwned + Send,
+{
+    type Rejection = ErrorResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let Path(params) = Path::<T>::from_request_parts(parts, state).await.map_err(|rejection| {
             (
                 StatusCode::BAD_REQUEST,
-                Json(json!({"error": "Invalid EX parameter"})),
+                Json(json!({"error": format!("Invalid path parameters: {}", rejection.body_text())})),
             )
         })?;
-        conn.set_ex(&key, &value, expire_seconds)
-    } else {
-        conn.set(&key, &value)
+        check_path_params(&state.redis_config, params.key(), params.field())?;
+        Ok(Self(params))
     }
-    .map_err(|e| {
-        error!("Redis SET failed: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Redis command failed"})),
-        )
-    })?;
+}
 
-    Ok(Json(RedisResponse {
-        result: redis_value_to_json(result),
-    }))
+fn check_path_params(config: &RedisHandlerConfig, key: &str, field: Option<&str>) -> Result<(), ErrorResponse> {
+    let bad_request = |message: String| (StatusCode::BAD_REQUEST, Json(json!({"error": message})));
+
+    if key.is_empty() {
+        return Err(bad_request("Key must not be empty".to_string()));
+    }
+    if key.len() > config.max_key_bytes {
+        return Err(bad_request(format!(
+            "Key exceeds the maximum length of {} bytes",
+            config.max_key_bytes
+        )));
+    }
+    if field == Some("") {
+        return Err(bad_request("Hash field must not be empty".to_string()));
+    }
+    Ok(())
 }
 
-/// Handle GET command
-pub async fn handle_get(
-    State(state): State<Arc<AppState>>,
-    Path((instance_id, key)): Path<(Uuid, String)>,
-    Query(query): Query<HashMap<String, String>>,
-    headers: HeaderMap,
-) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+/// Reject commands with too many arguments (400) or whose arguments exceed the
+/// configured size limits (413)
+fn check_command_size(config: &RedisHandlerConfig, command: &str, args: &[String]) -> Result<(), ErrorResponse> {
+    if args.len() > config.max_command_args {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("Command exceeds the maximum of {} arguments", config.max_command_args)})),
+        ));
+    }
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+    let too_large = |message: String| (StatusCode::PAYLOAD_TOO_LARGE, Json(json!({"error": message})));
 
-    let result: redis::Value = conn.get(&key).map_err(|e| {
-        error!("Redis GET failed: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Redis command failed"})),
-        )
-    })?;
+    if let Some(index) = args.iter().position(|arg| arg.len() > config.max_arg_bytes) {
+        return Err(too_large(format!(
+            "Argument {} exceeds the maximum size of {} bytes",
+            index + 1,
+            config.max_arg_bytes
+        )));
+    }
 
-    Ok(Json(RedisResponse {
-        result: redis_value_to_json(result),
-    }))
+    let total = command.len() + args.iter().map(String::len).sum::<usize>();
+    if total > config.max_command_bytes {
+        return Err(too_large(format!(
+            "Command exceeds the maximum size of {} bytes",
+            config.max_command_bytes
+        )));
+    }
+
+    Ok(())
 }
 
-/// Handle DEL command
-pub async fn handle_del(
-    State(state): State<Arc<AppState>>,
-    Path((instance_id, key)): Path<(Uuid, String)>,
-    Query(query): Query<HashMap<String, String>>,
-    headers: HeaderMap,
-) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+/// The instance's limit on values written by SET-family commands, if it has one
+fn value_limit(instance: &RedisInstance) -> Option<usize> {
+    instance.max_value_bytes.map(|limit| limit as usize)
+}
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+/// Reject a value larger than the instance's `max_value_bytes` (413)
+fn check_value_size(max_value_bytes: Option<usize>, len: usize) -> Result<(), ErrorResponse> {
+    match max_value_bytes {
+        Some(limit) if len > limit => Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(json!({"error": format!("Value exceeds the instance's maximum of {} bytes", limit)})),
+        )),
+        _ => Ok(()),
+    }
+}
 
-    let result: i32 = conn.del(&key).map_err(|e| {
-        error!("Redis DEL failed: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Redis command failed"})),
-        )
-    })?;
+/// Values written by a SET-family command sent to the generic endpoint
+fn set_values<'a>(command: &str, args: &'a [String]) -> Vec<&'a String> {
+    match command.to_ascii_uppercase().as_str() {
+        "SET" | "SETNX" | "GETSET" | "APPEND" => args.get(1).into_iter().collect(),
+        "SETEX" | "PSETEX" | "SETRANGE" => args.get(2).into_iter().collect(),
+        "MSET" | "MSETNX" => args.iter().skip(1).step_by(2).collect(),
+        _ => Vec::new(),
+    }
+}
 
-    Ok(Json(RedisResponse {
-        result: Value::Number(serde_json::Number::from(result)),
-    }))
+/// Whether a command only reads data or can modify it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandAccess {
+    Read,
+    Write,
 }
 
-/// Handle generic Redis command via POST with JSON body
-pub async fn handle_generic_command(
-    State(state): State<Arc<AppState>>,
-    Path(instance_id): Path<Uuid>,
-    Query(query): Query<HashMap<String, String>>,
-    headers: HeaderMap,
-    Json(payload): Json<Vec<Value>>,
-) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+impl CommandAccess {
+    /// API key scope needed to run commands of this kind
+    pub fn required_scope(self) -> &'static str {
+        match self {
+            CommandAccess::Read => "read",
+            CommandAccess::Write => "write",
+        }
+    }
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+    // Write and admin keys may also read
+    pub(crate) fn allowed_by(self, scopes: &[String]) -> bool {
+        let allowed: &[&str] = match self {
+            CommandAccess::Read => &["read", "write", "admin"],
+            CommandAccess::Write => &["write", "admin"],
+        };
+        scopes.iter().any(|scope| allowed.contains(&scope.as_str()))
+    }
+}
 
-    if payload.is_empty() {
+/// Commands the generic endpoint handles explicitly, and the access each needs.
+/// Other commands are passed through to Redis as-is and need the write scope.
+const SUPPORTED_COMMANDS: &[(&str, CommandAccess)] = &[
+    ("PING", CommandAccess::Read),
+    ("GET", CommandAccess::Read),
+    ("SET", CommandAccess::Write),
+    ("DEL", CommandAccess::Write),
+    ("INCR", CommandAccess::Write),
+    ("DECR", CommandAccess::Write),
+    ("EXISTS", CommandAccess::Read),
+    ("TOUCH", CommandAccess::Read),
+    ("EXPIRE", CommandAccess::Write),
+    ("PEXPIRE", CommandAccess::Write),
+    ("EXPIREAT", CommandAccess::Write),
+    ("PEXPIREAT", CommandAccess::Write),
+    ("TTL", CommandAccess::Read),
+    ("DUMP", CommandAccess::Read),
+    ("RESTORE", CommandAccess::Write),
+    ("COPY", CommandAccess::Write),
+    ("APPEND", CommandAccess::Write),
+    ("STRLEN", CommandAccess::Read),
+    ("LPUSH", CommandAccess::Write),
+    ("RPUSH", CommandAccess::Write),
+    ("LPOP", CommandAccess::Write),
+    ("RPOP", CommandAccess::Write),
+    ("LLEN", CommandAccess::Read),
+    ("LRANGE", CommandAccess::Read),
+    ("LPOS", CommandAccess::Read),
+    ("HSET", CommandAccess::Write),
+    ("HGET", CommandAccess::Read),
+    ("HDEL", CommandAccess::Write),
+    ("HEXISTS", CommandAccess::Read),
+    ("HGETALL", CommandAccess::Read),
+    ("HKEYS", CommandAccess::Read),
+    ("HVALS", CommandAccess::Read),
+    ("HSCAN", CommandAccess::Read),
+    ("SADD", CommandAccess::Write),
+    ("SREM", CommandAccess::Write),
+    ("SISMEMBER", CommandAccess::Read),
+    ("SMISMEMBER", CommandAccess::Read),
+    ("SMEMBERS", CommandAccess::Read),
+    ("SCARD", CommandAccess::Read),
+    ("SINTERCARD", CommandAccess::Read),
+    ("SSCAN", CommandAccess::Read),
+    ("GEOADD", CommandAccess::Write),
+    ("GEOSEARCH", CommandAccess::Read),
+    ("GEODIST", CommandAccess::Read),
+    ("GEOPOS", CommandAccess::Read),
+    ("ZADD", CommandAccess::Write),
+    ("ZSCAN", CommandAccess::Read),
+    ("SORT", CommandAccess::Write),
+    ("SORT_RO", CommandAccess::Read),
+    ("OBJECT", CommandAccess::Read),
+    ("MEMORY", CommandAccess::Read),
+    ("PUBLISH", CommandAccess::Write),
+    ("PUBSUB", CommandAccess::Read),
+];
+
+/// Access needed by an explicitly supported command, or None for pass-through commands
+pub(crate) fn command_access(command: &str) -> Option<CommandAccess> {
+    SUPPORTED_COMMANDS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(command))
+        .map(|(_, access)| *access)
+}
+
+// Key a generic-endpoint command acts on, for command auditing; None for keyless and pass-through commands
+fn command_key<'a>(command: &str, payload: &'a [Value]) -> Option<&'a str> {
+    let position = match command.to_ascii_uppercase().as_str() {
+        "PING" | "PUBLISH" | "PUBSUB" => return None,
+        // Subcommand or key count first
+        "OBJECT" | "MEMORY" | "SINTERCARD" => 2,
+        _ if command_access(command).is_some() => 1,
+        _ => return None,
+    };
+    payload.get(position).and_then(Value::as_str)
+}
+
+// Refuse a generic-endpoint command the API key's scopes do not allow. Pass-through commands
+// are not classified, and some (FLUSHALL, CONFIG, EVAL) change the whole instance, so they need
+// a key that may write.
+fn check_command_scope(claims: &ApiKeyClaims, command: &str) -> Result<(), ErrorResponse> {
+    let access = command_access(command).unwrap_or(CommandAccess::Write);
+    if access.allowed_by(&claims.scopes) {
+        return Ok(());
+    }
+    warn!("API key {} lacks the {} scope for {}", claims.key_prefix, access.required_scope(), command);
+    Err((
+        StatusCode::FORBIDDEN,
+        Json(json!({"error": format!("API key requires the {} scope", access.required_scope())})),
+    ))
+}
+
+// Commands that leave state on the connection they run on, such as an open transaction or
+// client tracking. Connections are pooled and shared between API keys, so the next request
+// would inherit that state.
+const CONNECTION_STATE_COMMANDS: &[&str] = &[
+    "MULTI", "EXEC", "DISCARD", "WATCH", "UNWATCH", "SELECT", "AUTH", "HELLO", "RESET", "QUIT",
+    "CLIENT", "MONITOR", "READONLY", "READWRITE", "UNSUBSCRIBE", "PUNSUBSCRIBE", "SUNSUBSCRIBE",
+];
+
+// Refuse a generic-endpoint command that would change the state of a pooled connection
+fn check_connection_command(command: &str) -> Result<(), ErrorResponse> {
+    let name = command.to_ascii_uppercase();
+    // Subscribing would tie up a pooled connection; subscriptions need a streaming endpoint
+    if matches!(name.as_str(), "SUBSCRIBE" | "PSUBSCRIBE" | "SSUBSCRIBE") {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(json!({"error": "Empty command"})),
+            Json(json!({"error": format!("{} is not supported over request/response; use a streaming endpoint", name)})),
         ));
     }
-
-    // Extract command and arguments
-    let command = payload[0].as_str().ok_or_else(|| {
-        (
+    if CONNECTION_STATE_COMMANDS.contains(&name.as_str()) {
+        return Err((
             StatusCode::BAD_REQUEST,
-            Json(json!({"error": "Invalid command format"})),
-        )
-    })?;
+            Json(json!({"error": format!("{} is not supported: it changes the state of the gateway's shared connections", name)})),
+        ));
+    }
+    Ok(())
+}
+
+// Hand a command the API key may run to the organization's command audit, which records a
+// sample if auditing is on. Called once per request.
+fn audit_command(state: &AppState, instance: &RedisInstance, claims: &ApiKeyClaims, command: &str, key: Option<&str>) {
+    state.command_audit.record(AuditedCommand {
+        organization_id: instance.organization_id,
+        instance_id: instance.id,
+        api_key_id: claims.api_key_id,
+        command,
+        key,
+        scope: command_access(command).map(CommandAccess::required_scope),
+    });
+}
 
-    let args: Vec<String> = payload[1..]
+/// List the commands the generic endpoint supports explicitly, with the scope each requires
+pub async fn handle_list_commands() -> Json<RedisResponse> {
+    let commands = SUPPORTED_COMMANDS
         .iter()
-        .map(|v| match v {
-            Value::String(s) => s.clone(),
-            Value::Number(n) => n.to_string(),
-            _ => v.to_string(),
+        .map(|(name, access)| {
+            json!({
+                "name": name,
+                "access": access,
+                "required_scope": access.required_scope(),
+            })
         })
         .collect();
 
-    info!("Executing Redis command: {} with args: {:?}", command, args);
+    Json(RedisResponse {
+        result: Value::Array(commands),
+    })
+}
+
+/// Parse `SINTERCARD numkeys key [key ...] [LIMIT limit]` into the keys and limit
+fn parse_sintercard_args(args: &[String]) -> Result<(&[String], Option<u64>), String> {
+    let numkeys: usize = args
+        .first()
+        .and_then(|numkeys| numkeys.parse().ok())
+        .filter(|numkeys| *numkeys > 0)
+        .ok_or("SINTERCARD requires a positive numkeys")?;
+    let keys = args.get(1..=numkeys).ok_or("SINTERCARD numkeys does not match the number of keys")?;
+
+    match &args[numkeys + 1..] {
+        [] => Ok((keys, None)),
+        [option, limit] if option.eq_ignore_ascii_case("LIMIT") => {
+            let limit = limit.parse().map_err(|_| "SINTERCARD LIMIT must be a non-negative integer")?;
+            Ok((keys, Some(limit)))
+        }
+        _ => Err("SINTERCARD accepts only an optional LIMIT after the keys".to_string()),
+    }
+}
+
+/// Arguments of `ZADD key [NX | XX] [GT | LT] [CH] [INCR] score member [...]`
+#[derive(Debug, PartialEq)]
+struct ZAddArgs<'a> {
+    key: &'a str,
+    flags: Vec<&'static str>,
+    incr: bool, // Add the score to the member's and reply with the new score
+    items: Vec<(&'a str, &'a str)>, // Scores are passed on as given, so `+inf` and `-inf` keep working
+}
+
+fn parse_zadd_args(args: &[String]) -> Result<ZAddArgs<'_>, String> {
+    let [key, rest @ ..] = args else {
+        return Err("ZADD requires key and score member pairs".to_string());
+    };
+    let mut flags = Vec::new();
+    let mut incr = false;
+    let mut rest = rest;
+    while let [flag, tail @ ..] = rest {
+        match flag.to_uppercase().as_str() {
+            "NX" => flags.push("NX"),
+            "XX" => flags.push("XX"),
+            "GT" => flags.push("GT"),
+            "LT" => flags.push("LT"),
+            "CH" => flags.push("CH"),
+            "INCR" => incr = true,
+            _ => break,
+        }
+        rest = tail;
+    }
+    let has = |flag| flags.contains(&flag);
+    if has("NX") && has("XX") {
+        return Err("ZADD NX cannot be combined with XX".to_string());
+    }
+    if has("GT") && has("LT") {
+        return Err("ZADD GT cannot be combined with LT".to_string());
+    }
+    if has("NX") && (has("GT") || has("LT")) {
+        return Err("ZADD NX cannot be combined with GT or LT".to_string());
+    }
+    if rest.is_empty() || rest.len() % 2 != 0 {
+        return Err("ZADD requires key and score member pairs".to_string());
+    }
+    if incr && rest.len() != 2 {
+        return Err("ZADD INCR accepts a single score member pair".to_string());
+    }
+
+    let items = rest
+        .chunks(2)
+        .map(|pair| match pair[0].parse::<f64>() {
+            Ok(score) if !score.is_nan() => Ok((pair[0].as_str(), pair[1].as_str())),
+            _ => Err(format!("ZADD score is not a number: {}", pair[0])),
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(ZAddArgs { key, flags, incr, items })
+}
+
+/// Options for `LPOS key element [RANK rank] [COUNT num-matches] [MAXLEN len]`
+#[derive(Debug, Default, PartialEq)]
+struct LposOptions {
+    rank: Option<i64>,
+    count: Option<u64>,
+    maxlen: Option<u64>,
+}
+
+fn parse_lpos_options(options: &[String]) -> Result<LposOptions, String> {
+    let mut parsed = LposOptions::default();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .ok_or_else(|| format!("LPOS {} requires a value", option.to_uppercase()))?;
+        match option.to_uppercase().as_str() {
+            "RANK" => {
+                let rank = value.parse().ok().filter(|rank| *rank != 0);
+                parsed.rank = Some(rank.ok_or("LPOS RANK must be a non-zero integer")?);
+            }
+            "COUNT" => parsed.count = Some(value.parse().map_err(|_| "LPOS COUNT must be a non-negative integer")?),
+            "MAXLEN" => parsed.maxlen = Some(value.parse().map_err(|_| "LPOS MAXLEN must be a non-negative integer")?),
+            other => return Err(format!("Unknown LPOS option {}", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Validate the conditional flags of EXPIRE and its variants. NX excludes the others and
+/// GT excludes LT; XX may be combined with GT or LT.
+fn parse_expire_flags(flags: &[String]) -> Result<Vec<&'static str>, String> {
+    let mut parsed: Vec<&'static str> = Vec::new();
+    for flag in flags {
+        let flag = match flag.to_uppercase().as_str() {
+            "NX" => "NX",
+            "XX" => "XX",
+            "GT" => "GT",
+            "LT" => "LT",
+            other => return Err(format!("Unknown expire flag {}", other)),
+        };
+        if !parsed.contains(&flag) {
+            parsed.push(flag);
+        }
+    }
+
+    let has = |flag| parsed.contains(&flag);
+    if (has("NX") && parsed.len() > 1) || (has("GT") && has("LT")) {
+        return Err("NX cannot be combined with other flags, and GT cannot be combined with LT".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Arguments of `RESTORE key ttl serialized-value [REPLACE] [ABSTTL]`, with the
+/// serialized value given as base64 (as returned by DUMP)
+#[derive(Debug, PartialEq)]
+struct RestoreArgs<'a> {
+    key: &'a str,
+    ttl_ms: u64,
+    payload: Vec<u8>,
+    replace: bool,
+    absttl: bool,
+}
+
+fn parse_restore_args(args: &[String]) -> Result<RestoreArgs<'_>, String> {
+    let [key, ttl, payload, options @ ..] = args else {
+        return Err("RESTORE requires key, ttl and serialized value".to_string());
+    };
+    let mut restore = RestoreArgs {
+        key,
+        ttl_ms: ttl.parse().map_err(|_| "RESTORE ttl must be a non-negative integer")?,
+        payload: STANDARD
+            .decode(payload)
+            .map_err(|_| "RESTORE serialized value must be base64-encoded")?,
+        replace: false,
+        absttl: false,
+    };
+    for option in options {
+        match option.to_uppercase().as_str() {
+            "REPLACE" => restore.replace = true,
+            "ABSTTL" => restore.absttl = true,
+            other => return Err(format!("Unknown RESTORE option {}", other)),
+        }
+    }
+    Ok(restore)
+}
+
+/// Arguments of `COPY source destination [DB db] [REPLACE]`
+#[derive(Debug, PartialEq)]
+struct CopyArgs<'a> {
+    source: &'a str,
+    destination: &'a str,
+    db: Option<i64>,
+    replace: bool,
+}
+
+// The destination database must be one the gateway lets clients select
+fn parse_copy_args(args: &[String], max_db: i64) -> Result<CopyArgs<'_>, String> {
+    let [source, destination, options @ ..] = args else {
+        return Err("COPY requires source and destination".to_string());
+    };
+    let mut copy = CopyArgs {
+        source,
+        destination,
+        db: None,
+        replace: false,
+    };
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match option.to_uppercase().as_str() {
+            "REPLACE" => copy.replace = true,
+            "DB" => {
+                let db = options
+                    .next()
+                    .and_then(|db| db.parse::<i64>().ok())
+                    .filter(|db| (0..=max_db).contains(db))
+                    .ok_or_else(|| format!("COPY DB must be between 0 and {}", max_db))?;
+                copy.db = Some(db);
+            }
+            other => return Err(format!("Unknown COPY option {}", other)),
+        }
+    }
+    Ok(copy)
+}
+
+/// Arguments of `SORT key [BY pattern] [LIMIT offset count] [GET pattern ...] [ASC | DESC] [ALPHA] [STORE destination]`
+#[derive(Debug, PartialEq)]
+struct SortArgs<'a> {
+    key: &'a str,
+    by: Option<&'a str>,
+    limit: Option<(i64, i64)>,
+    get: Vec<&'a str>, // In order; each adds one element per sorted item
+    descending: bool,
+    alpha: bool,
+    store: Option<&'a str>,
+}
+
+impl SortArgs<'_> {
+    // Rebuilt from the parsed options so no unchecked token reaches Redis
+    fn command(&self, name: &str) -> redis::Cmd {
+        let mut cmd = redis::cmd(name);
+        cmd.arg(self.key);
+        if let Some(pattern) = self.by {
+            cmd.arg("BY").arg(pattern);
+        }
+        if let Some((offset, count)) = self.limit {
+            cmd.arg("LIMIT").arg(offset).arg(count);
+        }
+        for pattern in &self.get {
+            cmd.arg("GET").arg(*pattern);
+        }
+        if self.descending {
+            cmd.arg("DESC");
+        }
+        if self.alpha {
+            cmd.arg("ALPHA");
+        }
+        if let Some(destination) = self.store {
+            cmd.arg("STORE").arg(destination);
+        }
+        cmd
+    }
+}
+
+// Options may come in any order; as in Redis, a repeated BY, LIMIT or ASC/DESC replaces the
+// earlier one. SORT_RO has no STORE.
+fn parse_sort_args<'a>(command: &str, args: &'a [String]) -> Result<SortArgs<'a>, String> {
+    let [key, options @ ..] = args else {
+        return Err(format!("{} requires key", command));
+    };
+    let mut sort = SortArgs {
+        key,
+        by: None,
+        limit: None,
+        get: Vec::new(),
+        descending: false,
+        alpha: false,
+        store: None,
+    };
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut operand = |name: &str| {
+            options
+                .next()
+                .map(String::as_str)
+                .ok_or_else(|| format!("{} {} requires a value", command, name))
+        };
+        match option.to_uppercase().as_str() {
+            "BY" => sort.by = Some(operand("BY")?),
+            "GET" => sort.get.push(operand("GET")?),
+            "STORE" if command == "SORT" => sort.store = Some(operand("STORE")?),
+            "LIMIT" => {
+                let offset = operand("LIMIT")?.parse::<i64>();
+                let count = operand("LIMIT")?.parse::<i64>();
+                match (offset, count) {
+                    (Ok(offset), Ok(count)) if offset >= 0 => sort.limit = Some((offset, count)),
+                    _ => return Err(format!("{} LIMIT requires a non-negative offset and a count", command)),
+                }
+            }
+            "ASC" => sort.descending = false,
+            "DESC" => sort.descending = true,
+            "ALPHA" => sort.alpha = true,
+            other => return Err(format!("Unknown {} option {}", command, other)),
+        }
+    }
+    Ok(sort)
+}
+
+// Longitude and latitude within the range Redis can index
+fn parse_coordinates(longitude: &str, latitude: &str) -> Result<(f64, f64), String> {
+    let longitude = longitude
+        .parse::<f64>()
+        .ok()
+        .filter(|longitude| (-180.0..=180.0).contains(longitude))
+        .ok_or("Longitude must be a number between -180 and 180")?;
+    let latitude = latitude
+        .parse::<f64>()
+        .ok()
+        .filter(|latitude| (-85.05112878..=85.05112878).contains(latitude))
+        .ok_or("Latitude must be a number between -85.05112878 and 85.05112878")?;
+    Ok((longitude, latitude))
+}
+
+fn parse_geo_unit(unit: &str) -> Result<&'static str, String> {
+    match unit.to_lowercase().as_str() {
+        "m" => Ok("m"),
+        "km" => Ok("km"),
+        "ft" => Ok("ft"),
+        "mi" => Ok("mi"),
+        _ => Err(format!("Unknown distance unit {}; use m, km, ft or mi", unit)),
+    }
+}
+
+fn parse_geo_distance(distance: &str) -> Result<f64, String> {
+    distance
+        .parse::<f64>()
+        .ok()
+        .filter(|distance| distance.is_finite() && *distance >= 0.0)
+        .ok_or_else(|| "Distances must be non-negative numbers".to_string())
+}
+
+/// Arguments of `GEOADD key [NX | XX] [CH] longitude latitude member [...]`
+#[derive(Debug, PartialEq)]
+struct GeoAddArgs<'a> {
+    key: &'a str,
+    flags: Vec<&'static str>,
+    items: Vec<(f64, f64, &'a str)>,
+}
+
+fn parse_geoadd_args(args: &[String]) -> Result<GeoAddArgs<'_>, String> {
+    let [key, rest @ ..] = args else {
+        return Err("GEOADD requires key and longitude latitude member triples".to_string());
+    };
+    let mut flags = Vec::new();
+    let mut rest = rest;
+    while let [flag, tail @ ..] = rest {
+        match flag.to_uppercase().as_str() {
+            "NX" => flags.push("NX"),
+            "XX" => flags.push("XX"),
+            "CH" => flags.push("CH"),
+            _ => break,
+        }
+        rest = tail;
+    }
+    if flags.contains(&"NX") && flags.contains(&"XX") {
+        return Err("GEOADD NX cannot be combined with XX".to_string());
+    }
+    if rest.is_empty() || rest.len() % 3 != 0 {
+        return Err("GEOADD requires key and longitude latitude member triples".to_string());
+    }
+
+    let items = rest
+        .chunks(3)
+        .map(|triple| -> Result<_, String> {
+            let (longitude, latitude) = parse_coordinates(&triple[0], &triple[1])?;
+            Ok((longitude, latitude, triple[2].as_str()))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(GeoAddArgs { key, flags, items })
+}
+
+/// Center of a GEOSEARCH: an existing member, or a position
+#[derive(Debug, PartialEq)]
+enum GeoOrigin<'a> {
+    Member(&'a str),
+    LonLat(f64, f64),
+}
+
+/// Area of a GEOSEARCH, with its unit
+#[derive(Debug, PartialEq)]
+enum GeoShape {
+    Radius(f64, &'static str),
+    Box(f64, f64, &'static str),
+}
+
+/// Arguments of `GEOSEARCH key FROMMEMBER member | FROMLONLAT longitude latitude
+/// BYRADIUS radius unit | BYBOX width height unit [ASC | DESC] [COUNT count [ANY]] [WITHCOORD] [WITHDIST]`
+#[derive(Debug, PartialEq)]
+struct GeoSearchArgs<'a> {
+    key: &'a str,
+    origin: GeoOrigin<'a>,
+    shape: GeoShape,
+    order: Option<&'static str>,
+    count: Option<(u64, bool)>, // Limit, and whether any matches will do rather than the nearest
+    with_coord: bool,
+    with_dist: bool,
+}
+
+fn parse_geosearch_args(args: &[String]) -> Result<GeoSearchArgs<'_>, String> {
+    let [key, options @ ..] = args else {
+        return Err("GEOSEARCH requires key, FROMMEMBER or FROMLONLAT, and BYRADIUS or BYBOX".to_string());
+    };
+    let mut origin = None;
+    let mut shape = None;
+    let mut order = None;
+    let mut count = None;
+    let mut with_coord = false;
+    let mut with_dist = false;
+
+    let mut options = options.iter().peekable();
+    while let Some(option) = options.next() {
+        let option = option.to_uppercase();
+        let mut value = || options.next().ok_or_else(|| format!("GEOSEARCH {} is missing a value", option));
+        match option.as_str() {
+            "FROMMEMBER" if origin.is_none() => origin = Some(GeoOrigin::Member(value()?.as_str())),
+            "FROMLONLAT" if origin.is_none() => {
+                let (longitude, latitude) = parse_coordinates(value()?, value()?)?;
+                origin = Some(GeoOrigin::LonLat(longitude, latitude));
+            }
+            "BYRADIUS" if shape.is_none() => {
+                let radius = parse_geo_distance(value()?)?;
+                shape = Some(GeoShape::Radius(radius, parse_geo_unit(value()?)?));
+            }
+            "BYBOX" if shape.is_none() => {
+                let width = parse_geo_distance(value()?)?;
+                let height = parse_geo_distance(value()?)?;
+                shape = Some(GeoShape::Box(width, height, parse_geo_unit(value()?)?));
+            }
+            "ASC" => order = Some("ASC"),
+            "DESC" => order = Some("DESC"),
+            "COUNT" => {
+                let limit = value()?
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|limit| *limit > 0)
+                    .ok_or("GEOSEARCH COUNT must be a positive integer")?;
+                let any = options.next_if(|next| next.eq_ignore_ascii_case("ANY")).is_some();
+                count = Some((limit, any));
+            }
+            "WITHCOORD" => with_coord = true,
+            "WITHDIST" => with_dist = true,
+            "FROMMEMBER" | "FROMLONLAT" => return Err("GEOSEARCH accepts only one of FROMMEMBER and FROMLONLAT".to_string()),
+            "BYRADIUS" | "BYBOX" => return Err("GEOSEARCH accepts only one of BYRADIUS and BYBOX".to_string()),
+            other => return Err(format!("Unknown GEOSEARCH option {}", other)),
+        }
+    }
+
+    Ok(GeoSearchArgs {
+        key,
+        origin: origin.ok_or("GEOSEARCH requires FROMMEMBER or FROMLONLAT")?,
+        shape: shape.ok_or("GEOSEARCH requires BYRADIUS or BYBOX")?,
+        order,
+        count,
+        with_coord,
+        with_dist,
+    })
+}
+
+impl GeoSearchArgs<'_> {
+    fn command(&self) -> redis::Cmd {
+        let mut cmd = redis::cmd("GEOSEARCH");
+        cmd.arg(self.key);
+        match self.origin {
+            GeoOrigin::Member(member) => cmd.arg("FROMMEMBER").arg(member),
+            GeoOrigin::LonLat(longitude, latitude) => cmd.arg("FROMLONLAT").arg(longitude).arg(latitude),
+        };
+        match self.shape {
+            GeoShape::Radius(radius, unit) => cmd.arg("BYRADIUS").arg(radius).arg(unit),
+            GeoShape::Box(width, height, unit) => cmd.arg("BYBOX").arg(width).arg(height).arg(unit),
+        };
+        if let Some(order) = self.order {
+            cmd.arg(order);
+        }
+        if let Some((limit, any)) = self.count {
+            cmd.arg("COUNT").arg(limit);
+            if any {
+                cmd.arg("ANY");
+            }
+        }
+        // Redis replies with the distance before the coordinates, whatever the argument order
+        if self.with_dist {
+            cmd.arg("WITHDIST");
+        }
+        if self.with_coord {
+            cmd.arg("WITHCOORD");
+        }
+        cmd
+    }
+}
+
+fn geo_entry(fields: Vec<(&str, redis::Value)>) -> redis::Value {
+    redis::Value::Map(
+        fields
+            .into_iter()
+            .map(|(name, value)| (redis::Value::SimpleString(name.to_string()), value))
+            .collect(),
+    )
+}
+
+fn geo_position(position: Option<(f64, f64)>) -> Vec<(&'static str, redis::Value)> {
+    match position {
+        Some((longitude, latitude)) => vec![
+            ("longitude", redis::Value::Double(longitude)),
+            ("latitude", redis::Value::Double(latitude)),
+        ],
+        None => Vec::new(),
+    }
+}
+
+/// Render GEOSEARCH matches: plain member names, or `{"member", "distance", "longitude",
+/// "latitude"}` objects when WITHDIST or WITHCOORD asked for more
+fn geo_search_results(reply: redis::Value, with_dist: bool, with_coord: bool) -> redis::RedisResult<redis::Value> {
+    if !with_dist && !with_coord {
+        return Ok(reply);
+    }
+
+    let matches: Vec<Vec<redis::Value>> = redis::from_owned_redis_value(reply)?;
+    let entries = matches
+        .into_iter()
+        .map(|parts| -> redis::RedisResult<redis::Value> {
+            let mut parts = parts.into_iter();
+            let mut next = || {
+                parts
+                    .next()
+                    .ok_or_else(|| redis::RedisError::from((redis::ErrorKind::TypeError, "Short GEOSEARCH reply")))
+            };
+            let member: String = redis::from_owned_redis_value(next()?)?;
+            let mut fields = vec![("member", redis::Value::SimpleString(member))];
+            if with_dist {
+                fields.push(("distance", redis::Value::Double(redis::from_owned_redis_value(next()?)?)));
+            }
+            if with_coord {
+                fields.extend(geo_position(Some(redis::from_owned_redis_value(next()?)?)));
+            }
+            Ok(geo_entry(fields))
+        })
+        .collect::<redis::RedisResult<_>>()?;
+    Ok(redis::Value::Array(entries))
+}
+
+/// Body of the POST variants of the per-command routes. Values travel in the body rather than
+/// the path so they may contain `/`, newlines or, base64-encoded, arbitrary bytes.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValueBody {
+    value: String,
+    #[serde(default)]
+    encoding: ValueEncoding,
+}
+
+/// Body of `POST /redis/:instance_id/set/:key`
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetBody {
+    value: String,
+    #[serde(default)]
+    encoding: ValueEncoding,
+    /// Expiry in seconds, like the `EX` query parameter of the GET route
+    ex: Option<u64>,
+}
+
+/// One entry of the `POST /redis/:instance_id/mset-ex` body
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetExItem {
+    key: String,
+    value: String,
+    #[serde(default)]
+    encoding: ValueEncoding,
+    /// Expiry in seconds; must be positive
+    ttl: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ValueEncoding {
+    #[default]
+    Utf8,
+    Base64,
+}
+
+fn decode_body_value(value: &str, encoding: ValueEncoding) -> Result<Vec<u8>, ErrorResponse> {
+    match encoding {
+        ValueEncoding::Utf8 => Ok(value.as_bytes().to_vec()),
+        ValueEncoding::Base64 => STANDARD.decode(value).map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Value is not valid base64"})),
+            )
+        }),
+    }
+}
+
+/// Options for `HSCAN/SSCAN/ZSCAN key cursor [MATCH pattern] [COUNT count]`
+#[derive(Debug, Default, PartialEq)]
+struct ScanOptions {
+    pattern: Option<String>,
+    count: Option<u64>,
+}
+
+fn parse_scan_options(command: &str, options: &[String]) -> Result<ScanOptions, String> {
+    let mut parsed = ScanOptions::default();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .ok_or_else(|| format!("{} {} requires a value", command, option.to_uppercase()))?;
+        match option.to_uppercase().as_str() {
+            "MATCH" => parsed.pattern = Some(value.clone()),
+            "COUNT" => {
+                let count = value.parse().ok().filter(|count| *count > 0);
+                parsed.count = Some(count.ok_or_else(|| format!("{} COUNT must be a positive integer", command))?);
+            }
+            other => return Err(format!("Unknown {} option {}", command, other)),
+        }
+    }
+    Ok(parsed)
+}
+
+fn parse_scan_cursor(cursor: &str) -> Result<u64, ErrorResponse> {
+    cursor.parse().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Invalid cursor"})),
+        )
+    })
+}
+
+/// Render one page of a collection scan as `{"cursor": "...", "items": [...]}`. HSCAN items
+/// are `{"field", "value"}` pairs and ZSCAN items `{"member", "score"}` pairs; a cursor of
+/// "0" means the scan is complete.
+fn scan_page(command: &str, cursor: String, items: Vec<String>) -> redis::Value {
+    let pair = |first: &str, second: &str, values: &[String]| -> redis::Value {
+        let second_value = match (command, values.get(1)) {
+            ("ZSCAN", Some(score)) => score
+                .parse()
+                .map(redis::Value::Double)
+                .unwrap_or_else(|_| redis::Value::SimpleString(score.clone())),
+            (_, Some(value)) => redis::Value::SimpleString(value.clone()),
+            (_, None) => redis::Value::Nil,
+        };
+        redis::Value::Map(vec![
+            (redis::Value::SimpleString(first.to_string()), redis::Value::SimpleString(values[0].clone())),
+            (redis::Value::SimpleString(second.to_string()), second_value),
+        ])
+    };
+    let items = match command {
+        "HSCAN" => items.chunks(2).map(|values| pair("field", "value", values)).collect(),
+        "ZSCAN" => items.chunks(2).map(|values| pair("member", "score", values)).collect(),
+        _ => items.into_iter().map(redis::Value::SimpleString).collect(),
+    };
+
+    redis::Value::Map(vec![
+        (redis::Value::SimpleString("cursor".to_string()), redis::Value::SimpleString(cursor)),
+        (redis::Value::SimpleString("items".to_string()), redis::Value::Array(items)),
+    ])
+}
+
+/// Fetch one page of HSCAN, SSCAN or ZSCAN
+async fn scan_collection(
+    conn: &mut LimitedConnection,
+    timeout: Duration,
+    command: &str,
+    key: &str,
+    cursor: u64,
+    options: &ScanOptions,
+) -> Result<redis::Value, ErrorResponse> {
+    let mut cmd = redis::cmd(command);
+    cmd.arg(key).arg(cursor);
+    if let Some(pattern) = &options.pattern {
+        cmd.arg("MATCH").arg(pattern);
+    }
+    if let Some(count) = options.count {
+        cmd.arg("COUNT").arg(count);
+    }
+    let (next_cursor, items): (String, Vec<String>) =
+        with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| match e.code() {
+            Some("WRONGTYPE") => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": format!("{} does not match the type of key {}", command, key)})),
+            ),
+            _ => {
+                error!("Redis {} failed: {}", command, e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            }
+        })?;
+    Ok(scan_page(command, next_cursor, items))
+}
+
+/// Await a Redis command, failing with 504 if it runs past the timeout
+async fn with_timeout<T>(
+    timeout: Duration,
+    command: impl Future<Output = redis::RedisResult<T>>,
+) -> Result<redis::RedisResult<T>, ErrorResponse> {
+    tokio::time::timeout(timeout, command).await.map_err(|_| {
+        warn!("Redis command timed out after {:?}", timeout);
+        (
+            StatusCode::GATEWAY_TIMEOUT,
+            Json(json!({"error": "Redis command timed out"})),
+        )
+    })
+}
+
+/// Convert Redis value to JSON
+pub(crate) fn redis_value_to_json(value: redis::Value) -> Value {
+    match value {
+        redis::Value::Nil => Value::Null,
+        redis::Value::Int(i) => Value::Number(serde_json::Number::from(i)),
+        redis::Value::BulkString(bytes) => {
+            if let Ok(s) = String::from_utf8(bytes) {
+                Value::String(s)
+            } else {
+                Value::Null
+            }
+        }
+        redis::Value::Array(values) | redis::Value::Set(values) => {
+            let json_values: Vec<Value> = values
+                .into_iter()
+                .map(redis_value_to_json)
+                .collect();
+            Value::Array(json_values)
+        }
+        redis::Value::SimpleString(s) => Value::String(s),
+        redis::Value::Okay => Value::String("OK".to_string()),
+        // RESP3 types
+        redis::Value::Map(pairs) => {
+            let object: serde_json::Map<String, Value> = pairs
+                .into_iter()
+                .map(|(k, v)| (redis_map_key(k), redis_value_to_json(v)))
+                .collect();
+            Value::Object(object)
+        }
+        redis::Value::Attribute { data, .. } => redis_value_to_json(*data),
+        redis::Value::Double(d) => serde_json::Number::from_f64(d)
+            .map(Value::Number)
+            .unwrap_or_else(|| Value::String(d.to_string())), // inf/nan have no JSON number form
+        redis::Value::Boolean(b) => Value::Bool(b),
+        redis::Value::VerbatimString { text, .. } => Value::String(text),
+        // Big numbers are rendered as strings to avoid losing precision
+        redis::Value::BigNumber(n) => Value::String(n.to_string()),
+        redis::Value::Push { data, .. } => {
+            Value::Array(data.into_iter().map(redis_value_to_json).collect())
+        }
+        redis::Value::ServerError(e) => json!({"error": e.code(), "detail": e.details()}),
+    }
+}
+
+/// Render a RESP3 map key as a JSON object key
+pub(crate) fn redis_map_key(key: redis::Value) -> String {
+    match redis_value_to_json(key) {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+/// Handle PING command
+pub async fn handle_ping(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    Path(instance_id): Path<Uuid>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    info!("PING request for instance_id: {}", instance_id);
+    
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Read), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "PING", None);
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result: String = with_timeout(timeout, redis::cmd("PING").query_async(&mut conn)).await?.map_err(|e| {
+        error!("Redis PING failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: Value::String(result),
+    }))
+}
+
+/// Handle SET command
+pub async fn handle_set(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyValuePath { instance_id, key, value }): CommandPathParams<KeyValuePath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    check_value_size(value_limit(&instance), value.len())?;
+    audit_command(&state, &instance, &claims, "SET", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    // Handle optional parameters from query string
+    let expire_seconds: Option<u64> = match query.get("EX") {
+        Some(ex) => Some(ex.parse().map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid EX parameter"})),
+            )
+        })?),
+        None => None,
+    };
+
+    let command = match expire_seconds {
+        Some(seconds) => conn.set_ex(&key, &value, seconds),
+        None => conn.set(&key, &value),
+    };
+    let result: redis::Value = with_timeout(timeout, command).await?.map_err(|e| {
+        error!("Redis SET failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: redis_value_to_json(result),
+    }))
+}
+
+/// Handle SET command via POST route, with the value in a JSON body
+pub async fn handle_set_json(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    Json(body): Json<SetBody>,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+    let value = decode_body_value(&body.value, body.encoding)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    check_value_size(value_limit(&instance), value.len())?;
+    audit_command(&state, &instance, &claims, "SET", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let command = match body.ex {
+        Some(seconds) => conn.set_ex(&key, &value, seconds),
+        None => conn.set(&key, &value),
+    };
+    let result: redis::Value = with_timeout(timeout, command).await?.map_err(|e| {
+        error!("Redis SET failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: redis_value_to_json(result),
+    }))
+}
+
+/// Handle a batch of `SET key value EX ttl` via POST, sent to Redis as one pipeline.
+///
+/// The result has one entry per item, in order: `{"key", "ok": true}`, or `"ok": false` with
+/// an `error` for items that were invalid or that Redis rejected. Invalid items are not sent.
+pub async fn handle_mset_ex(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    Path(instance_id): Path<Uuid>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    Json(items): Json<Vec<SetExItem>>,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "MSET-EX", None);
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result = set_ex_batch(&state.redis_config, value_limit(&instance), &mut conn, timeout, &items).await?;
+
+    Ok(Json(RedisResponse { result }))
+}
+
+/// Check the items of an MSET-EX batch and pipeline the valid ones
+async fn set_ex_batch(
+    config: &RedisHandlerConfig,
+    max_value_bytes: Option<usize>,
+    conn: &mut LimitedConnection,
+    timeout: Duration,
+    items: &[SetExItem],
+) -> Result<Value, ErrorResponse> {
+    if items.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Batch must not be empty"})),
+        ));
+    }
+    if items.len() > config.max_pipeline_commands {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(json!({"error": format!("Batch exceeds the maximum of {} items", config.max_pipeline_commands)})),
+        ));
+    }
+
+    let error_message = |(_, Json(body)): ErrorResponse| body["error"].as_str().unwrap_or_default().to_string();
+    let checked: Vec<Result<Vec<u8>, String>> = items
+        .iter()
+        .map(|item| {
+            check_path_params(config, &item.key, None).map_err(error_message)?;
+            if item.ttl == 0 {
+                return Err("ttl must be a positive number of seconds".to_string());
+            }
+            let value = decode_body_value(&item.value, item.encoding).map_err(error_message)?;
+            if value.len() > config.max_arg_bytes {
+                return Err(format!("Value exceeds the maximum size of {} bytes", config.max_arg_bytes));
+            }
+            check_value_size(max_value_bytes, value.len()).map_err(error_message)?;
+            Ok(value)
+        })
+        .collect();
+
+    let mut pipe = redis::pipe();
+    for (item, value) in items.iter().zip(&checked) {
+        if let Ok(value) = value {
+            pipe.cmd("SET").arg(&item.key).arg(value).arg("EX").arg(item.ttl);
+        }
+    }
+    let sent = checked.iter().filter(|value| value.is_ok()).count();
+
+    // Sent as a plain pipeline rather than with query_async, which fails the whole batch
+    // when any one command errors
+    let replies = if sent == 0 {
+        Vec::new()
+    } else {
+        with_timeout(timeout, conn.req_packed_commands(&pipe, 0, sent)).await?.map_err(|e| {
+            error!("Redis MSET-EX pipeline failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Redis command failed"})),
+            )
+        })?
+    };
+    let mut replies = replies.into_iter();
+
+    let statuses = items
+        .iter()
+        .zip(checked)
+        .map(|(item, value)| {
+            let error = match value {
+                Err(message) => Some(message),
+                Ok(_) => match replies.next() {
+                    Some(reply) => reply.extract_error().err().map(|e| e.to_string()),
+                    None => Some("No reply from Redis".to_string()),
+                },
+            };
+            match error {
+                None => json!({"key": item.key, "ok": true}),
+                Some(error) => json!({"key": item.key, "ok": false, "error": error}),
+            }
+        })
+        .collect();
+    Ok(Value::Array(statuses))
+}
+
+/// Body of `POST /redis/:instance_id/cas-numeric`
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CasNumericRequest {
+    key: String,
+    op: NumericComparison,
+    value: serde_json::Number,
+}
+
+/// Which way a new value must move the stored one for CAS-NUMERIC to write it
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NumericComparison {
+    Gt,
+    Lt,
+}
+
+impl NumericComparison {
+    fn as_str(self) -> &'static str {
+        match self {
+            NumericComparison::Gt => "gt",
+            NumericComparison::Lt => "lt",
+        }
+    }
+}
+
+// Writes ARGV[2] when the key is missing or the comparison holds, keeping any TTL. Replies
+// {1, new} when written, {0, current} when not and {-1, type} when the stored value is not
+// a number. Values are compared as Lua numbers (doubles).
+const CAS_NUMERIC_SCRIPT: &str = r#"
+local kind = redis.call('TYPE', KEYS[1]).ok
+if kind ~= 'none' and kind ~= 'string' then
+  return {-1, kind}
+end
+local current = redis.call('GET', KEYS[1])
+if current then
+  local number = tonumber(current)
+  if not number then
+    return {-1, 'string'}
+  end
+  local candidate = tonumber(ARGV[2])
+  if (ARGV[1] == 'gt' and candidate <= number) or (ARGV[1] == 'lt' and candidate >= number) then
+    return {0, current}
+  end
+end
+redis.call('SET', KEYS[1], ARGV[2], 'KEEPTTL')
+return {1, ARGV[2]}
+"#;
+
+/// Handle CAS-NUMERIC: set a key only if the new number is greater (or less) than the stored one
+pub async fn handle_cas_numeric(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    Path(instance_id): Path<Uuid>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    Json(request): Json<CasNumericRequest>,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+    check_path_params(&state.redis_config, &request.key, None)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "CAS-NUMERIC", Some(&request.key));
+    // The read and write ACL users cannot run scripts. Only the fixed script above is ever run,
+    // so it runs as the gateway's admin user once the key's write scope has been checked.
+    let mut conn = get_redis_connection(&state, &instance, AclUser::Admin, endpoint, db).await?;
+
+    let script = redis::Script::new(CAS_NUMERIC_SCRIPT);
+    let mut invocation = script.key(&request.key);
+    invocation.arg(request.op.as_str()).arg(request.value.to_string());
+    let reply: (i64, String) = with_timeout(timeout, invocation.invoke_async(&mut conn))
+        .await?
+        .map_err(|e| {
+            error!("Redis CAS-NUMERIC script failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Redis command failed"})),
+            )
+        })?;
+
+    let result = cas_numeric_result(reply)?;
+    Ok(Json(RedisResponse { result }))
+}
+
+// Turn the script's reply into `{"updated", "value"}`
+fn cas_numeric_result((status, value): (i64, String)) -> Result<Value, ErrorResponse> {
+    if status < 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({"error": "Stored value is not a number"})),
+        ));
+    }
+    let number = serde_json::from_str::<serde_json::Number>(value.trim())
+        .map(Value::Number)
+        .unwrap_or(Value::String(value));
+    Ok(json!({"updated": status == 1, "value": number}))
+}
+
+/// Handle GET command
+pub async fn handle_get(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Read), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "GET", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result: redis::Value = with_timeout(timeout, conn.get(&key)).await?.map_err(|e| {
+        error!("Redis GET failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: redis_value_to_json(result),
+    }))
+}
+
+/// Handle DEL command
+pub async fn handle_del(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "DEL", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result: i32 = with_timeout(timeout, conn.del(&key)).await?.map_err(|e| {
+        error!("Redis DEL failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: Value::Number(serde_json::Number::from(result)),
+    }))
+}
+
+/// Handle generic Redis command via POST with JSON body
+pub async fn handle_generic_command(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    Path(instance_id): Path<Uuid>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    Json(payload): Json<Vec<Value>>,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let access = payload.first().and_then(Value::as_str).and_then(command_access);
+    let endpoint = command_endpoint(access, &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result = execute_command(&state.redis_config, value_limit(&instance), &mut conn, &claims, timeout, &payload).await;
+    // Commands refused for the key's scopes never ran, so they are not audited
+    if !matches!(result, Err((StatusCode::FORBIDDEN, _))) {
+        if let Some(command) = payload.first().and_then(Value::as_str) {
+            audit_command(&state, &instance, &claims, command, command_key(command, &payload));
+        }
+    }
+
+    Ok(Json(RedisResponse {
+        result: redis_value_to_json(result?),
+    }))
+}
+
+/// Handle a pipeline of commands via POST, sent to Redis in one round trip.
+///
+/// The body is a list of commands in the generic endpoint's format, such as
+/// `[["SET", "a", "1"], ["GET", "a"]]`. Commands are passed to Redis as-is. The result has one
+/// entry per command, in order: `{"result": ...}`, or `{"error": ...}` for commands Redis
+/// rejected; the others still run.
+pub async fn handle_pipeline(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    Path(instance_id): Path<Uuid>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    Json(commands): Json<Vec<Vec<Value>>>,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    let commands = check_pipeline(&state.redis_config, value_limit(&instance), &claims, &commands)?;
+    audit_command(&state, &instance, &claims, "PIPELINE", None);
+
+    // Only pipelines of nothing but reads may go to a replica
+    let reads_only = commands
+        .iter()
+        .all(|(command, _)| command_access(command) == Some(CommandAccess::Read));
+    let endpoint = command_endpoint(reads_only.then_some(CommandAccess::Read), &query)?;
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result = run_pipeline(&mut conn, timeout, &commands).await?;
+
+    Ok(Json(RedisResponse { result }))
+}
+
+/// Check the commands of a pipeline against the pipeline limits, the per-command size limits
+/// and the API key's scopes. Any invalid command fails the whole pipeline before it is sent.
+fn check_pipeline(
+    config: &RedisHandlerConfig,
+    max_value_bytes: Option<usize>,
+    claims: &ApiKeyClaims,
+    commands: &[Vec<Value>],
+) -> Result<Vec<(String, Vec<String>)>, ErrorResponse> {
+    if commands.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Pipeline must not be empty"})),
+        ));
+    }
+    if commands.len() > config.max_pipeline_commands {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(json!({"error": format!("Pipeline exceeds the maximum of {} commands", config.max_pipeline_commands)})),
+        ));
+    }
+    let total_args: usize = commands.iter().map(|command| command.len().saturating_sub(1)).sum();
+    if total_args > config.max_pipeline_args {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(json!({"error": format!("Pipeline exceeds the maximum of {} arguments", config.max_pipeline_args)})),
+        ));
+    }
+
+    commands
+        .iter()
+        .enumerate()
+        .map(|(index, payload)| {
+            let command = payload.first().and_then(Value::as_str).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Invalid command format at position {}", index + 1)})),
+                )
+            })?;
+            let args: Vec<String> = payload[1..].iter().map(command_arg).collect();
+            check_command_size(config, command, &args)?;
+            for value in set_values(command, &args) {
+                check_value_size(max_value_bytes, value.len())?;
+            }
+            check_command_scope(claims, command)?;
+            check_connection_command(command)?;
+            Ok((command.to_string(), args))
+        })
+        .collect()
+}
+
+/// Send checked pipeline commands and pair each with its reply
+async fn run_pipeline(
+    conn: &mut LimitedConnection,
+    timeout: Duration,
+    commands: &[(String, Vec<String>)],
+) -> Result<Value, ErrorResponse> {
+    let mut pipe = redis::pipe();
+    for (command, args) in commands {
+        pipe.cmd(command).arg(args);
+    }
+
+    // Sent as a plain pipeline rather than with query_async, which fails the whole pipeline
+    // when any one command errors
+    let replies = with_timeout(timeout, conn.req_packed_commands(&pipe, 0, commands.len()))
+        .await?
+        .map_err(|e| {
+            error!("Redis pipeline failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Redis command failed"})),
+            )
+        })?;
+
+    let results = replies
+        .into_iter()
+        .map(|reply| match reply.extract_error() {
+            Ok(reply) => json!({"result": redis_value_to_json(reply)}),
+            Err(e) => json!({"error": e.to_string()}),
+        })
+        .collect();
+    Ok(Value::Array(results))
+}
+
+// A generic-endpoint argument as sent to Redis: strings as-is, anything else as JSON
+fn command_arg(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Check and run one command sent to the generic endpoint: size limits, the API key's
+/// scopes, then dispatch on the command name
+async fn execute_command(
+    config: &RedisHandlerConfig,
+    max_value_bytes: Option<usize>,
+    conn: &mut LimitedConnection,
+    claims: &ApiKeyClaims,
+    timeout: Duration,
+    payload: &[Value],
+) -> Result<redis::Value, ErrorResponse> {
+    if payload.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Empty command"})),
+        ));
+    }
+
+    // Extract command and arguments
+    let command = payload[0].as_str().ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Invalid command format"})),
+        )
+    })?;
+
+    let args: Vec<String> = payload[1..].iter().map(command_arg).collect();
+
+    check_command_size(config, command, &args)?;
+    for value in set_values(command, &args) {
+        check_value_size(max_value_bytes, value.len())?;
+    }
+
+    check_command_scope(claims, command)?;
+    check_connection_command(command)?;
+
+    info!("Executing Redis command: {} with args: {:?}", command, args);
 
     let result = match command.to_uppercase().as_str() {
         "PING" => {
-            let result: String = redis::cmd("PING").query(&mut conn).map_err(|e| {
+            let result: String = with_timeout(timeout, redis::cmd("PING").query_async(&mut *conn)).await?.map_err(|e| {
                 error!("Redis PING failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(json!({"error": "Redis command failed"})),
                 )
             })?;
-            redis::Value::Status(result)
+            redis::Value::SimpleString(result)
         }
         "SET" => {
             if args.len() < 2 {
@@ -355,7 +1997,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "SET requires key and value"})),
                 ));
             }
-            conn.set(&args[0], &args[1]).map_err(|e| {
+            with_timeout(timeout, conn.set(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis SET failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -370,7 +2012,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "GET requires key"})),
                 ));
             }
-            conn.get(&args[0]).map_err(|e| {
+            with_timeout(timeout, conn.get(&args[0])).await?.map_err(|e| {
                 error!("Redis GET failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -385,7 +2027,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "DEL requires key"})),
                 ));
             }
-            let count: i32 = conn.del(&args[0]).map_err(|e| {
+            let count: i32 = with_timeout(timeout, conn.del(&args[0])).await?.map_err(|e| {
                 error!("Redis DEL failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -402,7 +2044,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "INCR requires key"})),
                 ));
             }
-            let result: i64 = conn.incr(&args[0], 1).map_err(|e| {
+            let result: i64 = with_timeout(timeout, conn.incr(&args[0], 1)).await?.map_err(|e| {
                 error!("Redis INCR failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -418,7 +2060,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "DECR requires key"})),
                 ));
             }
-            let result: i64 = conn.decr(&args[0], 1).map_err(|e| {
+            let result: i64 = with_timeout(timeout, conn.decr(&args[0], 1)).await?.map_err(|e| {
                 error!("Redis DECR failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -427,15 +2069,62 @@ pub async fn handle_generic_command(
             })?;
             redis::Value::Int(result)
         }
-        "EXISTS" => {
-            if args.is_empty() {
+        "EXISTS" => {
+            if args.is_empty() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "EXISTS requires key"})),
+                ));
+            }
+            let result: bool = with_timeout(timeout, conn.exists(&args[0])).await?.map_err(|e| {
+                error!("Redis EXISTS failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            redis::Value::Int(if result { 1 } else { 0 })
+        }
+        // Refreshes the last access time of each key, which LRU eviction goes by
+        "TOUCH" => {
+            if args.is_empty() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "TOUCH requires at least one key"})),
+                ));
+            }
+            let result: i64 = with_timeout(timeout, redis::cmd("TOUCH").arg(&args).query_async(&mut *conn))
+                .await?
+                .map_err(|e| {
+                    error!("Redis TOUCH failed: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": "Redis command failed"})),
+                    )
+                })?;
+            redis::Value::Int(result)
+        }
+        "EXPIRE" | "PEXPIRE" | "EXPIREAT" | "PEXPIREAT" => {
+            let name = command.to_uppercase();
+            if args.len() < 2 {
                 return Err((
                     StatusCode::BAD_REQUEST,
-                    Json(json!({"error": "EXISTS requires key"})),
+                    Json(json!({"error": format!("{} requires key and time", name)})),
                 ));
             }
-            let result: bool = conn.exists(&args[0]).map_err(|e| {
-                error!("Redis EXISTS failed: {}", e);
+            let time: i64 = args[1].parse().map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "Invalid expire time"})),
+                )
+            })?;
+            let flags = parse_expire_flags(&args[2..])
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let mut cmd = redis::cmd(&name);
+            cmd.arg(&args[0]).arg(time).arg(flags);
+            // 1 if the TTL was set, 0 if the key is missing or a flag's condition was not met
+            let result: bool = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis {} failed: {}", name, e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(json!({"error": "Redis command failed"})),
@@ -443,21 +2132,70 @@ pub async fn handle_generic_command(
             })?;
             redis::Value::Int(if result { 1 } else { 0 })
         }
-        "EXPIRE" => {
-            if args.len() < 2 {
+        // Serialized values are binary, so they travel as base64
+        "DUMP" => {
+            if args.len() != 1 {
                 return Err((
                     StatusCode::BAD_REQUEST,
-                    Json(json!({"error": "EXPIRE requires key and seconds"})),
+                    Json(json!({"error": "DUMP requires key"})),
                 ));
             }
-            let seconds: i64 = args[1].parse().map_err(|_| {
-                (
+            let result: Option<Vec<u8>> = with_timeout(timeout, redis::cmd("DUMP").arg(&args[0]).query_async(&mut *conn))
+                .await?
+                .map_err(|e| {
+                    error!("Redis DUMP failed: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": "Redis command failed"})),
+                    )
+                })?;
+            result.map_or(redis::Value::Nil, |payload| redis::Value::SimpleString(STANDARD.encode(payload)))
+        }
+        "RESTORE" => {
+            let restore = parse_restore_args(&args)
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let mut cmd = redis::cmd("RESTORE");
+            cmd.arg(restore.key).arg(restore.ttl_ms).arg(&restore.payload);
+            if restore.replace {
+                cmd.arg("REPLACE");
+            }
+            if restore.absttl {
+                cmd.arg("ABSTTL");
+            }
+            with_timeout(timeout, cmd.query_async::<()>(&mut *conn)).await?.map_err(|e| match e.code() {
+                Some("BUSYKEY") => (
+                    StatusCode::CONFLICT,
+                    Json(json!({"error": "Target key already exists; pass REPLACE to overwrite it"})),
+                ),
+                Some("ERR") if e.to_string().contains("payload") => (
                     StatusCode::BAD_REQUEST,
-                    Json(json!({"error": "Invalid expire time"})),
-                )
+                    Json(json!({"error": "Invalid serialized value"})),
+                ),
+                _ => {
+                    error!("Redis RESTORE failed: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": "Redis command failed"})),
+                    )
+                }
             })?;
-            let result: bool = conn.expire(&args[0], seconds).map_err(|e| {
-                error!("Redis EXPIRE failed: {}", e);
+            redis::Value::Okay
+        }
+        // Copies within the request's database, or into another one with DB
+        "COPY" => {
+            let copy = parse_copy_args(&args, config.max_db)
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let mut cmd = redis::cmd("COPY");
+            cmd.arg(copy.source).arg(copy.destination);
+            if let Some(db) = copy.db {
+                cmd.arg("DB").arg(db);
+            }
+            if copy.replace {
+                cmd.arg("REPLACE");
+            }
+            // 1 if copied, 0 if the source is missing or the destination exists without REPLACE
+            let result: bool = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis COPY failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(json!({"error": "Redis command failed"})),
@@ -465,6 +2203,19 @@ pub async fn handle_generic_command(
             })?;
             redis::Value::Int(if result { 1 } else { 0 })
         }
+        "SORT" | "SORT_RO" => {
+            let command = command.to_uppercase();
+            let sort = parse_sort_args(&command, &args)
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            // The sorted elements (with GET, each item's patterns in turn), or the stored count with STORE
+            with_timeout(timeout, sort.command(&command).query_async::<redis::Value>(&mut *conn)).await?.map_err(|e| {
+                error!("Redis {} failed: {}", command, e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?
+        }
         "TTL" => {
             if args.is_empty() {
                 return Err((
@@ -472,7 +2223,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "TTL requires key"})),
                 ));
             }
-            let result: i64 = conn.ttl(&args[0]).map_err(|e| {
+            let result: i64 = with_timeout(timeout, conn.ttl(&args[0])).await?.map_err(|e| {
                 error!("Redis TTL failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -489,7 +2240,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "LPUSH requires key and value"})),
                 ));
             }
-            let result: i32 = conn.lpush(&args[0], &args[1]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.lpush(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis LPUSH failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -505,7 +2256,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "RPUSH requires key and value"})),
                 ));
             }
-            let result: i32 = conn.rpush(&args[0], &args[1]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.rpush(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis RPUSH failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -521,7 +2272,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "LPOP requires key"})),
                 ));
             }
-            conn.lpop(&args[0], None).map_err(|e| {
+            with_timeout(timeout, conn.lpop(&args[0], None)).await?.map_err(|e| {
                 error!("Redis LPOP failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -536,7 +2287,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "RPOP requires key"})),
                 ));
             }
-            conn.rpop(&args[0], None).map_err(|e| {
+            with_timeout(timeout, conn.rpop(&args[0], None)).await?.map_err(|e| {
                 error!("Redis RPOP failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -551,7 +2302,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "LLEN requires key"})),
                 ));
             }
-            let result: i32 = conn.llen(&args[0]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.llen(&args[0])).await?.map_err(|e| {
                 error!("Redis LLEN failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -579,7 +2330,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "Invalid stop index"})),
                 )
             })?;
-            conn.lrange(&args[0], start, stop).map_err(|e| {
+            with_timeout(timeout, conn.lrange(&args[0], start, stop)).await?.map_err(|e| {
                 error!("Redis LRANGE failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -587,6 +2338,35 @@ pub async fn handle_generic_command(
                 )
             })?
         }
+        "LPOS" => {
+            if args.len() < 2 {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "LPOS requires key and element"})),
+                ));
+            }
+            let options = parse_lpos_options(&args[2..])
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let mut cmd = redis::cmd("LPOS");
+            cmd.arg(&args[0]).arg(&args[1]);
+            if let Some(rank) = options.rank {
+                cmd.arg("RANK").arg(rank);
+            }
+            if let Some(count) = options.count {
+                cmd.arg("COUNT").arg(count);
+            }
+            if let Some(maxlen) = options.maxlen {
+                cmd.arg("MAXLEN").arg(maxlen);
+            }
+            // A position (or nil) without COUNT, a list of positions with it
+            with_timeout(timeout, cmd.query_async::<redis::Value>(&mut *conn)).await?.map_err(|e| {
+                error!("Redis LPOS failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?
+        }
         // Hash operations
         "HSET" => {
             if args.len() < 3 {
@@ -595,7 +2375,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "HSET requires key, field, and value"})),
                 ));
             }
-            let result: i32 = conn.hset(&args[0], &args[1], &args[2]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.hset(&args[0], &args[1], &args[2])).await?.map_err(|e| {
                 error!("Redis HSET failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -611,7 +2391,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "HGET requires key and field"})),
                 ));
             }
-            conn.hget(&args[0], &args[1]).map_err(|e| {
+            with_timeout(timeout, conn.hget(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis HGET failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -626,7 +2406,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "HDEL requires key and field"})),
                 ));
             }
-            let result: i32 = conn.hdel(&args[0], &args[1]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.hdel(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis HDEL failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -642,7 +2422,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "HEXISTS requires key and field"})),
                 ));
             }
-            let result: bool = conn.hexists(&args[0], &args[1]).map_err(|e| {
+            let result: bool = with_timeout(timeout, conn.hexists(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis HEXISTS failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -658,7 +2438,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "HGETALL requires key"})),
                 ));
             }
-            conn.hgetall(&args[0]).map_err(|e| {
+            with_timeout(timeout, conn.hgetall(&args[0])).await?.map_err(|e| {
                 error!("Redis HGETALL failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -673,7 +2453,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "HKEYS requires key"})),
                 ));
             }
-            conn.hkeys(&args[0]).map_err(|e| {
+            with_timeout(timeout, conn.hkeys(&args[0])).await?.map_err(|e| {
                 error!("Redis HKEYS failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -688,7 +2468,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "HVALS requires key"})),
                 ));
             }
-            conn.hvals(&args[0]).map_err(|e| {
+            with_timeout(timeout, conn.hvals(&args[0])).await?.map_err(|e| {
                 error!("Redis HVALS failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -704,7 +2484,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "SADD requires key and member"})),
                 ));
             }
-            let result: i32 = conn.sadd(&args[0], &args[1]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.sadd(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis SADD failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -720,7 +2500,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "SREM requires key and member"})),
                 ));
             }
-            let result: i32 = conn.srem(&args[0], &args[1]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.srem(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis SREM failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -736,7 +2516,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "SISMEMBER requires key and member"})),
                 ));
             }
-            let result: bool = conn.sismember(&args[0], &args[1]).map_err(|e| {
+            let result: bool = with_timeout(timeout, conn.sismember(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis SISMEMBER failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -745,6 +2525,25 @@ pub async fn handle_generic_command(
             })?;
             redis::Value::Int(if result { 1 } else { 0 })
         }
+        "SMISMEMBER" => {
+            if args.len() < 2 {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "SMISMEMBER requires key and at least one member"})),
+                ));
+            }
+            let mut cmd = redis::cmd("SMISMEMBER");
+            cmd.arg(&args);
+            // One flag per member, in the order the members were given
+            let result: Vec<bool> = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis SMISMEMBER failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            redis::Value::Array(result.into_iter().map(redis::Value::Boolean).collect())
+        }
         "SMEMBERS" => {
             if args.is_empty() {
                 return Err((
@@ -752,7 +2551,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "SMEMBERS requires key"})),
                 ));
             }
-            conn.smembers(&args[0]).map_err(|e| {
+            with_timeout(timeout, conn.smembers(&args[0])).await?.map_err(|e| {
                 error!("Redis SMEMBERS failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -767,7 +2566,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "SCARD requires key"})),
                 ));
             }
-            let result: i32 = conn.scard(&args[0]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.scard(&args[0])).await?.map_err(|e| {
                 error!("Redis SCARD failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -776,6 +2575,174 @@ pub async fn handle_generic_command(
             })?;
             redis::Value::Int(result as i64)
         }
+        "SINTERCARD" => {
+            let (keys, limit) = parse_sintercard_args(&args)
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let mut cmd = redis::cmd("SINTERCARD");
+            cmd.arg(keys.len()).arg(keys);
+            if let Some(limit) = limit {
+                cmd.arg("LIMIT").arg(limit);
+            }
+            let result: i64 = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis SINTERCARD failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            redis::Value::Int(result)
+        }
+        // Geospatial commands
+        "GEOADD" => {
+            let geoadd = parse_geoadd_args(&args)
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let mut cmd = redis::cmd("GEOADD");
+            cmd.arg(geoadd.key).arg(&geoadd.flags);
+            for (longitude, latitude, member) in &geoadd.items {
+                cmd.arg(longitude).arg(latitude).arg(member);
+            }
+            // Members added, or added and updated with CH
+            let result: i64 = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis GEOADD failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            redis::Value::Int(result)
+        }
+        "GEOSEARCH" => {
+            let search = parse_geosearch_args(&args)
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let reply: redis::Value = with_timeout(timeout, search.command().query_async(&mut *conn))
+                .await?
+                .map_err(|e| match e.code() {
+                    // FROMMEMBER names a member the key does not have
+                    Some("ERR") if e.to_string().contains("could not decode") => (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({"error": "FROMMEMBER member does not exist"})),
+                    ),
+                    _ => {
+                        error!("Redis GEOSEARCH failed: {}", e);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({"error": "Redis command failed"})),
+                        )
+                    }
+                })?;
+            geo_search_results(reply, search.with_dist, search.with_coord).map_err(|e| {
+                error!("Unexpected GEOSEARCH reply: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?
+        }
+        "GEODIST" => {
+            let (key, first, second, unit) = match args.as_slice() {
+                [key, first, second] => (key, first, second, "m"),
+                [key, first, second, unit] => (
+                    key,
+                    first,
+                    second,
+                    parse_geo_unit(unit).map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?,
+                ),
+                _ => {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({"error": "GEODIST requires key and two members, with an optional unit"})),
+                    ))
+                }
+            };
+            // Null when either member is missing
+            let result: Option<f64> = with_timeout(
+                timeout,
+                redis::cmd("GEODIST").arg(key).arg(first).arg(second).arg(unit).query_async(&mut *conn),
+            )
+            .await?
+            .map_err(|e| {
+                error!("Redis GEODIST failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            result.map_or(redis::Value::Nil, redis::Value::Double)
+        }
+        "GEOPOS" => {
+            if args.len() < 2 {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "GEOPOS requires key and at least one member"})),
+                ));
+            }
+            let positions: Vec<Option<(f64, f64)>> = with_timeout(
+                timeout,
+                redis::cmd("GEOPOS").arg(&args[0]).arg(&args[1..]).query_async(&mut *conn),
+            )
+            .await?
+            .map_err(|e| {
+                error!("Redis GEOPOS failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            // `{"longitude", "latitude"}` per member, or null for missing members
+            redis::Value::Array(
+                positions
+                    .into_iter()
+                    .map(|position| position.map_or(redis::Value::Nil, |position| geo_entry(geo_position(Some(position)))))
+                    .collect(),
+            )
+        }
+        // Sorted set operations
+        "ZADD" => {
+            let zadd = parse_zadd_args(&args)
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            let mut cmd = redis::cmd("ZADD");
+            cmd.arg(zadd.key).arg(&zadd.flags);
+            if zadd.incr {
+                cmd.arg("INCR");
+            }
+            for (score, member) in &zadd.items {
+                cmd.arg(score).arg(member);
+            }
+            let reply: redis::Value = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis ZADD failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            if zadd.incr {
+                // The new score, or null when NX, XX, GT or LT kept the member from changing
+                let score: Option<f64> = redis::from_redis_value(&reply).map_err(|e| {
+                    error!("Unexpected ZADD INCR reply: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": "Redis command failed"})),
+                    )
+                })?;
+                score.map_or(redis::Value::Nil, redis::Value::Double)
+            } else {
+                // Members added, or added and updated with CH
+                reply
+            }
+        }
+        "HSCAN" | "SSCAN" | "ZSCAN" => {
+            let name = command.to_uppercase();
+            if args.len() < 2 {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("{} requires key and cursor", name)})),
+                ));
+            }
+            let cursor = parse_scan_cursor(&args[1])?;
+            let options = parse_scan_options(&name, &args[2..])
+                .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({"error": message}))))?;
+            scan_collection(conn, timeout, &name, &args[0], cursor, &options).await?
+        }
         // Additional string operations
         "APPEND" => {
             if args.len() < 2 {
@@ -784,7 +2751,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "APPEND requires key and value"})),
                 ));
             }
-            let result: i32 = conn.append(&args[0], &args[1]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.append(&args[0], &args[1])).await?.map_err(|e| {
                 error!("Redis APPEND failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -800,7 +2767,7 @@ pub async fn handle_generic_command(
                     Json(json!({"error": "STRLEN requires key"})),
                 ));
             }
-            let result: i32 = conn.strlen(&args[0]).map_err(|e| {
+            let result: i32 = with_timeout(timeout, conn.strlen(&args[0])).await?.map_err(|e| {
                 error!("Redis STRLEN failed: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -809,6 +2776,110 @@ pub async fn handle_generic_command(
             })?;
             redis::Value::Int(result as i64)
         }
+        // Introspection commands
+        "OBJECT" if args.first().is_some_and(|sub| {
+            ["ENCODING", "IDLETIME", "FREQ"].contains(&sub.to_uppercase().as_str())
+        }) => {
+            let subcommand = args[0].to_uppercase();
+            if args.len() != 2 {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("OBJECT {} requires key", subcommand)})),
+                ));
+            }
+            let mut cmd = redis::cmd("OBJECT");
+            cmd.arg(&subcommand).arg(&args[1]);
+            if subcommand == "ENCODING" {
+                let result: Option<String> = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                    error!("Redis OBJECT ENCODING failed: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": format!("Redis command failed: {}", e)})),
+                    )
+                })?;
+                result.map_or(redis::Value::Nil, redis::Value::SimpleString)
+            } else {
+                // FREQ fails unless an LFU maxmemory-policy is configured; surface Redis's message
+                let result: Option<i64> = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                    error!("Redis OBJECT {} failed: {}", subcommand, e);
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({"error": format!("Redis command failed: {}", e)})),
+                    )
+                })?;
+                result.map_or(redis::Value::Nil, redis::Value::Int)
+            }
+        }
+        "MEMORY" if args.first().is_some_and(|sub| sub.eq_ignore_ascii_case("USAGE")) => {
+            let samples = match &args[1..] {
+                [_key] => None,
+                [_key, option, samples] if option.eq_ignore_ascii_case("SAMPLES") => {
+                    Some(samples.parse::<u64>().map_err(|_| {
+                        (
+                            StatusCode::BAD_REQUEST,
+                            Json(json!({"error": "MEMORY USAGE SAMPLES must be a non-negative integer"})),
+                        )
+                    })?)
+                }
+                _ => {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({"error": "MEMORY USAGE requires key and optional SAMPLES count"})),
+                    ));
+                }
+            };
+            let mut cmd = redis::cmd("MEMORY");
+            cmd.arg("USAGE").arg(&args[1]);
+            if let Some(samples) = samples {
+                cmd.arg("SAMPLES").arg(samples);
+            }
+            let result: Option<i64> = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis MEMORY USAGE failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Redis command failed"})),
+                )
+            })?;
+            result.map_or(redis::Value::Nil, redis::Value::Int)
+        }
+        // Pub/Sub commands
+        "PUBLISH" => {
+            if args.len() != 2 {
+                return Err((StatusCode::BAD_REQUEST, Json(json!({"error": "PUBLISH requires channel and message"}))));
+            }
+            let receivers: i64 = with_timeout(timeout, conn.publish(&args[0], &args[1])).await?.map_err(|e| {
+                error!("Redis PUBLISH failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Redis command failed"})))
+            })?;
+            redis::Value::Int(receivers)
+        }
+        "PUBSUB" if args.first().is_some_and(|sub| sub.eq_ignore_ascii_case("CHANNELS")) => {
+            if args.len() > 2 {
+                return Err((StatusCode::BAD_REQUEST, Json(json!({"error": "PUBSUB CHANNELS takes at most one pattern"}))));
+            }
+            let mut cmd = redis::cmd("PUBSUB");
+            cmd.arg("CHANNELS").arg(&args[1..]);
+            let channels: Vec<String> = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis PUBSUB CHANNELS failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Redis command failed"})))
+            })?;
+            redis::Value::Array(channels.into_iter().map(redis::Value::SimpleString).collect())
+        }
+        "PUBSUB" if args.first().is_some_and(|sub| sub.eq_ignore_ascii_case("NUMSUB")) => {
+            let mut cmd = redis::cmd("PUBSUB");
+            cmd.arg("NUMSUB").arg(&args[1..]);
+            let counts: Vec<(String, i64)> = with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
+                error!("Redis PUBSUB NUMSUB failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Redis command failed"})))
+            })?;
+            // Rendered as a {channel: subscribers} object
+            redis::Value::Map(
+                counts
+                    .into_iter()
+                    .map(|(channel, count)| (redis::Value::SimpleString(channel), redis::Value::Int(count)))
+                    .collect(),
+            )
+        }
         // Generic command execution using cmd 
         _ => {
             // For any other command, build it dynamically
@@ -816,7 +2887,7 @@ pub async fn handle_generic_command(
             for arg in &args {
                 cmd.arg(arg);
             }
-            cmd.query(&mut conn).map_err(|e| {
+            with_timeout(timeout, cmd.query_async(&mut *conn)).await?.map_err(|e| {
                 error!("Redis command {} failed: {}", command, e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -826,53 +2897,240 @@ pub async fn handle_generic_command(
         }
     };
 
+    Ok(result)
+}
+
+/// Debug handler to see what requests are coming in
+pub async fn handle_debug_request(
+    State(_state): State<Arc<AppState>>,
+    Path((instance_id, path)): Path<(Uuid, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    method: axum::http::Method,
+) -> Result<Json<Value>, ErrorResponse> {
+    info!("DEBUG: {} request to /redis/{}/{} with query: {:?}", method, instance_id, path, query);
+    
+    // Log authorization header
+    if let Some(auth_header) = headers.get("authorization") {
+        if let Ok(auth_str) = auth_header.to_str() {
+            info!("DEBUG: Authorization header: {}", auth_str);
+        }
+    }
+    
+    Err((
+        StatusCode::NOT_IMPLEMENTED,
+        Json(json!({"error": format!("Debug: {} to /redis/{}/{} not implemented", method, instance_id, path)})),
+    ))
+}
+
+/// Handle INCR command via GET route
+pub async fn handle_incr(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "INCR", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result: i64 = with_timeout(timeout, conn.incr(&key, 1)).await?.map_err(|e| {
+        error!("Redis INCR failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: Value::Number(serde_json::Number::from(result)),
+    }))
+}
+
+/// Handle HSET command via GET route
+pub async fn handle_hset(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(FieldValuePath { instance_id, key, field, value }): CommandPathParams<FieldValuePath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "HSET", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result: i32 = with_timeout(timeout, conn.hset(&key, &field, &value)).await?.map_err(|e| {
+        error!("Redis HSET failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: Value::Number(serde_json::Number::from(result)),
+    }))
+}
+
+/// Handle HSET command via POST route, with the value in a JSON body
+pub async fn handle_hset_json(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(FieldPath { instance_id, key, field }): CommandPathParams<FieldPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    Json(body): Json<ValueBody>,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+    let value = decode_body_value(&body.value, body.encoding)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "HSET", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result: i32 = with_timeout(timeout, conn.hset(&key, &field, &value)).await?.map_err(|e| {
+        error!("Redis HSET failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: Value::Number(serde_json::Number::from(result)),
+    }))
+}
+
+/// Handle HGET command via GET route
+pub async fn handle_hget(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(FieldPath { instance_id, key, field }): CommandPathParams<FieldPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Read), &query)?;
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "HGET", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result: redis::Value = with_timeout(timeout, conn.hget(&key, &field)).await?.map_err(|e| {
+        error!("Redis HGET failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Redis command failed"})),
+        )
+    })?;
+
+    Ok(Json(RedisResponse {
+        result: redis_value_to_json(result),
+    }))
+}
+
+/// Handle one page of HSCAN, SSCAN or ZSCAN via GET route, with `cursor` (default 0),
+/// `match` and `count` query parameters
+async fn handle_collection_scan(
+    state: &AppState,
+    claims: &ApiKeyClaims,
+    command: &str,
+    instance_id: Uuid,
+    key: &str,
+    query: HashMap<String, String>,
+    headers: &HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    let timeout = command_timeout(state, headers, &query)?;
+    let db = logical_db(state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Read), &query)?;
+    let cursor = parse_scan_cursor(query.get("cursor").map_or("0", String::as_str))?;
+    let options = ScanOptions {
+        pattern: query.get("match").cloned(),
+        count: match query.get("count") {
+            Some(count) => Some(count.parse().ok().filter(|count| *count > 0).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "count must be a positive integer"})),
+                )
+            })?),
+            None => None,
+        },
+    };
+
+    let instance = instance_for_key(state, claims, instance_id).await?;
+    audit_command(state, &instance, claims, command, Some(key));
+    let mut conn = get_redis_connection(state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
+
+    let result = scan_collection(&mut conn, timeout, command, key, cursor, &options).await?;
+
     Ok(Json(RedisResponse {
         result: redis_value_to_json(result),
     }))
 }
 
-/// Debug handler to see what requests are coming in
-pub async fn handle_debug_request(
-    State(_state): State<Arc<AppState>>,
-    Path((instance_id, path)): Path<(Uuid, String)>,
+/// Handle HSCAN command via GET route
+pub async fn handle_hscan(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    handle_collection_scan(&state, &claims, "HSCAN", instance_id, &key, query, &headers).await
+}
+
+/// Handle SSCAN command via GET route
+pub async fn handle_sscan(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    handle_collection_scan(&state, &claims, "SSCAN", instance_id, &key, query, &headers).await
+}
+
+/// Handle ZSCAN command via GET route
+pub async fn handle_zscan(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
-    method: axum::http::Method,
-) -> Result<Json<Value>, ErrorResponse> {
-    info!("DEBUG: {} request to /redis/{}/{} with query: {:?}", method, instance_id, path, query);
-    
-    // Log authorization header
-    if let Some(auth_header) = headers.get("authorization") {
-        if let Ok(auth_str) = auth_header.to_str() {
-            info!("DEBUG: Authorization header: {}", auth_str);
-        }
-    }
-    
-    Err((
-        StatusCode::NOT_IMPLEMENTED,
-        Json(json!({"error": format!("Debug: {} to /redis/{}/{} not implemented", method, instance_id, path)})),
-    ))
+) -> Result<Json<RedisResponse>, ErrorResponse> {
+    handle_collection_scan(&state, &claims, "ZSCAN", instance_id, &key, query, &headers).await
 }
 
-/// Handle INCR command via GET route
-pub async fn handle_incr(
+/// Handle LPUSH command via GET route  
+pub async fn handle_lpush(
     State(state): State<Arc<AppState>>,
-    Path((instance_id, key)): Path<(Uuid, String)>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyValuePath { instance_id, key, value }): CommandPathParams<KeyValuePath>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
 ) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "LPUSH", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
 
-    let result: i64 = conn.incr(&key, 1).map_err(|e| {
-        error!("Redis INCR failed: {}", e);
+    let result: i32 = with_timeout(timeout, conn.lpush(&key, &value)).await?.map_err(|e| {
+        error!("Redis LPUSH failed: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({"error": "Redis command failed"})),
@@ -884,25 +3142,26 @@ pub async fn handle_incr(
     }))
 }
 
-/// Handle HSET command via GET route
-pub async fn handle_hset(
+/// Handle LPUSH command via POST route, with the value in a JSON body
+pub async fn handle_lpush_json(
     State(state): State<Arc<AppState>>,
-    Path((instance_id, key, field, value)): Path<(Uuid, String, String, String)>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
+    Json(body): Json<ValueBody>,
 ) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
+    let value = decode_body_value(&body.value, body.encoding)?;
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "LPUSH", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
 
-    let result: i32 = conn.hset(&key, &field, &value).map_err(|e| {
-        error!("Redis HSET failed: {}", e);
+    let result: i32 = with_timeout(timeout, conn.lpush(&key, &value)).await?.map_err(|e| {
+        error!("Redis LPUSH failed: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({"error": "Redis command failed"})),
@@ -914,25 +3173,24 @@ pub async fn handle_hset(
     }))
 }
 
-/// Handle HGET command via GET route
-pub async fn handle_hget(
+/// Handle LPOP command via GET route
+pub async fn handle_lpop(
     State(state): State<Arc<AppState>>,
-    Path((instance_id, key, field)): Path<(Uuid, String, String)>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    CommandPathParams(KeyPath { instance_id, key }): CommandPathParams<KeyPath>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
 ) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+    let endpoint = command_endpoint(Some(CommandAccess::Write), &query)?;
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    audit_command(&state, &instance, &claims, "LPOP", Some(&key));
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), endpoint, db).await?;
 
-    let result: redis::Value = conn.hget(&key, &field).map_err(|e| {
-        error!("Redis HGET failed: {}", e);
+    let result: redis::Value = with_timeout(timeout, conn.lpop(&key, None)).await?.map_err(|e| {
+        error!("Redis LPOP failed: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({"error": "Redis command failed"})),
@@ -943,63 +3201,889 @@ pub async fn handle_hget(
         result: redis_value_to_json(result),
     }))
 }
+/// INFO fields exported by the metrics endpoint: (INFO field, metric name, type, help)
+const INFO_METRICS: &[(&str, &str, &str, &str)] = &[
+    ("used_memory", "redis_used_memory_bytes", "gauge", "Memory used by Redis in bytes"),
+    ("connected_clients", "redis_connected_clients", "gauge", "Number of connected clients"),
+    ("instantaneous_ops_per_sec", "redis_instantaneous_ops_per_sec", "gauge", "Commands processed per second"),
+    ("keyspace_hits", "redis_keyspace_hits_total", "counter", "Successful key lookups"),
+    ("keyspace_misses", "redis_keyspace_misses_total", "counter", "Failed key lookups"),
+    ("evicted_keys", "redis_evicted_keys_total", "counter", "Keys evicted due to maxmemory"),
+];
 
-/// Handle LPUSH command via GET route  
-pub async fn handle_lpush(
+/// Parse the output of INFO into a field -> value map, skipping section headers
+pub(crate) fn parse_redis_info(info: &str) -> HashMap<String, String> {
+    info.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(field, value)| (field.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Render selected INFO fields as Prometheus exposition text
+fn render_info_metrics(info: &HashMap<String, String>, instance_slug: &str) -> String {
+    let label = instance_slug
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    let mut output = String::new();
+
+    for (field, metric, kind, help) in INFO_METRICS {
+        let Some(value) = info.get(*field).and_then(|v| v.parse::<f64>().ok()) else {
+            continue;
+        };
+        output.push_str(&format!("# HELP {} {}\n", metric, help));
+        output.push_str(&format!("# TYPE {} {}\n", metric, kind));
+        output.push_str(&format!("{}{{instance=\"{}\"}} {}\n", metric, label, value));
+    }
+
+    output
+}
+
+/// Expose Redis INFO statistics for an instance in Prometheus format.
+///
+/// Requires an API key with the `metrics` or `admin` scope.
+pub async fn handle_metrics(
     State(state): State<Arc<AppState>>,
-    Path((instance_id, key, value)): Path<(Uuid, String, String)>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    Path(instance_id): Path<Uuid>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
-) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
-        )
-    })?;
-
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
 
-    let result: i32 = conn.lpush(&key, &value).map_err(|e| {
-        error!("Redis LPUSH failed: {}", e);
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+    let mut conn = get_redis_connection(&state, &instance, AclUser::for_scopes(&claims.scopes), Endpoint::Primary, 0).await?;
+    let info: String = with_timeout(timeout, redis::cmd("INFO").query_async(&mut conn)).await?.map_err(|e| {
+        error!("Redis INFO failed: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({"error": "Redis command failed"})),
         )
     })?;
 
-    Ok(Json(RedisResponse {
-        result: Value::Number(serde_json::Number::from(result)),
-    }))
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        render_info_metrics(&parse_redis_info(&info), &instance.slug),
+    ))
 }
 
-/// Handle LPOP command via GET route
-pub async fn handle_lpop(
+/// Key events that can be streamed, with the `notify-keyspace-events` class that enables each
+const KEY_EVENTS: &[(&str, char)] = &[
+    ("set", '$'),
+    ("del", 'g'),
+    ("expire", 'g'),
+    ("expired", 'x'),
+    ("evicted", 'e'),
+];
+
+/// Work out the `notify-keyspace-events` value needed to publish the given key events.
+///
+/// Returns `None` when the current flags already cover them, so the server config is
+/// only touched when necessary and existing flags are never dropped.
+fn notify_keyspace_flags(current: &str, events: &[&str]) -> Option<String> {
+    // 'A' is an alias for all event classes, but not for the K/E channel selectors
+    let enabled = |flag: char| current.contains(flag) || (flag != 'E' && current.contains('A'));
+
+    let mut missing = String::new();
+    let needed = KEY_EVENTS
+        .iter()
+        .filter(|(event, _)| events.contains(event))
+        .map(|(_, class)| *class);
+    for flag in std::iter::once('E').chain(needed) {
+        if !enabled(flag) && !missing.contains(flag) {
+            missing.push(flag);
+        }
+    }
+
+    (!missing.is_empty()).then(|| format!("{}{}", current, missing))
+}
+
+/// Stream key events (expirations, deletions, writes) as Server-Sent Events.
+///
+/// Enables keyspace notifications on the instance if needed. The `events` query
+/// parameter filters by event type, e.g. `?events=expired,del`.
+pub async fn handle_keyspace_events(
     State(state): State<Arc<AppState>>,
-    Path((instance_id, key)): Path<(Uuid, String)>,
+    Extension(claims): Extension<ApiKeyClaims>,
+    Path(instance_id): Path<Uuid>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
-) -> Result<Json<RedisResponse>, ErrorResponse> {
-    let api_key = extract_api_key(&headers, &Query(query)).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing API key"})),
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ErrorResponse> {
+    let timeout = command_timeout(&state, &headers, &query)?;
+    let db = logical_db(&state, &query)?;
+
+    let events: Vec<&'static str> = match query.get("events") {
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                KEY_EVENTS.iter().map(|(known, _)| *known).find(|known| *known == e).ok_or_else(|| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({"error": format!("Unsupported key event '{}'", e)})),
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        None => KEY_EVENTS.iter().map(|(event, _)| *event).collect(),
+    };
+    if events.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "No key events selected"})),
+        ));
+    }
+    let filtered = query.contains_key("events");
+
+    let instance = instance_for_key(&state, &claims, instance_id).await?;
+
+    // Turn on the notification classes we need, keeping whatever is already enabled. This
+    // reconfigures the server, so it runs as the gateway's admin user whatever the key's scopes.
+    let mut conn = get_redis_connection(&state, &instance, AclUser::Admin, Endpoint::Primary, 0).await?;
+    let current: Vec<String> = with_timeout(
+        timeout,
+        redis::cmd("CONFIG").arg("GET").arg("notify-keyspace-events").query_async(&mut conn),
+    )
+    .await?
+    .map_err(|e| {
+        error!("Redis CONFIG GET notify-keyspace-events failed: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Redis command failed"})))
+    })?;
+    let current = current.get(1).map(String::as_str).unwrap_or_default();
+    if let Some(flags) = notify_keyspace_flags(current, &events) {
+        info!("Enabling keyspace notifications '{}' on instance {}", flags, instance_id);
+        with_timeout(
+            timeout,
+            redis::cmd("CONFIG")
+                .arg("SET")
+                .arg("notify-keyspace-events")
+                .arg(&flags)
+                .query_async::<()>(&mut conn),
         )
+        .await?
+        .map_err(|e| {
+            error!("Redis CONFIG SET notify-keyspace-events failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to enable keyspace notifications"})))
+        })?;
+    }
+    drop(conn);
+
+    let mut pubsub = with_timeout(state.redis_config.connect_timeout, state.redis_pools.pubsub(&instance, AclUser::Admin))
+        .await?
+        .map_err(|e| {
+            error!("Failed to open Redis Pub/Sub connection: {}", e);
+            (StatusCode::SERVICE_UNAVAILABLE, Json(json!({"error": "Redis instance unavailable"})))
+        })?;
+    let subscription = if filtered {
+        let channels: Vec<String> = events.iter().map(|event| format!("__keyevent@{}__:{}", db, event)).collect();
+        with_timeout(timeout, pubsub.subscribe(channels)).await?
+    } else {
+        with_timeout(timeout, pubsub.psubscribe(format!("__keyevent@{}__:*", db))).await?
+    };
+    subscription.map_err(|e| {
+        error!("Redis keyspace event subscription failed: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Redis command failed"})))
     })?;
 
-    let (instance, _claims) = authenticate_and_get_instance(&state, &api_key, instance_id).await?;
-    let mut conn = get_redis_connection(&instance).await?;
+    // Key event channels are named __keyevent@<db>__:<event> and carry the key as payload
+    let shutdown = state.shutdown.subscribe();
+    let stream = pubsub.into_on_message().map(move |message| {
+        let channel = message.get_channel_name();
+        let event = channel.rsplit(':').next().unwrap_or(channel).to_string();
+        let key: String = message.get_payload().unwrap_or_default();
+        Ok(Event::default()
+            .event(event.clone())
+            .data(json!({"event": event, "key": key, "db": db}).to_string()))
+    });
 
-    let result: redis::Value = conn.lpop(&key, None).map_err(|e| {
-        error!("Redis LPOP failed: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Redis command failed"})),
+    Ok(Sse::new(until_shutdown(stream, shutdown)).keep_alive(KeepAlive::default()))
+}
+
+/// Forward a stream of Server-Sent Events until the gateway starts shutting down, then send a
+/// final `close` event and end it, so clients see a clean close rather than a dropped connection.
+fn until_shutdown<S>(
+    events: S,
+    shutdown: broadcast::Receiver<()>,
+) -> impl Stream<Item = Result<Event, Infallible>>
+where
+    S: Stream<Item = Result<Event, Infallible>> + Send + 'static,
+{
+    futures::stream::unfold((Box::pin(events), Some(shutdown)), |(mut events, shutdown)| async move {
+        let mut shutdown = shutdown?;
+        tokio::select! {
+            event = events.next() => Some((event?, (events, Some(shutdown)))),
+            _ = shutdown.recv() => {
+                let close = Event::default().event("close").data(json!({"reason": "shutdown"}).to_string());
+                Some((Ok(close), (events, None)))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis_memory::{test_instance, test_state, MemoryBackend};
+
+    fn test_claims(scopes: &[&str]) -> ApiKeyClaims {
+        ApiKeyClaims::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            scopes.iter().map(|scope| scope.to_string()).collect(),
+            "rg_test".to_string(),
+            None,
         )
-    })?;
+    }
 
-    Ok(Json(RedisResponse {
-        result: redis_value_to_json(result),
-    }))
-}
\ No newline at end of file
+    // Run a generic-endpoint command as a key with the given scopes
+    async fn run(state: &AppState, scopes: &[&str], payload: Value) -> Result<Value, ErrorResponse> {
+        let claims = test_claims(scopes);
+        let instance = test_instance(claims.organization_id);
+        let mut conn = get_redis_connection(state, &instance, AclUser::for_scopes(&claims.scopes), Endpoint::Primary, 0).await?;
+        let payload = payload.as_array().unwrap().clone();
+        let result = execute_command(&state.redis_config, None, &mut conn, &claims, Duration::from_secs(1), &payload).await?;
+        Ok(redis_value_to_json(result))
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_dispatch() {
+        let state = test_state(Arc::new(MemoryBackend::default()));
+
+        assert_eq!(run(&state, &["write"], json!(["PING"])).await.unwrap(), "PONG");
+        assert_eq!(run(&state, &["write"], json!(["SET", "greeting", "hello"])).await.unwrap(), "OK");
+        assert_eq!(run(&state, &["read"], json!(["GET", "greeting"])).await.unwrap(), "hello");
+        assert_eq!(run(&state, &["write"], json!(["INCR", "counter"])).await.unwrap(), 1);
+        assert_eq!(run(&state, &["write"], json!(["LPUSH", "queue", "a"])).await.unwrap(), 1);
+        assert_eq!(run(&state, &["read"], json!(["LRANGE", "queue", 0, -1])).await.unwrap(), json!(["a"]));
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_enforces_scopes() {
+        let state = test_state(Arc::new(MemoryBackend::default()));
+
+        let (status, Json(body)) = run(&state, &["read"], json!(["SET", "key", "value"])).await.unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "API key requires the write scope");
+        assert_eq!(run(&state, &["read"], json!(["GET", "key"])).await.unwrap(), Value::Null);
+        assert_eq!(run(&state, &["admin"], json!(["SET", "key", "value"])).await.unwrap(), "OK");
+
+        // Pass-through commands need the write scope
+        let (status, Json(body)) = run(&state, &["read"], json!(["FLUSHALL"])).await.unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "API key requires the write scope");
+        assert_eq!(run(&state, &["read"], json!(["GET", "key"])).await.unwrap(), "value");
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_error_mapping() {
+        let state = test_state(Arc::new(MemoryBackend::default()));
+
+        let (status, _) = run(&state, &["write"], json!([])).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = run(&state, &["write"], json!(["GET"])).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        run(&state, &["write"], json!(["LPUSH", "queue", "a"])).await.unwrap();
+        let (status, Json(body)) = run(&state, &["read"], json!(["GET", "queue"])).await.unwrap_err();
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"], "Redis command failed");
+
+        let (status, Json(body)) = run(&state, &["write"], json!(["NOSUCHCOMMAND"])).await.unwrap_err();
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(body["error"].as_str().unwrap().contains("unknown command"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_refuses_connection_state() {
+        let state = test_state(Arc::new(MemoryBackend::default()));
+
+        for command in [json!(["MULTI"]), json!(["watch", "key"]), json!(["CLIENT", "REPLY", "OFF"]), json!(["SELECT", 1])] {
+            let (status, Json(body)) = run(&state, &["admin"], command).await.unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(body["error"].as_str().unwrap().contains("shared connections"));
+        }
+        let (status, Json(body)) = run(&state, &["write"], json!(["SUBSCRIBE", "news"])).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "SUBSCRIBE is not supported over request/response; use a streaming endpoint");
+    }
+
+    #[tokio::test]
+    async fn test_pipeline() {
+        let state = test_state(Arc::new(MemoryBackend::default()));
+        let config = RedisHandlerConfig {
+            max_pipeline_commands: 3,
+            max_pipeline_args: 5,
+            ..RedisHandlerConfig::default()
+        };
+        let claims = test_claims(&["write"]);
+        let commands = |value: Value| serde_json::from_value::<Vec<Vec<Value>>>(value).unwrap();
+
+        let (status, _) = check_pipeline(&config, None, &claims, &[]).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let too_many = commands(json!([["PING"], ["PING"], ["PING"], ["PING"]]));
+        let (status, Json(body)) = check_pipeline(&config, None, &claims, &too_many).unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "Pipeline exceeds the maximum of 3 commands");
+        let too_many_args = commands(json!([["MSET", "a", "1", "b", "2"], ["MGET", "a", "b"]]));
+        let (status, Json(body)) = check_pipeline(&config, None, &claims, &too_many_args).unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "Pipeline exceeds the maximum of 5 arguments");
+        let with_write = commands(json!([["GET", "a"], ["SET", "a", "1"]]));
+        let (status, _) = check_pipeline(&config, None, &test_claims(&["read"]), &with_write).unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let with_pass_through = commands(json!([["GET", "a"], ["FLUSHALL"]]));
+        let (status, _) = check_pipeline(&config, None, &test_claims(&["read"]), &with_pass_through).unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let with_transaction = commands(json!([["MULTI"], ["INCR", "a"]]));
+        let (status, _) = check_pipeline(&config, None, &claims, &with_transaction).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let checked = check_pipeline(&config, None, &claims, &commands(json!([["SET", "a", 1], ["INCR", "a"], ["GET", "a"]]))).unwrap();
+        let instance = test_instance(claims.organization_id);
+        let mut conn = get_redis_connection(&state, &instance, AclUser::Write, Endpoint::Primary, 0).await.unwrap();
+        let result = run_pipeline(&mut conn, Duration::from_secs(1), &checked).await.unwrap();
+        assert_eq!(result, json!([{"result": "OK"}, {"result": 2}, {"result": "2"}]));
+    }
+
+    #[tokio::test]
+    async fn test_set_ex_batch() {
+        let state = test_state(Arc::new(MemoryBackend::default()));
+        let config = RedisHandlerConfig {
+            max_pipeline_commands: 3,
+            ..RedisHandlerConfig::default()
+        };
+        let instance = test_instance(Uuid::new_v4());
+        let mut conn = get_redis_connection(&state, &instance, AclUser::Write, Endpoint::Primary, 0).await.unwrap();
+        let items = |value: Value| serde_json::from_value::<Vec<SetExItem>>(value).unwrap();
+        let timeout = Duration::from_secs(1);
+
+        let batch = items(json!([
+            {"key": "a", "value": "1", "ttl": 60},
+            {"key": "b", "value": "AP8=", "encoding": "base64", "ttl": 0},
+            {"key": "c", "value": "3", "ttl": 60},
+        ]));
+        let result = set_ex_batch(&config, None, &mut conn, timeout, &batch).await.unwrap();
+        assert_eq!(result[0], json!({"key": "a", "ok": true}));
+        assert_eq!(result[1]["ok"], false);
+        assert_eq!(result[2], json!({"key": "c", "ok": true}));
+        let value: Option<String> = conn.get("c").await.unwrap();
+        assert_eq!(value.as_deref(), Some("3"));
+        let ttl: i64 = conn.ttl("a").await.unwrap();
+        assert!(ttl > 0 && ttl <= 60);
+
+        let (status, _) = set_ex_batch(&config, None, &mut conn, timeout, &[]).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let too_many = items(json!([
+            {"key": "a", "value": "1", "ttl": 1},
+            {"key": "b", "value": "2", "ttl": 1},
+            {"key": "c", "value": "3", "ttl": 1},
+            {"key": "d", "value": "4", "ttl": 1},
+        ]));
+        let (status, _) = set_ex_batch(&config, None, &mut conn, timeout, &too_many).await.unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_cas_numeric() {
+        let request: CasNumericRequest =
+            serde_json::from_value(json!({"key": "score", "op": "gt", "value": 1.5})).unwrap();
+        assert_eq!(request.op, NumericComparison::Gt);
+        assert_eq!(request.value.to_string(), "1.5");
+        assert!(serde_json::from_value::<CasNumericRequest>(json!({"key": "k", "op": "eq", "value": 1})).is_err());
+        assert!(serde_json::from_value::<CasNumericRequest>(json!({"key": "k", "op": "lt", "value": "1"})).is_err());
+
+        assert_eq!(cas_numeric_result((1, "42".to_string())).unwrap(), json!({"updated": true, "value": 42}));
+        assert_eq!(cas_numeric_result((0, "2.5".to_string())).unwrap(), json!({"updated": false, "value": 2.5}));
+        assert_eq!(cas_numeric_result((0, "0x10".to_string())).unwrap(), json!({"updated": false, "value": "0x10"}));
+        let (status, _) = cas_numeric_result((-1, "hash".to_string())).unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_get_redis_connection_errors() {
+        let backend = Arc::new(MemoryBackend::default());
+        let state = test_state(backend.clone());
+        let mut instance = test_instance(Uuid::new_v4());
+        instance.max_concurrent_commands = Some(1);
+
+        let held = get_redis_connection(&state, &instance, AclUser::Read, Endpoint::Primary, 0).await.unwrap();
+        let (status, _) = get_redis_connection(&state, &instance, AclUser::Read, Endpoint::Primary, 0).await.err().unwrap();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        drop(held);
+
+        backend.reject_auth(true);
+        let (status, _) = get_redis_connection(&state, &instance, AclUser::Read, Endpoint::Primary, 0).await.err().unwrap();
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+    }
+
+    #[test]
+    fn test_check_path_params() {
+        let config = RedisHandlerConfig {
+            max_key_bytes: 8,
+            ..RedisHandlerConfig::default()
+        };
+
+        assert!(check_path_params(&config, "a/b c", None).is_ok());
+        assert!(check_path_params(&config, "12345678", Some("field")).is_ok());
+        assert_eq!(check_path_params(&config, "", None).unwrap_err().0, StatusCode::BAD_REQUEST);
+        assert_eq!(check_path_params(&config, "123456789", None).unwrap_err().0, StatusCode::BAD_REQUEST);
+        assert_eq!(check_path_params(&config, "key", Some("")).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_decode_body_value() {
+        assert_eq!(decode_body_value("a/b\n", ValueEncoding::Utf8).unwrap(), b"a/b\n");
+        assert_eq!(decode_body_value("AP8=", ValueEncoding::Base64).unwrap(), vec![0x00, 0xff]);
+        assert!(decode_body_value("not base64!", ValueEncoding::Base64).is_err());
+
+        let body: SetBody = serde_json::from_value(json!({"value": "AP8=", "encoding": "base64", "ex": 10})).unwrap();
+        assert_eq!(body.encoding, ValueEncoding::Base64);
+        assert_eq!(body.ex, Some(10));
+        assert!(serde_json::from_value::<ValueBody>(json!({"value": "v", "ex": 10})).is_err());
+    }
+
+    #[test]
+    fn test_check_command_size() {
+        let config = RedisHandlerConfig {
+            max_arg_bytes: 8,
+            max_command_bytes: 16,
+            ..RedisHandlerConfig::default()
+        };
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert!(check_command_size(&config, "SET", &args(&["key", "12345678"])).is_ok());
+
+        let (status, Json(body)) = check_command_size(&config, "SET", &args(&["key", "123456789"])).unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "Argument 2 exceeds the maximum size of 8 bytes");
+
+        let (status, _) = check_command_size(&config, "MSET", &args(&["k1", "12345678", "k2", "v"])).unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let few_args = RedisHandlerConfig {
+            max_command_args: 2,
+            ..config
+        };
+        assert!(check_command_size(&few_args, "MGET", &args(&["a", "b"])).is_ok());
+        let (status, Json(body)) = check_command_size(&few_args, "MGET", &args(&["a", "b", "c"])).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Command exceeds the maximum of 2 arguments");
+    }
+
+    #[test]
+    fn test_check_value_size() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+        let mset = args(&["k1", "v1", "k2", "v2"]);
+        assert_eq!(set_values("mset", &mset), vec!["v1", "v2"]);
+        let setex = args(&["key", "60", "value"]);
+        assert_eq!(set_values("SETEX", &setex), vec!["value"]);
+        let set = args(&["key", "value", "EX", "60"]);
+        assert_eq!(set_values("SET", &set), vec!["value"]);
+        assert!(set_values("GET", &args(&["key"])).is_empty());
+        assert!(set_values("SET", &args(&["key"])).is_empty());
+
+        assert!(check_value_size(None, usize::MAX).is_ok());
+        assert!(check_value_size(Some(5), 5).is_ok());
+        let (status, Json(body)) = check_value_size(Some(5), 6).unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "Value exceeds the instance's maximum of 5 bytes");
+    }
+
+    #[test]
+    fn test_command_access() {
+        let scopes = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(command_access("get"), Some(CommandAccess::Read));
+        assert_eq!(command_access("HSET"), Some(CommandAccess::Write));
+        assert_eq!(command_access("smismember"), Some(CommandAccess::Read));
+        assert_eq!(command_access("ZRANGE"), None);
+
+        assert!(CommandAccess::Read.allowed_by(&scopes(&["read"])));
+        assert!(CommandAccess::Read.allowed_by(&scopes(&["write"])));
+        assert!(!CommandAccess::Write.allowed_by(&scopes(&["read"])));
+        assert!(CommandAccess::Write.allowed_by(&scopes(&["admin"])));
+        assert!(!CommandAccess::Read.allowed_by(&scopes(&["metrics"])));
+    }
+
+    #[test]
+    fn test_command_endpoint() {
+        let query = |consistency: Option<&str>| {
+            consistency.map(|value| ("consistency".to_string(), value.to_string())).into_iter().collect::<HashMap<_, _>>()
+        };
+
+        assert_eq!(command_endpoint(Some(CommandAccess::Read), &query(None)).unwrap(), Endpoint::Replica);
+        assert_eq!(command_endpoint(Some(CommandAccess::Read), &query(Some("replica"))).unwrap(), Endpoint::Replica);
+        assert_eq!(command_endpoint(Some(CommandAccess::Read), &query(Some("primary"))).unwrap(), Endpoint::Primary);
+        assert_eq!(command_endpoint(Some(CommandAccess::Write), &query(Some("replica"))).unwrap(), Endpoint::Primary);
+        assert_eq!(command_endpoint(None, &query(None)).unwrap(), Endpoint::Primary);
+        assert_eq!(command_endpoint(Some(CommandAccess::Read), &query(Some("eventual"))).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_parse_sintercard_args() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        let without_limit = args(&["2", "a", "b"]);
+        let (keys, limit) = parse_sintercard_args(&without_limit).unwrap();
+        assert_eq!(keys, args(&["a", "b"]));
+        assert_eq!(limit, None);
+        let with_limit = args(&["1", "a", "limit", "5"]);
+        assert_eq!(parse_sintercard_args(&with_limit).unwrap().1, Some(5));
+
+        assert!(parse_sintercard_args(&args(&["0"])).is_err());
+        assert!(parse_sintercard_args(&args(&["3", "a", "b"])).is_err());
+        assert!(parse_sintercard_args(&args(&["1", "a", "LIMIT"])).is_err());
+        assert!(parse_sintercard_args(&args(&["1", "a", "LIMIT", "-1"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_zadd_args() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_zadd_args(&args(&["board", "xx", "gt", "CH", "10", "alice", "+inf", "bob"])).unwrap(),
+            ZAddArgs {
+                key: "board",
+                flags: vec!["XX", "GT", "CH"],
+                incr: false,
+                items: vec![("10", "alice"), ("+inf", "bob")],
+            }
+        );
+        let incr = args(&["hits", "NX", "incr", "1.5", "page"]);
+        let parsed = parse_zadd_args(&incr).unwrap();
+        assert!(parsed.incr);
+        assert_eq!(parsed.flags, vec!["NX"]);
+
+        assert!(parse_zadd_args(&args(&["board"])).is_err());
+        assert!(parse_zadd_args(&args(&["board", "1"])).is_err());
+        assert!(parse_zadd_args(&args(&["board", "one", "alice"])).is_err());
+        assert!(parse_zadd_args(&args(&["board", "NX", "XX", "1", "a"])).is_err());
+        assert!(parse_zadd_args(&args(&["board", "GT", "LT", "1", "a"])).is_err());
+        assert!(parse_zadd_args(&args(&["board", "NX", "GT", "1", "a"])).is_err());
+        assert!(parse_zadd_args(&args(&["board", "INCR", "1", "a", "2", "b"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_scan_options() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(parse_scan_options("HSCAN", &[]).unwrap(), ScanOptions::default());
+        assert_eq!(
+            parse_scan_options("SSCAN", &args(&["match", "user:*", "COUNT", "100"])).unwrap(),
+            ScanOptions {
+                pattern: Some("user:*".to_string()),
+                count: Some(100),
+            }
+        );
+
+        assert!(parse_scan_options("ZSCAN", &args(&["COUNT", "0"])).is_err());
+        assert!(parse_scan_options("ZSCAN", &args(&["MATCH"])).is_err());
+        assert!(parse_scan_options("ZSCAN", &args(&["NOVALUES", "1"])).is_err());
+    }
+
+    #[test]
+    fn test_scan_page_pairs_items() {
+        let items = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            redis_value_to_json(scan_page("HSCAN", "17".to_string(), items(&["name", "john", "age", "30"]))),
+            json!({"cursor": "17", "items": [{"field": "name", "value": "john"}, {"field": "age", "value": "30"}]})
+        );
+        assert_eq!(
+            redis_value_to_json(scan_page("ZSCAN", "0".to_string(), items(&["alice", "1.5"]))),
+            json!({"cursor": "0", "items": [{"member": "alice", "score": 1.5}]})
+        );
+        assert_eq!(
+            redis_value_to_json(scan_page("SSCAN", "0".to_string(), items(&["a", "b"]))),
+            json!({"cursor": "0", "items": ["a", "b"]})
+        );
+    }
+
+    #[test]
+    fn test_parse_lpos_options() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(parse_lpos_options(&[]).unwrap(), LposOptions::default());
+        assert_eq!(
+            parse_lpos_options(&args(&["rank", "-1", "COUNT", "0", "MAXLEN", "100"])).unwrap(),
+            LposOptions {
+                rank: Some(-1),
+                count: Some(0),
+                maxlen: Some(100),
+            }
+        );
+
+        assert!(parse_lpos_options(&args(&["RANK", "0"])).is_err());
+        assert!(parse_lpos_options(&args(&["COUNT"])).is_err());
+        assert!(parse_lpos_options(&args(&["FIRST", "1"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_expire_flags() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert!(parse_expire_flags(&[]).unwrap().is_empty());
+        assert_eq!(parse_expire_flags(&args(&["nx"])).unwrap(), vec!["NX"]);
+        assert_eq!(parse_expire_flags(&args(&["XX", "GT"])).unwrap(), vec!["XX", "GT"]);
+        assert_eq!(parse_expire_flags(&args(&["LT", "lt"])).unwrap(), vec!["LT"]);
+
+        assert!(parse_expire_flags(&args(&["NX", "XX"])).is_err());
+        assert!(parse_expire_flags(&args(&["GT", "LT"])).is_err());
+        assert!(parse_expire_flags(&args(&["KEEPTTL"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_restore_args() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        let with_options = args(&["key", "1000", "AAEC", "replace", "ABSTTL"]);
+        assert_eq!(
+            parse_restore_args(&with_options).unwrap(),
+            RestoreArgs {
+                key: "key",
+                ttl_ms: 1000,
+                payload: vec![0, 1, 2],
+                replace: true,
+                absttl: true,
+            }
+        );
+
+        assert!(parse_restore_args(&args(&["key", "0"])).is_err());
+        assert!(parse_restore_args(&args(&["key", "-1", "AAEC"])).is_err());
+        assert!(parse_restore_args(&args(&["key", "0", "not base64!"])).is_err());
+        assert!(parse_restore_args(&args(&["key", "0", "AAEC", "IDLETIME"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_geoadd_args() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_geoadd_args(&args(&["places", "xx", "CH", "13.361389", "38.115556", "Palermo"])).unwrap(),
+            GeoAddArgs {
+                key: "places",
+                flags: vec!["XX", "CH"],
+                items: vec![(13.361389, 38.115556, "Palermo")],
+            }
+        );
+        assert_eq!(parse_geoadd_args(&args(&["places", "1", "2", "a", "3", "4", "b"])).unwrap().items.len(), 2);
+
+        assert!(parse_geoadd_args(&args(&["places"])).is_err());
+        assert!(parse_geoadd_args(&args(&["places", "1", "2"])).is_err());
+        assert!(parse_geoadd_args(&args(&["places", "NX", "XX", "1", "2", "a"])).is_err());
+        assert!(parse_geoadd_args(&args(&["places", "181", "0", "a"])).is_err());
+        assert!(parse_geoadd_args(&args(&["places", "0", "86", "a"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_geosearch_args() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        let search = args(&["places", "FROMLONLAT", "15", "37", "byradius", "200", "KM", "asc", "COUNT", "5", "ANY", "WITHCOORD", "WITHDIST"]);
+        assert_eq!(
+            parse_geosearch_args(&search).unwrap(),
+            GeoSearchArgs {
+                key: "places",
+                origin: GeoOrigin::LonLat(15.0, 37.0),
+                shape: GeoShape::Radius(200.0, "km"),
+                order: Some("ASC"),
+                count: Some((5, true)),
+                with_coord: true,
+                with_dist: true,
+            }
+        );
+        let search = args(&["places", "BYBOX", "400", "300", "mi", "FROMMEMBER", "Palermo"]);
+        let parsed = parse_geosearch_args(&search).unwrap();
+        assert_eq!(parsed.origin, GeoOrigin::Member("Palermo"));
+        assert_eq!(parsed.shape, GeoShape::Box(400.0, 300.0, "mi"));
+        assert_eq!(parsed.count, None);
+
+        assert!(parse_geosearch_args(&args(&["places", "BYRADIUS", "1", "km"])).is_err());
+        assert!(parse_geosearch_args(&args(&["places", "FROMMEMBER", "a"])).is_err());
+        assert!(parse_geosearch_args(&args(&["places", "FROMMEMBER", "a", "FROMMEMBER", "b", "BYRADIUS", "1", "m"])).is_err());
+        assert!(parse_geosearch_args(&args(&["places", "FROMMEMBER", "a", "BYRADIUS", "1", "yards"])).is_err());
+        assert!(parse_geosearch_args(&args(&["places", "FROMMEMBER", "a", "BYRADIUS", "-1", "m"])).is_err());
+        assert!(parse_geosearch_args(&args(&["places", "FROMMEMBER", "a", "BYRADIUS", "1", "m", "COUNT", "0"])).is_err());
+        assert!(parse_geosearch_args(&args(&["places", "FROMMEMBER", "a", "BYBOX", "1", "m"])).is_err());
+    }
+
+    #[test]
+    fn test_geo_search_results() {
+        let bulk = |value: &str| redis::Value::BulkString(value.as_bytes().to_vec());
+        let names = redis::Value::Array(vec![bulk("Palermo")]);
+        assert_eq!(geo_search_results(names.clone(), false, false).unwrap(), names);
+
+        let reply = redis::Value::Array(vec![redis::Value::Array(vec![
+            bulk("Palermo"),
+            bulk("190.4424"),
+            redis::Value::Array(vec![bulk("13.36138933897018433"), bulk("38.11555639549629859")]),
+        ])]);
+        let rendered = redis_value_to_json(geo_search_results(reply, true, true).unwrap());
+        assert_eq!(rendered[0]["member"], "Palermo");
+        assert_eq!(rendered[0]["distance"], 190.4424);
+        assert!((rendered[0]["longitude"].as_f64().unwrap() - 13.361389).abs() < 1e-6);
+
+        let short = redis::Value::Array(vec![redis::Value::Array(vec![bulk("Palermo")])]);
+        assert!(geo_search_results(short, true, false).is_err());
+    }
+
+    #[test]
+    fn test_parse_copy_args() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_copy_args(&args(&["src", "dst", "db", "3", "REPLACE"]), 15).unwrap(),
+            CopyArgs {
+                source: "src",
+                destination: "dst",
+                db: Some(3),
+                replace: true,
+            }
+        );
+        assert_eq!(parse_copy_args(&args(&["src", "dst"]), 15).unwrap().db, None);
+
+        assert!(parse_copy_args(&args(&["src"]), 15).is_err());
+        assert!(parse_copy_args(&args(&["src", "dst", "DB"]), 15).is_err());
+        assert!(parse_copy_args(&args(&["src", "dst", "DB", "16"]), 15).is_err());
+        assert!(parse_copy_args(&args(&["src", "dst", "DB", "-1"]), 15).is_err());
+        assert!(parse_copy_args(&args(&["src", "dst", "KEEPTTL"]), 15).is_err());
+    }
+
+    #[test]
+    fn test_notify_keyspace_flags() {
+        assert_eq!(notify_keyspace_flags("", &["expired", "del"]), Some("Egx".to_string()));
+        assert_eq!(notify_keyspace_flags("Kx", &["expired", "set"]), Some("KxE$".to_string()));
+        assert_eq!(notify_keyspace_flags("AE", &["set", "del", "expired"]), None);
+        assert_eq!(notify_keyspace_flags("xE", &["expired"]), None);
+    }
+
+    #[test]
+    fn test_parse_sort_args() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+
+        let sort_args = args(&["ids", "by", "weight_*", "GET", "#", "GET", "name_*", "LIMIT", "0", "10", "DESC", "ALPHA", "STORE", "out"]);
+        assert_eq!(
+            parse_sort_args("SORT", &sort_args).unwrap(),
+            SortArgs {
+                key: "ids",
+                by: Some("weight_*"),
+                limit: Some((0, 10)),
+                get: vec!["#", "name_*"],
+                descending: true,
+                alpha: true,
+                store: Some("out"),
+            }
+        );
+        let plain_args = args(&["ids", "DESC", "ASC"]);
+        let plain = parse_sort_args("SORT", &plain_args).unwrap();
+        assert!(!plain.descending);
+        assert_eq!(plain.by, None);
+
+        assert!(parse_sort_args("SORT", &args(&[])).is_err());
+        assert!(parse_sort_args("SORT", &args(&["ids", "BY"])).is_err());
+        assert!(parse_sort_args("SORT", &args(&["ids", "LIMIT", "0"])).is_err());
+        assert!(parse_sort_args("SORT", &args(&["ids", "LIMIT", "-1", "10"])).is_err());
+        assert!(parse_sort_args("SORT", &args(&["ids", "LIMIT", "0", "ten"])).is_err());
+        assert!(parse_sort_args("SORT", &args(&["ids", "NOSORT"])).is_err());
+        assert!(parse_sort_args("SORT_RO", &args(&["ids", "STORE", "out"])).is_err());
+    }
+
+    #[test]
+    fn test_command_key() {
+        assert_eq!(command_key("get", &[json!("get"), json!("user:1")]), Some("user:1"));
+        assert_eq!(command_key("OBJECT", &[json!("OBJECT"), json!("ENCODING"), json!("user:1")]), Some("user:1"));
+        assert_eq!(command_key("SINTERCARD", &[json!("SINTERCARD"), json!(2), json!("a"), json!("b")]), Some("a"));
+        assert_eq!(command_key("PUBLISH", &[json!("PUBLISH"), json!("news"), json!("hi")]), None);
+        assert_eq!(command_key("GET", &[json!("GET")]), None);
+        assert_eq!(command_key("FLUSHALL", &[json!("FLUSHALL")]), None);
+    }
+
+    #[tokio::test]
+    async fn test_until_shutdown_closes_stream() {
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let events = futures::stream::iter([Ok(Event::default().data("first"))]).chain(futures::stream::pending());
+        let mut stream = Box::pin(until_shutdown(events, shutdown_rx));
+
+        assert!(stream.next().await.is_some());
+        shutdown_tx.send(()).unwrap();
+        // The close event, then the end of the stream
+        assert!(stream.next().await.is_some());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_until_shutdown_ends_with_events() {
+        let (_shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let events = futures::stream::iter([Ok(Event::default().data("only"))]);
+        let stream = until_shutdown(events, shutdown_rx);
+
+        assert_eq!(stream.collect::<Vec<_>>().await.len(), 1);
+    }
+
+    #[test]
+    fn test_redis_value_to_json_resp2() {
+        assert_eq!(redis_value_to_json(redis::Value::Nil), Value::Null);
+        assert_eq!(redis_value_to_json(redis::Value::Int(42)), json!(42));
+        assert_eq!(redis_value_to_json(redis::Value::Okay), json!("OK"));
+        assert_eq!(
+            redis_value_to_json(redis::Value::BulkString(b"hello".to_vec())),
+            json!("hello")
+        );
+        assert_eq!(
+            redis_value_to_json(redis::Value::Array(vec![
+                redis::Value::Int(1),
+                redis::Value::SimpleString("two".to_string()),
+            ])),
+            json!([1, "two"])
+        );
+    }
+
+    #[test]
+    fn test_redis_value_to_json_resp3() {
+        let map = redis::Value::Map(vec![
+            (
+                redis::Value::BulkString(b"field".to_vec()),
+                redis::Value::BulkString(b"value".to_vec()),
+            ),
+            (redis::Value::Int(7), redis::Value::Boolean(true)),
+        ]);
+        assert_eq!(redis_value_to_json(map), json!({"field": "value", "7": true}));
+
+        let set = redis::Value::Set(vec![redis::Value::BulkString(b"a".to_vec())]);
+        assert_eq!(redis_value_to_json(set), json!(["a"]));
+
+        assert_eq!(redis_value_to_json(redis::Value::Double(1.5)), json!(1.5));
+        assert_eq!(redis_value_to_json(redis::Value::Double(f64::INFINITY)), json!("inf"));
+        assert_eq!(redis_value_to_json(redis::Value::Boolean(false)), json!(false));
+        assert_eq!(
+            redis_value_to_json(redis::Value::VerbatimString {
+                format: redis::VerbatimFormat::Text,
+                text: "some text".to_string(),
+            }),
+            json!("some text")
+        );
+    }
+
+    #[test]
+    fn test_parse_redis_info() {
+        let info = "# Memory\r\nused_memory:1024\r\nused_memory_human:1.00K\r\n\r\n# Clients\r\nconnected_clients:3\r\n";
+        let parsed = parse_redis_info(info);
+        assert_eq!(parsed.get("used_memory").map(String::as_str), Some("1024"));
+        assert_eq!(parsed.get("connected_clients").map(String::as_str), Some("3"));
+        assert_eq!(parsed.len(), 3);
+    }
+
+    #[test]
+    fn test_render_info_metrics() {
+        let info = parse_redis_info("used_memory:2048\nkeyspace_hits:10\nrole:master\n");
+        let output = render_info_metrics(&info, "cache");
+        assert!(output.contains("# TYPE redis_used_memory_bytes gauge\n"));
+        assert!(output.contains("redis_used_memory_bytes{instance=\"cache\"} 2048\n"));
+        assert!(output.contains("redis_keyspace_hits_total{instance=\"cache\"} 10\n"));
+        assert!(!output.contains("redis_evicted_keys_total"));
+    }
+}
@@ -1,29 +1,82 @@
 // Redis instance management handlers
 
 use axum::{
+    body::Body,
     extract::{Extension, Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
+use futures::{AsyncBufReadExt, StreamExt};
 use chrono::Utc;
-use sqlx::{Row, types::BigDecimal};
+use serde_json::json;
+use sqlx::{PgExecutor, Row, types::BigDecimal};
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::api_models::{
-    ApiResponse, CreateRedisInstanceRequest, PaginatedResponse, PaginationParams,
-    RedisInstanceResponse,
+    ApiResponse, ErrorCode, CreateRedisInstanceRequest, DeleteRedisInstanceParams, DeletedRedisInstanceResponse, InstanceLogsParams,
+    PaginatedResponse,
+    PaginationParams,
+    RedisInstanceCreationResponse, RedisInstanceResponse, RedisInstanceStatusResponse, RedisPasswordRotationResponse, RedisPoolRefreshResponse,
 };
+use crate::audit::{self, AuditEvent};
 use crate::auth::hash_password;
-use crate::k8s_service::K8sRedisService;
+use crate::config::{is_dns_label, NamespaceMode};
+use crate::handlers::keyspace::{admin_instance, instance_connection, run_command};
+use crate::handlers::access::require_org_admin;
+use crate::handlers::api_keys::{revoke_api_keys, RevokedKeys};
+use crate::idempotency::IdempotencyKey;
+use crate::k8s_service::{K8sRedisService, K8sUnavailable};
 use crate::middleware::{AppState, CurrentUser};
-use crate::models::RedisInstance;
+use crate::models::{Plan, RedisInstance};
+use crate::redis_acl::AclUser;
+use crate::redis_pool::Endpoint;
+use crate::webhooks::WebhookNotifier;
 
 type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
 
+// How often and how long a restart is watched before the instance is marked as failed
+const RESTART_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const RESTART_TIMEOUT: Duration = Duration::from_secs(600);
+
+// Namespace of an organization's new instances in per-organization mode: `redis-{org_slug}`,
+// falling back to the UUID when that name is not a valid label or belongs to another
+// organization (e.g. after a rename)
+async fn organization_namespace<'e, E: PgExecutor<'e>>(executor: E, org_id: Uuid, org_slug: &str) -> Result<String, sqlx::Error> {
+    let candidate = format!("redis-{}", org_slug);
+    let taken = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM redis_instances WHERE namespace = $1 AND organization_id <> $2) AS "taken!""#,
+        candidate,
+        org_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    if is_dns_label(&candidate) && !taken {
+        Ok(candidate)
+    } else {
+        Ok(format!("redis-{}", org_id.simple()))
+    }
+}
+
 // Helper function to convert RedisInstance to RedisInstanceResponse
-fn redis_instance_to_response(redis_instance: RedisInstance) -> RedisInstanceResponse {
+// The Kubernetes service, or None in local-only mode where instances are never deployed.
+// Fails with 503 while the API server has not been reached.
+fn kubernetes_service(state: &AppState) -> Result<Option<K8sRedisService>, ErrorResponse> {
+    match state.kubernetes.service() {
+        Ok(service) => Ok(Some(service)),
+        Err(K8sUnavailable::Disabled) => Ok(None),
+        Err(e) => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Kubernetes not available: {}", e))),
+        )),
+    }
+}
+
+// Instances stored without a version report the configured default
+pub(crate) fn redis_instance_to_response(redis_instance: RedisInstance, default_redis_version: &str) -> RedisInstanceResponse {
     RedisInstanceResponse {
         id: redis_instance.id,
         name: redis_instance.name,
@@ -34,9 +87,10 @@ fn redis_instance_to_response(redis_instance: RedisInstance) -> RedisInstanceRes
         domain: redis_instance.domain,
         max_memory: redis_instance.max_memory.unwrap_or(0),
         current_memory: redis_instance.current_memory.unwrap_or(0),
-        redis_version: redis_instance.redis_version.unwrap_or_else(|| "7.0".to_string()),
+        redis_version: redis_instance.redis_version.unwrap_or_else(|| default_redis_version.to_string()),
         namespace: redis_instance.namespace.unwrap_or_else(|| "default".to_string()),
         status: redis_instance.status.unwrap_or_else(|| "unknown".to_string()),
+        status_reason: redis_instance.status_reason,
         health_status: redis_instance.health_status.unwrap_or_else(|| "unknown".to_string()),
         cpu_usage_percent: redis_instance.cpu_usage_percent
             .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0))
@@ -45,12 +99,16 @@ fn redis_instance_to_response(redis_instance: RedisInstance) -> RedisInstanceRes
             .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0))
             .unwrap_or(0.0),
         connections_count: redis_instance.connections_count.unwrap_or(0),
-        max_connections: redis_instance.max_connections.unwrap_or(1000),
+        max_connections: redis_instance.max_connections,
+        eviction_policy: redis_instance.eviction_policy.unwrap_or_else(|| "allkeys-lru".to_string()),
+        tls_enabled: redis_instance.tls_enabled,
+        max_concurrent_commands: redis_instance.max_concurrent_commands,
+        max_value_bytes: redis_instance.max_value_bytes,
         persistence_enabled: redis_instance.persistence_enabled.unwrap_or(false),
         backup_enabled: redis_instance.backup_enabled.unwrap_or(false),
         last_backup_at: redis_instance.last_backup_at,
-        created_at: redis_instance.created_at.unwrap_or_else(|| Utc::now()),
-        updated_at: redis_instance.updated_at.unwrap_or_else(|| Utc::now()),
+        created_at: redis_instance.created_at.unwrap_or_else(Utc::now),
+        updated_at: redis_instance.updated_at.unwrap_or_else(Utc::now),
     }
 }
 
@@ -71,13 +129,14 @@ fn generate_redis_password() -> String {
 pub async fn create_redis_instance(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
+    headers: HeaderMap,
     Json(payload): Json<CreateRedisInstanceRequest>,
-) -> Result<Json<ApiResponse<RedisInstanceResponse>>, ErrorResponse> {
+) -> Result<Json<ApiResponse<RedisInstanceCreationResponse>>, ErrorResponse> {
     // Validate input
     if let Err(errors) = payload.validate() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ApiResponse::<()>::error(format!("Validation error: {:?}", errors))),
+            Json(ApiResponse::<()>::validation_error(&errors)),
         ));
     }
 
@@ -95,13 +154,74 @@ pub async fn create_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    // Return the original instance when a request is retried with the same Idempotency-Key.
+    // The password is read back from the instance Secret; simulated instances are never
+    // deployed and keep their password nowhere, so replays of their creation have none.
+    let idempotency_key =
+        IdempotencyKey::from_headers(&headers, current_user.id, "create_redis_instance", &payload)?;
+    if let Some(key) = &idempotency_key {
+        if let Some(instance_id) = key.replayed_resource(&state.db_pool).await? {
+            let redis_instance = sqlx::query_as!(
+                RedisInstance,
+                "SELECT * FROM redis_instances WHERE id = $1",
+                instance_id
+            )
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch created Redis instance: {}", e))),
+                )
+            })?;
+
+            let password = state
+                .redis_pools
+                .instance_address(&redis_instance, Endpoint::Primary)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Failed to read the instance password: {}", e))),
+                    )
+                })?
+                .password;
+            return Ok(Json(ApiResponse::success(RedisInstanceCreationResponse {
+                redis_instance: redis_instance_to_response(redis_instance, &state.kubernetes_config.default_redis_version),
+                password,
+            })));
+        }
+    }
+
+    // The quota checks and the insert run in one transaction holding the organization row, so
+    // concurrent creations cannot each pass the checks and exceed the quotas together
+    let mut tx = state.db_pool.begin().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?;
+
+    let org_limits = sqlx::query!(
+        "SELECT slug, plan, max_redis_instances FROM organizations WHERE id = $1 FOR UPDATE",
+        payload.organization_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
@@ -110,34 +230,52 @@ pub async fn create_redis_instance(
         "SELECT COUNT(*) as count FROM redis_instances WHERE organization_id = $1 AND deleted_at IS NULL",
         payload.organization_id
     )
-    .fetch_one(&state.db_pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .count
     .unwrap_or(0);
 
-    let org_limits = sqlx::query!(
-        "SELECT max_redis_instances FROM organizations WHERE id = $1",
+    let plan = Plan::from_db(org_limits.plan.as_deref());
+    let max_redis_instances = org_limits
+        .max_redis_instances
+        .unwrap_or_else(|| plan.max_redis_instances());
+
+    if instance_count >= max_redis_instances as i64 {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(ErrorCode::QuotaExceeded, "Organization has reached the maximum number of Redis instances".to_string())),
+        ));
+    }
+
+    // The instance count alone would let a small plan provision a few very large instances
+    let total_max_memory = sqlx::query_scalar!(
+        r#"SELECT COALESCE(SUM(max_memory), 0)::BIGINT AS "total!" FROM redis_instances WHERE organization_id = $1 AND deleted_at IS NULL"#,
         payload.organization_id
     )
-    .fetch_one(&state.db_pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
-    if instance_count >= org_limits.max_redis_instances.unwrap_or(3) as i64 {
+    if total_max_memory + payload.max_memory > plan.max_total_memory() {
         return Err((
             StatusCode::CONFLICT,
-            Json(ApiResponse::<()>::error("Organization has reached the maximum number of Redis instances".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::QuotaExceeded, format!(
+                "Organization memory quota exceeded: {} of {} bytes already allocated on the {} plan",
+                total_max_memory,
+                plan.max_total_memory(),
+                plan.as_str()
+            ))),
         ));
     }
 
@@ -152,14 +290,14 @@ pub async fn create_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
     if existing_instance.is_some() {
         return Err((
             StatusCode::CONFLICT,
-            Json(ApiResponse::<()>::error("Redis instance with this slug already exists in the organization".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::SlugTaken, "Redis instance with this slug already exists in the organization".to_string())),
         ));
     }
 
@@ -172,23 +310,119 @@ pub async fn create_redis_instance(
     let redis_password_hash = hash_password(&redis_password).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Password hashing error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::InternalError, format!("Password hashing error: {}", e))),
         )
     })?;
 
     // Create Redis instance
     let instance_id = Uuid::new_v4();
-    let redis_version = payload.redis_version.unwrap_or_else(|| "7.2".to_string());
+    let redis_version = payload
+        .redis_version
+        .clone()
+        .unwrap_or_else(|| state.kubernetes_config.default_redis_version.clone());
+    if !state.kubernetes_config.supports_redis_version(&redis_version) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, format!(
+                "Redis version '{}' is not supported; choose one of {}",
+                redis_version,
+                state.kubernetes_config.supported_redis_versions.join(", ")
+            ))),
+        ));
+    }
     let persistence_enabled = payload.persistence_enabled.unwrap_or(true);
     let backup_enabled = payload.backup_enabled.unwrap_or(false);
-    let namespace = format!("redis-{}", payload.organization_id.simple());
+    let max_connections = payload.max_connections.unwrap_or_else(|| plan.max_connections());
+    if max_connections > plan.max_connections() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::QuotaExceeded, format!(
+                "max_connections cannot exceed {} on the {} plan",
+                plan.max_connections(),
+                plan.as_str()
+            ))),
+        ));
+    }
+    let max_value_bytes = payload.max_value_bytes.unwrap_or_else(|| plan.max_value_bytes());
+    if max_value_bytes > plan.max_value_bytes() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::QuotaExceeded, format!(
+                "max_value_bytes cannot exceed {} on the {} plan",
+                plan.max_value_bytes(),
+                plan.as_str()
+            ))),
+        ));
+    }
+    let eviction_policy = payload
+        .eviction_policy
+        .clone()
+        .unwrap_or_else(|| plan.default_eviction_policy().to_string());
+    let namespace = match &state.kubernetes_config.namespace_mode {
+        NamespaceMode::Shared(namespace) => namespace.clone(),
+        NamespaceMode::PerOrganization => organization_namespace(&mut *tx, payload.organization_id, &org_limits.slug)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+                )
+            })?,
+    };
+
+    // Resources are named after the slug, so it must be unique in the namespace, which other
+    // organizations may share. The (namespace, slug) index catches concurrent creations.
+    let taken = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM redis_instances WHERE namespace = $1 AND slug = $2 AND deleted_at IS NULL) AS "taken!""#,
+        namespace,
+        payload.slug
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?;
+    if taken {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(ErrorCode::SlugTaken, format!("Redis instance slug is already used in namespace {}", namespace))),
+        ));
+    }
     
     // Use a default port range (Redis typically uses 6379, but we'll assign dynamically)
     let port = 6379;
-    let domain = format!("{}.{}.redis.local", payload.slug, payload.organization_id.simple());
+    let domain = state.domain_config.render(&payload.slug, &org_limits.slug).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, format!(
+                "Slug '{}' does not produce a valid hostname",
+                payload.slug
+            ))),
+        )
+    })?;
+
+    // Provision per-scope ACL users when the gateway can derive their passwords
+    let acl_users: Vec<(AclUser, String)> = match &state.redis_config.acl_secret {
+        Some(secret) => AclUser::ALL
+            .iter()
+            .map(|user| (*user, user.password(secret, instance_id)))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut pod_security = state.kubernetes_config.pod_security.clone();
+    if let Some(overrides) = &payload.pod_security {
+        pod_security.run_as_user = overrides.run_as_user.unwrap_or(pod_security.run_as_user);
+        pod_security.fs_group = overrides.fs_group.unwrap_or(pod_security.fs_group);
+        pod_security.read_only_root_filesystem =
+            overrides.read_only_root_filesystem.unwrap_or(pod_security.read_only_root_filesystem);
+    }
 
     // Try to deploy to Kubernetes if available
-    let k8s_deployment_result = match crate::k8s_service::K8sRedisService::new().await {
+    let k8s_deployment_result = match state.kubernetes.service() {
         Ok(k8s_service) => {
             let config = crate::k8s_service::RedisDeploymentConfig {
                 name: payload.name.clone(),
@@ -200,7 +434,32 @@ pub async fn create_redis_instance(
                 max_memory: payload.max_memory,
                 redis_password: redis_password.clone(),
                 port,
+                max_connections,
+                eviction_policy: eviction_policy.clone(),
+                domain: domain.clone(),
+                tls_enabled: payload.tls_enabled.unwrap_or(false),
+                tls_issuer: state.kubernetes_config.tls_issuer.clone(),
+                acl_users: acl_users.clone(),
+                pod_security,
             };
+
+            // Bound concurrent deployments so provisioning bursts don't overwhelm the API server
+            let _permit = tokio::time::timeout(
+                state.kubernetes_config.provision_queue_timeout,
+                state.provisioning.clone().acquire_owned(),
+            )
+            .await
+            .map_err(|_| {
+                tracing::warn!("Timed out waiting to provision Redis instance {}", instance_id);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(ApiResponse::<()>::error(
+                        ErrorCode::ProvisioningBusy,
+                        "Too many instances are being provisioned; try again later".to_string(),
+                    )),
+                )
+            })?
+            .expect("the provisioning semaphore is never closed");
             
             match k8s_service.create_redis_instance(config).await {
                 Ok(result) => {
@@ -220,6 +479,9 @@ pub async fn create_redis_instance(
     };
 
     // Use deployment result or mock data for database record
+    // Simulated instances are served by the development Redis, which has no TLS
+    let tls_enabled = k8s_deployment_result.is_some() && payload.tls_enabled.unwrap_or(false);
+    let acl_enabled = k8s_deployment_result.is_some() && !acl_users.is_empty();
     let (actual_port, actual_domain, actual_namespace, deployment_name, service_name, status) = 
         if let Some(ref result) = k8s_deployment_result {
             (
@@ -241,16 +503,16 @@ pub async fn create_redis_instance(
             )
         };
 
-    sqlx::query(
+    let inserted = sqlx::query(
         r#"
         INSERT INTO redis_instances (
             id, name, slug, organization_id, port, domain,
             max_memory, current_memory, password_hash, redis_version, namespace,
             pod_name, service_name, status, health_status, cpu_usage_percent, memory_usage_percent,
-            connections_count, max_connections, persistence_enabled, backup_enabled,
-            created_at, updated_at
+            connections_count, max_connections, eviction_policy, tls_enabled, acl_enabled, max_concurrent_commands,
+            max_value_bytes, persistence_enabled, backup_enabled, created_at, updated_at
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28)
         "#,
     )
     .bind(instance_id)
@@ -271,26 +533,41 @@ pub async fn create_redis_instance(
     .bind(BigDecimal::new(0.into(), 2)) // cpu_usage_percent
     .bind(BigDecimal::new(0.into(), 2)) // memory_usage_percent
     .bind(0i32) // connections_count
-    .bind(100i32) // max_connections (default)
+    .bind(max_connections)
+    .bind(&eviction_policy)
+    .bind(tls_enabled)
+    .bind(acl_enabled)
+    .bind(payload.max_concurrent_commands)
+    .bind(max_value_bytes)
     .bind(persistence_enabled)
     .bind(backup_enabled)
     .bind(now)
     .bind(now)
-    .execute(&state.db_pool)
-    .await
-    .map_err(|e| {
+    .execute(&mut *tx)
+    .await;
+    let committed = match inserted {
+        Ok(_) => tx.commit().await,
+        Err(e) => Err(e),
+    };
+    committed.map_err(|e| {
         // If database insert fails, we should clean up K8s resources if they were created
-        if let Some(result) = k8s_deployment_result {
+        if let (Some(result), Ok(k8s_service)) = (k8s_deployment_result, state.kubernetes.service()) {
             tokio::spawn(async move {
-                if let Ok(k8s_service) = crate::k8s_service::K8sRedisService::new().await {
-                    let _ = k8s_service.delete_redis_instance(&result.namespace, &payload.slug).await;
-                }
+                let _ = k8s_service.delete_redis_instance(&result.namespace, &payload.slug, true, true).await;
             });
         }
-        
+
+        // A concurrent creation took the slug (or its domain) since it was checked
+        if e.as_database_error().is_some_and(|e| e.is_unique_violation()) {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiResponse::<()>::error(ErrorCode::SlugTaken, "Redis instance slug is already taken".to_string())),
+            );
+        }
+
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to create Redis instance: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to create Redis instance: {}", e))),
         )
     })?;
 
@@ -305,13 +582,22 @@ pub async fn create_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to fetch created Redis instance: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to fetch created Redis instance: {}", e))),
         )
     })?;
 
-    let instance_response = redis_instance_to_response(redis_instance);
+    if let Some(key) = &idempotency_key {
+        if let Err(e) = key.store(&state.db_pool, instance_id).await {
+            tracing::warn!("Failed to store idempotency key: {}", e);
+        }
+    }
 
-    Ok(Json(ApiResponse::success(instance_response)))
+    let creation_response = RedisInstanceCreationResponse {
+        redis_instance: redis_instance_to_response(redis_instance, &state.kubernetes_config.default_redis_version),
+        password: Some(redis_password), // Return the plaintext password (only on creation)
+    };
+
+    Ok(Json(ApiResponse::success(creation_response)))
 }
 
 pub async fn list_redis_instances(
@@ -334,13 +620,13 @@ pub async fn list_redis_instances(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
@@ -366,7 +652,7 @@ pub async fn list_redis_instances(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?;
 
@@ -380,7 +666,7 @@ pub async fn list_redis_instances(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .count
@@ -388,7 +674,7 @@ pub async fn list_redis_instances(
 
     let instance_responses: Vec<RedisInstanceResponse> = redis_instances
         .into_iter()
-        .map(redis_instance_to_response)
+        .map(|redis_instance| redis_instance_to_response(redis_instance, &state.kubernetes_config.default_redis_version))
         .collect();
 
     let total_pages = ((total_count as f64) / (limit as f64)).ceil() as u32;
@@ -404,6 +690,75 @@ pub async fn list_redis_instances(
     Ok(Json(ApiResponse::success(paginated_response)))
 }
 
+// Soft-deleted instances that can still be recovered, most recently deleted first
+pub async fn list_deleted_redis_instances(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(params): Query<PaginationParams>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<PaginatedResponse<DeletedRedisInstanceResponse>>>, ErrorResponse> {
+    require_org_admin(&state, &current_user, org_id, "list deleted Redis instances").await?;
+
+    let page = params.page.unwrap_or(1);
+    let limit = params.limit.unwrap_or(20).min(100);
+    let offset = (page - 1) * limit;
+    let retention = &state.retention_config;
+    let cutoff = retention.cutoff(Utc::now());
+
+    let database_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    };
+    let redis_instances: Vec<RedisInstance> = sqlx::query_as(
+        r#"
+        SELECT * FROM redis_instances
+        WHERE organization_id = $1 AND deleted_at IS NOT NULL AND deleted_at > $2
+        ORDER BY deleted_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(org_id)
+    .bind(cutoff)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(database_error)?;
+
+    let total_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM redis_instances WHERE organization_id = $1 AND deleted_at IS NOT NULL AND deleted_at > $2",
+    )
+    .bind(org_id)
+    .bind(cutoff)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(database_error)?;
+
+    let items = redis_instances
+        .into_iter()
+        .filter_map(|redis_instance| {
+            let deleted_at = redis_instance.deleted_at?;
+            Some(DeletedRedisInstanceResponse {
+                redis_instance: redis_instance_to_response(redis_instance, &state.kubernetes_config.default_redis_version),
+                deleted_at,
+                retained_until: retention.retained_until(deleted_at),
+            })
+        })
+        .collect();
+
+    let total_pages = ((total_count as f64) / (limit as f64)).ceil() as u32;
+
+    Ok(Json(ApiResponse::success(PaginatedResponse {
+        items,
+        total_count,
+        page,
+        limit,
+        total_pages,
+    })))
+}
+
 pub async fn get_redis_instance(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
@@ -423,13 +778,13 @@ pub async fn get_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
@@ -445,17 +800,17 @@ pub async fn get_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Redis instance not found".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Redis instance not found".to_string())),
         )
     })?;
 
-    let instance_response = redis_instance_to_response(redis_instance);
+    let instance_response = redis_instance_to_response(redis_instance, &state.kubernetes_config.default_redis_version);
 
     Ok(Json(ApiResponse::success(instance_response)))
 }
@@ -464,7 +819,10 @@ pub async fn delete_redis_instance(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
     Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<DeleteRedisInstanceParams>,
 ) -> Result<Json<ApiResponse<()>>, ErrorResponse> {
+    let purge_data = params.purge_data.unwrap_or(false);
+
     // Check if user has admin access to the organization
     let org_membership = sqlx::query!(
         r#"
@@ -479,20 +837,20 @@ pub async fn delete_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
     if !["admin", "owner"].contains(&org_membership.role.as_str()) {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ApiResponse::<()>::error("Insufficient permissions to delete Redis instances".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Insufficient permissions to delete Redis instances".to_string())),
         ));
     }
 
@@ -507,40 +865,35 @@ pub async fn delete_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Redis instance not found".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Redis instance not found".to_string())),
         )
     })?;
 
     let now = Utc::now();
 
     // Delete from Kubernetes first
-    let k8s_service = K8sRedisService::new().await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to initialize Kubernetes client: {}", e))),
-        )
-    })?;
+    let k8s_service = kubernetes_service(&state)?;
 
     let namespace: Option<String> = redis_instance.try_get("namespace").ok();
     let slug: Option<String> = redis_instance.try_get("slug").ok();
     let api_key_id: uuid::Uuid = redis_instance.try_get("api_key_id").map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database field error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database field error: {}", e))),
         )
     })?;
 
-    if let (Some(namespace), Some(slug)) = (&namespace, &slug) {
-        k8s_service.delete_redis_instance(namespace, slug).await.map_err(|e| {
+    if let (Some(k8s_service), Some(namespace), Some(slug)) = (&k8s_service, &namespace, &slug) {
+        k8s_service.delete_redis_instance(namespace, slug, purge_data, purge_data).await.map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(format!("Failed to delete Redis from Kubernetes: {}", e))),
+                Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Failed to delete Redis from Kubernetes: {}", e))),
             )
         })?;
     }
@@ -557,29 +910,48 @@ pub async fn delete_redis_instance(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to delete Redis instance: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to delete Redis instance: {}", e))),
         )
     })?;
 
     // Deactivate associated API key
-    sqlx::query!(
-        "UPDATE api_keys SET is_active = false, updated_at = $1 WHERE id = $2",
-        now,
-        api_key_id
+    revoke_api_keys(&state.db_pool, org_id, RevokedKeys::Key(api_key_id), now)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to deactivate API key: {}", e))),
+            )
+        })?;
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "delete",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "slug": slug, "purge_data": purge_data }),
+        },
     )
-    .execute(&state.db_pool)
     .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Failed to deactivate API key: {}", e))),
-        )
-    })?;
+    {
+        tracing::warn!("Failed to record instance deletion in audit log: {}", e);
+    }
+
+    let message = if purge_data {
+        "Redis instance and its data deleted successfully"
+    } else {
+        "Redis instance deleted successfully; data is retained for the restore window"
+    };
 
     Ok(Json(ApiResponse {
         success: true,
         data: None,
-        message: Some("Redis instance deleted successfully".to_string()),
+        message: Some(message.to_string()),
+        code: None,
+        errors: None,
         timestamp: Utc::now(),
     }))
 }
@@ -588,7 +960,7 @@ pub async fn update_redis_instance_status(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
     Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
-) -> Result<Json<ApiResponse<RedisInstanceResponse>>, ErrorResponse> {
+) -> Result<Json<ApiResponse<RedisInstanceStatusResponse>>, ErrorResponse> {
     // Check if user has access to the organization
     let _org_membership = sqlx::query!(
         r#"
@@ -603,13 +975,13 @@ pub async fn update_redis_instance_status(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Organization not found or access denied".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
         )
     })?;
 
@@ -624,13 +996,13 @@ pub async fn update_redis_instance_status(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Redis instance not found".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Redis instance not found".to_string())),
         )
     })?;
 
@@ -639,25 +1011,42 @@ pub async fn update_redis_instance_status(
     let slug: Option<String> = redis_instance.try_get("slug").ok();
     let current_status: Option<String> = redis_instance.try_get("status").ok();
 
-    if let (Some(namespace), Some(slug)) = (&namespace, &slug) {
-        let k8s_service = K8sRedisService::new().await.map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(format!("Failed to initialize Kubernetes client: {}", e))),
-            )
-        })?;
+    let mut pod = None;
 
-        let k8s_status = k8s_service.get_deployment_status(namespace, slug).await.map_err(|e| {
+    // In local-only mode there is no deployment to check, so the stored status stands
+    if let (Some(k8s_service), Some(namespace), Some(slug)) = (kubernetes_service(&state)?, &namespace, &slug) {
+        let mut k8s_status = k8s_service.get_deployment_status(namespace, slug).await.map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::error(format!("Failed to check Kubernetes status: {}", e))),
+                Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Failed to check Kubernetes status: {}", e))),
             )
         })?;
 
+        // The old pod keeps serving during a restart, so wait for the rollout to finish
+        if current_status.as_deref() == Some("restarting")
+            && k8s_status == "running"
+            && !k8s_service.rollout_complete(namespace, slug).await.unwrap_or(false)
+        {
+            k8s_status = "restarting".to_string();
+        }
+
+        // An instance that timed out stays failed until its deployment actually becomes ready
+        if current_status.as_deref() == Some("failed") && k8s_status == "pending" {
+            k8s_status = "failed".to_string();
+        }
+
+        // Explain why the instance is not running; diagnostics are best-effort
+        if k8s_status != "running" {
+            pod = k8s_service.pod_diagnostics(namespace, slug).await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to read pod diagnostics of instance {}: {}", instance_id, e);
+                None
+            });
+        }
+
         // Update status in database if it changed
         if current_status.as_deref() != Some(&k8s_status) {
             sqlx::query(
-                "UPDATE redis_instances SET status = $1, updated_at = $2 WHERE id = $3",
+                "UPDATE redis_instances SET status = $1, status_reason = NULL, updated_at = $2 WHERE id = $3",
             )
             .bind(&k8s_status)
             .bind(chrono::Utc::now())
@@ -667,9 +1056,11 @@ pub async fn update_redis_instance_status(
             .map_err(|e| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::<()>::error(format!("Failed to update status: {}", e))),
+                    Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to update status: {}", e))),
                 )
             })?;
+
+            state.webhooks.status_changed(org_id, instance_id, current_status.as_deref(), &k8s_status);
         }
     }
 
@@ -685,17 +1076,362 @@ pub async fn update_redis_instance_status(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::error(format!("Database error: {}", e))),
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ApiResponse::<()>::error("Redis instance not found".to_string())),
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Redis instance not found".to_string())),
         )
     })?;
 
-    let instance_response = redis_instance_to_response(updated_instance);
+    Ok(Json(ApiResponse::success(RedisInstanceStatusResponse {
+        redis_instance: redis_instance_to_response(updated_instance, &state.kubernetes_config.default_redis_version),
+        pod,
+    })))
+}
 
-    Ok(Json(ApiResponse::success(instance_response)))
-}
\ No newline at end of file
+pub async fn rotate_redis_password(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<RedisPasswordRotationResponse>>, ErrorResponse> {
+    // Check if user has admin access to the organization
+    let org_membership = sqlx::query!(
+        r#"
+        SELECT role FROM organization_memberships 
+        WHERE organization_id = $1 AND user_id = $2 AND is_active = true
+        "#,
+        org_id,
+        current_user.id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Organization not found or access denied".to_string())),
+        )
+    })?;
+
+    if !["admin", "owner"].contains(&org_membership.role.as_str()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(ErrorCode::Forbidden, "Insufficient permissions to rotate Redis passwords".to_string())),
+        ));
+    }
+
+    // Get Redis instance
+    let redis_instance = sqlx::query_as!(
+        RedisInstance,
+        "SELECT * FROM redis_instances WHERE id = $1 AND organization_id = $2 AND deleted_at IS NULL",
+        instance_id,
+        org_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Redis instance not found".to_string())),
+        )
+    })?;
+
+    let new_password = generate_redis_password();
+    let new_password_hash = hash_password(&new_password).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Failed to hash password: {}", e))),
+        )
+    })?;
+
+    // Update Kubernetes first so the stored hash never gets ahead of the running instance
+    if let (Some(k8s_service), Some(namespace)) = (kubernetes_service(&state)?, &redis_instance.namespace) {
+        k8s_service
+            .rotate_redis_password(namespace, &redis_instance.slug, &new_password)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Failed to rotate password in Kubernetes: {}", e))),
+                )
+            })?;
+    }
+
+    sqlx::query!(
+        "UPDATE redis_instances SET password_hash = $1, updated_at = $2 WHERE id = $3",
+        new_password_hash,
+        Utc::now(),
+        instance_id
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to store new password: {}", e))),
+        )
+    })?;
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "rotate_password",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "slug": redis_instance.slug }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record password rotation in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(RedisPasswordRotationResponse {
+        instance_id,
+        password: new_password,
+    })))
+}
+
+pub async fn restart_redis_instance(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<RedisInstanceResponse>>, ErrorResponse> {
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+
+    if instance.status.as_deref() == Some("restarting") {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(ErrorCode::ResourceInUse, "Redis instance is already restarting".to_string())),
+        ));
+    }
+    let namespace = match &instance.namespace {
+        Some(namespace) if instance.status.as_deref() != Some("simulation") => namespace.clone(),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(ErrorCode::InvalidRequest, "Redis instance is not deployed to Kubernetes".to_string())),
+            ));
+        }
+    };
+
+    let k8s_service = state.kubernetes.service().map_err(|e| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Kubernetes not available: {}", e))),
+        )
+    })?;
+
+    k8s_service.restart(&namespace, &instance.slug).await.map_err(|e| {
+        (
+            StatusCode::BAD_GATEWAY,
+            Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Failed to restart Redis instance: {}", e))),
+        )
+    })?;
+
+    let redis_instance = sqlx::query_as!(
+        RedisInstance,
+        "UPDATE redis_instances SET status = 'restarting', updated_at = $1 WHERE id = $2 RETURNING *",
+        Utc::now(),
+        instance_id
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to update status: {}", e))),
+        )
+    })?;
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "restart_redis_instance",
+            resource_type: "redis_instance",
+            resource_id: Some(instance_id),
+            details: json!({ "previous_status": instance.status }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record Redis instance restart in audit log: {}", e);
+    }
+
+    state.webhooks.status_changed(org_id, instance_id, instance.status.as_deref(), "restarting");
+
+    tokio::spawn(watch_restart(
+        state.db_pool.clone(),
+        state.webhooks.clone(),
+        k8s_service,
+        namespace,
+        instance.slug,
+        org_id,
+        instance_id,
+    ));
+
+    Ok(Json(ApiResponse::success(redis_instance_to_response(redis_instance, &state.kubernetes_config.default_redis_version))))
+}
+
+// Drop an instance's cached connection pools and reconnect, e.g. right after its password was
+// rotated or its pod replaced, instead of waiting for a failed command to evict them
+pub async fn refresh_redis_pool(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<RedisPoolRefreshResponse>>, ErrorResponse> {
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+
+    state.redis_pools.evict(instance.id);
+
+    let ping = async {
+        let mut conn = instance_connection(&state, &instance).await?;
+        run_command(&state, redis::cmd("PING").query_async::<String>(&mut *conn)).await
+    };
+    let health_status = match ping.await {
+        Ok(_) => "healthy",
+        Err((_, Json(error))) => {
+            tracing::warn!("Redis instance {} did not answer after a pool refresh: {}", instance.id, error.message.unwrap_or_default());
+            "unhealthy"
+        }
+    };
+
+    let checked_at = Utc::now();
+    sqlx::query!(
+        "UPDATE redis_instances SET health_status = $1, last_health_check_at = $2 WHERE id = $3",
+        health_status,
+        checked_at,
+        instance_id
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Failed to update health status: {}", e))),
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(RedisPoolRefreshResponse {
+        instance_id,
+        health_status: health_status.to_string(),
+        checked_at,
+    })))
+}
+
+// Mark a restarting instance as running once the new pod is ready, or as failed after the timeout
+async fn watch_restart(
+    pool: sqlx::PgPool,
+    webhooks: Arc<WebhookNotifier>,
+    k8s_service: K8sRedisService,
+    namespace: String,
+    slug: String,
+    org_id: Uuid,
+    instance_id: Uuid,
+) {
+    let deadline = tokio::time::Instant::now() + RESTART_TIMEOUT;
+    let mut status = "error";
+
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(RESTART_POLL_INTERVAL).await;
+        if k8s_service.rollout_complete(&namespace, &slug).await.unwrap_or(false) {
+            status = "running";
+            break;
+        }
+    }
+
+    if status == "error" {
+        tracing::warn!("Redis instance {} did not become ready after a restart", instance_id);
+    }
+
+    // Only finish restarts that were not superseded by another status change
+    match sqlx::query!(
+        "UPDATE redis_instances SET status = $1, updated_at = $2 WHERE id = $3 AND status = 'restarting'",
+        status,
+        Utc::now(),
+        instance_id
+    )
+    .execute(&pool)
+    .await
+    {
+        Ok(result) if result.rows_affected() > 0 => {
+            webhooks.status_changed(org_id, instance_id, Some("restarting"), status);
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to update status of restarted Redis instance {}: {}", instance_id, e),
+    }
+}
+
+pub async fn get_redis_instance_logs(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((org_id, instance_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<InstanceLogsParams>,
+) -> Result<Response, ErrorResponse> {
+    if let Err(errors) = params.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    let instance = admin_instance(&state, &current_user, org_id, instance_id).await?;
+    let namespace = instance.namespace.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "Redis instance is not deployed to Kubernetes".to_string())),
+        )
+    })?;
+
+    let k8s_service = state.kubernetes.service().map_err(|e| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Kubernetes not available: {}", e))),
+        )
+    })?;
+
+    let logs = k8s_service
+        .pod_logs(
+            &namespace,
+            &instance.slug,
+            params.tail_lines.unwrap_or(100),
+            params.follow.unwrap_or(false),
+            params.previous.unwrap_or(false),
+        )
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(ApiResponse::<()>::error(ErrorCode::KubernetesError, format!("Failed to fetch pod logs: {}", e))),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error(ErrorCode::NotFound, "No pod found for this Redis instance".to_string())),
+            )
+        })?;
+
+    // Forward lines as they arrive so `follow` streams until the client disconnects
+    let body = Body::from_stream(logs.lines().map(|line| line.map(|line| format!("{}\n", line))));
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response())
+}
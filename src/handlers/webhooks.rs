@@ -0,0 +1,179 @@
+// Organization status-change webhook handlers. Webhooks expose instance activity to outside
+// systems, so only organization admins and owners manage them.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::api_models::{ApiResponse, ErrorCode, UpdateWebhookRequest, WebhookResponse};
+use crate::audit::{self, AuditEvent};
+use crate::handlers::access::require_org_admin;
+use crate::middleware::{AppState, CurrentUser};
+use crate::webhooks;
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+fn database_error(e: sqlx::Error) -> ErrorResponse {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiResponse::<()>::error(ErrorCode::DatabaseError, format!("Database error: {}", e))),
+    )
+}
+
+// Random hex secret used to sign webhook payloads
+fn generate_webhook_secret() -> String {
+    rand::random::<[u8; 32]>().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub async fn get_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WebhookResponse>>, ErrorResponse> {
+    require_org_admin(&state, &current_user, org_id, "manage webhooks").await?;
+
+    let webhook = sqlx::query!(
+        "SELECT organization_id, url, created_at, updated_at FROM organization_webhooks WHERE organization_id = $1",
+        org_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(database_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "No webhook configured for this organization".to_string())),
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(WebhookResponse {
+        organization_id: webhook.organization_id,
+        url: webhook.url,
+        secret: None,
+        created_at: webhook.created_at,
+        updated_at: webhook.updated_at,
+    })))
+}
+
+// Create or replace the organization's webhook. A signing secret is generated when the webhook
+// is first created or `rotate_secret` is set, and returned only in that response.
+pub async fn update_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> Result<Json<ApiResponse<WebhookResponse>>, ErrorResponse> {
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::validation_error(&errors)),
+        ));
+    }
+
+    require_org_admin(&state, &current_user, org_id, "manage webhooks").await?;
+
+    if let Err(e) = webhooks::check_webhook_url(&payload.url).await {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(ErrorCode::ValidationError, format!("Webhook URL is not allowed: {}", e))),
+        ));
+    }
+
+    let new_secret = generate_webhook_secret();
+    let webhook = sqlx::query!(
+        r#"
+        INSERT INTO organization_webhooks (organization_id, url, secret)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (organization_id) DO UPDATE
+        SET url = EXCLUDED.url,
+            secret = CASE WHEN $4 THEN EXCLUDED.secret ELSE organization_webhooks.secret END,
+            updated_at = NOW()
+        RETURNING organization_id, url, secret, created_at, updated_at
+        "#,
+        org_id,
+        payload.url,
+        new_secret,
+        payload.rotate_secret.unwrap_or(false)
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(database_error)?;
+
+    let secret_generated = webhook.secret == new_secret;
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "update_webhook",
+            resource_type: "organization",
+            resource_id: Some(org_id),
+            details: json!({ "url": webhook.url, "secret_rotated": secret_generated }),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record webhook change in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(WebhookResponse {
+        organization_id: webhook.organization_id,
+        url: webhook.url,
+        secret: secret_generated.then_some(webhook.secret),
+        created_at: webhook.created_at,
+        updated_at: webhook.updated_at,
+    })))
+}
+
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, ErrorResponse> {
+    require_org_admin(&state, &current_user, org_id, "manage webhooks").await?;
+
+    let result = sqlx::query!("DELETE FROM organization_webhooks WHERE organization_id = $1", org_id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(database_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(ErrorCode::NotFound, "No webhook configured for this organization".to_string())),
+        ));
+    }
+
+    if let Err(e) = audit::record(
+        &state.db_pool,
+        &AuditEvent {
+            user_id: Some(current_user.id),
+            organization_id: Some(org_id),
+            action: "delete_webhook",
+            resource_type: "organization",
+            resource_id: Some(org_id),
+            details: json!({}),
+        },
+    )
+    .await
+    {
+        tracing::warn!("Failed to record webhook removal in audit log: {}", e);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: None,
+        message: Some("Webhook deleted successfully".to_string()),
+        code: None,
+        errors: None,
+        timestamp: Utc::now(),
+    }))
+}
@@ -0,0 +1,150 @@
+// Background task that pings Redis instances and keeps their health columns current
+
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::HealthCheckConfig;
+use crate::middleware::AppState;
+use crate::models::RedisInstance;
+use crate::redis_acl::AclUser;
+
+// Instances pinged at the same time
+const MAX_CONCURRENT_CHECKS: usize = 16;
+
+/// Consecutive failed pings per instance
+#[derive(Debug, Default)]
+struct FailureTracker {
+    failures: HashMap<Uuid, u32>,
+}
+
+impl FailureTracker {
+    /// Record a ping result and return the health status to store, if it should change.
+    ///
+    /// Instances are only marked unhealthy after `threshold` consecutive failures so a
+    /// single dropped ping doesn't flap the status.
+    fn record(&mut self, instance_id: Uuid, healthy: bool, threshold: u32) -> Option<&'static str> {
+        if healthy {
+            self.failures.remove(&instance_id);
+            return Some("healthy");
+        }
+
+        let failures = self.failures.entry(instance_id).or_insert(0);
+        *failures += 1;
+        (*failures >= threshold).then_some("unhealthy")
+    }
+
+    /// Forget instances that are no longer checked (deleted or stopped)
+    fn retain(&mut self, instance_ids: &[Uuid]) {
+        self.failures.retain(|id, _| instance_ids.contains(id));
+    }
+}
+
+/// Run health checks at the configured interval until the gateway starts shutting down
+pub async fn run(state: Arc<AppState>, config: HealthCheckConfig) {
+    let mut shutdown = state.shutdown.subscribe();
+    let mut tracker = FailureTracker::default();
+    let mut interval = tokio::time::interval(config.interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.recv() => break,
+        }
+        tokio::select! {
+            result = check_all(&state, &config, &mut tracker) => {
+                if let Err(e) = result {
+                    tracing::warn!("Redis health check round failed: {}", e);
+                }
+            }
+            _ = shutdown.recv() => break,
+        }
+    }
+    tracing::info!("Redis health checks stopped for shutdown");
+}
+
+async fn check_all(
+    state: &AppState,
+    config: &HealthCheckConfig,
+    tracker: &mut FailureTracker,
+) -> Result<(), sqlx::Error> {
+    // Simulated instances are served by the development Redis, so they are checked too
+    let instances = sqlx::query_as!(
+        RedisInstance,
+        "SELECT * FROM redis_instances WHERE deleted_at IS NULL AND status IN ('running', 'simulation')"
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    let checks: Vec<_> = instances
+        .iter()
+        .map(|instance| async move { (instance.id, ping(state, instance).await) })
+        .collect();
+    let results: Vec<(Uuid, bool)> = futures::stream::iter(checks)
+        .buffer_unordered(MAX_CONCURRENT_CHECKS)
+        .collect()
+        .await;
+
+    let instance_ids: Vec<Uuid> = instances.iter().map(|instance| instance.id).collect();
+    tracker.retain(&instance_ids);
+
+    for (instance_id, healthy) in results {
+        let health_status = tracker.record(instance_id, healthy, config.failure_threshold);
+        if health_status == Some("unhealthy") {
+            tracing::warn!("Redis instance {} failed its health check", instance_id);
+        }
+
+        // One failed update should not keep the rest of the round from being recorded
+        let updated = sqlx::query!(
+            r#"
+            UPDATE redis_instances
+            SET health_status = COALESCE($1, health_status), last_health_check_at = NOW()
+            WHERE id = $2
+            "#,
+            health_status,
+            instance_id
+        )
+        .execute(&state.db_pool)
+        .await;
+        if let Err(e) = updated {
+            tracing::warn!("Failed to record the health of Redis instance {}: {}", instance_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn ping(state: &AppState, instance: &RedisInstance) -> bool {
+    let check = async {
+        let mut conn = state.redis_pools.connection(instance, AclUser::Read).await.ok()?;
+        redis::cmd("PING").query_async::<String>(&mut *conn).await.ok()
+    };
+
+    matches!(
+        tokio::time::timeout(state.redis_config.command_timeout, check).await,
+        Ok(Some(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unhealthy_after_consecutive_failures() {
+        let mut tracker = FailureTracker::default();
+        let id = Uuid::new_v4();
+
+        assert_eq!(tracker.record(id, false, 3), None);
+        assert_eq!(tracker.record(id, false, 3), None);
+        assert_eq!(tracker.record(id, false, 3), Some("unhealthy"));
+        assert_eq!(tracker.record(id, true, 3), Some("healthy"));
+        // A success resets the count
+        assert_eq!(tracker.record(id, false, 3), None);
+
+        tracker.retain(&[]);
+        assert!(tracker.failures.is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+// Idempotency-Key support for resource creation endpoints
+
+use axum::{http::HeaderMap, http::StatusCode, response::Json};
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::api_models::{ApiResponse, ErrorCode};
+
+type ErrorResponse = (StatusCode, Json<ApiResponse<()>>);
+
+// How long a key is remembered after the original request
+const IDEMPOTENCY_TTL_HOURS: i64 = 24;
+
+// A client-supplied idempotency key bound to one user, endpoint and payload
+pub struct IdempotencyKey {
+    user_id: Uuid,
+    endpoint: &'static str,
+    key: String,
+    request_hash: String,
+}
+
+impl IdempotencyKey {
+    // Read the Idempotency-Key header; returns None when the client did not send one
+    pub fn from_headers<T: Serialize>(
+        headers: &HeaderMap,
+        user_id: Uuid,
+        endpoint: &'static str,
+        payload: &T,
+    ) -> Result<Option<Self>, ErrorResponse> {
+        let Some(value) = headers.get("idempotency-key") else {
+            return Ok(None);
+        };
+
+        let key = value
+            .to_str()
+            .ok()
+            .map(str::trim)
+            .filter(|key| !key.is_empty() && key.len() <= 255)
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error(ErrorCode::InvalidHeader, "Invalid Idempotency-Key header".to_string())),
+                )
+            })?;
+
+        Ok(Some(Self {
+            user_id,
+            endpoint,
+            key: key.to_string(),
+            request_hash: request_hash(payload),
+        }))
+    }
+
+    // Return the resource created by an earlier request with the same key, if any.
+    // Reusing a key with a different payload is a conflict.
+    pub async fn replayed_resource(&self, pool: &PgPool) -> Result<Option<Uuid>, ErrorResponse> {
+        let cutoff = Utc::now() - Duration::hours(IDEMPOTENCY_TTL_HOURS);
+        let existing = sqlx::query!(
+            r#"
+            SELECT request_hash, resource_id FROM idempotency_keys
+            WHERE user_id = $1 AND endpoint = $2 AND idempotency_key = $3 AND created_at > $4
+            "#,
+            self.user_id,
+            self.endpoint,
+            self.key,
+            cutoff
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(ErrorCode::InternalError, format!("Database error: {}", e))),
+            )
+        })?;
+
+        match existing {
+            Some(row) if row.request_hash != self.request_hash => Err((
+                StatusCode::CONFLICT,
+                Json(ApiResponse::<()>::error(
+                    ErrorCode::IdempotencyKeyReused,
+                    "Idempotency-Key was already used with a different request".to_string(),
+                )),
+            )),
+            Some(row) => Ok(Some(row.resource_id)),
+            None => Ok(None),
+        }
+    }
+
+    // Remember the resource created for this key, replacing any expired entry
+    pub async fn store(&self, pool: &PgPool, resource_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (user_id, endpoint, idempotency_key, request_hash, resource_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, endpoint, idempotency_key)
+            DO UPDATE SET request_hash = EXCLUDED.request_hash, resource_id = EXCLUDED.resource_id, created_at = EXCLUDED.created_at
+            "#,
+            self.user_id,
+            self.endpoint,
+            self.key,
+            self.request_hash,
+            resource_id,
+            Utc::now()
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+// SHA-256 of the serialized request payload
+fn request_hash<T: Serialize>(payload: &T) -> String {
+    let body = serde_json::to_vec(payload).unwrap_or_default();
+    format!("{:x}", Sha256::digest(&body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_hash_is_stable() {
+        let a = request_hash(&json!({"name": "cache", "max_memory": 1048576}));
+        let b = request_hash(&json!({"name": "cache", "max_memory": 1048576}));
+        let c = request_hash(&json!({"name": "cache", "max_memory": 2097152}));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.len(), 64);
+    }
+
+    #[test]
+    fn test_from_headers() {
+        let payload = json!({"name": "cache"});
+        let headers = HeaderMap::new();
+        assert!(IdempotencyKey::from_headers(&headers, Uuid::new_v4(), "test", &payload)
+            .unwrap()
+            .is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", "retry-1".parse().unwrap());
+        let key = IdempotencyKey::from_headers(&headers, Uuid::new_v4(), "test", &payload)
+            .unwrap()
+            .unwrap();
+        assert_eq!(key.key, "retry-1");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", " ".parse().unwrap());
+        assert!(IdempotencyKey::from_headers(&headers, Uuid::new_v4(), "test", &payload).is_err());
+    }
+}
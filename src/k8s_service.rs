@@ -1,20 +1,145 @@
 // Kubernetes service for Redis instance management
 
 use k8s_openapi::api::apps::v1::{Deployment, DeploymentSpec};
+use k8s_openapi::api::batch::v1::CronJob;
+use futures::AsyncBufRead;
 use k8s_openapi::api::core::v1::{
-    Container, ContainerPort, EnvVar, PodSpec, PodTemplateSpec, Secret, Service, ServicePort, ServiceSpec,
+    Capabilities, Container, ContainerPort, EmptyDirVolumeSource, EnvVar, Event, PersistentVolumeClaim, Pod, PodSecurityContext,
+    PodSpec, PodTemplateSpec, SeccompProfile, Secret, SecretVolumeSource, SecurityContext, Service, ServicePort, ServiceSpec,
+    Volume, VolumeMount,
 };
 use k8s_openapi::api::networking::v1::{Ingress, IngressBackend, IngressRule, IngressServiceBackend, IngressSpec, HTTPIngressPath, HTTPIngressRuleValue};
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta};
 use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use kube::api::{DeleteParams, ListParams, LogParams, Patch, PatchParams};
+use kube::core::{ApiResource, DynamicObject, GroupVersionKind};
 use kube::{Api, Client, Error as KubeError};
+use serde::Serialize;
+use serde_json::json;
 use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use uuid::Uuid;
 
+use crate::config::{PodSecurityConfig, TlsIssuer};
+use crate::redis_acl::{self, AclUser};
+
+// Where the instance's TLS Secret is mounted in the Redis container
+const REDIS_TLS_DIR: &str = "/etc/redis/tls";
+
+// Redis's working directory in the official image; writable even with a read-only root filesystem
+const REDIS_DATA_DIR: &str = "/data";
+
+/// Build the redis-server command line. With TLS, the plaintext port is disabled and
+/// the instance port only accepts TLS connections.
+pub(crate) fn redis_server_command(config: &RedisDeploymentConfig) -> Vec<String> {
+    let mut command = vec![
+        "redis-server".to_string(),
+        "--requirepass".to_string(),
+        "$(REDIS_PASSWORD)".to_string(),
+        "--maxmemory".to_string(),
+        format!("{}b", config.max_memory),
+        "--maxmemory-policy".to_string(),
+        config.eviction_policy.clone(),
+        "--maxclients".to_string(),
+        config.max_connections.to_string(),
+    ];
+
+    let acl_users: Vec<AclUser> = config.acl_users.iter().map(|(user, _)| *user).collect();
+    command.extend(redis_acl::server_args(&acl_users));
+
+    if config.tls_enabled {
+        command.extend([
+            "--port".to_string(),
+            "0".to_string(),
+            "--tls-port".to_string(),
+            config.port.to_string(),
+            "--tls-cert-file".to_string(),
+            format!("{}/tls.crt", REDIS_TLS_DIR),
+            "--tls-key-file".to_string(),
+            format!("{}/tls.key", REDIS_TLS_DIR),
+            "--tls-ca-cert-file".to_string(),
+            format!("{}/ca.crt", REDIS_TLS_DIR),
+            "--tls-auth-clients".to_string(),
+            "no".to_string(),
+        ]);
+    }
+
+    command
+}
+
+#[derive(Clone)]
 pub struct K8sRedisService {
     client: Client,
 }
 
+/// Why no Kubernetes service is available
+#[derive(Debug)]
+pub enum K8sUnavailable {
+    Disabled,   // K8S_ENABLED=false: local-only mode
+    Connecting, // Not (yet) connected to the API server
+}
+
+impl fmt::Display for K8sUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            K8sUnavailable::Disabled => write!(f, "Kubernetes integration is disabled"),
+            K8sUnavailable::Connecting => write!(f, "not connected to the Kubernetes API server yet"),
+        }
+    }
+}
+
+/// Kubernetes client shared by handlers and background tasks.
+///
+/// The connection is made in the background by `connect`, so the server starts in a degraded,
+/// Kubernetes-less state when the API server is unreachable and recovers once it answers.
+pub struct KubernetesClient {
+    enabled: bool,
+    service: RwLock<Option<K8sRedisService>>,
+}
+
+impl KubernetesClient {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            service: RwLock::new(None),
+        }
+    }
+
+    pub fn service(&self) -> Result<K8sRedisService, K8sUnavailable> {
+        if !self.enabled {
+            return Err(K8sUnavailable::Disabled);
+        }
+        self.service.read().unwrap().clone().ok_or(K8sUnavailable::Connecting)
+    }
+
+    /// Retry connecting to the API server with exponential backoff until it succeeds
+    pub async fn connect(self: Arc<Self>, initial_backoff: Duration, max_backoff: Duration) {
+        if !self.enabled {
+            tracing::info!("Kubernetes integration disabled; running in local-only mode");
+            return;
+        }
+
+        let mut backoff = initial_backoff;
+        loop {
+            match K8sRedisService::new().await {
+                Ok(service) => match service.check_connectivity().await {
+                    Ok(version) => {
+                        tracing::info!("Connected to Kubernetes API server {}", version);
+                        *self.service.write().unwrap() = Some(service);
+                        return;
+                    }
+                    Err(e) => tracing::warn!("Kubernetes API server unreachable, retrying in {:?}: {}", backoff, e),
+                },
+                Err(e) => tracing::warn!("Kubernetes client unavailable, retrying in {:?}: {}", backoff, e),
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(max_backoff);
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct RedisDeploymentConfig {
     pub name: String,
@@ -26,6 +151,201 @@ pub struct RedisDeploymentConfig {
     pub max_memory: i64,
     pub redis_password: String,
     pub port: i32,
+    pub max_connections: i32,
+    pub eviction_policy: String,
+    pub domain: String,
+    pub tls_enabled: bool, // Serve from a `redis-{slug}-tls` Secret with tls.crt, tls.key and ca.crt, issued by tls_issuer
+    pub tls_issuer: TlsIssuer,
+    pub acl_users: Vec<(AclUser, String)>, // ACL users to provision, with their passwords
+    pub pod_security: PodSecurityConfig,
+}
+
+/// Labels on every resource of an instance. In a shared namespace, `organization-id` is what
+/// tells organizations' resources apart.
+fn instance_labels(config: &RedisDeploymentConfig) -> BTreeMap<String, String> {
+    let mut labels = BTreeMap::new();
+    labels.insert("app".to_string(), format!("redis-{}", config.slug));
+    labels.insert("created-by".to_string(), "redisgate".to_string());
+    labels.insert("organization-id".to_string(), config.organization_id.to_string());
+    labels.insert("instance-id".to_string(), config.instance_id.to_string());
+    labels
+}
+
+// cert-manager Certificate resource, which is not part of k8s-openapi
+fn certificate_resource() -> ApiResource {
+    ApiResource::from_gvk(&GroupVersionKind::gvk("cert-manager.io", "v1", "Certificate"))
+}
+
+/// cert-manager Certificate for a TLS-enabled instance. cert-manager writes the key pair and CA
+/// to the `redis-{slug}-tls` Secret the pod mounts, for the instance's domain and its in-cluster
+/// Service names.
+pub(crate) fn tls_certificate(config: &RedisDeploymentConfig) -> DynamicObject {
+    let service_name = format!("redis-{}-service", config.slug);
+    let mut certificate = DynamicObject::new(&format!("redis-{}-tls", config.slug), &certificate_resource())
+        .within(&config.namespace)
+        .data(json!({
+            "spec": {
+                "secretName": format!("redis-{}-tls", config.slug),
+                "secretTemplate": { "labels": instance_labels(config) },
+                "dnsNames": [
+                    config.domain,
+                    service_name,
+                    format!("{}.{}.svc", service_name, config.namespace),
+                    format!("{}.{}.svc.cluster.local", service_name, config.namespace),
+                ],
+                "usages": ["server auth"],
+                "issuerRef": {
+                    "name": config.tls_issuer.name,
+                    "kind": config.tls_issuer.kind,
+                    "group": "cert-manager.io",
+                },
+            }
+        }));
+    certificate.metadata.labels = Some(instance_labels(config));
+    certificate
+}
+
+/// Pod- and container-level security contexts. Privilege escalation is always off and the
+/// runtime's default seccomp profile applies, as the restricted PodSecurity standard requires.
+pub(crate) fn security_contexts(security: &PodSecurityConfig) -> (PodSecurityContext, SecurityContext) {
+    let pod = PodSecurityContext {
+        run_as_non_root: Some(security.run_as_non_root),
+        run_as_user: Some(security.run_as_user),
+        run_as_group: Some(security.fs_group),
+        fs_group: Some(security.fs_group),
+        seccomp_profile: Some(SeccompProfile {
+            type_: "RuntimeDefault".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let container = SecurityContext {
+        allow_privilege_escalation: Some(false),
+        capabilities: security.drop_all_capabilities.then(|| Capabilities {
+            drop: Some(vec!["ALL".to_string()]),
+            ..Default::default()
+        }),
+        read_only_root_filesystem: Some(security.read_only_root_filesystem),
+        ..Default::default()
+    };
+    (pod, container)
+}
+
+/// Volumes of the Redis pod and their mounts in the container: the TLS Secret, and a writable
+/// data directory when the root filesystem is read-only
+pub(crate) fn instance_volumes(config: &RedisDeploymentConfig) -> (Vec<Volume>, Vec<VolumeMount>) {
+    let mut volumes = Vec::new();
+    let mut mounts = Vec::new();
+    if config.tls_enabled {
+        volumes.push(Volume {
+            name: "tls".to_string(),
+            secret: Some(SecretVolumeSource {
+                secret_name: Some(format!("redis-{}-tls", config.slug)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        mounts.push(VolumeMount {
+            name: "tls".to_string(),
+            mount_path: REDIS_TLS_DIR.to_string(),
+            read_only: Some(true),
+            ..Default::default()
+        });
+    }
+    if config.pod_security.read_only_root_filesystem {
+        volumes.push(Volume {
+            name: "data".to_string(),
+            empty_dir: Some(EmptyDirVolumeSource::default()),
+            ..Default::default()
+        });
+        mounts.push(VolumeMount {
+            name: "data".to_string(),
+            mount_path: REDIS_DATA_DIR.to_string(),
+            ..Default::default()
+        });
+    }
+    (volumes, mounts)
+}
+
+// Most recent events returned with pod diagnostics
+const MAX_POD_EVENTS: usize = 10;
+
+/// Why an instance's pod is (not) running, for troubleshooting provisioning problems
+#[derive(Debug, Serialize)]
+pub struct PodDiagnostics {
+    pub pod_name: String,
+    pub phase: Option<String>,
+    pub conditions: Vec<PodConditionSummary>,
+    pub waiting: Vec<ContainerWaiting>, // Containers not started yet, e.g. ImagePullBackOff
+    pub events: Vec<PodEventSummary>,   // Newest first
+}
+
+#[derive(Debug, Serialize)]
+pub struct PodConditionSummary {
+    #[serde(rename = "type")]
+    pub condition_type: String,
+    pub status: String,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContainerWaiting {
+    pub container: String,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PodEventSummary {
+    #[serde(rename = "type")]
+    pub event_type: Option<String>, // Normal or Warning
+    pub reason: Option<String>,
+    pub message: Option<String>,
+    pub count: Option<i32>,
+    pub last_seen: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl PodDiagnostics {
+    /// Best explanation of why the pod is not ready: a waiting container's reason, an
+    /// unschedulable condition, or the latest Warning event
+    pub fn failure_reason(&self) -> Option<String> {
+        let waiting = self
+            .waiting
+            .iter()
+            .find_map(|waiting| Some(describe_reason(waiting.reason.as_deref()?, waiting.message.as_deref())));
+        let unschedulable = || {
+            self.conditions
+                .iter()
+                .find(|condition| condition.condition_type == "PodScheduled" && condition.status == "False")
+                .map(|condition| {
+                    describe_reason(condition.reason.as_deref().unwrap_or("Unschedulable"), condition.message.as_deref())
+                })
+        };
+        let warning = || {
+            self.events
+                .iter()
+                .find(|event| event.event_type.as_deref() == Some("Warning"))
+                .map(|event| describe_reason(event.reason.as_deref().unwrap_or("Warning"), event.message.as_deref()))
+        };
+        waiting.or_else(unschedulable).or_else(warning)
+    }
+}
+
+// "Reason: message", as kubectl shows pod problems
+pub(crate) fn describe_reason(reason: &str, message: Option<&str>) -> String {
+    match message.filter(|message| !message.is_empty()) {
+        Some(message) => format!("{}: {}", reason, message),
+        None => reason.to_string(),
+    }
+}
+
+/// A deployment labeled `created-by=redisgate`
+#[derive(Debug)]
+pub struct ManagedDeployment {
+    pub namespace: String,
+    pub name: String,
+    pub instance_id: Option<Uuid>, // From the `instance-id` label, when present and valid
 }
 
 #[derive(Debug)]
@@ -52,7 +372,7 @@ impl K8sRedisService {
         let deployment_name = format!("redis-{}", config.slug);
         let service_name = format!("redis-{}-service", config.slug);
         let ingress_name = format!("redis-{}-ingress", config.slug);
-        let domain = format!("{}.{}.redis.local", config.slug, config.organization_id.simple());
+        let domain = config.domain.clone();
 
         // Create namespace if it doesn't exist
         self.ensure_namespace(&config.namespace).await?;
@@ -60,6 +380,11 @@ impl K8sRedisService {
         // Create Redis secret for password
         self.create_redis_secret(&config).await?;
 
+        // Request the TLS certificate; the pod starts once cert-manager has issued it
+        if config.tls_enabled {
+            self.create_redis_certificate(&config).await?;
+        }
+
         // Create Redis deployment
         self.create_redis_deployment(&config).await?;
 
@@ -79,11 +404,16 @@ impl K8sRedisService {
         })
     }
 
-    /// Delete a Redis deployment and all related resources
+    /// Delete a Redis deployment and all related resources.
+    ///
+    /// Persistent volumes and backup CronJobs are only removed when requested, so data
+    /// can be kept around for the restore window.
     pub async fn delete_redis_instance(
         &self,
         namespace: &str,
         slug: &str,
+        delete_volumes: bool,
+        delete_backup_jobs: bool,
     ) -> Result<(), KubeError> {
         let deployment_name = format!("redis-{}", slug);
         let service_name = format!("redis-{}-service", slug);
@@ -106,9 +436,167 @@ impl K8sRedisService {
         let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
         let _ = secrets.delete(&secret_name, &Default::default()).await;
 
+        // Delete the TLS certificate, and its Secret, which cert-manager leaves behind
+        let tls_name = format!("redis-{}-tls", slug);
+        let certificates: Api<DynamicObject> =
+            Api::namespaced_with(self.client.clone(), namespace, &certificate_resource());
+        let _ = certificates.delete(&tls_name, &Default::default()).await;
+        let _ = secrets.delete(&tls_name, &Default::default()).await;
+
+        let instance_selector = ListParams::default().labels(&format!("app=redis-{}", slug));
+
+        // Delete backup jobs
+        if delete_backup_jobs {
+            let cron_jobs: Api<CronJob> = Api::namespaced(self.client.clone(), namespace);
+            cron_jobs
+                .delete_collection(&DeleteParams::default(), &instance_selector)
+                .await?;
+        }
+
+        // Delete persistent volume claims
+        if delete_volumes {
+            let volume_claims: Api<PersistentVolumeClaim> = Api::namespaced(self.client.clone(), namespace);
+            volume_claims
+                .delete_collection(&DeleteParams::default(), &instance_selector)
+                .await?;
+        }
+
         Ok(())
     }
 
+    /// Delete the persistent volume claims and backup CronJobs kept for a deleted instance.
+    ///
+    /// Only resources labeled `created-by=redisgate` with the instance's id are selected, so a
+    /// newer instance that reused the slug keeps its own.
+    pub async fn purge_retained_resources(&self, namespace: &str, instance_id: Uuid) -> Result<(), KubeError> {
+        let selector = ListParams::default().labels(&format!("created-by=redisgate,instance-id={}", instance_id));
+
+        let cron_jobs: Api<CronJob> = Api::namespaced(self.client.clone(), namespace);
+        cron_jobs.delete_collection(&DeleteParams::default(), &selector).await?;
+
+        let volume_claims: Api<PersistentVolumeClaim> = Api::namespaced(self.client.clone(), namespace);
+        volume_claims.delete_collection(&DeleteParams::default(), &selector).await?;
+
+        Ok(())
+    }
+
+    /// Password of the instance's default user, from the instance secret
+    pub async fn redis_password(&self, namespace: &str, slug: &str) -> Result<Option<String>, KubeError> {
+        let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+        let secret = secrets.get(&format!("redis-{}-secret", slug)).await?;
+        Ok(secret
+            .data
+            .and_then(|mut data| data.remove("redis-password"))
+            .and_then(|password| String::from_utf8(password.0).ok()))
+    }
+
+    /// Replace the password stored in the instance secret and roll the deployment.
+    ///
+    /// The rolling restart starts a new pod with the new password before the old one
+    /// is terminated, so clients can reconnect without a gap in availability.
+    pub async fn rotate_redis_password(
+        &self,
+        namespace: &str,
+        slug: &str,
+        new_password: &str,
+    ) -> Result<(), KubeError> {
+        let deployment_name = format!("redis-{}", slug);
+        let secret_name = format!("redis-{}-secret", slug);
+
+        // Update secret
+        let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+        let patch = serde_json::json!({
+            "stringData": { "redis-password": new_password }
+        });
+        secrets
+            .patch(&secret_name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await?;
+
+        // Restart pods so they pick up the new password
+        let deployments: Api<Deployment> = Api::namespaced(self.client.clone(), namespace);
+        deployments.restart(&deployment_name).await?;
+
+        Ok(())
+    }
+
+    /// Trigger a rolling restart by stamping the pod template with a restart annotation,
+    /// the same change `kubectl rollout restart` makes.
+    pub async fn restart(&self, namespace: &str, slug: &str) -> Result<(), KubeError> {
+        let deployment_name = format!("redis-{}", slug);
+        let deployments: Api<Deployment> = Api::namespaced(self.client.clone(), namespace);
+        let patch = serde_json::json!({
+            "spec": {
+                "template": {
+                    "metadata": {
+                        "annotations": { "kubectl.kubernetes.io/restartedAt": chrono::Utc::now().to_rfc3339() }
+                    }
+                }
+            }
+        });
+        deployments
+            .patch(&deployment_name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the latest rollout has finished: every replica runs the current pod
+    /// template, is ready, and the old pods are gone.
+    pub async fn rollout_complete(&self, namespace: &str, slug: &str) -> Result<bool, KubeError> {
+        let deployment_name = format!("redis-{}", slug);
+        let deployments: Api<Deployment> = Api::namespaced(self.client.clone(), namespace);
+        let deployment = deployments.get(&deployment_name).await?;
+
+        let generation = deployment.metadata.generation.unwrap_or(0);
+        let desired = deployment.spec.and_then(|spec| spec.replicas).unwrap_or(1);
+        Ok(deployment.status.is_some_and(|status| {
+            status.observed_generation.unwrap_or(0) >= generation
+                && status.updated_replicas.unwrap_or(0) >= desired
+                && status.ready_replicas.unwrap_or(0) >= desired
+                && status.replicas.unwrap_or(0) == desired
+        }))
+    }
+
+    /// Stream the logs of an instance's newest pod, or `None` when it has no pod.
+    ///
+    /// With `previous`, the logs come from the last terminated container, which is what
+    /// explains a crash loop.
+    pub async fn pod_logs(
+        &self,
+        namespace: &str,
+        slug: &str,
+        tail_lines: i64,
+        follow: bool,
+        previous: bool,
+    ) -> Result<Option<impl AsyncBufRead>, KubeError> {
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+        let list = pods
+            .list(&ListParams::default().labels(&format!("app=redis-{}", slug)))
+            .await?;
+
+        let Some(pod_name) = list
+            .items
+            .into_iter()
+            .max_by(|a, b| a.metadata.creation_timestamp.cmp(&b.metadata.creation_timestamp))
+            .and_then(|pod| pod.metadata.name)
+        else {
+            return Ok(None);
+        };
+
+        let params = LogParams {
+            tail_lines: Some(tail_lines),
+            follow,
+            previous,
+            ..Default::default()
+        };
+        Ok(Some(pods.log_stream(&pod_name, &params).await?))
+    }
+
+    /// Lightweight API server round trip used by readiness checks
+    pub async fn check_connectivity(&self) -> Result<String, KubeError> {
+        let version = self.client.apiserver_version().await?;
+        Ok(version.git_version)
+    }
+
     /// Check deployment status
     pub async fn get_deployment_status(
         &self,
@@ -138,6 +626,103 @@ impl K8sRedisService {
         }
     }
 
+    /// Conditions, container states and recent events of an instance's newest pod,
+    /// or None when the deployment has no pod
+    pub async fn pod_diagnostics(&self, namespace: &str, slug: &str) -> Result<Option<PodDiagnostics>, KubeError> {
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+        let list = pods
+            .list(&ListParams::default().labels(&format!("app=redis-{}", slug)))
+            .await?;
+
+        let Some(pod) = list
+            .items
+            .into_iter()
+            .max_by(|a, b| a.metadata.creation_timestamp.cmp(&b.metadata.creation_timestamp))
+        else {
+            return Ok(None);
+        };
+        let pod_name = pod.metadata.name.unwrap_or_default();
+        let status = pod.status.unwrap_or_default();
+
+        let conditions = status
+            .conditions
+            .unwrap_or_default()
+            .into_iter()
+            .map(|condition| PodConditionSummary {
+                condition_type: condition.type_,
+                status: condition.status,
+                reason: condition.reason,
+                message: condition.message,
+            })
+            .collect();
+
+        let waiting = status
+            .container_statuses
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|container| {
+                let waiting = container.state?.waiting?;
+                Some(ContainerWaiting {
+                    container: container.name,
+                    reason: waiting.reason,
+                    message: waiting.message,
+                })
+            })
+            .collect();
+
+        let events: Api<Event> = Api::namespaced(self.client.clone(), namespace);
+        let mut events: Vec<Event> = events
+            .list(&ListParams::default().fields(&format!("involvedObject.kind=Pod,involvedObject.name={}", pod_name)))
+            .await?
+            .items;
+        events.sort_by(|a, b| b.last_timestamp.cmp(&a.last_timestamp));
+        let events = events
+            .into_iter()
+            .take(MAX_POD_EVENTS)
+            .map(|event| PodEventSummary {
+                event_type: event.type_,
+                reason: event.reason,
+                message: event.message,
+                count: event.count,
+                last_seen: event.last_timestamp.map(|time| time.0),
+            })
+            .collect();
+
+        Ok(Some(PodDiagnostics {
+            pod_name,
+            phase: status.phase,
+            conditions,
+            waiting,
+            events,
+        }))
+    }
+
+    /// List the deployments RedisGate created, across all namespaces
+    pub async fn list_managed_deployments(&self) -> Result<Vec<ManagedDeployment>, KubeError> {
+        let deployments: Api<Deployment> = Api::all(self.client.clone());
+        let list = deployments
+            .list(&ListParams::default().labels("created-by=redisgate"))
+            .await?;
+
+        Ok(list
+            .items
+            .into_iter()
+            .map(|deployment| {
+                let instance_id = deployment
+                    .metadata
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get("instance-id"))
+                    .and_then(|id| id.parse().ok());
+                ManagedDeployment {
+                    namespace: deployment.metadata.namespace.unwrap_or_default(),
+                    name: deployment.metadata.name.unwrap_or_default(),
+                    instance_id,
+                }
+            })
+            .collect())
+    }
+
     async fn ensure_namespace(&self, namespace: &str) -> Result<(), KubeError> {
         use k8s_openapi::api::core::v1::Namespace;
         
@@ -172,18 +757,15 @@ impl K8sRedisService {
 
         let mut string_data = BTreeMap::new();
         string_data.insert("redis-password".to_string(), config.redis_password.clone());
+        for (user, password) in &config.acl_users {
+            string_data.insert(user.secret_key(), password.clone());
+        }
 
         let secret = Secret {
             metadata: ObjectMeta {
                 name: Some(secret_name),
                 namespace: Some(config.namespace.clone()),
-                labels: Some({
-                    let mut labels = BTreeMap::new();
-                    labels.insert("app".to_string(), format!("redis-{}", config.slug));
-                    labels.insert("created-by".to_string(), "redisgate".to_string());
-                    labels.insert("instance-id".to_string(), config.instance_id.to_string());
-                    labels
-                }),
+                labels: Some(instance_labels(config)),
                 ..Default::default()
             },
             string_data: Some(string_data),
@@ -194,11 +776,21 @@ impl K8sRedisService {
         Ok(())
     }
 
+    async fn create_redis_certificate(&self, config: &RedisDeploymentConfig) -> Result<(), KubeError> {
+        let certificates: Api<DynamicObject> =
+            Api::namespaced_with(self.client.clone(), &config.namespace, &certificate_resource());
+        certificates.create(&Default::default(), &tls_certificate(config)).await?;
+        Ok(())
+    }
+
     async fn create_redis_deployment(&self, config: &RedisDeploymentConfig) -> Result<(), KubeError> {
         let deployment_name = format!("redis-{}", config.slug);
         let secret_name = format!("redis-{}-secret", config.slug);
         let deployments: Api<Deployment> = Api::namespaced(self.client.clone(), &config.namespace);
 
+        let (pod_security_context, container_security_context) = security_contexts(&config.pod_security);
+        let (volumes, volume_mounts) = instance_volumes(config);
+
         let memory_limit = format!("{}Mi", config.max_memory / (1024 * 1024)); // Convert bytes to Mi
         let memory_request = format!("{}Mi", std::cmp::max(64, config.max_memory / (1024 * 1024) / 2)); // At least 64Mi, half of limit
 
@@ -206,13 +798,7 @@ impl K8sRedisService {
             metadata: ObjectMeta {
                 name: Some(deployment_name.clone()),
                 namespace: Some(config.namespace.clone()),
-                labels: Some({
-                    let mut labels = BTreeMap::new();
-                    labels.insert("app".to_string(), format!("redis-{}", config.slug));
-                    labels.insert("created-by".to_string(), "redisgate".to_string());
-                    labels.insert("instance-id".to_string(), config.instance_id.to_string());
-                    labels
-                }),
+                labels: Some(instance_labels(config)),
                 ..Default::default()
             },
             spec: Some(DeploymentSpec {
@@ -231,6 +817,7 @@ impl K8sRedisService {
                             let mut labels = BTreeMap::new();
                             labels.insert("app".to_string(), format!("redis-{}", config.slug));
                             labels.insert("created-by".to_string(), "redisgate".to_string());
+                            labels.insert("organization-id".to_string(), config.organization_id.to_string());
                             labels
                         }),
                         ..Default::default()
@@ -245,29 +832,26 @@ impl K8sRedisService {
                                 protocol: Some("TCP".to_string()),
                                 ..Default::default()
                             }]),
-                            env: Some(vec![
-                                EnvVar {
-                                    name: "REDIS_PASSWORD".to_string(),
-                                    value_from: Some(k8s_openapi::api::core::v1::EnvVarSource {
-                                        secret_key_ref: Some(k8s_openapi::api::core::v1::SecretKeySelector {
-                                            name: Some(secret_name),
-                                            key: "redis-password".to_string(),
-                                            optional: Some(false),
+                            env: Some(
+                                std::iter::once(("REDIS_PASSWORD".to_string(), "redis-password".to_string()))
+                                    .chain(config.acl_users.iter().map(|(user, _)| (user.env_var(), user.secret_key())))
+                                    .map(|(name, key)| EnvVar {
+                                        name,
+                                        value_from: Some(k8s_openapi::api::core::v1::EnvVarSource {
+                                            secret_key_ref: Some(k8s_openapi::api::core::v1::SecretKeySelector {
+                                                name: Some(secret_name.clone()),
+                                                key,
+                                                optional: Some(false),
+                                            }),
+                                            ..Default::default()
                                         }),
                                         ..Default::default()
-                                    }),
-                                    ..Default::default()
-                                },
-                            ]),
-                            command: Some(vec![
-                                "redis-server".to_string(),
-                                "--requirepass".to_string(),
-                                "$(REDIS_PASSWORD)".to_string(),
-                                "--maxmemory".to_string(),
-                                format!("{}b", config.max_memory),
-                                "--maxmemory-policy".to_string(),
-                                "allkeys-lru".to_string(),
-                            ]),
+                                    })
+                                    .collect(),
+                            ),
+                            command: Some(redis_server_command(config)),
+                            volume_mounts: (!volume_mounts.is_empty()).then_some(volume_mounts),
+                            security_context: Some(container_security_context),
                             resources: Some(k8s_openapi::api::core::v1::ResourceRequirements {
                                 limits: Some({
                                     let mut limits = BTreeMap::new();
@@ -285,6 +869,8 @@ impl K8sRedisService {
                             }),
                             ..Default::default()
                         }],
+                        volumes: (!volumes.is_empty()).then_some(volumes),
+                        security_context: Some(pod_security_context),
                         ..Default::default()
                     }),
                 },
@@ -305,13 +891,7 @@ impl K8sRedisService {
             metadata: ObjectMeta {
                 name: Some(service_name.clone()),
                 namespace: Some(config.namespace.clone()),
-                labels: Some({
-                    let mut labels = BTreeMap::new();
-                    labels.insert("app".to_string(), format!("redis-{}", config.slug));
-                    labels.insert("created-by".to_string(), "redisgate".to_string());
-                    labels.insert("instance-id".to_string(), config.instance_id.to_string());
-                    labels
-                }),
+                labels: Some(instance_labels(config)),
                 ..Default::default()
             },
             spec: Some(ServiceSpec {
@@ -340,7 +920,6 @@ impl K8sRedisService {
     async fn create_redis_ingress(&self, config: &RedisDeploymentConfig) -> Result<(), KubeError> {
         let ingress_name = format!("redis-{}-ingress", config.slug);
         let service_name = format!("redis-{}-service", config.slug);
-        let domain = format!("{}.{}.redis.local", config.slug, config.organization_id.simple());
         
         let ingresses: Api<Ingress> = Api::namespaced(self.client.clone(), &config.namespace);
 
@@ -348,13 +927,7 @@ impl K8sRedisService {
             metadata: ObjectMeta {
                 name: Some(ingress_name.clone()),
                 namespace: Some(config.namespace.clone()),
-                labels: Some({
-                    let mut labels = BTreeMap::new();
-                    labels.insert("app".to_string(), format!("redis-{}", config.slug));
-                    labels.insert("created-by".to_string(), "redisgate".to_string());
-                    labels.insert("instance-id".to_string(), config.instance_id.to_string());
-                    labels
-                }),
+                labels: Some(instance_labels(config)),
                 annotations: Some({
                     let mut annotations = BTreeMap::new();
                     annotations.insert("nginx.ingress.kubernetes.io/tcp-services-configmap".to_string(), 
@@ -366,7 +939,7 @@ impl K8sRedisService {
             },
             spec: Some(IngressSpec {
                 rules: Some(vec![IngressRule {
-                    host: Some(domain.clone()),
+                    host: Some(config.domain.clone()),
                     http: Some(HTTPIngressRuleValue {
                         paths: vec![HTTPIngressPath {
                             path: Some("/".to_string()),
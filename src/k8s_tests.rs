@@ -4,7 +4,11 @@ use uuid::Uuid;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::k8s_service::{K8sRedisService, RedisDeploymentConfig, K8sDeploymentResult};
+    use crate::config::{PodSecurityConfig, TlsIssuer};
+    use crate::k8s_service::{
+        describe_reason, instance_volumes, redis_server_command, security_contexts, tls_certificate, ContainerWaiting, K8sRedisService, PodConditionSummary, PodDiagnostics,
+        PodEventSummary, RedisDeploymentConfig, K8sDeploymentResult,
+    };
 
     #[tokio::test]
     async fn test_k8s_service_initialization() {
@@ -44,6 +48,13 @@ mod tests {
             max_memory: 1024 * 1024 * 100, // 100MB
             redis_password: "test-password".to_string(),
             port: 6379,
+            max_connections: 100,
+            eviction_policy: "allkeys-lru".to_string(),
+            domain: "test-redis.test-org.redis.local".to_string(),
+            tls_enabled: false,
+            tls_issuer: TlsIssuer::default(),
+            acl_users: Vec::new(),
+            pod_security: PodSecurityConfig::default(),
         };
 
         assert_eq!(config.name, "test-redis");
@@ -52,6 +63,120 @@ mod tests {
         println!("✅ RedisDeploymentConfig creation test passed");
     }
 
+    #[test]
+    fn test_redis_server_command_tls() {
+        let mut config = RedisDeploymentConfig {
+            name: "test-redis".to_string(),
+            slug: "test-redis".to_string(),
+            namespace: "test-namespace".to_string(),
+            organization_id: Uuid::new_v4(),
+            instance_id: Uuid::new_v4(),
+            redis_version: "7.2".to_string(),
+            max_memory: 1024 * 1024 * 100, // 100MB
+            redis_password: "test-password".to_string(),
+            port: 6379,
+            max_connections: 100,
+            eviction_policy: "allkeys-lru".to_string(),
+            domain: "test-redis.test-org.redis.local".to_string(),
+            tls_enabled: false,
+            tls_issuer: TlsIssuer::default(),
+            acl_users: Vec::new(),
+            pod_security: PodSecurityConfig::default(),
+        };
+        assert!(!redis_server_command(&config).contains(&"--tls-port".to_string()));
+
+        config.tls_enabled = true;
+        let command = redis_server_command(&config).join(" ");
+        assert!(command.contains("--port 0 --tls-port 6379"));
+        assert!(command.contains("--tls-cert-file /etc/redis/tls/tls.crt"));
+        println!("✅ Redis TLS command test passed");
+    }
+
+    #[test]
+    fn test_tls_certificate() {
+        let config = RedisDeploymentConfig {
+            name: "test-redis".to_string(),
+            slug: "test-redis".to_string(),
+            namespace: "test-namespace".to_string(),
+            organization_id: Uuid::new_v4(),
+            instance_id: Uuid::new_v4(),
+            redis_version: "7.2".to_string(),
+            max_memory: 1024 * 1024 * 100, // 100MB
+            redis_password: "test-password".to_string(),
+            port: 6379,
+            max_connections: 100,
+            eviction_policy: "allkeys-lru".to_string(),
+            domain: "test-redis.test-org.redis.local".to_string(),
+            tls_enabled: true,
+            tls_issuer: TlsIssuer::default(),
+            acl_users: Vec::new(),
+            pod_security: PodSecurityConfig::default(),
+        };
+
+        let certificate = tls_certificate(&config);
+        assert_eq!(certificate.metadata.namespace.as_deref(), Some("test-namespace"));
+        let spec = &certificate.data["spec"];
+        // The pod mounts the Secret cert-manager writes
+        assert_eq!(spec["secretName"], "redis-test-redis-tls");
+        assert_eq!(spec["issuerRef"]["name"], "redisgate-ca");
+        assert_eq!(spec["issuerRef"]["kind"], "ClusterIssuer");
+        let dns_names = spec["dnsNames"].as_array().unwrap();
+        assert!(dns_names.contains(&serde_json::json!("test-redis.test-org.redis.local")));
+        assert!(dns_names.contains(&serde_json::json!("redis-test-redis-service.test-namespace.svc")));
+        println!("✅ TLS certificate test passed");
+    }
+
+    #[test]
+    fn test_pod_security_context() {
+        let mut config = RedisDeploymentConfig {
+            name: "test-redis".to_string(),
+            slug: "test-redis".to_string(),
+            namespace: "test-namespace".to_string(),
+            organization_id: Uuid::new_v4(),
+            instance_id: Uuid::new_v4(),
+            redis_version: "7.2".to_string(),
+            max_memory: 1024 * 1024 * 100, // 100MB
+            redis_password: "test-password".to_string(),
+            port: 6379,
+            max_connections: 100,
+            eviction_policy: "allkeys-lru".to_string(),
+            domain: "test-redis.test-org.redis.local".to_string(),
+            tls_enabled: true,
+            tls_issuer: TlsIssuer::default(),
+            acl_users: Vec::new(),
+            pod_security: PodSecurityConfig {
+                run_as_non_root: true,
+                run_as_user: 1000,
+                fs_group: 2000,
+                drop_all_capabilities: true,
+                read_only_root_filesystem: true,
+            },
+        };
+
+        let (pod, container) = security_contexts(&config.pod_security);
+        assert_eq!(pod.run_as_non_root, Some(true));
+        assert_eq!(pod.run_as_user, Some(1000));
+        assert_eq!(pod.fs_group, Some(2000));
+        assert_eq!(container.allow_privilege_escalation, Some(false));
+        assert_eq!(container.capabilities.unwrap().drop, Some(vec!["ALL".to_string()]));
+        assert_eq!(container.read_only_root_filesystem, Some(true));
+
+        // The data directory stays writable under a read-only root filesystem
+        let (volumes, mounts) = instance_volumes(&config);
+        let names: Vec<&str> = volumes.iter().map(|volume| volume.name.as_str()).collect();
+        assert_eq!(names, vec!["tls", "data"]);
+        assert!(volumes[1].empty_dir.is_some());
+        assert_eq!(mounts[1].mount_path, "/data");
+
+        config.tls_enabled = false;
+        config.pod_security.read_only_root_filesystem = false;
+        config.pod_security.drop_all_capabilities = false;
+        assert!(security_contexts(&config.pod_security).1.capabilities.is_none());
+        let (volumes, mounts) = instance_volumes(&config);
+        assert!(volumes.is_empty() && mounts.is_empty());
+        println!("✅ Pod security context test passed");
+    }
+
     #[test]
     fn test_deployment_result_structure() {
         let result = K8sDeploymentResult {
@@ -68,4 +193,51 @@ mod tests {
         assert_eq!(result.port, 6379);
         println!("✅ K8sDeploymentResult structure test passed");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_describe_reason() {
+        assert_eq!(
+            describe_reason("ImagePullBackOff", Some("Back-off pulling image \"redis:nope\"")),
+            "ImagePullBackOff: Back-off pulling image \"redis:nope\""
+        );
+        assert_eq!(describe_reason("Unschedulable", Some("")), "Unschedulable");
+        assert_eq!(describe_reason("Unschedulable", None), "Unschedulable");
+    }
+
+    #[test]
+    fn test_pod_diagnostics_failure_reason() {
+        let mut diagnostics = PodDiagnostics {
+            pod_name: "redis-test-abc".to_string(),
+            phase: Some("Pending".to_string()),
+            conditions: vec![PodConditionSummary {
+                condition_type: "PodScheduled".to_string(),
+                status: "False".to_string(),
+                reason: Some("Unschedulable".to_string()),
+                message: Some("0/3 nodes are available: 3 Insufficient memory.".to_string()),
+            }],
+            waiting: vec![],
+            events: vec![PodEventSummary {
+                event_type: Some("Warning".to_string()),
+                reason: Some("FailedScheduling".to_string()),
+                message: None,
+                count: Some(4),
+                last_seen: None,
+            }],
+        };
+        assert_eq!(
+            diagnostics.failure_reason().as_deref(),
+            Some("Unschedulable: 0/3 nodes are available: 3 Insufficient memory.")
+        );
+
+        diagnostics.waiting.push(ContainerWaiting {
+            container: "redis".to_string(),
+            reason: Some("ImagePullBackOff".to_string()),
+            message: None,
+        });
+        assert_eq!(diagnostics.failure_reason().as_deref(), Some("ImagePullBackOff"));
+
+        diagnostics.waiting.clear();
+        diagnostics.conditions.clear();
+        assert_eq!(diagnostics.failure_reason().as_deref(), Some("FailedScheduling"));
+    }
+}
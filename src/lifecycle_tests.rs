@@ -0,0 +1,244 @@
+// End-to-end instance lifecycle test against a real cluster and database.
+//
+// Built only with `--features kind-tests`, and needs:
+// - a kind or k3d cluster as the current kubeconfig context, with `kubectl` on PATH
+// - a disposable Postgres in TEST_DATABASE_URL; migrations are applied to it
+// - local port 6379 free, since the gateway reaches instances on 127.0.0.1:6379 and the
+//   test port-forwards the instance service there
+//
+//   cargo test --features kind-tests lifecycle -- --nocapture
+
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Json;
+use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::core::v1::{Secret, Service};
+use kube::Api;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::process::{Child, Command};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::api_models::{ApiResponse, CreateRedisInstanceRequest, DeleteRedisInstanceParams};
+use crate::auth::{ApiKeyClaims, JwtManager};
+use crate::config::{AppConfig, KubernetesConfig, RedisHandlerConfig};
+use crate::handlers::redis::handle_generic_command;
+use crate::handlers::redis_instances;
+use crate::middleware::{AppState, CurrentUser};
+
+const READY_TIMEOUT: Duration = Duration::from_secs(180);
+const CLEANUP_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+type HandlerResult<T> = Result<Json<ApiResponse<T>>, (StatusCode, Json<ApiResponse<()>>)>;
+
+fn data<T>(result: HandlerResult<T>) -> T {
+    match result {
+        Ok(Json(response)) => response.data.expect("successful responses carry data"),
+        Err((status, Json(error))) => panic!("handler failed with {}: {:?}", status, error.message),
+    }
+}
+
+// A user owning a fresh organization
+async fn create_owner(pool: &PgPool) -> (CurrentUser, Uuid) {
+    let suffix = Uuid::new_v4().simple().to_string()[..8].to_string();
+    let user_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO users (email, username, password_hash) VALUES ($1, $2, 'unused') RETURNING id",
+    )
+    .bind(format!("lifecycle-{}@example.com", suffix))
+    .bind(format!("lifecycle-{}", suffix))
+    .fetch_one(pool)
+    .await
+    .expect("insert user");
+    let org_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO organizations (name, slug, owner_id) VALUES ($1, $1, $2) RETURNING id",
+    )
+    .bind(format!("lifecycle-{}", suffix))
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .expect("insert organization");
+    sqlx::query("INSERT INTO organization_memberships (user_id, organization_id, role, joined_at) VALUES ($1, $2, 'owner', NOW())")
+        .bind(user_id)
+        .bind(org_id)
+        .execute(pool)
+        .await
+        .expect("insert membership");
+
+    let user = CurrentUser {
+        id: user_id,
+        email: format!("lifecycle-{}@example.com", suffix),
+        username: format!("lifecycle-{}", suffix),
+        org_id: Some(org_id),
+    };
+    (user, org_id)
+}
+
+async fn test_state() -> Arc<AppState> {
+    let database_url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a disposable database");
+    let pool = PgPool::connect(&database_url).await.expect("connect to the test database");
+    crate::MIGRATOR.run(&pool).await.expect("run migrations");
+
+    // ACL users let the gateway authenticate to the instance, which runs with requirepass
+    let config = AppConfig {
+        redis: RedisHandlerConfig {
+            acl_secret: Some("kind-tests-acl-secret".to_string()),
+            ..RedisHandlerConfig::from_env()
+        },
+        kubernetes: KubernetesConfig {
+            enabled: true,
+            ..KubernetesConfig::default()
+        },
+        ..AppConfig::from_env()
+    };
+    let state = Arc::new(AppState::new(pool, JwtManager::new("kind-tests-jwt-secret"), config));
+
+    tokio::time::timeout(
+        Duration::from_secs(30),
+        state.kubernetes.clone().connect(Duration::from_secs(1), Duration::from_secs(5)),
+    )
+    .await
+    .expect("the cluster in the current kubeconfig context is reachable");
+    state
+}
+
+async fn resources_exist(client: &kube::Client, namespace: &str, slug: &str) -> [bool; 3] {
+    let deployments: Api<Deployment> = Api::namespaced(client.clone(), namespace);
+    let services: Api<Service> = Api::namespaced(client.clone(), namespace);
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), namespace);
+    [
+        deployments.get_opt(&format!("redis-{}", slug)).await.unwrap().is_some(),
+        services.get_opt(&format!("redis-{}-service", slug)).await.unwrap().is_some(),
+        secrets.get_opt(&format!("redis-{}-secret", slug)).await.unwrap().is_some(),
+    ]
+}
+
+// Kills the port-forward when the test ends, including on failure
+struct PortForward(Child);
+
+impl PortForward {
+    async fn start(namespace: &str, service: &str) -> Self {
+        let child = Command::new("kubectl")
+            .args(["port-forward", "--namespace", namespace, &format!("service/{}", service), "6379:6379"])
+            .spawn()
+            .expect("kubectl is on PATH");
+        let forward = PortForward(child);
+        for _ in 0..30 {
+            if tokio::net::TcpStream::connect("127.0.0.1:6379").await.is_ok() {
+                return forward;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        panic!("port-forward to {}/{} did not come up", namespace, service);
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+// Run a command through the gateway's generic endpoint handler
+async fn gateway_command(state: &Arc<AppState>, claims: &ApiKeyClaims, instance_id: Uuid, command: Value) -> Value {
+    let payload = command.as_array().unwrap().clone();
+    let extension = Extension(claims.clone());
+    match handle_generic_command(State(state.clone()), extension, Path(instance_id), Query(HashMap::new()), HeaderMap::new(), Json(payload)).await {
+        Ok(Json(response)) => serde_json::to_value(response).unwrap()["result"].clone(),
+        Err((status, Json(error))) => panic!("gateway command {} failed with {}: {}", command, status, error),
+    }
+}
+
+#[tokio::test]
+async fn test_instance_lifecycle() {
+    let state = test_state().await;
+    let client = kube::Client::try_default().await.expect("kubeconfig for the test cluster");
+    let (user, org_id) = create_owner(&state.db_pool).await;
+    let slug = format!("lifecycle-{}", &Uuid::new_v4().simple().to_string()[..8]);
+
+    // Create: the deployment, service and secret are applied right away
+    let created = data(
+        redis_instances::create_redis_instance(
+            State(state.clone()),
+            Extension(user.clone()),
+            HeaderMap::new(),
+            Json(CreateRedisInstanceRequest {
+                name: "Lifecycle test".to_string(),
+                slug: slug.clone(),
+                organization_id: org_id,
+                max_memory: 64 * 1024 * 1024,
+                redis_version: None,
+                persistence_enabled: Some(false),
+                backup_enabled: Some(false),
+                max_connections: None,
+                eviction_policy: None,
+                tls_enabled: Some(false),
+                max_concurrent_commands: None,
+                max_value_bytes: None,
+                pod_security: None,
+            }),
+        )
+        .await,
+    );
+    let instance = created.redis_instance;
+    assert_eq!(instance.status, "pending", "instance was not deployed to the cluster");
+    let namespace = instance.namespace.clone();
+    assert_eq!(resources_exist(&client, &namespace, &slug).await, [true; 3]);
+
+    // Status: poll until the deployment reports ready
+    let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+    loop {
+        let status = data(
+            redis_instances::update_redis_instance_status(
+                State(state.clone()),
+                Extension(user.clone()),
+                Path((org_id, instance.id)),
+            )
+            .await,
+        );
+        match status.redis_instance.status.as_str() {
+            "running" => break,
+            "failed" => panic!("instance failed: {:?} {:?}", status.redis_instance.status_reason, status.pod),
+            _ if tokio::time::Instant::now() > deadline => panic!("instance not running after {:?}: {:?}", READY_TIMEOUT, status.pod),
+            _ => tokio::time::sleep(POLL_INTERVAL).await,
+        }
+    }
+
+    // Command: through the gateway, authenticated as the write ACL user
+    {
+        let _forward = PortForward::start(&namespace, &format!("redis-{}-service", slug)).await;
+        let claims = ApiKeyClaims::new(Uuid::new_v4(), user.id, org_id, vec!["write".to_string()], "rg_kind".to_string(), None);
+        assert_eq!(gateway_command(&state, &claims, instance.id, json!(["SET", "lifecycle", "ok"])).await, "OK");
+        assert_eq!(gateway_command(&state, &claims, instance.id, json!(["GET", "lifecycle"])).await, "ok");
+    }
+
+    // Delete: the cluster resources go away
+    if let Err((status, Json(error))) = redis_instances::delete_redis_instance(
+        State(state.clone()),
+        Extension(user.clone()),
+        Path((org_id, instance.id)),
+        Query(DeleteRedisInstanceParams { purge_data: Some(true) }),
+    )
+    .await
+    {
+        panic!("delete failed with {}: {:?}", status, error.message);
+    }
+    let deadline = tokio::time::Instant::now() + CLEANUP_TIMEOUT;
+    loop {
+        let remaining = resources_exist(&client, &namespace, &slug).await;
+        if remaining == [false; 3] {
+            break;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "deployment, service, secret still present after {:?}: {:?}",
+            CLEANUP_TIMEOUT,
+            remaining
+        );
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
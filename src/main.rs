@@ -1,32 +1,59 @@
 use axum::{
-    extract::Extension,
+    extract::{Extension, State},
+    http::StatusCode,
     middleware as axum_middleware,
     response::Json,
     routing::{delete, get, post, put},
     Router,
 };
 use serde_json::json;
+use sqlx::migrate::Migrator;
 use sqlx::{PgPool, Row};
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
+use tokio::sync::watch;
 use tower_http::services::ServeDir;
+use tower_http::timeout::TimeoutLayer;
 use tracing::{info, warn};
 
 mod api_models;
+mod audit;
 mod auth;
+mod command_audit;
+mod config;
+mod drift;
 mod handlers;
+mod health_monitor;
+mod idempotency;
 pub mod k8s_service;
 #[cfg(test)]
 mod k8s_tests;
+#[cfg(all(test, feature = "kind-tests"))]
+mod lifecycle_tests;
 mod middleware;
 mod models;
+mod provisioning;
+mod purge;
+mod rate_limit;
+mod redis_acl;
+#[cfg(test)]
+mod redis_memory;
+mod redis_pool;
+mod redis_routes;
+#[cfg(unix)]
+mod unix_socket;
+mod webhooks;
+
+// Migrations embedded in the binary; the schema it expects
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 #[tokio::main]
 async fn main() {
     // Initialize tracing
-    tracing_subscriber::fmt::init();
+    match config::LogConfig::from_env().format {
+        config::LogFormat::Json => tracing_subscriber::fmt().json().init(),
+        config::LogFormat::Pretty => tracing_subscriber::fmt().init(),
+    }
 
     // In development, build frontend first
     #[cfg(debug_assertions)]
@@ -74,14 +101,19 @@ async fn main() {
 
     // Database connection
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let jwt_secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret-key".to_string());
+    let jwt_manager = auth::JwtManager::from_config(&config::JwtConfig::from_env()).expect("Invalid JWT configuration");
+    let server_config = config::ServerConfig::from_env();
+    let cors_config = config::CorsConfig::from_env();
+    let app_config = config::AppConfig::from_env();
+    app_config.domain.validate().expect("Invalid REDIS_DOMAIN_TEMPLATE");
+    app_config.kubernetes.validate().expect("Invalid Kubernetes configuration");
 
     let pool = PgPool::connect(&database_url)
         .await
         .expect("Failed to connect to Postgres");
 
     // Run migrations
-    sqlx::migrate!("./migrations")
+    MIGRATOR
         .run(&pool)
         .await
         .expect("Failed to run migrations");
@@ -89,42 +121,119 @@ async fn main() {
     info!("Database migrations completed successfully");
 
     // Create application state
-    let app_state = Arc::new(middleware::AppState::new(pool.clone(), &jwt_secret));
+    let app_state = Arc::new(middleware::AppState::new(pool.clone(), jwt_manager, app_config));
+
+    // Start without Kubernetes if the API server is unreachable and keep retrying
+    tokio::spawn(app_state.kubernetes.clone().connect(
+        app_state.kubernetes_config.connect_initial_backoff,
+        app_state.kubernetes_config.connect_max_backoff,
+    ));
+
+    // Move new instances to running once ready, or to failed if they never get there
+    tokio::spawn(provisioning::run(app_state.clone(), config::ProvisioningConfig::from_env()));
+
+    // Close pooled Redis connections that sat idle past REDIS_POOL_IDLE_TIMEOUT_SECS, and drop
+    // pools unused for REDIS_POOL_EVICT_IDLE_SECS along with their instances' concurrency limits
+    tokio::spawn(app_state.redis_pools.clone().reap_idle_connections(app_state.instance_limiter.clone()));
+
+    // Drop the rate-limit buckets of instances no longer being called
+    tokio::spawn(app_state.rate_limiter.clone().evict_idle_buckets());
+
+    // Keep instance health_status current in the background
+    tokio::spawn(health_monitor::run(app_state.clone(), config::HealthCheckConfig::from_env()));
+
+    // Periodically log instances and deployments that no longer match
+    tokio::spawn(drift::run(pool.clone(), app_state.kubernetes.clone(), config::DriftCheckConfig::from_env()));
+
+    // Hard-delete instances, and their retained volumes and backup jobs, once past the retention window
+    tokio::spawn(purge::run(app_state.clone()));
 
     // Static files service - adjust path based on your frontend build output
     let static_files_service = ServeDir::new("app/frontend-redis/dist")
         .not_found_service(ServeDir::new("app/frontend-redis/dist").append_index_html_on_directories(true));
 
+    let streams_shutdown = app_state.shutdown.clone();
+    let redis_pools = app_state.redis_pools.clone();
+    let in_flight = Arc::new(middleware::InFlightRequests::default());
+
     // Build application with all routes
     let app = Router::new()
         // API routes first (higher priority)
         
         // Public API routes (no authentication required)
-        .route("/health", get(health_check))
-        .route("/version", get(version))
-        .route("/stats", get(database_stats))
-        .route("/auth/register", post(handlers::auth::register))
-        .route("/auth/login", post(handlers::auth::login))
+        .merge(
+            Router::new()
+                .route("/health", get(health_check))
+                .route("/livez", get(liveness))
+                .route("/healthz", get(readiness))
+                .route("/version", get(version))
+                .route("/stats", get(database_stats))
+                .route("/auth/register", post(handlers::auth::register))
+                .route("/auth/login", post(handlers::auth::login))
+                .layer(TimeoutLayer::new(server_config.request_timeout))
+        )
         
         // Protected API routes (authentication required)
         .nest("/api", 
             Router::new()
-                .route("/organizations", post(handlers::organizations::create_organization))
-                .route("/organizations", get(handlers::organizations::list_organizations))
-                .route("/organizations/:org_id", get(handlers::organizations::get_organization))
-                .route("/organizations/:org_id", put(handlers::organizations::update_organization))
-                .route("/organizations/:org_id", delete(handlers::organizations::delete_organization))
-                
-                .route("/organizations/:org_id/api-keys", post(handlers::api_keys::create_api_key))
-                .route("/organizations/:org_id/api-keys", get(handlers::api_keys::list_api_keys))
-                .route("/organizations/:org_id/api-keys/:key_id", get(handlers::api_keys::get_api_key))
-                .route("/organizations/:org_id/api-keys/:key_id", delete(handlers::api_keys::revoke_api_key))
-                
-                .route("/organizations/:org_id/redis-instances", post(handlers::redis_instances::create_redis_instance))
-                .route("/organizations/:org_id/redis-instances", get(handlers::redis_instances::list_redis_instances))
-                .route("/organizations/:org_id/redis-instances/:instance_id", get(handlers::redis_instances::get_redis_instance))
-                .route("/organizations/:org_id/redis-instances/:instance_id/status", put(handlers::redis_instances::update_redis_instance_status))
-                .route("/organizations/:org_id/redis-instances/:instance_id", delete(handlers::redis_instances::delete_redis_instance))
+                .merge(
+                    Router::new()
+                        .route("/users/me", get(handlers::auth::get_current_user))
+                        
+                        .route("/admin/drift", get(handlers::admin::get_drift))
+                        .route("/admin/pools/health", get(handlers::admin::get_pool_health))
+                        .route("/admin/redis-instances", get(handlers::admin::list_all_redis_instances))
+                        
+                        .route("/organizations", post(handlers::organizations::create_organization))
+                        .route("/organizations", get(handlers::organizations::list_organizations))
+                        .route("/organizations/:org_id", get(handlers::organizations::get_organization))
+                        .route("/organizations/:org_id", put(handlers::organizations::update_organization))
+                        .route("/organizations/:org_id", delete(handlers::organizations::delete_organization))
+                        .route("/organizations/:org_id/usage", get(handlers::organizations::get_organization_usage))
+                        .route("/organizations/:org_id/members", get(handlers::organizations::list_organization_members))
+                        .route("/organizations/:org_id/members/:user_id", delete(handlers::organizations::remove_organization_member))
+                        .route("/organizations/:org_id/plan", put(handlers::organizations::update_plan))
+                        .route("/organizations/:org_id/transfer-ownership", post(handlers::organizations::transfer_ownership))
+                        .route("/organizations/:org_id/webhook", get(handlers::webhooks::get_webhook))
+                        .route("/organizations/:org_id/webhook", put(handlers::webhooks::update_webhook))
+                        .route("/organizations/:org_id/webhook", delete(handlers::webhooks::delete_webhook))
+                        .route("/organizations/:org_id/command-audit", get(handlers::command_audit::get_command_audit))
+                        .route("/organizations/:org_id/command-audit", put(handlers::command_audit::update_command_audit))
+                        .route("/organizations/:org_id/api-key-scopes", get(handlers::api_keys::get_api_key_scopes))
+                        .route("/organizations/:org_id/api-key-scopes", put(handlers::api_keys::update_api_key_scopes))
+                        
+                        .route("/organizations/:org_id/api-keys", post(handlers::api_keys::create_api_key))
+                        .route("/organizations/:org_id/api-keys", get(handlers::api_keys::list_api_keys))
+                        .route("/organizations/:org_id/api-keys/:key_id", get(handlers::api_keys::get_api_key))
+                        .route("/organizations/:org_id/api-keys/:key_id", delete(handlers::api_keys::revoke_api_key))
+                        .route("/organizations/:org_id/members/:user_id/revoke-keys", post(handlers::api_keys::revoke_member_api_keys))
+                        
+                        .route("/organizations/:org_id/redis-instances", post(handlers::redis_instances::create_redis_instance))
+                        .route("/organizations/:org_id/redis-instances", get(handlers::redis_instances::list_redis_instances))
+                        .route("/organizations/:org_id/redis-instances/deleted", get(handlers::redis_instances::list_deleted_redis_instances))
+                        .route("/organizations/:org_id/redis-instances/:instance_id", get(handlers::redis_instances::get_redis_instance))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/status", put(handlers::redis_instances::update_redis_instance_status))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/rotate-password", post(handlers::redis_instances::rotate_redis_password))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/restart", post(handlers::redis_instances::restart_redis_instance))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/refresh-pool", post(handlers::redis_instances::refresh_redis_pool))
+                        .route("/organizations/:org_id/redis-instances/:instance_id", delete(handlers::redis_instances::delete_redis_instance))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/delete-keys", post(handlers::keyspace::delete_keys))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/ttl", post(handlers::keyspace::get_key_ttls))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/idletime", post(handlers::keyspace::get_key_idle_times))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/stats", get(handlers::keyspace::get_instance_stats))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/big-keys", get(handlers::keyspace::get_big_keys))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/expire", post(handlers::keyspace::set_key_ttls))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/config", get(handlers::instance_config::get_instance_config))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/config", post(handlers::instance_config::update_instance_config))
+                        .layer(TimeoutLayer::new(server_config.request_timeout))
+                )
+                // Streaming and bulk transfer routes are exempt from the request timeout
+                .merge(
+                    Router::new()
+                        .route("/organizations/:org_id/redis-instances/:instance_id/logs", get(handlers::redis_instances::get_redis_instance_logs))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/export", get(handlers::keyspace::export_keys))
+                        .route("/organizations/:org_id/redis-instances/:instance_id/import", post(handlers::keyspace::import_keys))
+                )
                 
                 // Apply authentication middleware only to protected routes
                 .layer(axum_middleware::from_fn_with_state(
@@ -134,40 +243,131 @@ async fn main() {
         )
         
         // Redis HTTP API routes (uses API key authentication)
-        .route("/redis/:instance_id/ping", get(handlers::redis::handle_ping))
-        .route("/redis/:instance_id/set/:key/:value", get(handlers::redis::handle_set))
-        .route("/redis/:instance_id/get/:key", get(handlers::redis::handle_get))
-        .route("/redis/:instance_id/del/:key", get(handlers::redis::handle_del))
-        .route("/redis/:instance_id/incr/:key", get(handlers::redis::handle_incr))
-        .route("/redis/:instance_id/hset/:key/:field/:value", get(handlers::redis::handle_hset))
-        .route("/redis/:instance_id/hget/:key/:field", get(handlers::redis::handle_hget))
-        .route("/redis/:instance_id/lpush/:key/:value", get(handlers::redis::handle_lpush))
-        .route("/redis/:instance_id/lpop/:key", get(handlers::redis::handle_lpop))
-        
-        // Generic Redis command endpoint (for POST with JSON body)
-        .route("/redis/:instance_id", post(handlers::redis::handle_generic_command))
-        
-        // Catch-all route for debugging Redis requests
-        .route("/redis/:instance_id/*path", get(handlers::redis::handle_debug_request))
+        .merge(redis_routes::router(&app_state, server_config.request_timeout))
         
         // Serve static files for all other routes (must be last)
         .fallback_service(static_files_service)
         
-        .layer(CorsLayer::permissive())
+        .layer(cors_config.layer())
         .with_state(app_state)
-        .layer(Extension(Arc::new(pool)));
+        .layer(Extension(Arc::new(pool.clone())))
+        .layer(axum_middleware::from_fn_with_state(
+            in_flight.clone(),
+            middleware::in_flight_middleware,
+        ))
+        // Outermost, so every response carries the request id
+        .layer(axum_middleware::from_fn(middleware::request_id_middleware));
 
     // Start server
-    let listener = TcpListener::bind("0.0.0.0:3000")
-        .await
-        .expect("Failed to bind to address");
-
-    info!("Server starting on 0.0.0.0:3000");
+    server_config.validate().expect("Invalid server configuration");
+    let tcp_listener = if server_config.tcp_enabled {
+        let listener = TcpListener::bind("0.0.0.0:3000")
+            .await
+            .expect("Failed to bind to address");
+        info!("Server starting on 0.0.0.0:3000");
+        Some(listener)
+    } else {
+        None
+    };
+    #[cfg(unix)]
+    let unix_listener = server_config.unix_socket.as_deref().map(|path| {
+        let listener = unix_socket::bind(path).expect("Failed to bind Unix socket");
+        info!("Server starting on Unix socket {}", path.display());
+        listener
+    });
     info!("Serving frontend static files from: app/frontend-redis/dist");
 
-    axum::serve(listener, app)
-        .await
-        .expect("Failed to start server");
+    // Stop accepting connections on SIGINT/SIGTERM and let in-flight requests
+    // finish, but no longer than the configured grace period
+    // Streaming responses are told first, so they can send a final event and end
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let signal_in_flight = in_flight.clone();
+    let signal = tokio::spawn(async move {
+        shutdown_signal().await;
+        let draining = signal_in_flight.current();
+        info!(in_flight_requests = draining, "Shutdown signal received, draining in-flight requests");
+        let _ = streams_shutdown.send(());
+        let _ = shutdown_tx.send(true);
+        signal_in_flight.completed()
+    });
+    let shutdown_started = |mut shutdown_rx: watch::Receiver<bool>| async move {
+        let _ = shutdown_rx.wait_for(|started| *started).await;
+    };
+
+    let tcp_server = async {
+        if let Some(listener) = tcp_listener {
+            axum::serve(listener, app.clone())
+                .with_graceful_shutdown(shutdown_started(shutdown_rx.clone()))
+                .await
+                .expect("Failed to start server");
+        }
+    };
+    #[cfg(unix)]
+    let unix_server = async {
+        if let Some(listener) = unix_listener {
+            unix_socket::serve(listener, app.clone(), shutdown_started(shutdown_rx.clone())).await;
+        }
+    };
+    #[cfg(not(unix))]
+    let unix_server = async {};
+    let grace_period_elapsed = async {
+        shutdown_started(shutdown_rx.clone()).await;
+        tokio::time::sleep(server_config.shutdown_grace_period).await;
+    };
+
+    tokio::select! {
+        _ = async { tokio::join!(tcp_server, unix_server) } => {
+            info!("All in-flight requests completed");
+        }
+        _ = grace_period_elapsed => {
+            warn!(
+                "Shutdown grace period of {:?} elapsed with requests still in flight; forcing shutdown",
+                server_config.shutdown_grace_period
+            );
+        }
+    }
+
+    // Requests that finished after the signal were drained; any still running are cut off
+    let completed_at_signal = signal.await.unwrap_or_default();
+    let pool_stats = redis_pools.stats();
+    info!(
+        redis_pools = pool_stats.len(),
+        open_connections = pool_stats.iter().map(|pool| pool.size).sum::<usize>(),
+        in_use_connections = pool_stats.iter().map(|pool| pool.in_use).sum::<usize>(),
+        requests_drained = in_flight.completed().saturating_sub(completed_at_signal),
+        requests_interrupted = in_flight.current(),
+        "Shutdown summary"
+    );
+
+    if let Some(path) = &server_config.unix_socket {
+        let _ = std::fs::remove_file(path);
+    }
+    pool.close().await;
+    info!("Server shut down");
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
 
 async fn health_check(Extension(pool): Extension<Arc<PgPool>>) -> Json<serde_json::Value> {
@@ -197,11 +397,95 @@ async fn health_check(Extension(pool): Extension<Arc<PgPool>>) -> Json<serde_jso
     }))
 }
 
+// Liveness probe: the process is up and serving requests
+async fn liveness() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+// Embedded migrations that have not been applied successfully to the database
+async fn pending_migrations(pool: &PgPool) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| json!({ "version": migration.version, "description": migration.description }))
+        .collect())
+}
+
+// Readiness probe: the database is reachable and fully migrated, and the Kubernetes API is reachable
+async fn readiness(
+    State(state): State<Arc<middleware::AppState>>,
+    Extension(pool): Extension<Arc<PgPool>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let database_ok = match sqlx::query("SELECT 1").execute(pool.as_ref()).await {
+        Ok(_) => true,
+        Err(e) => {
+            warn!("Readiness check: database unreachable: {}", e);
+            false
+        }
+    };
+
+    // A binary rolled out against an un-migrated database would fail at query time
+    let migrations = match pending_migrations(pool.as_ref()).await {
+        Ok(pending) if pending.is_empty() => json!({ "status": "current" }),
+        Ok(pending) => {
+            warn!("Readiness check: {} migrations pending", pending.len());
+            json!({ "status": "pending", "pending": pending })
+        }
+        Err(e) => {
+            warn!("Readiness check: could not read applied migrations: {}", e);
+            json!({ "status": "unknown", "error": e.to_string() })
+        }
+    };
+    let migrations_ok = migrations["status"] == "current";
+
+    // Local-only mode runs without a cluster by design, so it does not hold readiness back
+    let kubernetes = match state.kubernetes.service() {
+        Ok(k8s) => match k8s.check_connectivity().await {
+            Ok(version) => json!({ "status": "healthy", "version": version }),
+            Err(e) => {
+                warn!("Readiness check: Kubernetes API unreachable: {}", e);
+                json!({ "status": "unhealthy", "error": e.to_string() })
+            }
+        },
+        Err(k8s_service::K8sUnavailable::Disabled) => json!({ "status": "disabled" }),
+        Err(e) => {
+            warn!("Readiness check: Kubernetes client unavailable: {}", e);
+            json!({ "status": "unhealthy", "error": e.to_string() })
+        }
+    };
+    let kubernetes_ok = kubernetes["status"] != "unhealthy";
+
+    let status = if database_ok && migrations_ok && kubernetes_ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": if status == StatusCode::OK { "ok" } else { "unavailable" },
+            "database": if database_ok { "healthy" } else { "unhealthy" },
+            "migrations": migrations,
+            "kubernetes": kubernetes,
+            "timestamp": chrono::Utc::now()
+        })),
+    )
+}
+
 async fn version() -> Json<serde_json::Value> {
+    // GIT_SHA and BUILD_TIMESTAMP are set by the build (see the Dockerfile); null for local builds
     Json(json!({
         "name": "redisgate",
         "version": env!("CARGO_PKG_VERSION"),
-        "description": "Cloud Redis on Kubernetes HTTP Gateway"
+        "description": "Cloud Redis on Kubernetes HTTP Gateway",
+        "git_sha": option_env!("GIT_SHA"),
+        "build_timestamp": option_env!("BUILD_TIMESTAMP")
     }))
 }
 
@@ -1,16 +1,31 @@
 // Authentication middleware for protecting routes
 
 use axum::{
+    body::{Body, HttpBody},
     extract::{Request, State},
-    http::header::AUTHORIZATION,
+    http::{
+        header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER},
+        HeaderName, HeaderValue, StatusCode,
+    },
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Json, Response},
 };
+use serde_json::{json, Value};
 use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use tokio::sync::{broadcast, Semaphore};
+use tracing::Instrument;
+use uuid::Uuid;
 
-use crate::auth::{AuthError, JwtManager};
+use crate::auth::{ApiKeyClaims, AuthError, JwtManager};
+use crate::command_audit::CommandAuditor;
+use crate::config::{AdminConfig, AppConfig, DomainConfig, KubernetesConfig, RedisHandlerConfig, RetentionConfig};
+use crate::k8s_service::KubernetesClient;
 use crate::models::User;
+use crate::rate_limit::{path_instance_id, GatewayRateLimiter};
+use crate::redis_pool::{InstanceLimiter, RedisExecutor, RedisPoolManager};
+use crate::webhooks::WebhookNotifier;
 
 // Middleware for JWT authentication
 pub async fn auth_middleware(
@@ -54,6 +69,165 @@ pub async fn auth_middleware(
     Ok(next.run(request).await)
 }
 
+// Middleware telling clients when to retry Redis requests that failed with 503
+pub async fn retry_after_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE && !response.headers().contains_key(RETRY_AFTER) {
+        let retry_after = state.redis_config.pool_timeout.as_secs().max(1);
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+
+    response
+}
+
+// Middleware rejecting Redis API requests over the gateway's global or per-instance rate with 429.
+// Runs after the API key check, so requests without a valid key take no tokens, and each
+// instance's rate is counted per organization calling it.
+pub async fn rate_limit_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let instance = request
+        .extensions()
+        .get::<ApiKeyClaims>()
+        .zip(path_instance_id(request.uri().path()))
+        .map(|(claims, instance_id)| (claims.organization_id, instance_id));
+    if let Err(wait) = state.rate_limiter.check(instance) {
+        tracing::warn!("Rate limit exceeded for Redis API request to {}", request.uri().path());
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({"error": "Rate limit exceeded"})),
+        )
+            .into_response();
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+
+    next.run(request).await
+}
+
+static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+// Largest error body the request id is added to; larger ones are passed through unchanged
+const MAX_ERROR_BODY_BYTES: u64 = 64 * 1024;
+
+// Accept client-supplied ids only when they are short and safe to log and echo
+fn is_valid_request_id(id: &str) -> bool {
+    (1..=128).contains(&id.len())
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Requests being handled right now, and how many have finished, for the shutdown summary
+#[derive(Debug, Default)]
+pub struct InFlightRequests {
+    current: AtomicUsize,
+    completed: AtomicU64,
+}
+
+impl InFlightRequests {
+    /// Count a request as in flight until the guard is dropped
+    pub fn enter(&self) -> InFlightGuard<'_> {
+        self.current.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard(self)
+    }
+
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    pub fn completed(&self) -> u64 {
+        self.completed.load(Ordering::Relaxed)
+    }
+}
+
+pub struct InFlightGuard<'a>(&'a InFlightRequests);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.current.fetch_sub(1, Ordering::Relaxed);
+        self.0.completed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// Middleware counting requests in flight. Streaming responses count until their handler
+// returns the response, not until the stream ends.
+pub async fn in_flight_middleware(State(in_flight): State<Arc<InFlightRequests>>, request: Request, next: Next) -> Response {
+    let _guard = in_flight.enter();
+    next.run(request).await
+}
+
+// Middleware assigning each request an id, taken from the X-Request-Id header or generated.
+// The id is attached to the request's log lines, echoed in the X-Request-Id response header
+// and added to JSON error bodies, so users can point operators at the right log lines.
+pub async fn request_id_middleware(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = tracing::info_span!("request", request_id = %id);
+    let response = next.run(request).instrument(span).await;
+
+    with_request_id(response, &id).await
+}
+
+async fn with_request_id(response: Response, id: &str) -> Response {
+    let (mut parts, body) = response.into_parts();
+    if let Ok(value) = HeaderValue::from_str(id) {
+        parts.headers.insert(X_REQUEST_ID.clone(), value);
+    }
+
+    let is_json_error = (parts.status.is_client_error() || parts.status.is_server_error())
+        && parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(is_json_content_type);
+    // Only bodies known to be small are buffered; streamed or large ones are left alone
+    let is_small = body.size_hint().upper().is_some_and(|size| size <= MAX_ERROR_BODY_BYTES);
+    if !is_json_error || !is_small {
+        return Response::from_parts(parts, body);
+    }
+
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to read error response body: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut object)) => {
+            object.insert("request_id".to_string(), Value::String(id.to_string()));
+            parts.headers.remove(CONTENT_LENGTH);
+            Body::from(Value::Object(object).to_string())
+        }
+        _ => Body::from(bytes),
+    };
+
+    Response::from_parts(parts, body)
+}
+
+// Whether a Content-Type is exactly JSON, with or without parameters such as charset
+fn is_json_content_type(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
+
 // Current user info extracted from JWT
 #[derive(Debug, Clone)]
 pub struct CurrentUser {
@@ -68,13 +242,120 @@ pub struct CurrentUser {
 pub struct AppState {
     pub db_pool: PgPool,
     pub jwt_manager: JwtManager,
+    pub redis_config: RedisHandlerConfig,
+    pub redis_pools: Arc<RedisPoolManager>,
+    pub redis_executor: Arc<dyn RedisExecutor>, // Runs Redis HTTP API commands; the instance pools outside tests
+    pub instance_limiter: Arc<InstanceLimiter>, // Concurrent Redis commands per instance
+    pub rate_limiter: Arc<GatewayRateLimiter>, // Redis API requests per second, overall and per instance
+    pub domain_config: DomainConfig,
+    pub admin_config: AdminConfig,
+    pub kubernetes_config: KubernetesConfig,
+    pub retention_config: RetentionConfig, // How long soft-deleted instances can still be found
+    pub kubernetes: Arc<KubernetesClient>, // Connected in the background; see KubernetesClient::connect
+    pub provisioning: Arc<Semaphore>, // Instance deployments allowed to run at once
+    pub webhooks: Arc<WebhookNotifier>, // Organization status-change webhooks
+    pub command_audit: Arc<CommandAuditor>, // Sampled command trail of organizations that audit commands
+    pub shutdown: broadcast::Sender<()>, // Sent once on shutdown so long-lived streams can close cleanly
 }
 
 impl AppState {
-    pub fn new(db_pool: PgPool, jwt_secret: &str) -> Self {
+    pub fn new(db_pool: PgPool, jwt_manager: JwtManager, config: AppConfig) -> Self {
+        let kubernetes = Arc::new(KubernetesClient::new(config.kubernetes.enabled));
+        let redis_pools = Arc::new(RedisPoolManager::new(config.redis.clone(), kubernetes.clone()));
         Self {
+            webhooks: Arc::new(WebhookNotifier::new(db_pool.clone(), config.webhook)),
+            command_audit: Arc::new(CommandAuditor::new(db_pool.clone())),
+            kubernetes,
+            provisioning: Arc::new(Semaphore::new(config.kubernetes.max_concurrent_provisions)),
+            shutdown: broadcast::channel(1).0,
             db_pool,
-            jwt_manager: JwtManager::new(jwt_secret),
+            jwt_manager,
+            domain_config: config.domain,
+            admin_config: config.admin,
+            kubernetes_config: config.kubernetes,
+            retention_config: config.retention,
+            redis_executor: redis_pools.clone(),
+            redis_pools,
+            instance_limiter: Arc::new(InstanceLimiter::new(config.redis.pool_max_size)),
+            rate_limiter: Arc::new(GatewayRateLimiter::new(&config.redis)),
+            redis_config: config.redis,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::{IntoResponse, Json};
+    use serde_json::json;
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_is_valid_request_id() {
+        assert!(is_valid_request_id("3f2a9c1e-0b7d-4e21-9c55-8a1f0e6b2d44"));
+        assert!(is_valid_request_id("trace:abc_1.2"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id(&"a".repeat(129)));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_added_to_json_errors() {
+        let error = (StatusCode::NOT_FOUND, Json(json!({"error": "Redis instance not found"}))).into_response();
+        let response = with_request_id(error, "req-1").await;
+        assert_eq!(response.headers()[&X_REQUEST_ID], "req-1");
+        assert_eq!(
+            body_json(response).await,
+            json!({"error": "Redis instance not found", "request_id": "req-1"})
+        );
+
+        let success = Json(json!({"result": "PONG"})).into_response();
+        let response = with_request_id(success, "req-2").await;
+        assert_eq!(response.headers()[&X_REQUEST_ID], "req-2");
+        assert_eq!(body_json(response).await, json!({"result": "PONG"}));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_skips_other_bodies() {
+        let problem = Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header(CONTENT_TYPE, "application/problem+json")
+            .body(Body::from(r#"{"title":"bad"}"#))
+            .unwrap();
+        let response = with_request_id(problem, "req-3").await;
+        assert_eq!(body_json(response).await, json!({"title": "bad"}));
+
+        let large = (StatusCode::BAD_REQUEST, Json(json!({"error": "x".repeat(MAX_ERROR_BODY_BYTES as usize)}))).into_response();
+        let response = with_request_id(large, "req-4").await;
+        assert_eq!(response.headers()[&X_REQUEST_ID], "req-4");
+        assert!(body_json(response).await.get("request_id").is_none());
+    }
+
+    #[test]
+    fn test_is_json_content_type() {
+        assert!(is_json_content_type("application/json"));
+        assert!(is_json_content_type("application/json; charset=utf-8"));
+        assert!(!is_json_content_type("application/json-seq"));
+        assert!(!is_json_content_type("text/plain"));
+    }
+
+    #[test]
+    fn test_in_flight_requests() {
+        let in_flight = InFlightRequests::default();
+        let first = in_flight.enter();
+        let second = in_flight.enter();
+        assert_eq!(in_flight.current(), 2);
+        assert_eq!(in_flight.completed(), 0);
+
+        drop(first);
+        assert_eq!(in_flight.current(), 1);
+        assert_eq!(in_flight.completed(), 1);
+        drop(second);
+        assert_eq!(in_flight.current(), 0);
+        assert_eq!(in_flight.completed(), 2);
+    }
+}
@@ -6,7 +6,6 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use sqlx::types::BigDecimal;
-use ipnetwork;
 
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct User {
@@ -40,6 +39,87 @@ pub struct Organization {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+// Subscription plan of an organization, with the quotas it grants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Plan {
+    Free,
+    Pro,
+    Enterprise,
+}
+
+impl Plan {
+    // Parse a stored plan name, treating missing or unknown values as the free plan
+    pub fn from_db(plan: Option<&str>) -> Self {
+        match plan {
+            Some("pro") => Plan::Pro,
+            Some("enterprise") => Plan::Enterprise,
+            _ => Plan::Free,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Plan::Free => "free",
+            Plan::Pro => "pro",
+            Plan::Enterprise => "enterprise",
+        }
+    }
+
+    pub fn max_redis_instances(&self) -> i32 {
+        match self {
+            Plan::Free => 3,
+            Plan::Pro => 20,
+            Plan::Enterprise => 100,
+        }
+    }
+
+    pub fn max_api_keys(&self) -> i32 {
+        match self {
+            Plan::Free => 10,
+            Plan::Pro => 50,
+            Plan::Enterprise => 500,
+        }
+    }
+
+    // Ceiling on the summed max_memory of all of an organization's instances, in bytes
+    pub fn max_total_memory(&self) -> i64 {
+        const GIB: i64 = 1024 * 1024 * 1024;
+        match self {
+            Plan::Free => GIB,
+            Plan::Pro => 32 * GIB,
+            Plan::Enterprise => 512 * GIB,
+        }
+    }
+
+    // Highest maxclients an instance may be created with; also the default
+    pub fn max_connections(&self) -> i32 {
+        match self {
+            Plan::Free => 100,
+            Plan::Pro => 1000,
+            Plan::Enterprise => 10000,
+        }
+    }
+
+    // Largest value an instance's SET-family commands may write, in bytes; also the default
+    pub fn max_value_bytes(&self) -> i32 {
+        const MIB: i32 = 1024 * 1024;
+        match self {
+            Plan::Free => MIB,
+            Plan::Pro => 16 * MIB,
+            Plan::Enterprise => 128 * MIB,
+        }
+    }
+
+    // Free instances behave like a cache; paid plans reject writes instead of dropping keys
+    pub fn default_eviction_policy(&self) -> &'static str {
+        match self {
+            Plan::Free => "allkeys-lru",
+            Plan::Pro | Plan::Enterprise => "noeviction",
+        }
+    }
+}
+
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct ApiKey {
     pub id: Uuid,
@@ -81,9 +161,11 @@ pub struct RedisInstance {
     pub namespace: Option<String>,
     pub pod_name: Option<String>,
     pub service_name: Option<String>,
+    pub replica_service_name: Option<String>, // Service of the instance's read replicas, if it has any
 
     // Instance status and metadata
     pub status: Option<String>,
+    pub status_reason: Option<String>, // Why the instance is in its status, e.g. why it failed
     pub last_health_check_at: Option<DateTime<Utc>>,
     pub health_status: Option<String>,
 
@@ -91,7 +173,12 @@ pub struct RedisInstance {
     pub cpu_usage_percent: Option<BigDecimal>,
     pub memory_usage_percent: Option<BigDecimal>,
     pub connections_count: Option<i32>,
-    pub max_connections: Option<i32>,
+    pub max_connections: i32,
+    pub eviction_policy: Option<String>,
+    pub tls_enabled: bool,
+    pub acl_enabled: bool,
+    pub max_concurrent_commands: Option<i32>,
+    pub max_value_bytes: Option<i32>,
 
     // Backup and persistence
     pub persistence_enabled: Option<bool>,
@@ -0,0 +1,125 @@
+// Background task that follows new Redis instances until their deployment is ready, and
+// marks instances that never become ready as failed
+
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::config::ProvisioningConfig;
+use crate::k8s_service::K8sRedisService;
+use crate::middleware::AppState;
+
+// Stored when the pod gives no better explanation
+const DEFAULT_FAILURE_REASON: &str = "Deployment did not become ready";
+
+/// Poll pending instances forever at the configured interval
+pub async fn run(state: Arc<AppState>, config: ProvisioningConfig) {
+    let mut interval = tokio::time::interval(config.poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        // Without a cluster nothing is deployed, so there is nothing to follow
+        let Ok(k8s_service) = state.kubernetes.service() else {
+            continue;
+        };
+
+        if let Err(e) = check_pending(&state, &k8s_service, &config).await {
+            tracing::warn!("Provisioning check failed: {}", e);
+        }
+    }
+}
+
+async fn check_pending(
+    state: &AppState,
+    k8s_service: &K8sRedisService,
+    config: &ProvisioningConfig,
+) -> Result<(), sqlx::Error> {
+    let instances = sqlx::query!(
+        r#"
+        SELECT id, organization_id, slug, namespace AS "namespace!", created_at AS "created_at!"
+        FROM redis_instances
+        WHERE status = 'pending' AND deleted_at IS NULL AND namespace IS NOT NULL
+        "#
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    for instance in instances {
+        let status = match k8s_service.get_deployment_status(&instance.namespace, &instance.slug).await {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::warn!("Failed to check deployment of instance {}: {}", instance.id, e);
+                continue;
+            }
+        };
+
+        let (new_status, reason) = if status == "running" {
+            ("running", None)
+        } else if timed_out(instance.created_at, config) {
+            let reason = match k8s_service.pod_diagnostics(&instance.namespace, &instance.slug).await {
+                Ok(Some(diagnostics)) => diagnostics
+                    .failure_reason()
+                    .unwrap_or_else(|| DEFAULT_FAILURE_REASON.to_string()),
+                Ok(None) => "No pod was created for the deployment".to_string(),
+                Err(e) => {
+                    tracing::warn!("Failed to read pod status of instance {}: {}", instance.id, e);
+                    DEFAULT_FAILURE_REASON.to_string()
+                }
+            };
+            tracing::warn!(
+                "Redis instance {} not ready after {:?}; marking it failed: {}",
+                instance.id,
+                config.creating_timeout,
+                reason
+            );
+            ("failed", Some(reason))
+        } else {
+            continue;
+        };
+
+        // The status endpoint may have moved the instance on since it was read
+        let result = sqlx::query!(
+            r#"
+            UPDATE redis_instances SET status = $1, status_reason = $2, updated_at = NOW()
+            WHERE id = $3 AND status = 'pending'
+            "#,
+            new_status,
+            reason,
+            instance.id
+        )
+        .execute(&state.db_pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            state.webhooks.status_changed(instance.organization_id, instance.id, Some("pending"), new_status);
+        }
+    }
+
+    Ok(())
+}
+
+fn timed_out(created_at: chrono::DateTime<Utc>, config: &ProvisioningConfig) -> bool {
+    (Utc::now() - created_at)
+        .to_std()
+        .is_ok_and(|age| age > config.creating_timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_timed_out() {
+        let config = ProvisioningConfig {
+            poll_interval: Duration::from_secs(15),
+            creating_timeout: Duration::from_secs(300),
+        };
+
+        assert!(!timed_out(Utc::now(), &config));
+        assert!(!timed_out(Utc::now() - chrono::Duration::seconds(299), &config));
+        assert!(timed_out(Utc::now() - chrono::Duration::seconds(301), &config));
+        assert!(!timed_out(Utc::now() + chrono::Duration::seconds(60), &config));
+    }
+}
@@ -0,0 +1,98 @@
+// Background task that hard-deletes instances soft-deleted before the retention window,
+// together with the volumes and backup jobs kept for them in the meantime
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEvent};
+use crate::config::RetentionConfig;
+use crate::k8s_service::{K8sRedisService, K8sUnavailable};
+use crate::middleware::AppState;
+
+// Instances purged per run; the rest wait for the next one
+const PURGE_BATCH_SIZE: i64 = 100;
+
+/// Purge expired instances forever at the configured interval
+pub async fn run(state: Arc<AppState>) {
+    let config = state.retention_config.clone();
+    let mut interval = tokio::time::interval(config.purge_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        // In local-only mode nothing was deployed, so only rows are removed. While the cluster
+        // is unreachable, rows are kept so their retained resources can still be found.
+        let k8s_service = match state.kubernetes.service() {
+            Ok(service) => Some(service),
+            Err(K8sUnavailable::Disabled) => None,
+            Err(K8sUnavailable::Connecting) => continue,
+        };
+
+        match purge_expired(&state.db_pool, k8s_service.as_ref(), &config, Utc::now()).await {
+            Ok(0) => {}
+            Ok(purged) => tracing::info!("Purged {} Redis instances past the retention window", purged),
+            Err(e) => tracing::warn!("Instance purge failed: {}", e),
+        }
+    }
+}
+
+/// Remove the retained cluster resources and the row of each instance deleted before the
+/// retention window. An instance whose resources cannot be removed is kept for the next run.
+async fn purge_expired(
+    pool: &PgPool,
+    k8s_service: Option<&K8sRedisService>,
+    config: &RetentionConfig,
+    now: DateTime<Utc>,
+) -> Result<usize, sqlx::Error> {
+    let cutoff = config.cutoff(now);
+    let expired: Vec<(Uuid, Uuid, String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT id, organization_id, slug, namespace FROM redis_instances
+        WHERE deleted_at IS NOT NULL AND deleted_at <= $1
+        ORDER BY deleted_at
+        LIMIT $2
+        "#,
+    )
+    .bind(cutoff)
+    .bind(PURGE_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let mut purged = 0;
+    for (instance_id, organization_id, slug, namespace) in expired {
+        if let (Some(k8s_service), Some(namespace)) = (k8s_service, &namespace) {
+            if let Err(e) = k8s_service.purge_retained_resources(namespace, instance_id).await {
+                tracing::warn!("Failed to purge retained resources of instance {}: {}", instance_id, e);
+                continue;
+            }
+        }
+
+        sqlx::query("DELETE FROM redis_instances WHERE id = $1 AND deleted_at <= $2")
+            .bind(instance_id)
+            .bind(cutoff)
+            .execute(pool)
+            .await?;
+        purged += 1;
+
+        if let Err(e) = audit::record(
+            pool,
+            &AuditEvent {
+                user_id: None,
+                organization_id: Some(organization_id),
+                action: "purge",
+                resource_type: "redis_instance",
+                resource_id: Some(instance_id),
+                details: json!({ "slug": slug, "namespace": namespace }),
+            },
+        )
+        .await
+        {
+            tracing::warn!("Failed to record instance purge in audit log: {}", e);
+        }
+    }
+    Ok(purged)
+}
@@ -0,0 +1,238 @@
+// Token-bucket rate limits on Redis HTTP API requests, across the gateway and per instance
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::config::RedisHandlerConfig;
+
+// How often instance buckets that have refilled are dropped
+const EVICT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// An instance as called by one organization's API keys. Keys of other organizations that
+/// name the same instance get their own bucket, so they cannot use up its rate.
+pub type InstanceBucketKey = (Uuid, Uuid);
+
+/// Holds up to `rate` tokens (one second of traffic) and refills at `rate` tokens per second
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(rate: u32, now: Instant) -> Self {
+        Self {
+            tokens: rate as f64,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, rate: u32, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+        self.refilled_at = now;
+    }
+
+    // Time until a token is available; zero when one is available now
+    fn wait(&self, rate: u32) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / rate as f64)
+        }
+    }
+}
+
+/// Limits requests per second across the whole gateway and per instance. A rate of 0 leaves
+/// that limit off.
+pub struct GatewayRateLimiter {
+    global_rate: u32,
+    instance_rate: u32,
+    global: Mutex<TokenBucket>,
+    instances: Mutex<HashMap<InstanceBucketKey, TokenBucket>>,
+}
+
+impl GatewayRateLimiter {
+    pub fn new(config: &RedisHandlerConfig) -> Self {
+        Self {
+            global_rate: config.global_rate_limit,
+            instance_rate: config.instance_rate_limit,
+            global: Mutex::new(TokenBucket::full(config.global_rate_limit, Instant::now())),
+            instances: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for a request to the instance, if any. Returns how long to wait before
+    /// retrying when either limit is exhausted; no token is taken then.
+    pub fn check(&self, instance: Option<InstanceBucketKey>) -> Result<(), Duration> {
+        self.check_at(instance, Instant::now())
+    }
+
+    fn check_at(&self, instance: Option<InstanceBucketKey>, now: Instant) -> Result<(), Duration> {
+        // Locked in a fixed order: instances, then global
+        let mut instances = self.instances.lock().unwrap();
+        let mut instance_bucket = match instance {
+            Some(instance) if self.instance_rate > 0 => {
+                let bucket = instances
+                    .entry(instance)
+                    .or_insert_with(|| TokenBucket::full(self.instance_rate, now));
+                bucket.refill(self.instance_rate, now);
+                Some(bucket)
+            }
+            _ => None,
+        };
+        let mut global = self.global.lock().unwrap();
+        if self.global_rate > 0 {
+            global.refill(self.global_rate, now);
+        }
+
+        let instance_wait = instance_bucket.as_ref().map_or(Duration::ZERO, |bucket| bucket.wait(self.instance_rate));
+        let global_wait = if self.global_rate > 0 { global.wait(self.global_rate) } else { Duration::ZERO };
+        let wait = instance_wait.max(global_wait);
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        if let Some(bucket) = instance_bucket.as_mut() {
+            bucket.tokens -= 1.0;
+        }
+        if self.global_rate > 0 {
+            global.tokens -= 1.0;
+        }
+        Ok(())
+    }
+
+    // Drop instance buckets that have refilled, as a new full bucket would replace them.
+    // Returns how many were dropped.
+    fn evict_full_buckets(&self, now: Instant) -> usize {
+        let rate = self.instance_rate;
+        let mut instances = self.instances.lock().unwrap();
+        let before = instances.len();
+        instances.retain(|_, bucket| {
+            bucket.refill(rate, now);
+            bucket.tokens < rate as f64
+        });
+        before - instances.len()
+    }
+
+    /// Periodically drop the buckets of instances that are no longer being called
+    pub async fn evict_idle_buckets(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(EVICT_INTERVAL);
+        loop {
+            interval.tick().await;
+            let evicted = self.evict_full_buckets(Instant::now());
+            if evicted > 0 {
+                tracing::debug!("Dropped {} idle instance rate-limit buckets", evicted);
+            }
+        }
+    }
+}
+
+/// Instance id of a Redis HTTP API path such as `/redis/{instance_id}/get/key`
+pub fn path_instance_id(path: &str) -> Option<Uuid> {
+    let rest = path.strip_prefix("/redis/")?;
+    let segment = rest.split('/').next()?;
+    Uuid::parse_str(segment).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(global_rate: u32, instance_rate: u32) -> GatewayRateLimiter {
+        GatewayRateLimiter::new(&RedisHandlerConfig {
+            global_rate_limit: global_rate,
+            instance_rate_limit: instance_rate,
+            ..RedisHandlerConfig::default()
+        })
+    }
+
+    fn instance() -> Option<InstanceBucketKey> {
+        Some((Uuid::new_v4(), Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_instance_limit() {
+        let limiter = limiter(0, 2);
+        let now = Instant::now();
+        let (first, second) = (instance(), instance());
+
+        assert!(limiter.check_at(first, now).is_ok());
+        assert!(limiter.check_at(first, now).is_ok());
+        let wait = limiter.check_at(first, now).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert!(limiter.check_at(second, now).is_ok());
+        assert!(limiter.check_at(None, now).is_ok());
+
+        assert!(limiter.check_at(first, now + Duration::from_millis(500)).is_ok());
+        assert!(limiter.check_at(first, now + Duration::from_millis(500)).is_err());
+
+        // Another organization's keys naming the same instance have their own bucket
+        let (_, instance_id) = first.unwrap();
+        assert!(limiter.check_at(Some((Uuid::new_v4(), instance_id)), now).is_ok());
+    }
+
+    #[test]
+    fn test_global_limit() {
+        let limiter = limiter(3, 0);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check_at(instance(), now).is_ok());
+        }
+        assert!(limiter.check_at(None, now).is_err());
+        assert!(limiter.check_at(None, now + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_rejected_requests_take_no_tokens() {
+        let limiter = limiter(1, 5);
+        let now = Instant::now();
+        let instance = instance();
+
+        assert!(limiter.check_at(instance, now).is_ok());
+        for _ in 0..10 {
+            assert!(limiter.check_at(instance, now).is_err());
+        }
+        let instances = limiter.instances.lock().unwrap();
+        assert_eq!(instances[&instance.unwrap()].tokens, 4.0);
+    }
+
+    #[test]
+    fn test_evict_full_buckets() {
+        let limiter = limiter(0, 2);
+        let now = Instant::now();
+        let (busy, idle) = (instance(), instance());
+
+        limiter.check_at(busy, now).unwrap();
+        limiter.check_at(busy, now).unwrap();
+        limiter.check_at(idle, now).unwrap();
+
+        // Half a second later the idle bucket is full again and the busy one is not
+        assert_eq!(limiter.evict_full_buckets(now + Duration::from_millis(500)), 1);
+        let instances = limiter.instances.lock().unwrap();
+        assert!(instances.contains_key(&busy.unwrap()));
+        assert!(!instances.contains_key(&idle.unwrap()));
+    }
+
+    #[test]
+    fn test_disabled_limits() {
+        let limiter = limiter(0, 0);
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert!(limiter.check_at(Some((Uuid::nil(), Uuid::nil())), now).is_ok());
+        }
+        assert!(limiter.instances.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_path_instance_id() {
+        let instance_id = Uuid::new_v4();
+        assert_eq!(path_instance_id(&format!("/redis/{}/get/key", instance_id)), Some(instance_id));
+        assert_eq!(path_instance_id(&format!("/redis/{}", instance_id)), Some(instance_id));
+        assert_eq!(path_instance_id("/redis/commands"), None);
+        assert_eq!(path_instance_id("/api/organizations"), None);
+    }
+}
@@ -0,0 +1,109 @@
+// Redis ACL users provisioned on managed instances.
+//
+// Each instance gets one ACL user per API key scope level, so Redis itself enforces what a
+// key may run in addition to the gateway's own checks. Passwords are derived from a gateway
+// secret and the instance ID, so the gateway can authenticate without storing them.
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AclUser {
+    Read,
+    Write,
+    Admin,
+}
+
+impl AclUser {
+    pub const ALL: [AclUser; 3] = [AclUser::Read, AclUser::Write, AclUser::Admin];
+
+    /// The most privileged user an API key's scopes allow
+    pub fn for_scopes(scopes: &[String]) -> Self {
+        if scopes.iter().any(|scope| scope == "admin") {
+            AclUser::Admin
+        } else if scopes.iter().any(|scope| scope == "write") {
+            AclUser::Write
+        } else {
+            AclUser::Read
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AclUser::Read => "gateway-read",
+            AclUser::Write => "gateway-write",
+            AclUser::Admin => "gateway-admin",
+        }
+    }
+
+    // Key patterns, channel patterns and command categories granted to the user
+    fn rules(self) -> &'static [&'static str] {
+        match self {
+            AclUser::Read => &["~*", "&*", "-@all", "+@read", "+@connection", "+select", "+info"],
+            AclUser::Write => &[
+                "~*", "&*", "-@all", "+@read", "+@write", "+@connection", "+@pubsub", "+select", "+info",
+            ],
+            AclUser::Admin => &["~*", "&*", "+@all"],
+        }
+    }
+
+    /// Password for this user on an instance
+    pub fn password(self, secret: &str, instance_id: Uuid) -> String {
+        let digest = Sha256::digest(format!("{}:{}:{}", secret, instance_id, self.name()));
+        format!("{:x}", digest)
+    }
+
+    /// Key holding the password in the instance secret
+    pub fn secret_key(self) -> String {
+        format!("acl-{}-password", self.name())
+    }
+
+    /// Container environment variable the password is exposed as
+    pub fn env_var(self) -> String {
+        format!("REDIS_ACL_{}_PASSWORD", self.name().to_uppercase().replace('-', "_"))
+    }
+}
+
+/// redis-server arguments defining the users. Passwords come from the container
+/// environment so they never appear in the pod spec.
+pub fn server_args(users: &[AclUser]) -> Vec<String> {
+    let mut args = Vec::new();
+    for user in users {
+        args.push("--user".to_string());
+        args.push(user.name().to_string());
+        args.push("on".to_string());
+        args.push(format!(">$({})", user.env_var()));
+        args.extend(user.rules().iter().map(|rule| rule.to_string()));
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acl_user_for_scopes() {
+        let scopes = |list: &[&str]| list.iter().map(|scope| scope.to_string()).collect::<Vec<_>>();
+        assert_eq!(AclUser::for_scopes(&scopes(&["read"])), AclUser::Read);
+        assert_eq!(AclUser::for_scopes(&scopes(&["metrics"])), AclUser::Read);
+        assert_eq!(AclUser::for_scopes(&scopes(&["read", "write"])), AclUser::Write);
+        assert_eq!(AclUser::for_scopes(&scopes(&["write", "admin"])), AclUser::Admin);
+    }
+
+    #[test]
+    fn test_acl_passwords_and_args() {
+        let instance_id = Uuid::new_v4();
+        let password = AclUser::Read.password("secret", instance_id);
+        assert_eq!(password.len(), 64);
+        assert_eq!(password, AclUser::Read.password("secret", instance_id));
+        assert_ne!(password, AclUser::Write.password("secret", instance_id));
+        assert_ne!(password, AclUser::Read.password("secret", Uuid::new_v4()));
+
+        let args = server_args(&[AclUser::Read]).join(" ");
+        assert_eq!(
+            args,
+            "--user gateway-read on >$(REDIS_ACL_GATEWAY_READ_PASSWORD) ~* &* -@all +@read +@connection +select +info"
+        );
+    }
+}
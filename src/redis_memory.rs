@@ -0,0 +1,473 @@
+// In-memory stand-in for Redis, used to exercise the Redis HTTP API handlers in tests
+
+use deadpool::managed::PoolError;
+use futures::future::BoxFuture;
+use redis::aio::ConnectionLike;
+use redis::{Arg, Cmd, Pipeline, RedisError, RedisFuture, RedisResult, Value};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::auth::JwtManager;
+use crate::config::{AppConfig, KubernetesConfig, RedisHandlerConfig};
+use crate::middleware::AppState;
+use crate::models::RedisInstance;
+use crate::redis_acl::AclUser;
+use crate::redis_pool::{CheckoutError, Endpoint, ExecutorConnection, RedisExecutor};
+
+#[derive(Debug, Clone)]
+enum Data {
+    String(Vec<u8>),
+    Hash(BTreeMap<Vec<u8>, Vec<u8>>),
+    List(VecDeque<Vec<u8>>),
+    Set(BTreeSet<Vec<u8>>),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    data: Data,
+    expires_at: Option<Instant>,
+}
+
+type Database = HashMap<Vec<u8>, Entry>;
+
+/// Keyspace shared by every connection of a `MemoryBackend`, one map per logical database
+#[derive(Default)]
+pub struct MemoryBackend {
+    databases: Arc<Mutex<HashMap<i64, Database>>>,
+    reject_auth: Mutex<bool>,
+}
+
+impl MemoryBackend {
+    /// Fail every checkout with WRONGPASS, as when the instance password drifted
+    pub fn reject_auth(&self, reject: bool) {
+        *self.reject_auth.lock().unwrap() = reject;
+    }
+
+    pub fn connect(&self) -> MemoryConnection {
+        MemoryConnection {
+            databases: self.databases.clone(),
+            db: 0,
+        }
+    }
+}
+
+impl RedisExecutor for MemoryBackend {
+    fn checkout<'a>(
+        &'a self,
+        _: &'a RedisInstance,
+        _: AclUser,
+        _: Endpoint,
+    ) -> BoxFuture<'a, Result<Box<dyn ExecutorConnection>, CheckoutError>> {
+        let result = if *self.reject_auth.lock().unwrap() {
+            Err(CheckoutError::Pool(PoolError::Backend(server_error("WRONGPASS invalid username-password pair"))))
+        } else {
+            Ok(Box::new(self.connect()) as Box<dyn ExecutorConnection>)
+        };
+        Box::pin(async move { result })
+    }
+}
+
+/// Connection to a `MemoryBackend`, understanding the common string, hash, list and set commands
+pub struct MemoryConnection {
+    databases: Arc<Mutex<HashMap<i64, Database>>>,
+    db: i64,
+}
+
+// Error the way Redis replies with it, so `code()` gives e.g. WRONGTYPE
+fn server_error(line: &str) -> RedisError {
+    redis::parse_redis_value(format!("-{}\r\n", line).as_bytes())
+        .and_then(|value| value.extract_error())
+        .expect_err("error replies parse to errors")
+}
+
+fn wrong_type() -> RedisError {
+    server_error("WRONGTYPE Operation against a key holding the wrong kind of value")
+}
+
+fn syntax_error() -> RedisError {
+    server_error("ERR syntax error")
+}
+
+fn bulk(bytes: &[u8]) -> Value {
+    Value::BulkString(bytes.to_vec())
+}
+
+fn parse_int(arg: &[u8]) -> RedisResult<i64> {
+    std::str::from_utf8(arg)
+        .ok()
+        .and_then(|arg| arg.parse().ok())
+        .ok_or_else(|| server_error("ERR value is not an integer or out of range"))
+}
+
+// Resolve a possibly negative LRANGE index against a list length
+fn list_index(index: i64, len: usize) -> usize {
+    if index < 0 {
+        (len as i64 + index).max(0) as usize
+    } else {
+        index as usize
+    }
+}
+
+impl MemoryConnection {
+    fn execute(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        let args: Vec<&[u8]> = cmd
+            .args_iter()
+            .filter_map(|arg| match arg {
+                Arg::Simple(arg) => Some(arg),
+                Arg::Cursor => None,
+            })
+            .collect();
+        let Some((name, args)) = args.split_first() else {
+            return Err(syntax_error());
+        };
+        let name = String::from_utf8_lossy(name).to_uppercase();
+
+        if name == "SELECT" {
+            let [db] = args else { return Err(syntax_error()) };
+            self.db = parse_int(db)?;
+            return Ok(Value::Okay);
+        }
+
+        let mut databases = self.databases.lock().unwrap();
+        let db = databases.entry(self.db).or_default();
+        let now = Instant::now();
+        db.retain(|_, entry| entry.expires_at.is_none_or(|at| at > now));
+
+        match (name.as_str(), args) {
+            ("PING", []) => Ok(Value::SimpleString("PONG".to_string())),
+            ("ECHO", [message]) => Ok(bulk(message)),
+            ("DBSIZE", []) => Ok(Value::Int(db.len() as i64)),
+            ("FLUSHDB", []) => {
+                db.clear();
+                Ok(Value::Okay)
+            }
+            ("GET", [key]) => match db.get(*key) {
+                None => Ok(Value::Nil),
+                Some(Entry { data: Data::String(value), .. }) => Ok(bulk(value)),
+                Some(_) => Err(wrong_type()),
+            },
+            ("SET", [key, value, options @ ..]) => {
+                let expires_at = match options {
+                    [] => None,
+                    [option, seconds] if option.eq_ignore_ascii_case(b"EX") => {
+                        Some(now + Duration::from_secs(parse_int(seconds)?.max(0) as u64))
+                    }
+                    _ => return Err(syntax_error()),
+                };
+                db.insert(key.to_vec(), Entry { data: Data::String(value.to_vec()), expires_at });
+                Ok(Value::Okay)
+            }
+            ("SETEX", [key, seconds, value]) => {
+                let expires_at = Some(now + Duration::from_secs(parse_int(seconds)?.max(0) as u64));
+                db.insert(key.to_vec(), Entry { data: Data::String(value.to_vec()), expires_at });
+                Ok(Value::Okay)
+            }
+            ("DEL", keys) if !keys.is_empty() => {
+                Ok(Value::Int(keys.iter().filter(|key| db.remove(**key).is_some()).count() as i64))
+            }
+            ("EXISTS", keys) if !keys.is_empty() => {
+                Ok(Value::Int(keys.iter().filter(|key| db.contains_key(**key)).count() as i64))
+            }
+            ("TYPE", [key]) => Ok(Value::SimpleString(
+                match db.get(*key).map(|entry| &entry.data) {
+                    None => "none",
+                    Some(Data::String(_)) => "string",
+                    Some(Data::Hash(_)) => "hash",
+                    Some(Data::List(_)) => "list",
+                    Some(Data::Set(_)) => "set",
+                }
+                .to_string(),
+            )),
+            ("TTL", [key]) => Ok(Value::Int(match db.get(*key) {
+                None => -2,
+                Some(Entry { expires_at: None, .. }) => -1,
+                Some(Entry { expires_at: Some(at), .. }) => at.saturating_duration_since(now).as_secs() as i64,
+            })),
+            ("EXPIRE", [key, seconds]) => {
+                let seconds = parse_int(seconds)?;
+                Ok(Value::Int(match db.get_mut(*key) {
+                    None => 0,
+                    Some(entry) => {
+                        entry.expires_at = Some(now + Duration::from_secs(seconds.max(0) as u64));
+                        1
+                    }
+                }))
+            }
+            ("INCR" | "DECR" | "INCRBY" | "DECRBY", [key, delta @ ..]) => {
+                let delta = match (name.as_str(), delta) {
+                    ("INCR", []) => 1,
+                    ("DECR", []) => -1,
+                    ("INCRBY", [delta]) => parse_int(delta)?,
+                    ("DECRBY", [delta]) => -parse_int(delta)?,
+                    _ => return Err(syntax_error()),
+                };
+                let entry = db.entry(key.to_vec()).or_insert_with(|| Entry {
+                    data: Data::String(b"0".to_vec()),
+                    expires_at: None,
+                });
+                let Data::String(value) = &mut entry.data else {
+                    return Err(wrong_type());
+                };
+                let result = parse_int(value)?
+                    .checked_add(delta)
+                    .ok_or_else(|| server_error("ERR increment or decrement would overflow"))?;
+                *value = result.to_string().into_bytes();
+                Ok(Value::Int(result))
+            }
+            ("APPEND", [key, suffix]) => {
+                let entry = db.entry(key.to_vec()).or_insert_with(|| Entry {
+                    data: Data::String(Vec::new()),
+                    expires_at: None,
+                });
+                let Data::String(value) = &mut entry.data else {
+                    return Err(wrong_type());
+                };
+                value.extend_from_slice(suffix);
+                Ok(Value::Int(value.len() as i64))
+            }
+            ("STRLEN", [key]) => match db.get(*key) {
+                None => Ok(Value::Int(0)),
+                Some(Entry { data: Data::String(value), .. }) => Ok(Value::Int(value.len() as i64)),
+                Some(_) => Err(wrong_type()),
+            },
+            ("HSET", [key, pairs @ ..]) if !pairs.is_empty() && pairs.len() % 2 == 0 => {
+                let entry = db.entry(key.to_vec()).or_insert_with(|| Entry {
+                    data: Data::Hash(BTreeMap::new()),
+                    expires_at: None,
+                });
+                let Data::Hash(hash) = &mut entry.data else {
+                    return Err(wrong_type());
+                };
+                let added = pairs
+                    .chunks(2)
+                    .filter(|pair| hash.insert(pair[0].to_vec(), pair[1].to_vec()).is_none())
+                    .count();
+                Ok(Value::Int(added as i64))
+            }
+            ("HGET" | "HEXISTS" | "HDEL" | "HGETALL" | "HKEYS" | "HVALS" | "HLEN", [key, fields @ ..]) => {
+                let hash = match db.get_mut(*key) {
+                    None => None,
+                    Some(Entry { data: Data::Hash(hash), .. }) => Some(hash),
+                    Some(_) => return Err(wrong_type()),
+                };
+                let empty = &mut BTreeMap::new();
+                let hash = hash.unwrap_or(empty);
+                match (name.as_str(), fields) {
+                    ("HGET", [field]) => Ok(hash.get(*field).map_or(Value::Nil, |value| bulk(value))),
+                    ("HEXISTS", [field]) => Ok(Value::Int(hash.contains_key(*field) as i64)),
+                    ("HDEL", fields) if !fields.is_empty() => {
+                        Ok(Value::Int(fields.iter().filter(|field| hash.remove(**field).is_some()).count() as i64))
+                    }
+                    ("HGETALL", []) => Ok(Value::Array(
+                        hash.iter().flat_map(|(field, value)| [bulk(field), bulk(value)]).collect(),
+                    )),
+                    ("HKEYS", []) => Ok(Value::Array(hash.keys().map(|field| bulk(field)).collect())),
+                    ("HVALS", []) => Ok(Value::Array(hash.values().map(|value| bulk(value)).collect())),
+                    ("HLEN", []) => Ok(Value::Int(hash.len() as i64)),
+                    _ => Err(syntax_error()),
+                }
+            }
+            ("LPUSH" | "RPUSH", [key, values @ ..]) if !values.is_empty() => {
+                let entry = db.entry(key.to_vec()).or_insert_with(|| Entry {
+                    data: Data::List(VecDeque::new()),
+                    expires_at: None,
+                });
+                let Data::List(list) = &mut entry.data else {
+                    return Err(wrong_type());
+                };
+                for value in values {
+                    if name == "LPUSH" {
+                        list.push_front(value.to_vec());
+                    } else {
+                        list.push_back(value.to_vec());
+                    }
+                }
+                Ok(Value::Int(list.len() as i64))
+            }
+            ("LPOP" | "RPOP" | "LLEN" | "LRANGE", [key, rest @ ..]) => {
+                let list = match db.get_mut(*key) {
+                    None => None,
+                    Some(Entry { data: Data::List(list), .. }) => Some(list),
+                    Some(_) => return Err(wrong_type()),
+                };
+                let empty = &mut VecDeque::new();
+                let list = list.unwrap_or(empty);
+                match (name.as_str(), rest) {
+                    ("LPOP", []) => Ok(list.pop_front().map_or(Value::Nil, Value::BulkString)),
+                    ("RPOP", []) => Ok(list.pop_back().map_or(Value::Nil, Value::BulkString)),
+                    ("LLEN", []) => Ok(Value::Int(list.len() as i64)),
+                    ("LRANGE", [start, stop]) => {
+                        let start = list_index(parse_int(start)?, list.len());
+                        let stop = list_index(parse_int(stop)?, list.len()).min(list.len().saturating_sub(1));
+                        Ok(Value::Array(
+                            list.iter()
+                                .enumerate()
+                                .filter(|(i, _)| !list.is_empty() && (start..=stop).contains(i))
+                                .map(|(_, value)| bulk(value))
+                                .collect(),
+                        ))
+                    }
+                    _ => Err(syntax_error()),
+                }
+            }
+            ("SADD", [key, members @ ..]) if !members.is_empty() => {
+                let entry = db.entry(key.to_vec()).or_insert_with(|| Entry {
+                    data: Data::Set(BTreeSet::new()),
+                    expires_at: None,
+                });
+                let Data::Set(set) = &mut entry.data else {
+                    return Err(wrong_type());
+                };
+                Ok(Value::Int(members.iter().filter(|member| set.insert(member.to_vec())).count() as i64))
+            }
+            ("SREM" | "SMEMBERS" | "SISMEMBER" | "SCARD", [key, members @ ..]) => {
+                let set = match db.get_mut(*key) {
+                    None => None,
+                    Some(Entry { data: Data::Set(set), .. }) => Some(set),
+                    Some(_) => return Err(wrong_type()),
+                };
+                let empty = &mut BTreeSet::new();
+                let set = set.unwrap_or(empty);
+                match (name.as_str(), members) {
+                    ("SREM", members) if !members.is_empty() => {
+                        Ok(Value::Int(members.iter().filter(|member| set.remove(**member)).count() as i64))
+                    }
+                    ("SMEMBERS", []) => Ok(Value::Array(set.iter().map(|member| bulk(member)).collect())),
+                    ("SISMEMBER", [member]) => Ok(Value::Int(set.contains(*member) as i64)),
+                    ("SCARD", []) => Ok(Value::Int(set.len() as i64)),
+                    _ => Err(syntax_error()),
+                }
+            }
+            (_, _) if is_known_command(&name) => Err(server_error(&format!(
+                "ERR wrong number of arguments for '{}' command",
+                name.to_lowercase()
+            ))),
+            _ => Err(server_error(&format!("ERR unknown command '{}'", name.to_lowercase()))),
+        }
+    }
+}
+
+fn is_known_command(name: &str) -> bool {
+    [
+        "PING", "ECHO", "DBSIZE", "FLUSHDB", "GET", "SET", "SETEX", "DEL", "EXISTS", "TYPE", "TTL", "EXPIRE",
+        "INCR", "DECR", "INCRBY", "DECRBY", "APPEND", "STRLEN", "HSET", "HGET", "HEXISTS", "HDEL", "HGETALL",
+        "HKEYS", "HVALS", "HLEN", "LPUSH", "RPUSH", "LPOP", "RPOP", "LLEN", "LRANGE", "SADD", "SREM", "SMEMBERS",
+        "SISMEMBER", "SCARD",
+    ]
+    .contains(&name)
+}
+
+impl ConnectionLike for MemoryConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        let result = self.execute(cmd);
+        Box::pin(async move { result })
+    }
+
+    // Pipelines run command by command; MULTI/EXEC are not emulated, so atomic pipelines are not supported
+    fn req_packed_commands<'a>(&'a mut self, pipeline: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        let result = pipeline
+            .cmd_iter()
+            .map(|cmd| self.execute(cmd))
+            .collect::<RedisResult<Vec<Value>>>()
+            .map(|values| values.into_iter().skip(offset).take(count).collect());
+        Box::pin(async move { result })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.db
+    }
+}
+
+impl ExecutorConnection for MemoryConnection {
+    fn select_db(&mut self, db: i64) -> RedisFuture<'_, ()> {
+        self.db = db;
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Application state whose Redis commands run against the given in-memory backend.
+/// The database pool is lazy and never connected, so handlers that query Postgres fail.
+pub fn test_state(backend: Arc<MemoryBackend>) -> AppState {
+    let db_pool = PgPool::connect_lazy("postgres://localhost/redisgate_test").expect("static URL parses");
+    let config = AppConfig {
+        redis: RedisHandlerConfig::default(),
+        kubernetes: KubernetesConfig::default(),
+        ..AppConfig::from_env()
+    };
+    let mut state = AppState::new(db_pool, JwtManager::new("test-secret"), config);
+    state.redis_executor = backend;
+    state
+}
+
+/// A running instance owned by `organization_id`
+pub fn test_instance(organization_id: Uuid) -> RedisInstance {
+    RedisInstance {
+        id: Uuid::new_v4(),
+        name: "test".to_string(),
+        slug: "test".to_string(),
+        organization_id,
+        api_key_id: None,
+        port: Some(6379),
+        private_ip_address: None,
+        public_ip_address: None,
+        domain: None,
+        max_memory: None,
+        current_memory: None,
+        password_hash: None,
+        redis_version: Some("7.2".to_string()),
+        namespace: None,
+        pod_name: None,
+        service_name: None,
+        replica_service_name: None,
+        status: Some("running".to_string()),
+        status_reason: None,
+        last_health_check_at: None,
+        health_status: None,
+        cpu_usage_percent: None,
+        memory_usage_percent: None,
+        connections_count: None,
+        max_connections: 1000,
+        eviction_policy: None,
+        tls_enabled: false,
+        acl_enabled: false,
+        max_concurrent_commands: None,
+        max_value_bytes: None,
+        persistence_enabled: None,
+        backup_enabled: None,
+        last_backup_at: None,
+        created_at: None,
+        updated_at: None,
+        deleted_at: None,
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_connection() {
+        let backend = MemoryBackend::default();
+        let mut conn = backend.connect();
+
+        let _: () = redis::cmd("SET").arg("counter").arg(41).query_async(&mut conn).await.unwrap();
+        let counter: i64 = redis::cmd("INCR").arg("counter").query_async(&mut conn).await.unwrap();
+        assert_eq!(counter, 42);
+
+        let _: () = redis::cmd("RPUSH").arg("list").arg(vec!["a", "b", "c"]).query_async(&mut conn).await.unwrap();
+        let items: Vec<String> = redis::cmd("LRANGE").arg("list").arg(1).arg(-1).query_async(&mut conn).await.unwrap();
+        assert_eq!(items, ["b", "c"]);
+
+        let error = redis::cmd("HGET").arg("list").arg("field").query_async::<()>(&mut conn).await.unwrap_err();
+        assert_eq!(error.code(), Some("WRONGTYPE"));
+
+        // Databases are separate, and other connections share the keyspace
+        let _: () = redis::cmd("SELECT").arg(1).query_async(&mut conn).await.unwrap();
+        let exists: bool = redis::cmd("EXISTS").arg("counter").query_async(&mut conn).await.unwrap();
+        assert!(!exists);
+        let counter: i64 = redis::cmd("GET").arg("counter").query_async(&mut backend.connect()).await.unwrap();
+        assert_eq!(counter, 42);
+    }
+}
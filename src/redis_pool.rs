@@ -0,0 +1,1176 @@
+// Per-instance Redis connection pools for the Redis HTTP API
+
+use deadpool::managed::{self, Metrics, Pool, PoolError, RecycleError, RecycleResult, TimeoutType};
+use deadpool::Runtime;
+use futures::future::BoxFuture;
+use redis::aio::{ConnectionLike, MultiplexedConnection, PubSub};
+use redis::{Arg, Client, Cmd, ConnectionAddr, ErrorKind, Pipeline, RedisError, RedisFuture, RedisResult, Value};
+use serde::Serialize;
+use socket2::{SockRef, TcpKeepalive};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+use crate::config::RedisHandlerConfig;
+use crate::handlers::redis::{command_access, CommandAccess};
+use crate::k8s_service::KubernetesClient;
+use crate::models::RedisInstance;
+use crate::redis_acl::AclUser;
+
+pub type RedisPool = Pool<RedisConnectionManager>;
+pub type PooledConnection = managed::Object<RedisConnectionManager>;
+
+/// Which of an instance's servers a connection goes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endpoint {
+    Primary,
+    Replica, // The instance's read-only replicas; only used for instances that have them
+}
+
+/// CLIENT SETNAME label of a pooled connection: `{gateway}:{instance_id}:{acl user}:{endpoint}`.
+/// Client names cannot contain spaces or newlines, so those in the gateway label are replaced.
+fn connection_name(gateway: &str, instance_id: Uuid, user: AclUser, endpoint: Endpoint) -> String {
+    let gateway: String = gateway.chars().map(|c| if c.is_ascii_graphic() { c } else { '-' }).collect();
+    let endpoint = match endpoint {
+        Endpoint::Primary => "primary",
+        Endpoint::Replica => "replica",
+    };
+    format!("{}:{}:{}:{}", gateway, instance_id, user.name(), endpoint)
+}
+
+// The development Redis serving instances that are not deployed to Kubernetes
+const LOCAL_REDIS_HOST: &str = "127.0.0.1:6379";
+
+/// Where the gateway reaches an instance's server, and the password of its default user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceAddress {
+    pub host: String,             // host:port
+    pub password: Option<String>, // None for the development Redis, which has no password
+}
+
+impl InstanceAddress {
+    fn local() -> Self {
+        Self {
+            host: LOCAL_REDIS_HOST.to_string(),
+            password: None,
+        }
+    }
+}
+
+// Percent-encode a URL userinfo component; generated passwords contain characters like @ and #
+fn encode_userinfo(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect()
+}
+
+// Connection URL for an instance; TLS-enabled instances use rediss://, instances with ACL
+// users are accessed as the given user, and others with their default user's password
+fn instance_url(
+    instance: &RedisInstance,
+    user: AclUser,
+    address: &InstanceAddress,
+    config: &RedisHandlerConfig,
+) -> String {
+    let host = &address.host;
+    let credentials = match (&config.acl_secret, &address.password) {
+        (Some(secret), _) if instance.acl_enabled => {
+            format!("{}:{}@", user.name(), encode_userinfo(&user.password(secret, instance.id)))
+        }
+        (_, Some(password)) => format!(":{}@", encode_userinfo(password)),
+        _ => String::new(),
+    };
+    if !instance.tls_enabled {
+        return format!("redis://{}{}/", credentials, host);
+    }
+
+    // Instance certificates are often issued by a cluster-internal CA the gateway does not trust
+    let fragment = if config.tls_insecure { "#insecure" } else { "" };
+    format!("rediss://{}{}/{}", credentials, host, fragment)
+}
+
+/// Whether Redis rejected the gateway's credentials (NOAUTH or WRONGPASS)
+pub fn is_auth_error(error: &RedisError) -> bool {
+    error.kind() == ErrorKind::AuthenticationFailed || matches!(error.code(), Some("NOAUTH") | Some("WRONGPASS"))
+}
+
+/// Why a connection could not be checked out
+#[derive(Debug)]
+pub enum CheckoutError {
+    /// The endpoint's circuit breaker is open after repeated connection failures
+    CircuitOpen,
+    Pool(PoolError<RedisError>),
+}
+
+impl From<PoolError<RedisError>> for CheckoutError {
+    fn from(error: PoolError<RedisError>) -> Self {
+        CheckoutError::Pool(error)
+    }
+}
+
+impl From<RedisError> for CheckoutError {
+    fn from(error: RedisError) -> Self {
+        CheckoutError::Pool(PoolError::Backend(error))
+    }
+}
+
+impl fmt::Display for CheckoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckoutError::CircuitOpen => write!(f, "circuit breaker open after too many recent connection failures"),
+            CheckoutError::Pool(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Consecutive connection failures of one instance endpoint. After `threshold` of them the breaker
+/// opens and checkouts fail fast for the cooldown; then a single probe is let through
+/// (half-open), which closes the breaker on success or reopens it on failure.
+#[derive(Debug, Default)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+    probe_until: Option<Instant>, // A half-open probe is in flight; expires in case it is abandoned
+}
+
+impl Breaker {
+    // Whether a checkout may try to connect now
+    fn allow(&mut self, cooldown: Duration, now: Instant) -> bool {
+        match self.open_until {
+            None => true,
+            Some(until) if now < until => false,
+            Some(_) if self.probe_until.is_some_and(|until| now < until) => false,
+            Some(_) => {
+                self.probe_until = Some(now + cooldown);
+                true
+            }
+        }
+    }
+
+    // Count a failure; returns whether it opened the breaker
+    fn record_failure(&mut self, threshold: u32, cooldown: Duration, now: Instant) -> bool {
+        self.failures += 1;
+        let probe_failed = self.probe_until.take().is_some();
+        let opened = probe_failed || (self.open_until.is_none() && self.failures >= threshold);
+        if opened {
+            self.open_until = Some(now + cooldown);
+        }
+        opened
+    }
+}
+
+/// Opens a fresh connection to replace one the server dropped
+pub trait Reconnect<C>: Send + Sync {
+    fn reconnect(&self) -> RedisFuture<'_, C>;
+}
+
+/// Opens multiplexed connections to one instance with the configured TCP keepalive, naming
+/// them so they can be told apart in the server's CLIENT LIST
+pub struct Connector {
+    client: Client,
+    tcp_keepalive: Option<Duration>,
+    client_name: Option<String>,
+    client_setinfo: bool,
+}
+
+impl Connector {
+    pub fn new(client: Client, tcp_keepalive: Option<Duration>) -> Self {
+        Self {
+            client,
+            tcp_keepalive,
+            client_name: None,
+            client_setinfo: false,
+        }
+    }
+
+    /// Name new connections with CLIENT SETNAME, and report the gateway as the client
+    /// library with CLIENT SETINFO when `setinfo` is set
+    pub fn with_identity(mut self, client_name: Option<String>, setinfo: bool) -> Self {
+        self.client_name = client_name;
+        self.client_setinfo = setinfo;
+        self
+    }
+
+    async fn connect(&self) -> RedisResult<MultiplexedConnection> {
+        let mut conn = self.open().await?;
+        self.identify(&mut conn).await;
+        Ok(conn)
+    }
+
+    // Naming is best effort: CLIENT SETINFO needs Redis 7.2, and a connection that works is
+    // not worth giving up over a label
+    async fn identify(&self, conn: &mut MultiplexedConnection) {
+        if let Some(name) = &self.client_name {
+            if let Err(e) = redis::cmd("CLIENT").arg("SETNAME").arg(name).query_async::<()>(&mut *conn).await {
+                tracing::debug!("CLIENT SETNAME {} failed: {}", name, e);
+            }
+        }
+        if self.client_setinfo {
+            for (attribute, value) in [("LIB-NAME", "redisgate"), ("LIB-VER", env!("CARGO_PKG_VERSION"))] {
+                if let Err(e) = redis::cmd("CLIENT").arg("SETINFO").arg(attribute).arg(value).query_async::<()>(&mut *conn).await {
+                    tracing::debug!("CLIENT SETINFO {} failed: {}", attribute, e);
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn open(&self) -> RedisResult<MultiplexedConnection> {
+        let info = self.client.get_connection_info();
+        let (ConnectionAddr::Tcp(host, port), Some(keepalive)) = (&info.addr, self.tcp_keepalive) else {
+            // TLS connections are set up by redis-rs, which enables keepalive with the system defaults
+            return self.client.get_multiplexed_async_connection().await;
+        };
+
+        let stream = TcpStream::connect((host.as_str(), *port)).await?;
+        stream.set_nodelay(true)?;
+        // Probe idle connections so ones silently dropped by a load balancer or NAT are detected
+        SockRef::from(&stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(keepalive).with_interval(keepalive))?;
+
+        let (conn, driver) = MultiplexedConnection::new(&info.redis, stream).await?;
+        tokio::spawn(driver);
+        Ok(conn)
+    }
+}
+
+impl Reconnect<MultiplexedConnection> for Connector {
+    fn reconnect(&self) -> RedisFuture<'_, MultiplexedConnection> {
+        Box::pin(self.connect())
+    }
+}
+
+/// A connection that transparently reconnects and retries a read-only command once if the
+/// server dropped the connection (broken pipe, connection reset).
+///
+/// A dropped write may already have been applied, so writes, pass-through commands and
+/// pipelines (which carry MULTI/EXEC transactions) fail instead; the pool then discards the
+/// connection. Redis errors such as `WRONGTYPE` are returned as-is and never retried.
+pub struct RetryingConnection<C = MultiplexedConnection> {
+    conn: C,
+    db: i64,
+    reconnector: Arc<dyn Reconnect<C>>,
+}
+
+impl<C: ConnectionLike + Send> RetryingConnection<C> {
+    pub fn new(conn: C, reconnector: Arc<dyn Reconnect<C>>) -> Self {
+        Self {
+            conn,
+            db: 0,
+            reconnector,
+        }
+    }
+
+    /// Switch the logical database; a replacement connection selects it again. Fails unless
+    /// Redis answers OK, such as when the connection was left inside a transaction and queues
+    /// the command instead.
+    pub async fn select_db(&mut self, db: i64) -> RedisResult<()> {
+        let reply: Value = redis::cmd("SELECT").arg(db).query_async(&mut self.conn).await?;
+        if reply != Value::Okay {
+            return Err((ErrorKind::ResponseError, "SELECT did not reply OK", format!("{:?}", reply)).into());
+        }
+        self.db = db;
+        Ok(())
+    }
+
+    async fn replace_connection(&mut self, error: &RedisError) -> RedisResult<()> {
+        tracing::warn!("Redis connection dropped ({}); retrying on a new connection", error);
+        let mut conn = self.reconnector.reconnect().await?;
+        if self.db != 0 {
+            redis::cmd("SELECT").arg(self.db).query_async::<()>(&mut conn).await?;
+        }
+        self.conn = conn;
+        Ok(())
+    }
+}
+
+impl<C: ConnectionLike + Send> ConnectionLike for RetryingConnection<C> {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(async move {
+            match self.conn.req_packed_command(cmd).await {
+                Err(e) if e.is_connection_dropped() && is_read_only(cmd) => {
+                    self.replace_connection(&e).await?;
+                    self.conn.req_packed_command(cmd).await
+                }
+                result => result,
+            }
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        self.conn.req_packed_commands(cmd, offset, count)
+    }
+
+    fn get_db(&self) -> i64 {
+        self.db
+    }
+}
+
+// Whether a command only reads, so sending it again after a dropped connection changes nothing
+fn is_read_only(cmd: &Cmd) -> bool {
+    match cmd.args_iter().next() {
+        Some(Arg::Simple(name)) => {
+            std::str::from_utf8(name).ok().and_then(command_access) == Some(CommandAccess::Read)
+        }
+        _ => false,
+    }
+}
+
+/// Creates and recycles connections to a single Redis instance
+pub struct RedisConnectionManager {
+    connector: Arc<Connector>,
+    idle_timeout: Duration,
+}
+
+impl managed::Manager for RedisConnectionManager {
+    type Type = RetryingConnection;
+    type Error = RedisError;
+
+    async fn create(&self) -> Result<RetryingConnection, RedisError> {
+        let conn = self.connector.connect().await?;
+        Ok(RetryingConnection::new(conn, self.connector.clone()))
+    }
+
+    async fn recycle(&self, conn: &mut RetryingConnection, metrics: &Metrics) -> RecycleResult<RedisError> {
+        if is_idle_expired(metrics, self.idle_timeout) {
+            return Err(RecycleError::message("connection idle for too long"));
+        }
+        // Requests may switch the logical database; reset it before the connection is reused.
+        // This doubles as a health check for idle connections, and discards connections left
+        // inside a transaction, where SELECT is queued rather than run.
+        conn.select_db(0).await?;
+        Ok(())
+    }
+}
+
+// Connections unused this long may have been dropped by the network without either side noticing
+fn is_idle_expired(metrics: &Metrics, idle_timeout: Duration) -> bool {
+    metrics.last_used() >= idle_timeout
+}
+
+/// A connection the gateway runs one request's commands on
+pub trait ExecutorConnection: ConnectionLike + Send {
+    /// Switch the logical database for the rest of the request
+    fn select_db(&mut self, db: i64) -> RedisFuture<'_, ()>;
+}
+
+// Returned to its pool when dropped
+struct PooledExecutorConnection(PooledConnection);
+
+impl ConnectionLike for PooledExecutorConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        self.0.req_packed_command(cmd)
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        self.0.req_packed_commands(cmd, offset, count)
+    }
+
+    fn get_db(&self) -> i64 {
+        self.0.get_db()
+    }
+}
+
+impl ExecutorConnection for PooledExecutorConnection {
+    fn select_db(&mut self, db: i64) -> RedisFuture<'_, ()> {
+        Box::pin(self.0.select_db(db))
+    }
+}
+
+/// Hands out the connections the Redis HTTP API runs commands on. The instance pools
+/// implement it; tests substitute an in-memory backend so handlers run without a live Redis.
+pub trait RedisExecutor: Send + Sync {
+    /// Check out a connection to an instance, authenticated as the given ACL user
+    fn checkout<'a>(
+        &'a self,
+        instance: &'a RedisInstance,
+        user: AclUser,
+        endpoint: Endpoint,
+    ) -> BoxFuture<'a, Result<Box<dyn ExecutorConnection>, CheckoutError>>;
+}
+
+/// Saturation of one instance's pool for an ACL user, taken from deadpool's `status()`
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub instance_id: Uuid,
+    pub acl_user: &'static str,
+    pub endpoint: Endpoint,
+    pub max_size: usize,
+    pub size: usize,      // Connections currently open
+    pub available: usize, // Open connections idle in the pool
+    pub in_use: usize,    // Connections checked out by requests
+    pub waiting: usize,   // Requests waiting for a connection; persistently high means the pool is undersized
+}
+
+// A pool with the time it was last checked out from, so pools of unused instances can be dropped
+struct CachedPool {
+    pool: RedisPool,
+    last_access: Mutex<Instant>,
+}
+
+/// Lazily creates one pool per Redis instance, ACL user and endpoint
+pub struct RedisPoolManager {
+    config: RedisHandlerConfig,
+    kubernetes: Arc<KubernetesClient>, // Reads the passwords of deployed instances from their Secrets
+    pools: RwLock<HashMap<(Uuid, AclUser, Endpoint), CachedPool>>,
+    breakers: Mutex<HashMap<(Uuid, Endpoint), Breaker>>, // Only endpoints with recent connection failures
+}
+
+impl RedisPoolManager {
+    pub fn new(config: RedisHandlerConfig, kubernetes: Arc<KubernetesClient>) -> Self {
+        Self {
+            config,
+            kubernetes,
+            pools: RwLock::new(HashMap::new()),
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check out a connection to an instance's primary, authenticated as the given ACL user
+    pub async fn connection(
+        &self,
+        instance: &RedisInstance,
+        user: AclUser,
+    ) -> Result<PooledConnection, CheckoutError> {
+        self.endpoint_connection(instance, user, Endpoint::Primary).await
+    }
+
+    /// Check out a connection to the given endpoint of an instance. Replica connections of
+    /// instances without replicas come from the primary pool. Each endpoint has its own
+    /// circuit breaker, so failing replicas do not take down the primary.
+    pub async fn endpoint_connection(
+        &self,
+        instance: &RedisInstance,
+        user: AclUser,
+        endpoint: Endpoint,
+    ) -> Result<PooledConnection, CheckoutError> {
+        let endpoint = if instance.replica_service_name.is_some() { endpoint } else { Endpoint::Primary };
+        if !self.breaker_allows(instance.id, endpoint) {
+            return Err(CheckoutError::CircuitOpen);
+        }
+
+        let pool = match self.cached_pool(&(instance.id, user, endpoint)) {
+            Some(pool) => pool,
+            None => {
+                let address = self.instance_address(instance, endpoint).await?;
+                self.pool_for(instance.id, user, endpoint, &instance_url(instance, user, &address, &self.config))?
+            }
+        };
+        let result = pool.get().await;
+        match &result {
+            Ok(_) => self.record_success(instance.id, endpoint),
+            // Waiting for a busy pool says nothing about the server's health
+            Err(PoolError::Timeout(TimeoutType::Wait)) => {}
+            Err(_) => self.record_failure(instance, endpoint),
+        }
+        match result {
+            Err(PoolError::Backend(e)) if is_auth_error(&e) => {
+                // The instance password drifted from the one the gateway holds. Drop the pool so
+                // the next request rebuilds it with freshly loaded credentials.
+                tracing::error!(
+                    "Redis credential mismatch for instance {} ({}): {}; evicting its connection pool",
+                    instance.id,
+                    instance.slug,
+                    e
+                );
+                self.evict(instance.id);
+                Err(PoolError::Backend(e).into())
+            }
+            result => Ok(result?),
+        }
+    }
+
+    // Whether the circuit breaker of an instance endpoint lets a checkout through
+    fn breaker_allows(&self, instance_id: Uuid, endpoint: Endpoint) -> bool {
+        match self.breakers.lock().unwrap().get_mut(&(instance_id, endpoint)) {
+            Some(breaker) => breaker.allow(self.config.breaker_cooldown, Instant::now()),
+            None => true,
+        }
+    }
+
+    fn record_success(&self, instance_id: Uuid, endpoint: Endpoint) {
+        let mut breakers = self.breakers.lock().unwrap();
+        if let Some(breaker) = breakers.remove(&(instance_id, endpoint)) {
+            if breaker.open_until.is_some() {
+                tracing::info!("Circuit breaker for instance {} {:?} closed", instance_id, endpoint);
+            }
+        }
+    }
+
+    fn record_failure(&self, instance: &RedisInstance, endpoint: Endpoint) {
+        let threshold = self.config.breaker_failure_threshold;
+        if threshold == 0 {
+            return;
+        }
+
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers.entry((instance.id, endpoint)).or_default();
+        if breaker.record_failure(threshold, self.config.breaker_cooldown, Instant::now()) {
+            tracing::warn!(
+                "Circuit breaker for instance {} ({}) {:?} opened after {} consecutive connection failures; \
+                 failing requests for {:?}",
+                instance.id,
+                instance.slug,
+                endpoint,
+                breaker.failures,
+                self.config.breaker_cooldown
+            );
+        }
+    }
+
+    /// Resolve where an instance's server is and its password: the instance's Service (or its
+    /// replicas' Service) and Secret once it is deployed, the development Redis for simulated
+    /// instances. Pools are created with the result, so evicting them makes the next checkout
+    /// read the Secret again.
+    pub async fn instance_address(&self, instance: &RedisInstance, endpoint: Endpoint) -> Result<InstanceAddress, RedisError> {
+        let service_name = match endpoint {
+            Endpoint::Primary => &instance.service_name,
+            Endpoint::Replica => &instance.replica_service_name,
+        };
+        let (Some(namespace), Some(service_name)) = (&instance.namespace, service_name) else {
+            return Ok(InstanceAddress::local());
+        };
+        if instance.status.as_deref() == Some("simulation") {
+            return Ok(InstanceAddress::local());
+        }
+
+        let unavailable = |detail: String| RedisError::from((ErrorKind::IoError, "Instance credentials unavailable", detail));
+        let k8s = self.kubernetes.service().map_err(|e| unavailable(e.to_string()))?;
+        let password = k8s
+            .redis_password(namespace, &instance.slug)
+            .await
+            .map_err(|e| unavailable(format!("failed to read the Secret of instance {}: {}", instance.id, e)))?;
+        Ok(InstanceAddress {
+            host: format!("{}.{}.svc:{}", service_name, namespace, instance.port.unwrap_or(6379)),
+            password,
+        })
+    }
+
+    /// Open a dedicated (unpooled) Pub/Sub connection to an instance
+    pub async fn pubsub(&self, instance: &RedisInstance, user: AclUser) -> Result<PubSub, RedisError> {
+        let address = self.instance_address(instance, Endpoint::Primary).await?;
+        Client::open(instance_url(instance, user, &address, &self.config))?.get_async_pubsub().await
+    }
+
+    /// Drop an instance's pools; idle connections close and the next checkout creates new pools
+    pub fn evict(&self, instance_id: Uuid) {
+        self.pools.write().unwrap().retain(|(id, _, _), _| *id != instance_id);
+    }
+
+    /// Close idle connections that exceeded the idle timeout, across all pools.
+    /// Returns how many connections were closed.
+    pub fn close_idle_connections(&self) -> usize {
+        let idle_timeout = self.config.pool_idle_timeout;
+        self.pools
+            .read()
+            .unwrap()
+            .values()
+            .map(|cached| cached.pool.retain(|_, metrics| !is_idle_expired(&metrics, idle_timeout)).removed.len())
+            .sum()
+    }
+
+    /// Drop pools nobody checked out from within the eviction window, closing their idle
+    /// connections; the next checkout creates the pool again. Pools with connections still
+    /// checked out are kept. Returns the instances left without any pool.
+    pub fn evict_idle_pools(&self, now: Instant) -> Vec<Uuid> {
+        let Some(evict_after) = self.config.pool_evict_after else {
+            return Vec::new();
+        };
+        let mut pools = self.pools.write().unwrap();
+        let mut evicted = Vec::new();
+        pools.retain(|(instance_id, _, _), cached| {
+            let idle = now.saturating_duration_since(*cached.last_access.lock().unwrap());
+            let status = cached.pool.status();
+            let keep = idle < evict_after || status.size > status.available;
+            if !keep {
+                evicted.push(*instance_id);
+            }
+            keep
+        });
+        evicted.sort();
+        evicted.dedup();
+        evicted.retain(|instance_id| !pools.keys().any(|(id, _, _)| id == instance_id));
+        evicted
+    }
+
+    /// Periodically close expired idle connections, so they are replaced before a request
+    /// finds them broken rather than when it checks them out, and drop the pools, and
+    /// concurrency limits, of instances that are no longer used
+    pub async fn reap_idle_connections(self: Arc<Self>, limiter: Arc<InstanceLimiter>) {
+        let mut interval = tokio::time::interval((self.config.pool_idle_timeout / 4).max(Duration::from_secs(1)));
+        loop {
+            interval.tick().await;
+            let closed = self.close_idle_connections();
+            if closed > 0 {
+                tracing::debug!("Closed {} idle Redis connections", closed);
+            }
+            let evicted = self.evict_idle_pools(Instant::now());
+            if !evicted.is_empty() {
+                tracing::debug!("Evicted the Redis connection pools of {} unused instances", evicted.len());
+                limiter.remove_idle(&evicted);
+            }
+        }
+    }
+
+    /// Current stats of every pool, ordered by instance, ACL user and endpoint
+    pub fn stats(&self) -> Vec<PoolStats> {
+        let mut stats: Vec<PoolStats> = self
+            .pools
+            .read()
+            .unwrap()
+            .iter()
+            .map(|((instance_id, user, endpoint), cached)| {
+                let status = cached.pool.status();
+                PoolStats {
+                    instance_id: *instance_id,
+                    acl_user: user.name(),
+                    endpoint: *endpoint,
+                    max_size: status.max_size,
+                    size: status.size,
+                    available: status.available,
+                    in_use: status.size.saturating_sub(status.available),
+                    waiting: status.waiting,
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| (a.instance_id, a.acl_user, a.endpoint).cmp(&(b.instance_id, b.acl_user, b.endpoint)));
+        stats
+    }
+
+    // The existing pool for an instance, ACL user and endpoint, marked as used
+    fn cached_pool(&self, key: &(Uuid, AclUser, Endpoint)) -> Option<RedisPool> {
+        let pools = self.pools.read().unwrap();
+        let cached = pools.get(key)?;
+        *cached.last_access.lock().unwrap() = Instant::now();
+        Some(cached.pool.clone())
+    }
+
+    /// Get the pool for an instance, ACL user and endpoint, creating it on first use
+    pub fn pool_for(
+        &self,
+        instance_id: Uuid,
+        user: AclUser,
+        endpoint: Endpoint,
+        redis_url: &str,
+    ) -> Result<RedisPool, RedisError> {
+        let key = (instance_id, user, endpoint);
+        if let Some(pool) = self.cached_pool(&key) {
+            return Ok(pool);
+        }
+
+        let mut pools = self.pools.write().unwrap();
+        if let Some(cached) = pools.get(&key) {
+            *cached.last_access.lock().unwrap() = Instant::now();
+            return Ok(cached.pool.clone());
+        }
+
+        let client_name = self
+            .config
+            .client_name
+            .as_deref()
+            .map(|gateway| connection_name(gateway, instance_id, user, endpoint));
+        let connector = Connector::new(Client::open(redis_url)?, self.config.tcp_keepalive)
+            .with_identity(client_name, self.config.client_setinfo);
+        let manager = RedisConnectionManager {
+            connector: Arc::new(connector),
+            idle_timeout: self.config.pool_idle_timeout,
+        };
+        let pool = Pool::builder(manager)
+            .max_size(self.config.pool_max_size)
+            .wait_timeout(Some(self.config.pool_timeout))
+            .create_timeout(Some(self.config.connect_timeout))
+            .recycle_timeout(Some(self.config.pool_recycle_timeout))
+            .runtime(Runtime::Tokio1)
+            .build()
+            .expect("runtime is configured, so building the pool cannot fail");
+
+        pools.insert(
+            key,
+            CachedPool {
+                pool: pool.clone(),
+                last_access: Mutex::new(Instant::now()),
+            },
+        );
+        Ok(pool)
+    }
+}
+
+impl RedisExecutor for RedisPoolManager {
+    fn checkout<'a>(
+        &'a self,
+        instance: &'a RedisInstance,
+        user: AclUser,
+        endpoint: Endpoint,
+    ) -> BoxFuture<'a, Result<Box<dyn ExecutorConnection>, CheckoutError>> {
+        Box::pin(async move {
+            let conn = self.endpoint_connection(instance, user, endpoint).await?;
+            Ok(Box::new(PooledExecutorConnection(conn)) as Box<dyn ExecutorConnection>)
+        })
+    }
+}
+
+/// A checked-out connection holding one of its instance's concurrency permits
+pub struct LimitedConnection {
+    conn: Box<dyn ExecutorConnection>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl LimitedConnection {
+    pub fn new(conn: Box<dyn ExecutorConnection>, permit: OwnedSemaphorePermit) -> Self {
+        Self { conn, _permit: permit }
+    }
+}
+
+impl ConnectionLike for LimitedConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        self.conn.req_packed_command(cmd)
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        self.conn.req_packed_commands(cmd, offset, count)
+    }
+
+    fn get_db(&self) -> i64 {
+        self.conn.get_db()
+    }
+}
+
+/// Bounds in-flight commands per instance, so one busy instance cannot take up
+/// all of the gateway's capacity
+pub struct InstanceLimiter {
+    default_limit: usize,
+    semaphores: RwLock<HashMap<Uuid, InstanceSemaphore>>,
+}
+
+// An instance's semaphore, holding `limit` permits once commands in flight when it shrank
+// have finished
+struct InstanceSemaphore {
+    limit: usize,
+    owed: usize, // Permits to forget as they are released, after the limit shrank below those in use
+    semaphore: Arc<Semaphore>,
+}
+
+impl InstanceSemaphore {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            owed: 0,
+            semaphore: Arc::new(Semaphore::new(limit)),
+        }
+    }
+
+    // Resize in place, so permits already held still count against the new limit
+    fn resize(&mut self, limit: usize) {
+        if limit > self.limit {
+            let added = limit - self.limit;
+            let repaid = added.min(self.owed);
+            self.owed -= repaid;
+            self.semaphore.add_permits(added - repaid);
+        } else {
+            self.owed += self.limit - limit;
+        }
+        self.limit = limit;
+        self.owed -= self.semaphore.forget_permits(self.owed);
+    }
+
+    fn is_idle(&self) -> bool {
+        self.semaphore.available_permits() == self.limit + self.owed
+    }
+}
+
+impl InstanceLimiter {
+    pub fn new(default_limit: usize) -> Self {
+        Self {
+            default_limit: default_limit.max(1),
+            semaphores: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Take a permit for the instance without waiting, or None when it is saturated.
+    /// `limit` overrides the default; changing it resizes the instance's semaphore.
+    pub fn try_acquire(&self, instance_id: Uuid, limit: Option<usize>) -> Option<OwnedSemaphorePermit> {
+        let limit = limit.filter(|limit| *limit > 0).unwrap_or(self.default_limit);
+        if let Some(entry) = self.semaphores.read().unwrap().get(&instance_id) {
+            if entry.limit == limit && entry.owed == 0 {
+                return entry.semaphore.clone().try_acquire_owned().ok();
+            }
+        }
+
+        let mut semaphores = self.semaphores.write().unwrap();
+        let entry = semaphores.entry(instance_id).or_insert_with(|| InstanceSemaphore::new(limit));
+        entry.resize(limit);
+        entry.semaphore.clone().try_acquire_owned().ok()
+    }
+
+    /// Drop the semaphores of instances with no commands in flight, such as deleted instances
+    /// whose pools were evicted. A later command starts a fresh semaphore.
+    pub fn remove_idle(&self, instance_ids: &[Uuid]) {
+        let mut semaphores = self.semaphores.write().unwrap();
+        for instance_id in instance_ids {
+            if semaphores.get(instance_id).is_some_and(InstanceSemaphore::is_idle) {
+                semaphores.remove(instance_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Fake connection that fails every command with a fixed error, or succeeds when there is none
+    struct FakeConnection {
+        error: Option<fn() -> RedisError>,
+    }
+
+    impl ConnectionLike for FakeConnection {
+        fn req_packed_command<'a>(&'a mut self, _: &'a Cmd) -> RedisFuture<'a, Value> {
+            let result = self.error.map_or(Ok(Value::Okay), |error| Err(error()));
+            Box::pin(async move { result })
+        }
+
+        fn req_packed_commands<'a>(&'a mut self, _: &'a Pipeline, _: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+            let result = self.error.map_or(Ok(vec![Value::Okay; count]), |error| Err(error()));
+            Box::pin(async move { result })
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+    }
+
+    // Hands out healthy connections and counts how often it was asked to
+    #[derive(Default)]
+    struct FakeReconnector {
+        reconnects: AtomicUsize,
+    }
+
+    impl Reconnect<FakeConnection> for FakeReconnector {
+        fn reconnect(&self) -> RedisFuture<'_, FakeConnection> {
+            self.reconnects.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(FakeConnection { error: None }) })
+        }
+    }
+
+    fn broken_pipe() -> RedisError {
+        io::Error::from(io::ErrorKind::BrokenPipe).into()
+    }
+
+    fn wrong_type() -> RedisError {
+        redis::parse_redis_value(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n")
+            .unwrap()
+            .extract_error()
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_is_retried_once() {
+        let reconnector = Arc::new(FakeReconnector::default());
+        let mut conn = RetryingConnection::new(FakeConnection { error: Some(broken_pipe) }, reconnector.clone());
+
+        let result: RedisResult<()> = redis::cmd("GET").arg("key").query_async(&mut conn).await;
+        assert!(result.is_ok());
+        let result: RedisResult<()> = redis::cmd("PING").query_async(&mut conn).await;
+        assert!(result.is_ok());
+        assert_eq!(reconnector.reconnects.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_writes_and_pipelines_are_not_retried() {
+        let reconnector = Arc::new(FakeReconnector::default());
+        let mut conn = RetryingConnection::new(FakeConnection { error: Some(broken_pipe) }, reconnector.clone());
+
+        let result: RedisResult<()> = redis::cmd("INCR").arg("counter").query_async(&mut conn).await;
+        assert!(result.unwrap_err().is_connection_dropped());
+        let result: RedisResult<()> = redis::cmd("EVAL").arg("return 1").arg(0).query_async(&mut conn).await;
+        assert!(result.unwrap_err().is_connection_dropped());
+        let result: RedisResult<()> = redis::pipe().atomic().cmd("GET").arg("key").query_async(&mut conn).await;
+        assert!(result.unwrap_err().is_connection_dropped());
+        assert_eq!(reconnector.reconnects.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_redis_errors_are_not_retried() {
+        let reconnector = Arc::new(FakeReconnector::default());
+        let mut conn = RetryingConnection::new(FakeConnection { error: Some(wrong_type) }, reconnector.clone());
+
+        let result: RedisResult<()> = redis::cmd("LPUSH").arg("key").arg("value").query_async(&mut conn).await;
+        assert_eq!(result.unwrap_err().code(), Some("WRONGTYPE"));
+        assert_eq!(reconnector.reconnects.load(Ordering::SeqCst), 0);
+    }
+
+    // A manager without Kubernetes, so only simulated instances can be reached
+    fn test_manager(config: RedisHandlerConfig) -> RedisPoolManager {
+        RedisPoolManager::new(config, Arc::new(KubernetesClient::new(false)))
+    }
+
+    #[test]
+    fn test_instance_url() {
+        let config = RedisHandlerConfig::default();
+        let mut instance = crate::redis_memory::test_instance(Uuid::new_v4());
+        let address = InstanceAddress {
+            host: "redis-test-service.org-1.svc:6379".to_string(),
+            password: Some("p@ss#w%rd".to_string()),
+        };
+
+        assert_eq!(
+            instance_url(&instance, AclUser::Read, &address, &config),
+            "redis://:p%40ss%23w%25rd@redis-test-service.org-1.svc:6379/"
+        );
+        assert_eq!(
+            instance_url(&instance, AclUser::Read, &InstanceAddress::local(), &config),
+            "redis://127.0.0.1:6379/"
+        );
+
+        instance.tls_enabled = true;
+        assert!(instance_url(&instance, AclUser::Read, &address, &config).starts_with("rediss://"));
+    }
+
+    #[tokio::test]
+    async fn test_instance_address() {
+        let manager = test_manager(RedisHandlerConfig::default());
+        let mut instance = crate::redis_memory::test_instance(Uuid::new_v4());
+        assert_eq!(manager.instance_address(&instance, Endpoint::Primary).await.unwrap(), InstanceAddress::local());
+
+        instance.namespace = Some("org-1".to_string());
+        instance.service_name = Some("redis-test-service".to_string());
+        instance.status = Some("simulation".to_string());
+        assert_eq!(manager.instance_address(&instance, Endpoint::Primary).await.unwrap(), InstanceAddress::local());
+
+        // A deployed instance's password is only in its Secret, which needs Kubernetes
+        instance.status = Some("running".to_string());
+        assert!(manager.instance_address(&instance, Endpoint::Primary).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pool_for_reuses_instance_pool() {
+        let manager = test_manager(RedisHandlerConfig {
+            pool_max_size: 4,
+            ..RedisHandlerConfig::default()
+        });
+        let instance_id = Uuid::new_v4();
+
+        let pool = manager.pool_for(instance_id, AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        assert_eq!(pool.status().max_size, 4);
+
+        manager.pool_for(instance_id, AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        manager.pool_for(instance_id, AclUser::Admin, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        manager.pool_for(instance_id, AclUser::Read, Endpoint::Replica, "redis://127.0.0.1:6380/").unwrap();
+        manager.pool_for(Uuid::new_v4(), AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        assert_eq!(manager.pools.read().unwrap().len(), 4);
+
+        let stats = manager.stats();
+        assert_eq!(stats.len(), 4);
+        assert!(stats.iter().all(|pool| pool.max_size == 4 && pool.size == 0 && pool.in_use == 0));
+
+        manager.evict(instance_id);
+        assert_eq!(manager.pools.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_is_idle_expired() {
+        let metrics = Metrics::default();
+        assert!(!is_idle_expired(&metrics, Duration::from_secs(60)));
+        assert!(is_idle_expired(&metrics, Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_close_idle_connections_without_connections() {
+        let manager = test_manager(RedisHandlerConfig::default());
+        manager.pool_for(Uuid::new_v4(), AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        assert_eq!(manager.close_idle_connections(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_evict_idle_pools() {
+        let manager = test_manager(RedisHandlerConfig {
+            pool_evict_after: Some(Duration::from_secs(60)),
+            ..RedisHandlerConfig::default()
+        });
+        let unused = Uuid::new_v4();
+        let used = Uuid::new_v4();
+
+        manager.pool_for(unused, AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        manager.pool_for(used, AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        let now = Instant::now();
+        assert!(manager.evict_idle_pools(now).is_empty());
+
+        // Checking out from a pool again keeps it
+        let unused_key = (unused, AclUser::Read, Endpoint::Primary);
+        *manager.pools.read().unwrap()[&unused_key].last_access.lock().unwrap() = now - Duration::from_secs(120);
+        manager.pool_for(used, AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        assert_eq!(manager.evict_idle_pools(Instant::now() + Duration::from_secs(30)), vec![unused]);
+        let pools = manager.pools.read().unwrap();
+        assert_eq!(pools.keys().map(|(id, _, _)| *id).collect::<Vec<_>>(), vec![used]);
+        drop(pools);
+
+        manager.pool_for(unused, AclUser::Read, Endpoint::Primary, "redis://127.0.0.1:6379/").unwrap();
+        assert_eq!(manager.pools.read().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_instance_limiter() {
+        let limiter = InstanceLimiter::new(2);
+        let instance_id = Uuid::new_v4();
+
+        let first = limiter.try_acquire(instance_id, None).unwrap();
+        let _second = limiter.try_acquire(instance_id, None).unwrap();
+        assert!(limiter.try_acquire(instance_id, None).is_none());
+        assert!(limiter.try_acquire(Uuid::new_v4(), None).is_some());
+
+        drop(first);
+        assert!(limiter.try_acquire(instance_id, None).is_some());
+
+        let overridden = Uuid::new_v4();
+        let _only = limiter.try_acquire(overridden, Some(1)).unwrap();
+        assert!(limiter.try_acquire(overridden, Some(1)).is_none());
+    }
+
+    #[test]
+    fn test_instance_limiter_resizes() {
+        let limiter = InstanceLimiter::new(2);
+        let instance_id = Uuid::new_v4();
+        let first = limiter.try_acquire(instance_id, Some(3)).unwrap();
+        let second = limiter.try_acquire(instance_id, Some(3)).unwrap();
+
+        // Shrinking below the permits in use admits nothing until enough are released
+        assert!(limiter.try_acquire(instance_id, Some(1)).is_none());
+        drop(first);
+        assert!(limiter.try_acquire(instance_id, Some(1)).is_none());
+        drop(second);
+        let only = limiter.try_acquire(instance_id, Some(1)).unwrap();
+        assert!(limiter.try_acquire(instance_id, Some(1)).is_none());
+
+        // Growing counts the permit still held
+        let _grown = limiter.try_acquire(instance_id, Some(3)).unwrap();
+        let _third = limiter.try_acquire(instance_id, Some(3)).unwrap();
+        assert!(limiter.try_acquire(instance_id, Some(3)).is_none());
+        drop(only);
+        assert!(limiter.try_acquire(instance_id, Some(3)).is_some());
+    }
+
+    #[test]
+    fn test_instance_limiter_remove_idle() {
+        let limiter = InstanceLimiter::new(1);
+        let busy = Uuid::new_v4();
+        let idle = Uuid::new_v4();
+        let _permit = limiter.try_acquire(busy, None).unwrap();
+        drop(limiter.try_acquire(idle, None).unwrap());
+
+        limiter.remove_idle(&[busy, idle]);
+        let semaphores = limiter.semaphores.read().unwrap();
+        assert_eq!(semaphores.keys().collect::<Vec<_>>(), vec![&busy]);
+    }
+
+    #[test]
+    fn test_is_auth_error() {
+        let wrongpass = redis::parse_redis_value(b"-WRONGPASS invalid username-password pair\r\n")
+            .unwrap()
+            .extract_error()
+            .unwrap_err();
+        assert!(is_auth_error(&wrongpass));
+        assert!(is_auth_error(&RedisError::from((ErrorKind::AuthenticationFailed, "Password authentication failed"))));
+        assert!(!is_auth_error(&RedisError::from((ErrorKind::TypeError, "Response type not string compatible"))));
+    }
+
+    #[test]
+    fn test_connection_name() {
+        let instance_id = Uuid::nil();
+        assert_eq!(
+            connection_name("gateway-7f9c", instance_id, AclUser::Write, Endpoint::Primary),
+            "gateway-7f9c:00000000-0000-0000-0000-000000000000:gateway-write:primary"
+        );
+        assert_eq!(
+            connection_name("edge gw\n", instance_id, AclUser::Read, Endpoint::Replica),
+            "edge-gw-:00000000-0000-0000-0000-000000000000:gateway-read:replica"
+        );
+    }
+
+    #[test]
+    fn test_breaker() {
+        let cooldown = Duration::from_secs(30);
+        let start = Instant::now();
+        let mut breaker = Breaker::default();
+
+        assert!(!breaker.record_failure(3, cooldown, start));
+        assert!(!breaker.record_failure(3, cooldown, start));
+        assert!(breaker.allow(cooldown, start));
+        assert!(breaker.record_failure(3, cooldown, start));
+        assert!(!breaker.allow(cooldown, start + Duration::from_secs(29)));
+
+        // Half-open: one probe at a time, and a failed probe reopens the breaker
+        let reopened = start + cooldown;
+        assert!(breaker.allow(cooldown, reopened));
+        assert!(!breaker.allow(cooldown, reopened));
+        assert!(breaker.record_failure(3, cooldown, reopened));
+        assert!(!breaker.allow(cooldown, reopened + Duration::from_secs(1)));
+
+        // An abandoned probe does not keep the breaker shut forever
+        let probing = reopened + cooldown;
+        assert!(breaker.allow(cooldown, probing));
+        assert!(breaker.allow(cooldown, probing + cooldown));
+    }
+
+    #[tokio::test]
+    async fn test_open_breaker_short_circuits_checkouts() {
+        let manager = test_manager(RedisHandlerConfig {
+            breaker_failure_threshold: 2,
+            ..RedisHandlerConfig::default()
+        });
+        let instance = crate::redis_memory::test_instance(Uuid::new_v4());
+
+        manager.record_failure(&instance, Endpoint::Primary);
+        assert!(manager.breaker_allows(instance.id, Endpoint::Primary));
+        manager.record_failure(&instance, Endpoint::Primary);
+        assert!(matches!(
+            manager.connection(&instance, AclUser::Read).await,
+            Err(CheckoutError::CircuitOpen)
+        ));
+        assert!(manager.breaker_allows(Uuid::new_v4(), Endpoint::Primary));
+        assert!(manager.pools.read().unwrap().is_empty());
+
+        manager.record_success(instance.id, Endpoint::Primary);
+        assert!(manager.breakers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_breakers_are_per_endpoint() {
+        let manager = test_manager(RedisHandlerConfig {
+            breaker_failure_threshold: 1,
+            ..RedisHandlerConfig::default()
+        });
+        let instance = crate::redis_memory::test_instance(Uuid::new_v4());
+
+        manager.record_failure(&instance, Endpoint::Replica);
+        assert!(!manager.breaker_allows(instance.id, Endpoint::Replica));
+        assert!(manager.breaker_allows(instance.id, Endpoint::Primary));
+    }
+}
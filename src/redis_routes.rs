@@ -0,0 +1,297 @@
+// Routes of the Redis HTTP API, each registered with the API key access it needs
+
+use axum::{
+    extract::{MatchedPath, Query, Request, State},
+    handler::Handler,
+    http::{Method, StatusCode},
+    middleware::{self as axum_middleware, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, MethodRouter},
+    Router,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::timeout::TimeoutLayer;
+use tracing::{error, warn};
+
+use crate::handlers::redis::{self as handlers, CommandAccess};
+use crate::middleware::{self, AppState};
+
+/// What an API key needs to call a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouteAccess {
+    /// No key needed; handlers of these routes check a key themselves if they use one
+    Public,
+    /// A key allowed to run commands of this kind
+    Command(CommandAccess),
+    /// A key with the `metrics` or `admin` scope
+    Metrics,
+    /// A key that may at least read. The command in the body is checked against the key's
+    /// scopes when it is dispatched.
+    Dispatch,
+}
+
+impl RouteAccess {
+    // Scope the key lacks for the route, for the 403 message; None when it may call it
+    fn missing_scope(self, scopes: &[String]) -> Option<&'static str> {
+        match self {
+            RouteAccess::Public => None,
+            RouteAccess::Command(access) => (!access.allowed_by(scopes)).then(|| access.required_scope()),
+            RouteAccess::Metrics => {
+                (!scopes.iter().any(|scope| scope == "metrics" || scope == "admin")).then_some("metrics or admin")
+            }
+            RouteAccess::Dispatch => {
+                (!CommandAccess::Read.allowed_by(scopes)).then(|| CommandAccess::Read.required_scope())
+            }
+        }
+    }
+}
+
+// Access registered for each (method, route path) pair
+type AccessTable = HashMap<(Method, String), RouteAccess>;
+
+/// Routes being built, with the access registered for each
+struct RedisRoutes {
+    router: Router<Arc<AppState>>,
+    access: AccessTable,
+}
+
+impl RedisRoutes {
+    fn new() -> Self {
+        Self {
+            router: Router::new(),
+            access: HashMap::new(),
+        }
+    }
+
+    fn get<H, T>(self, path: &'static str, access: RouteAccess, handler: H) -> Self
+    where
+        H: Handler<T, Arc<AppState>>,
+        T: 'static,
+    {
+        self.route(Method::GET, path, access, get(handler))
+    }
+
+    fn post<H, T>(self, path: &'static str, access: RouteAccess, handler: H) -> Self
+    where
+        H: Handler<T, Arc<AppState>>,
+        T: 'static,
+    {
+        self.route(Method::POST, path, access, post(handler))
+    }
+
+    fn route(mut self, method: Method, path: &'static str, access: RouteAccess, method_router: MethodRouter<Arc<AppState>>) -> Self {
+        self.access.insert((method, path.to_string()), access);
+        self.router = self.router.route(path, method_router);
+        self
+    }
+}
+
+// Every Redis HTTP API route: those bounded by the request timeout, then event streams,
+// which stay open
+fn redis_routes() -> (RedisRoutes, RedisRoutes) {
+    use RouteAccess::{Command, Dispatch, Metrics, Public};
+    const READ: RouteAccess = Command(CommandAccess::Read);
+    const WRITE: RouteAccess = Command(CommandAccess::Write);
+
+    let timed = RedisRoutes::new()
+        .get("/redis/commands", Public, handlers::handle_list_commands)
+        .get("/redis/auth/introspect", Public, handlers::handle_introspect_key)
+        .get("/redis/:instance_id/ping", READ, handlers::handle_ping)
+        .get("/redis/:instance_id/set/:key/:value", WRITE, handlers::handle_set)
+        .post("/redis/:instance_id/set/:key", WRITE, handlers::handle_set_json)
+        .post("/redis/:instance_id/mset-ex", WRITE, handlers::handle_mset_ex)
+        .post("/redis/:instance_id/cas-numeric", WRITE, handlers::handle_cas_numeric)
+        .get("/redis/:instance_id/get/:key", READ, handlers::handle_get)
+        .get("/redis/:instance_id/del/:key", WRITE, handlers::handle_del)
+        .get("/redis/:instance_id/incr/:key", WRITE, handlers::handle_incr)
+        .get("/redis/:instance_id/hset/:key/:field/:value", WRITE, handlers::handle_hset)
+        .post("/redis/:instance_id/hset/:key/:field", WRITE, handlers::handle_hset_json)
+        .get("/redis/:instance_id/hget/:key/:field", READ, handlers::handle_hget)
+        .get("/redis/:instance_id/hscan/:key", READ, handlers::handle_hscan)
+        .get("/redis/:instance_id/sscan/:key", READ, handlers::handle_sscan)
+        .get("/redis/:instance_id/zscan/:key", READ, handlers::handle_zscan)
+        .get("/redis/:instance_id/lpush/:key/:value", WRITE, handlers::handle_lpush)
+        .post("/redis/:instance_id/lpush/:key", WRITE, handlers::handle_lpush_json)
+        .get("/redis/:instance_id/lpop/:key", WRITE, handlers::handle_lpop)
+        .get("/redis/:instance_id/metrics", Metrics, handlers::handle_metrics)
+        // Generic Redis command endpoint (for POST with JSON body)
+        .post("/redis/:instance_id", Dispatch, handlers::handle_generic_command)
+        .post("/redis/:instance_id/pipeline", Dispatch, handlers::handle_pipeline)
+        // Catch-all route for debugging Redis requests
+        .get("/redis/:instance_id/*path", READ, handlers::handle_debug_request);
+
+    let streaming = RedisRoutes::new()
+        .get("/redis/:instance_id/keyspace-events", READ, handlers::handle_keyspace_events);
+
+    (timed, streaming)
+}
+
+/// The Redis HTTP API. Each request's API key is checked against the access registered for
+/// its route before the handler runs, and handlers get the key's claims as an extension.
+pub fn router(state: &Arc<AppState>, request_timeout: Duration) -> Router<Arc<AppState>> {
+    let (timed, streaming) = redis_routes();
+    let access: AccessTable = timed.access.into_iter().chain(streaming.access).collect();
+
+    Router::new()
+        .merge(timed.router.layer(TimeoutLayer::new(request_timeout)))
+        // Event streams stay open, so they are exempt from the request timeout
+        .merge(streaming.router)
+        // Shed load over the configured request rates before doing any work, once the
+        // API key is known
+        .route_layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::rate_limit_middleware,
+        ))
+        .route_layer(axum_middleware::from_fn_with_state(
+            (state.clone(), Arc::new(access)),
+            api_key_middleware,
+        ))
+        // Tell clients when to retry if no Redis connection is available
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::retry_after_middleware,
+        ))
+}
+
+// Middleware checking the request's API key against the access registered for its route.
+// Routes with no registered access are refused.
+async fn api_key_middleware(
+    State((state, access)): State<(Arc<AppState>, Arc<AccessTable>)>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    // HEAD requests are served by the GET handler
+    let method = if request.method() == Method::HEAD {
+        Method::GET
+    } else {
+        request.method().clone()
+    };
+    let Some(path) = request.extensions().get::<MatchedPath>().map(|path| path.as_str().to_string()) else {
+        error!("No matched route for Redis API request to {}", request.uri().path());
+        return internal_error();
+    };
+    let Some(route_access) = access.get(&(method, path.clone())).copied() else {
+        error!("No API key access registered for {} {}", request.method(), path);
+        return internal_error();
+    };
+    if route_access == RouteAccess::Public {
+        return next.run(request).await;
+    }
+
+    let query = Query::<HashMap<String, String>>::try_from_uri(request.uri())
+        .map(|Query(query)| query)
+        .unwrap_or_default();
+    let Some(api_key) = handlers::extract_api_key(request.headers(), &query) else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "Missing API key"})),
+        )
+            .into_response();
+    };
+    let claims = match handlers::verify_api_key(&state, &api_key) {
+        Ok(claims) => claims,
+        Err(error) => return error.into_response(),
+    };
+
+    if let Some(scope) = route_access.missing_scope(&claims.scopes) {
+        warn!("API key {} lacks the {} scope for {}", claims.key_prefix, scope, path);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": format!("API key requires the {} scope", scope)})),
+        )
+            .into_response();
+    }
+
+    request.extensions_mut().insert(claims);
+    next.run(request).await
+}
+
+fn internal_error() -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"error": "Internal server error"})),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::Service;
+    use uuid::Uuid;
+
+    use crate::auth::ApiKeyClaims;
+    use crate::redis_memory::{test_state, MemoryBackend};
+
+    // A request path the route pattern matches
+    fn example_path(pattern: &str) -> String {
+        pattern
+            .split('/')
+            .map(|segment| match segment {
+                ":instance_id" => Uuid::new_v4().to_string(),
+                _ if segment.starts_with(':') || segment.starts_with('*') => "x".to_string(),
+                _ => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn request(method: &Method, path: &str, api_key: Option<&str>) -> Request {
+        let mut builder = Request::builder().method(method.clone()).uri(path);
+        if let Some(api_key) = api_key {
+            builder = builder.header("authorization", format!("Bearer {}", api_key));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_every_route_checks_api_key_access() {
+        let state = Arc::new(test_state(Arc::new(MemoryBackend::default())));
+        let mut app = router(&state, Duration::from_secs(5)).with_state(state.clone());
+        let claims = ApiKeyClaims::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            vec!["read".to_string()],
+            "rg_test".to_string(),
+            None,
+        );
+        let read_key = state.jwt_manager.create_api_key_token(&claims).unwrap();
+
+        let (timed, streaming) = redis_routes();
+        for ((method, pattern), access) in timed.access.into_iter().chain(streaming.access) {
+            if access == RouteAccess::Public {
+                continue;
+            }
+            let path = example_path(&pattern);
+
+            let response = app.call(request(&method, &path, None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{} {} without a key", method, pattern);
+
+            if matches!(access, RouteAccess::Command(CommandAccess::Write) | RouteAccess::Metrics) {
+                let response = app.call(request(&method, &path, Some(&read_key))).await.unwrap();
+                assert_eq!(response.status(), StatusCode::FORBIDDEN, "{} {} with a read-only key", method, pattern);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_route_is_refused() {
+        let state = Arc::new(test_state(Arc::new(MemoryBackend::default())));
+        let mut app = Router::new()
+            .route("/redis/:instance_id/unlisted", get(|| async { "reached" }))
+            .route_layer(axum_middleware::from_fn_with_state(
+                (state.clone(), Arc::new(AccessTable::new())),
+                api_key_middleware,
+            ))
+            .with_state(state);
+
+        let path = format!("/redis/{}/unlisted", Uuid::new_v4());
+        let response = app.call(request(&Method::GET, &path, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,77 @@
+// Serving the gateway on a Unix domain socket, for sidecars sharing a pod with the app.
+// axum::serve only accepts TCP listeners, so connections are driven by hyper directly.
+
+use axum::{extract::Request, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use std::future::Future;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use tokio::net::UnixListener;
+use tokio::sync::watch;
+use tower::Service;
+
+// Bind the socket, replacing a socket file left behind by a previous run. Any other kind of
+// file at the path is left alone and binding fails.
+pub fn bind(path: &Path) -> io::Result<UnixListener> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => return Err(io::Error::new(io::ErrorKind::AlreadyExists, "path exists and is not a socket")),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    UnixListener::bind(path)
+}
+
+/// Serve the app until `shutdown` completes, then stop accepting connections and wait for
+/// in-flight requests to finish, like `axum::serve(..).with_graceful_shutdown(..)`
+pub async fn serve(listener: UnixListener, app: Router, shutdown: impl Future<Output = ()>) {
+    // Tells connections to finish their current request and close
+    let (signal_tx, signal_rx) = watch::channel(());
+    // Closed once every connection task has dropped its receiver
+    let (close_tx, close_rx) = watch::channel(());
+    tokio::pin!(shutdown);
+
+    loop {
+        let socket = tokio::select! {
+            result = listener.accept() => match result {
+                Ok((socket, _)) => socket,
+                Err(e) => {
+                    tracing::warn!("Failed to accept Unix socket connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let tower_service = app.clone();
+        let hyper_service =
+            hyper::service::service_fn(move |request: Request<Incoming>| tower_service.clone().call(request));
+        let mut signal_rx = signal_rx.clone();
+        let close_rx = close_rx.clone();
+
+        tokio::spawn(async move {
+            let builder = Builder::new(TokioExecutor::new());
+            let conn = builder.serve_connection_with_upgrades(TokioIo::new(socket), hyper_service);
+            tokio::pin!(conn);
+            let result = tokio::select! {
+                result = conn.as_mut() => result,
+                _ = signal_rx.changed() => {
+                    conn.as_mut().graceful_shutdown();
+                    conn.as_mut().await
+                }
+            };
+            if let Err(e) = result {
+                tracing::debug!("Unix socket connection closed with an error: {}", e);
+            }
+            drop(close_rx);
+        });
+    }
+
+    drop(listener);
+    let _ = signal_tx.send(());
+    drop(close_rx);
+    close_tx.closed().await;
+}
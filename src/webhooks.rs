@@ -0,0 +1,293 @@
+// Organization webhooks notified when one of their Redis instances changes status
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::WebhookConfig;
+
+// Header carrying `sha256=<hex HMAC of the body>`, keyed with the organization's webhook secret
+const SIGNATURE_HEADER: &str = "x-redisgate-signature";
+const EVENT_HEADER: &str = "x-redisgate-event";
+
+const STATUS_CHANGED_EVENT: &str = "instance.status_changed";
+
+/// Payload POSTed to the webhook URL
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusChangeEvent {
+    pub event: &'static str,
+    pub organization_id: Uuid,
+    pub instance_id: Uuid,
+    pub old_status: Option<String>,
+    pub new_status: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Sign a payload the way receivers are expected to verify it
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest: String = mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", digest)
+}
+
+/// Whether webhooks may be sent to an address. Only public unicast addresses are allowed, so a
+/// webhook cannot reach the cluster network, the node itself or cloud metadata (169.254.169.254).
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || first == 0
+                // Shared address space (100.64.0.0/10), used for pod and service networks by some clusters
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || first & 0xfe00 == 0xfc00 // Unique local (fc00::/7)
+                    || first & 0xffc0 == 0xfe80) // Link-local (fe80::/10)
+            }
+        },
+    }
+}
+
+/// Check the host of a webhook URL without resolving it: IP addresses must be public, and names
+/// must not be ones only cluster or local DNS answers
+pub fn check_webhook_host(host: &str) -> Result<(), String> {
+    if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        if !is_public_address(ip) {
+            return Err(format!("{} is not a public address", ip));
+        }
+        return Ok(());
+    }
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    // Single-label names resolve through the cluster's search domains, e.g. to a Service
+    let internal = !host.contains('.')
+        || [".localhost", ".local", ".svc", ".internal"].iter().any(|suffix| host.ends_with(suffix));
+    if internal {
+        return Err(format!("{} is an internal host name", host));
+    }
+    Ok(())
+}
+
+/// Resolve a webhook host, failing unless every address it resolves to is public
+pub async fn resolve_public(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("cannot resolve {}: {}", host, e))?
+        .collect();
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_address(addr.ip())) {
+        return Err(format!("{} resolves to {}, which is not a public address", host, addr.ip()));
+    }
+    if addrs.is_empty() {
+        return Err(format!("{} has no addresses", host));
+    }
+    Ok(addrs)
+}
+
+/// Check that a webhook URL only reaches public addresses, resolving its host
+pub async fn check_webhook_url(url: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    let host = url.host_str().ok_or_else(|| "URL has no host".to_string())?;
+    check_webhook_host(host)?;
+    if host.starts_with('[') || host.parse::<IpAddr>().is_ok() {
+        return Ok(());
+    }
+    resolve_public(host, url.port_or_known_default().unwrap_or(443)).await.map(|_| ())
+}
+
+// Resolver of the webhook client. Connections only go to the addresses it checked, so a name
+// re-pointed at an internal address after the webhook was saved is refused too.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            // reqwest sets the port of the returned addresses
+            let addrs = resolve_public(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Delivers status-change events to organization webhooks in the background
+pub struct WebhookNotifier {
+    db_pool: PgPool,
+    client: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl WebhookNotifier {
+    pub fn new(db_pool: PgPool, config: WebhookConfig) -> Self {
+        // Redirects are not followed and proxies not used: either could take a request to an
+        // address the resolver never checked
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy()
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .expect("Failed to build webhook HTTP client");
+        Self { db_pool, client, config }
+    }
+
+    /// Notify the organization's webhook, if any, of a status transition.
+    ///
+    /// Delivery (including retries) runs in a spawned task so status updates never wait on it.
+    pub fn status_changed(
+        self: &Arc<Self>,
+        organization_id: Uuid,
+        instance_id: Uuid,
+        old_status: Option<&str>,
+        new_status: &str,
+    ) {
+        if old_status == Some(new_status) {
+            return;
+        }
+
+        let event = StatusChangeEvent {
+            event: STATUS_CHANGED_EVENT,
+            organization_id,
+            instance_id,
+            old_status: old_status.map(str::to_string),
+            new_status: new_status.to_string(),
+            timestamp: Utc::now(),
+        };
+        let notifier = self.clone();
+        tokio::spawn(async move { notifier.deliver(event).await });
+    }
+
+    async fn deliver(&self, event: StatusChangeEvent) {
+        let webhook = match sqlx::query!(
+            "SELECT url, secret FROM organization_webhooks WHERE organization_id = $1",
+            event.organization_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await
+        {
+            Ok(Some(webhook)) => webhook,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Failed to load webhook for organization {}: {}", event.organization_id, e);
+                return;
+            }
+        };
+
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!("Failed to serialize webhook event: {}", e);
+                return;
+            }
+        };
+        let signature = sign(&webhook.secret, &body);
+
+        // Names are checked by the client's resolver; IP addresses never reach it
+        let host_check = reqwest::Url::parse(&webhook.url)
+            .map_err(|e| e.to_string())
+            .and_then(|url| check_webhook_host(url.host_str().unwrap_or_default()));
+        if let Err(e) = host_check {
+            tracing::warn!("Refusing webhook delivery for organization {}: {}", event.organization_id, e);
+            return;
+        }
+
+        let mut backoff = self.config.initial_backoff;
+        for attempt in 1..=self.config.max_attempts {
+            let result = self
+                .client
+                .post(&webhook.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, STATUS_CHANGED_EVENT)
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.clone())
+                .send()
+                .await;
+
+            match result {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) => tracing::warn!(
+                    "Webhook for organization {} answered {} (attempt {}/{})",
+                    event.organization_id,
+                    response.status(),
+                    attempt,
+                    self.config.max_attempts
+                ),
+                Err(e) => tracing::warn!(
+                    "Webhook for organization {} failed (attempt {}/{}): {}",
+                    event.organization_id,
+                    attempt,
+                    self.config.max_attempts,
+                    e
+                ),
+            }
+
+            if attempt < self.config.max_attempts {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(self.config.max_backoff);
+            }
+        }
+
+        tracing::warn!(
+            "Giving up on webhook delivery of instance {} status change to {}",
+            event.instance_id,
+            event.new_status
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_webhook_hosts_must_be_public() {
+        assert!(check_webhook_host("hooks.example.com").is_ok());
+        assert!(check_webhook_host("203.0.113.9").is_err()); // Documentation range
+        assert!(check_webhook_host("8.8.8.8").is_ok());
+        assert!(check_webhook_host("[2001:4860:4860::8888]").is_ok());
+
+        for host in [
+            "169.254.169.254",
+            "10.0.0.5",
+            "127.0.0.1",
+            "100.64.0.1",
+            "[::1]",
+            "[fd00::1]",
+            "[fe80::1]",
+            "[::ffff:10.0.0.5]",
+            "localhost",
+            "redis-gateway",
+            "kubernetes.default.svc",
+            "kube-dns.kube-system.svc.cluster.local",
+            "metadata.google.internal",
+        ] {
+            assert!(check_webhook_host(host).is_err(), "{}", host);
+        }
+    }
+}
@@ -54,9 +54,9 @@ class ApiClient:
         """Make GET request."""
         return await self._client.get(url, headers=headers, params=params)
     
-    async def post(self, url: str, json: Optional[Dict] = None, headers: Optional[Dict] = None, params: Optional[Dict] = None) -> httpx.Response:
+    async def post(self, url: str, json: Optional[Dict] = None, headers: Optional[Dict] = None, params: Optional[Dict] = None, content: Optional[str] = None) -> httpx.Response:
         """Make POST request."""
-        return await self._client.post(url, json=json, headers=headers, params=params)
+        return await self._client.post(url, json=json, headers=headers, params=params, content=content)
     
     async def put(self, url: str, json: Optional[Dict] = None, headers: Optional[Dict] = None) -> httpx.Response:
         """Make PUT request."""
@@ -7,6 +7,7 @@ This module tests:
 - Redis instance management endpoints
 """
 
+import json
 import pytest
 import time
 from uuid import uuid4
@@ -42,6 +43,31 @@ class TestOrganizations:
         assert "owner_id" in data["data"]
         assert data["data"]["owner_id"] == auth_user["user_id"]
     
+    @pytest.mark.protected
+    async def test_create_organization_idempotency_key(self, api_client: ApiClient, auth_user: Dict[str, Any], wait_for_server):
+        """Test retrying organization creation with the same Idempotency-Key."""
+        org_data = {
+            "name": f"Idempotent Organization {generate_test_key()}",
+            "slug": f"idem-org-{int(time.time() * 1000000)}",
+            "description": "Idempotency test"
+        }
+        headers = {**auth_user["auth_headers"], "Idempotency-Key": str(uuid4())}
+        
+        first = await api_client.post("/api/organizations", json=org_data, headers=headers)
+        assert first.status_code == 200
+        
+        replay = await api_client.post("/api/organizations", json=org_data, headers=headers)
+        assert replay.status_code == 200
+        assert replay.json()["data"]["id"] == first.json()["data"]["id"]
+        
+        conflict = await api_client.post(
+            "/api/organizations",
+            json={**org_data, "description": "Different payload"},
+            headers=headers
+        )
+        assert conflict.status_code == 409
+        assert conflict.json()["code"] == "IDEMPOTENCY_KEY_REUSED"
+    
     @pytest.mark.protected
     async def test_list_organizations(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
         """Test listing organizations."""
@@ -77,6 +103,111 @@ class TestOrganizations:
         assert data["data"]["name"] == test_organization["name"]
         assert data["data"]["description"] == test_organization["description"]
     
+    @pytest.mark.protected
+    async def test_get_organization_usage(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_api_key: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test getting aggregate usage for an organization."""
+        org_id = test_organization["id"]
+        
+        response = await api_client.get(
+            f"/api/organizations/{org_id}/usage",
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 200
+        data = response.json()
+        assert data["success"] == True
+        assert data["data"]["organization_id"] == org_id
+        assert data["data"]["instance_count"] >= 1
+        assert data["data"]["api_key_count"] >= 1
+        assert data["data"]["total_max_memory"] >= test_redis_instance["max_memory"]
+    
+    @pytest.mark.protected
+    async def test_list_organization_members(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test listing the members of an organization."""
+        org_id = test_organization["id"]
+        
+        response = await api_client.get(
+            f"/api/organizations/{org_id}/members?page=1&limit=10",
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 200
+        data = response.json()
+        assert data["success"] == True
+        assert data["data"]["total_count"] == 1
+        member = data["data"]["items"][0]
+        assert member["user_id"] == auth_user["user_id"]
+        assert member["role"] == "owner"
+        assert member["is_active"] == True
+        assert "joined_at" in member
+        
+        # Non-members cannot see the organization
+        response = await api_client.get(f"/api/organizations/{uuid4()}/members", headers=auth_user["auth_headers"])
+        assert response.status_code == 404
+    
+    @pytest.mark.protected
+    async def test_remove_organization_member_rules(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test that owners cannot remove themselves and unknown members are not found."""
+        org_id = test_organization["id"]
+        
+        response = await api_client.delete(
+            f"/api/organizations/{org_id}/members/{auth_user['user_id']}",
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 400
+        assert response.json()["code"] == "INVALID_REQUEST"
+        
+        response = await api_client.delete(
+            f"/api/organizations/{org_id}/members/{uuid4()}",
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 404
+    
+    @pytest.mark.protected
+    async def test_transfer_ownership_requires_member(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test that ownership can only be transferred to another active member."""
+        org_id = test_organization["id"]
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/transfer-ownership",
+            json={"new_owner_user_id": str(uuid4())},
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 400
+        assert response.json()["success"] == False
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/transfer-ownership",
+            json={"new_owner_user_id": auth_user["user_id"]},
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 400
+    
+    @pytest.mark.protected
+    async def test_update_organization_plan(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test changing an organization's plan applies its quotas."""
+        org_id = test_organization["id"]
+        
+        response = await api_client.put(
+            f"/api/organizations/{org_id}/plan",
+            json={"plan": "pro"},
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 200
+        data = response.json()
+        assert data["success"] == True
+        assert data["data"]["plan"] == "pro"
+        assert data["data"]["max_redis_instances"] == 20
+        assert data["data"]["max_api_keys"] == 50
+        
+        response = await api_client.put(
+            f"/api/organizations/{org_id}/plan",
+            json={"plan": "platinum"},
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code in [400, 422]
+    
     @pytest.mark.protected
     async def test_update_organization(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
         """Test updating an organization."""
@@ -88,10 +219,16 @@ class TestOrganizations:
             "description": "Updated description"
         }
         
+        get_response = await api_client.get(
+            f"/api/organizations/{org_id}",
+            headers=auth_user["auth_headers"]
+        )
+        etag = get_response.headers["etag"]
+        
         response = await api_client.put(
             f"/api/organizations/{org_id}",
             json=update_data,
-            headers=auth_user["auth_headers"]
+            headers={**auth_user["auth_headers"], "If-Match": etag}
         )
         
         assert response.status_code == 200
@@ -99,6 +236,22 @@ class TestOrganizations:
         assert data["success"] == True
         assert data["data"]["name"] == update_data["name"]
         assert data["data"]["description"] == update_data["description"]
+        assert response.headers["etag"] != etag
+        
+        # A second update with the stale version is rejected
+        stale_response = await api_client.put(
+            f"/api/organizations/{org_id}",
+            json=update_data,
+            headers={**auth_user["auth_headers"], "If-Match": etag}
+        )
+        assert stale_response.status_code == 412
+        
+        missing_response = await api_client.put(
+            f"/api/organizations/{org_id}",
+            json=update_data,
+            headers=auth_user["auth_headers"]
+        )
+        assert missing_response.status_code == 428
     
     @pytest.mark.protected
     async def test_delete_organization(self, api_client: ApiClient, auth_user: Dict[str, Any], wait_for_server):
@@ -132,6 +285,64 @@ class TestOrganizations:
             headers=auth_user["auth_headers"]
         )
         assert get_response.status_code == 404
+    
+    @pytest.mark.protected
+    async def test_status_webhook(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test registering, reading and removing the status-change webhook."""
+        org_id = test_organization["id"]
+        url = f"/api/organizations/{org_id}/webhook"
+        
+        response = await api_client.put(url, json={"url": "ftp://hooks.example.com"}, headers=auth_user["auth_headers"])
+        assert response.status_code == 400
+        assert "url" in response.json()["errors"]
+        
+        response = await api_client.put(url, json={"url": "https://hooks.example.com/a"}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        secret = response.json()["data"]["secret"]
+        assert len(secret) == 64
+        
+        # Changing the URL keeps the secret unless a rotation is requested
+        response = await api_client.put(url, json={"url": "https://hooks.example.com/b"}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert "secret" not in response.json()["data"]
+        
+        response = await api_client.put(url, json={"url": "https://hooks.example.com/b", "rotate_secret": True}, headers=auth_user["auth_headers"])
+        assert response.json()["data"]["secret"] != secret
+        
+        response = await api_client.get(url, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert response.json()["data"]["url"] == "https://hooks.example.com/b"
+        assert "secret" not in response.json()["data"]
+        
+        response = await api_client.delete(url, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        response = await api_client.get(url, headers=auth_user["auth_headers"])
+        assert response.status_code == 404
+    
+    @pytest.mark.protected
+    async def test_command_audit_settings(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test turning sampled command auditing on and off."""
+        org_id = test_organization["id"]
+        url = f"/api/organizations/{org_id}/command-audit"
+        
+        response = await api_client.get(url, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert response.json()["data"]["enabled"] is False
+        
+        response = await api_client.put(url, json={"enabled": True, "sample_rate": 0}, headers=auth_user["auth_headers"])
+        assert response.status_code == 400
+        
+        response = await api_client.put(url, json={"enabled": True, "sample_rate": 0.25}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        data = response.json()["data"]
+        assert data["enabled"] is True
+        assert data["sample_rate"] == 0.25
+        
+        response = await api_client.put(url, json={"enabled": False}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        response = await api_client.get(url, headers=auth_user["auth_headers"])
+        assert response.json()["data"]["enabled"] is False
+        assert response.json()["data"]["sample_rate"] == 1.0
 
 
 class TestApiKeys:
@@ -176,6 +387,37 @@ class TestApiKeys:
         assert jwt_token.count('.') == 2  # JWT has 3 parts separated by dots
         assert api_key_info["key_prefix"].startswith("rg_")  # RedisGate prefix
     
+    @pytest.mark.protected
+    async def test_api_key_scope_policy(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test the organization's default scopes for new API keys."""
+        org_id = test_organization["id"]
+        url = f"/api/organizations/{org_id}/api-key-scopes"
+        keys_url = f"/api/organizations/{org_id}/api-keys"
+        
+        response = await api_client.get(url, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert response.json()["data"]["default_api_key_scopes"] is None
+        
+        response = await api_client.put(url, json={"default_api_key_scopes": ["superuser"]}, headers=auth_user["auth_headers"])
+        assert response.status_code == 400
+        
+        response = await api_client.put(url, json={"default_api_key_scopes": ["read"]}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert response.json()["data"]["default_api_key_scopes"] == ["read"]
+        
+        # Keys without scopes get the defaults; broader scopes are refused
+        response = await api_client.post(keys_url, json={"name": f"Default Scopes {generate_test_key()}", "organization_id": org_id}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert response.json()["data"]["api_key"]["scopes"] == ["read"]
+        
+        response = await api_client.post(keys_url, json={"name": f"Broad Scopes {generate_test_key()}", "organization_id": org_id, "scopes": ["write"]}, headers=auth_user["auth_headers"])
+        assert response.status_code == 403
+        
+        response = await api_client.put(url, json={"default_api_key_scopes": None}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        response = await api_client.get(url, headers=auth_user["auth_headers"])
+        assert response.json()["data"]["default_api_key_scopes"] is None
+    
     @pytest.mark.protected
     async def test_list_api_keys(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
         """Test listing API keys."""
@@ -248,6 +490,36 @@ class TestApiKeys:
             headers=auth_user["auth_headers"]
         )
         assert get_response.status_code == 404
+    
+    @pytest.mark.protected
+    async def test_revoke_member_api_keys(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test revoking every API key a member owns at once."""
+        org_id = test_organization["id"]
+        
+        key_ids = []
+        for _ in range(2):
+            create_response = await api_client.post(
+                f"/api/organizations/{org_id}/api-keys",
+                json={"name": f"Temp API Key {generate_test_key()}", "organization_id": org_id, "scopes": ["read"]},
+                headers=auth_user["auth_headers"]
+            )
+            assert create_response.status_code == 200
+            key_ids.append(create_response.json()["data"]["api_key"]["id"])
+        
+        revoke_response = await api_client.post(
+            f"/api/organizations/{org_id}/members/{auth_user['user_id']}/revoke-keys",
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert revoke_response.status_code == 200
+        assert revoke_response.json()["data"]["revoked"] == 2
+        
+        for key_id in key_ids:
+            get_response = await api_client.get(
+                f"/api/organizations/{org_id}/api-keys/{key_id}",
+                headers=auth_user["auth_headers"]
+            )
+            assert get_response.status_code == 404
 
 
 class TestRedisInstances:
@@ -282,6 +554,79 @@ class TestRedisInstances:
         assert "organization_id" in data
         assert data["organization_id"] == org_id
     
+    @pytest.mark.protected
+    async def test_redis_instance_password_returned_once(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test the generated password is only returned on creation."""
+        org_id = test_organization["id"]
+        assert len(test_redis_instance["password"]) > 0
+        
+        response = await api_client.get(
+            f"/api/organizations/{org_id}/redis-instances/{test_redis_instance['id']}",
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 200
+        assert "password" not in response.json()["data"]
+    
+    @pytest.mark.protected
+    async def test_redis_instance_connection_limit_follows_plan(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test max_connections defaults to and is capped by the plan limit."""
+        org_id = test_organization["id"]
+        assert test_redis_instance["max_connections"] <= 100
+        assert test_redis_instance["eviction_policy"] == "allkeys-lru"
+        
+        slug = f"limit-{uuid4().hex[:8]}"
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/redis-instances",
+            json={
+                "name": "Connection limit",
+                "slug": slug,
+                "organization_id": org_id,
+                "max_memory": 1048576,
+                "max_connections": 100000,
+            },
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 400
+    
+    @pytest.mark.protected
+    async def test_redis_instance_version_is_validated(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test that only supported Redis versions are accepted and the default is applied."""
+        org_id = test_organization["id"]
+        assert test_redis_instance["redis_version"] == "7.2"
+        
+        for version in ["latest", "7.2; rm -rf /", "9.9"]:
+            response = await api_client.post(
+                f"/api/organizations/{org_id}/redis-instances",
+                json={
+                    "name": "Bad version",
+                    "slug": f"version-{uuid4().hex[:8]}",
+                    "organization_id": org_id,
+                    "max_memory": 1048576,
+                    "redis_version": version,
+                },
+                headers=auth_user["auth_headers"]
+            )
+            assert response.status_code == 400
+    
+    @pytest.mark.protected
+    async def test_redis_instance_memory_quota(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
+        """Test that the summed max_memory of an organization's instances is capped by the plan."""
+        org_id = test_organization["id"]
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/redis-instances",
+            json={
+                "name": "Too much memory",
+                "slug": f"memory-{uuid4().hex[:8]}",
+                "organization_id": org_id,
+                "max_memory": 2 * 1024 * 1024 * 1024,  # Above the free plan's 1GB total
+            },
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 409
+        assert response.json()["code"] == "QUOTA_EXCEEDED"
+    
     @pytest.mark.protected
     async def test_list_redis_instances(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
         """Test listing Redis instances."""
@@ -338,6 +683,253 @@ class TestRedisInstances:
         data = response.json()
         assert data["status"] == status_data["status"]
     
+    @pytest.mark.protected
+    async def test_delete_keys_by_pattern(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test bulk deleting keys that match a pattern."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/delete-keys",
+            json={"pattern": f"{generate_test_key('nomatch')}:*"},
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 200
+        data = response.json()["data"]
+        assert data["deleted"] == 0
+        assert data["complete"] == True
+        assert data["cursor"] == 0
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/delete-keys",
+            json={"pattern": ""},
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 400
+    
+    @pytest.mark.protected
+    async def test_export_keys_by_pattern(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test exporting keys as NDJSON with a match filter."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        
+        response = await api_client.get(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/export",
+            params={"match": f"{generate_test_key('nomatch')}:*"},
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 200
+        assert response.headers["content-type"] == "application/x-ndjson"
+        lines = [json.loads(line) for line in response.text.splitlines() if line]
+        assert all("key" not in line for line in lines)
+        assert lines[-1] == {"cursor": 0, "complete": True}
+    
+    @pytest.mark.protected
+    async def test_export_keys_validates_type(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test that the export type filter only accepts Redis data types."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        
+        response = await api_client.get(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/export",
+            params={"match": f"{generate_test_key('nomatch')}:*", "type": "hash"},
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 200
+        
+        response = await api_client.get(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/export",
+            params={"match": "*", "type": "bitmap"},
+            headers=auth_user["auth_headers"]
+        )
+        assert response.status_code == 400
+    
+    @pytest.mark.protected
+    async def test_import_keys_reports_line_errors(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test that an import reports bad lines without aborting."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        key = generate_test_key("import")
+        
+        body = "\n".join([
+            json.dumps({"key": key, "type": "hash", "ttl_ms": 60000, "value": {"name": "john"}}),
+            "not json",
+            json.dumps({"key": key, "type": "unknown", "value": "v"}),
+            json.dumps({"cursor": 0, "complete": True}),
+        ])
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/import",
+            content=body,
+            headers={**auth_user["auth_headers"], "Content-Type": "application/x-ndjson"}
+        )
+        
+        assert response.status_code == 200
+        data = response.json()["data"]
+        assert data["imported"] == 1
+        assert data["failed"] == 2
+        assert [error["line"] for error in data["errors"]] == [2, 3]
+    
+    @pytest.mark.protected
+    async def test_instance_logs_validates_tail_lines(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test that the pod log tail size is bounded."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        
+        response = await api_client.get(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/logs",
+            params={"tail_lines": 0},
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 400
+        assert "tail_lines" in response.json()["errors"]
+    
+    @pytest.mark.protected
+    async def test_restart_requires_kubernetes_deployment(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test that simulated instances cannot be restarted."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/restart",
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 400
+        assert response.json()["code"] == "INVALID_REQUEST"
+    
+    @pytest.mark.protected
+    async def test_refresh_redis_pool(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test rebuilding an instance's connection pool."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        
+        response = await api_client.post(
+            f"/api/organizations/{org_id}/redis-instances/{instance_id}/refresh-pool",
+            headers=auth_user["auth_headers"]
+        )
+        
+        assert response.status_code == 200
+        data = response.json()["data"]
+        assert data["instance_id"] == instance_id
+        assert data["health_status"] in ("healthy", "unhealthy")
+    
+    @pytest.mark.protected
+    async def test_bulk_key_ttls(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test reading and setting TTLs for several keys at once."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        base = f"/api/organizations/{org_id}/redis-instances/{instance_id}"
+        key = generate_test_key("ttl")
+        missing = generate_test_key("missing")
+        
+        await api_client.post(
+            f"{base}/import",
+            content=json.dumps({"key": key, "type": "string", "value": "v"}),
+            headers={**auth_user["auth_headers"], "Content-Type": "application/x-ndjson"}
+        )
+        
+        response = await api_client.post(f"{base}/expire", json={"keys": [key, missing], "seconds": 300}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert response.json()["data"]["updated"] == 1
+        
+        response = await api_client.post(f"{base}/ttl", json={"keys": [key, missing]}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        ttls = response.json()["data"]["ttls"]
+        assert 0 < ttls[key] <= 300
+        assert ttls[missing] == -2
+    
+    @pytest.mark.protected
+    async def test_key_idle_times(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test reading idle times for several keys at once."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        base = f"/api/organizations/{org_id}/redis-instances/{instance_id}"
+        key = generate_test_key("idle")
+        missing = generate_test_key("missing")
+        
+        await api_client.post(
+            f"{base}/import",
+            content=json.dumps({"key": key, "type": "string", "value": "v"}),
+            headers={**auth_user["auth_headers"], "Content-Type": "application/x-ndjson"}
+        )
+        
+        response = await api_client.post(f"{base}/idletime", json={"keys": [key, missing]}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        idle_seconds = response.json()["data"]["idle_seconds"]
+        assert idle_seconds[key] >= 0
+        assert idle_seconds[missing] is None
+        
+        response = await api_client.post(f"{base}/idletime", json={"keys": []}, headers=auth_user["auth_headers"])
+        assert response.status_code == 400
+    
+    @pytest.mark.protected
+    async def test_instance_stats(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test reading command and keyspace statistics for an instance."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        base = f"/api/organizations/{org_id}/redis-instances/{instance_id}"
+        
+        await api_client.post(f"{base}/idletime", json={"keys": [generate_test_key("stats")]}, headers=auth_user["auth_headers"])
+        
+        response = await api_client.get(f"{base}/stats", headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        data = response.json()["data"]
+        assert data["total_calls"] == sum(command["calls"] for command in data["commands"])
+        assert any(command["command"] == "object|idletime" for command in data["commands"])
+        assert all("db" in database and "keys" in database for database in data["keyspace"])
+    
+    @pytest.mark.protected
+    async def test_big_keys(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test finding the largest keys with a bounded scan."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        base = f"/api/organizations/{org_id}/redis-instances/{instance_id}"
+        key = generate_test_key("big")
+        
+        await api_client.post(
+            f"{base}/import",
+            content=json.dumps({"key": key, "type": "list", "value": ["x" * 1000] * 100}),
+            headers={**auth_user["auth_headers"], "Content-Type": "application/x-ndjson"}
+        )
+        
+        response = await api_client.get(f"{base}/big-keys", params={"top": 5, "type": "list"}, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        data = response.json()["data"]
+        assert len(data["keys"]) <= 5
+        big_key = next(entry for entry in data["keys"] if entry["key"] == key)
+        assert big_key["type"] == "list"
+        assert big_key["length"] == 100
+        assert big_key["memory_bytes"] >= 100000
+        sizes = [entry["memory_bytes"] for entry in data["keys"]]
+        assert sizes == sorted(sizes, reverse=True)
+        
+        response = await api_client.get(f"{base}/big-keys", params={"top": 0}, headers=auth_user["auth_headers"])
+        assert response.status_code == 400
+    
+    @pytest.mark.protected
+    async def test_instance_config_allowlist(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], test_redis_instance: Dict[str, Any], wait_for_server):
+        """Test that runtime config access is limited to allowlisted parameters."""
+        org_id = test_organization["id"]
+        instance_id = test_redis_instance["id"]
+        url = f"/api/organizations/{org_id}/redis-instances/{instance_id}/config"
+        
+        response = await api_client.get(url, headers=auth_user["auth_headers"])
+        assert response.status_code == 200
+        assert set(response.json()["data"]["parameters"]) == {"maxmemory", "maxmemory-policy", "timeout", "save"}
+        
+        response = await api_client.get(url, params={"parameters": "requirepass"}, headers=auth_user["auth_headers"])
+        assert response.status_code == 403
+        
+        response = await api_client.post(url, json={"parameters": {"save": ""}}, headers=auth_user["auth_headers"])
+        assert response.status_code == 403
+        
+        response = await api_client.post(url, json={"parameters": {"maxmemory": "1kb"}}, headers=auth_user["auth_headers"])
+        assert response.status_code == 400
+    
     @pytest.mark.protected
     async def test_delete_redis_instance(self, api_client: ApiClient, auth_user: Dict[str, Any], test_organization: Dict[str, Any], wait_for_server):
         """Test deleting a Redis instance."""
@@ -374,6 +966,43 @@ class TestRedisInstances:
             headers=auth_user["auth_headers"]
         )
         assert get_response.status_code == 404
+        
+        # It can still be found among the recently deleted instances
+        deleted_response = await api_client.get(
+            f"/api/organizations/{org_id}/redis-instances/deleted",
+            headers=auth_user["auth_headers"]
+        )
+        assert deleted_response.status_code == 200
+        deleted = next(item for item in deleted_response.json()["data"]["items"] if item["id"] == temp_instance["id"])
+        assert deleted["deleted_at"] < deleted["retained_until"]
+
+
+class TestAdmin:
+    """Test platform admin endpoints."""
+    
+    @pytest.mark.protected
+    async def test_drift_requires_platform_admin(self, api_client: ApiClient, auth_user: Dict[str, Any], wait_for_server):
+        """Test that regular users cannot read the drift report."""
+        response = await api_client.get("/api/admin/drift", headers=auth_user["auth_headers"])
+        
+        assert response.status_code == 403
+        assert response.json()["code"] == "FORBIDDEN"
+    
+    @pytest.mark.protected
+    async def test_pool_health_requires_platform_admin(self, api_client: ApiClient, auth_user: Dict[str, Any], wait_for_server):
+        """Test that regular users cannot read connection pool stats."""
+        response = await api_client.get("/api/admin/pools/health", headers=auth_user["auth_headers"])
+        
+        assert response.status_code == 403
+        assert response.json()["code"] == "FORBIDDEN"
+    
+    @pytest.mark.protected
+    async def test_all_instances_requires_platform_admin(self, api_client: ApiClient, auth_user: Dict[str, Any], wait_for_server):
+        """Test that organization owners cannot list every organization's instances."""
+        response = await api_client.get("/api/admin/redis-instances", params={"status": "running"}, headers=auth_user["auth_headers"])
+        
+        assert response.status_code == 403
+        assert response.json()["code"] == "FORBIDDEN"
 
 
 class TestUnauthorizedAccess:
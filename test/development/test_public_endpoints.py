@@ -3,8 +3,10 @@ Test public API endpoints that don't require authentication.
 
 This module tests:
 - Health check endpoint
+- Liveness and readiness probes
 - Version endpoint  
 - Database stats endpoint
+- Request ids on responses
 """
 
 import pytest
@@ -25,6 +27,26 @@ class TestPublicEndpoints:
         assert "timestamp" in data
         assert data["database"] == "healthy"
     
+    @pytest.mark.public
+    async def test_liveness(self, api_client: ApiClient, wait_for_server):
+        """Test the liveness probe endpoint."""
+        response = await api_client.get("/livez")
+        
+        assert response.status_code == 200
+        assert response.json()["status"] == "ok"
+    
+    @pytest.mark.public
+    async def test_readiness(self, api_client: ApiClient, wait_for_server):
+        """Test the readiness probe endpoint (503 when Kubernetes is unreachable)."""
+        response = await api_client.get("/healthz")
+        
+        assert response.status_code in (200, 503)
+        data = response.json()
+        assert data["database"] == "healthy"
+        assert data["migrations"]["status"] == "current"
+        assert data["kubernetes"]["status"] in ("healthy", "unhealthy")
+        assert "timestamp" in data
+    
     @pytest.mark.public
     async def test_version(self, api_client: ApiClient, wait_for_server):
         """Test the version endpoint."""
@@ -36,6 +58,8 @@ class TestPublicEndpoints:
         assert "name" in data
         assert "description" in data
         assert data["name"] == "redisgate"
+        assert "git_sha" in data
+        assert "build_timestamp" in data
     
     @pytest.mark.public
     async def test_database_stats(self, api_client: ApiClient, wait_for_server):
@@ -48,4 +72,18 @@ class TestPublicEndpoints:
         assert "timestamp" in data
         assert isinstance(data["tables"], dict)
         assert "users" in data["tables"]
-        assert "organizations" in data["tables"]
\ No newline at end of file
+        assert "organizations" in data["tables"]
+    
+    @pytest.mark.public
+    async def test_request_id(self, api_client: ApiClient, wait_for_server):
+        """Test that responses carry a request id, echoed from the client when given."""
+        response = await api_client.get("/livez")
+        assert response.headers.get("x-request-id")
+        
+        response = await api_client.get("/livez", headers={"X-Request-Id": "support-ticket-42"})
+        assert response.headers["x-request-id"] == "support-ticket-42"
+        
+        # Error bodies include the id so it can be quoted when reporting a problem
+        response = await api_client.get("/redis/00000000-0000-0000-0000-000000000000/ping", headers={"X-Request-Id": "req-404"})
+        assert response.status_code == 401
+        assert response.json()["request_id"] == "req-404"
@@ -209,6 +209,55 @@ class TestRedisHttpApi:
         lpop_data = lpop_response.json()
         assert lpop_data["result"] == value
     
+    @pytest.mark.redis
+    async def test_redis_json_body_routes(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test the POST variants of SET, HSET and LPUSH with values in a JSON body."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        key = generate_test_key("json_body")
+        value = "a/b\nc"
+        
+        response = await api_client.post(f"/redis/{instance_id}/set/{key}", json={"value": value, "ex": 60}, headers=headers)
+        assert response.status_code == 200
+        response = await api_client.get(f"/redis/{instance_id}/get/{key}", headers=headers)
+        assert response.json()["result"] == value
+        response = await api_client.post(f"/redis/{instance_id}", json=["TTL", key], headers=headers)
+        assert 0 < response.json()["result"] <= 60
+        
+        hash_key = generate_test_key("json_body_hash")
+        response = await api_client.post(f"/redis/{instance_id}/hset/{hash_key}/field", json={"value": value}, headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == 1
+        response = await api_client.get(f"/redis/{instance_id}/hget/{hash_key}/field", headers=headers)
+        assert response.json()["result"] == value
+        
+        list_key = generate_test_key("json_body_list")
+        response = await api_client.post(f"/redis/{instance_id}/lpush/{list_key}", json={"value": "aGVsbG8=", "encoding": "base64"}, headers=headers)
+        assert response.status_code == 200
+        response = await api_client.get(f"/redis/{instance_id}/lpop/{list_key}", headers=headers)
+        assert response.json()["result"] == "hello"
+        
+        response = await api_client.post(f"/redis/{instance_id}/set/{key}", json={"value": "not base64!", "encoding": "base64"}, headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_path_key_decoding(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test percent-encoded keys and key validation on the path-based routes."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        key = generate_test_key("path key/with slash")
+        encoded_key = key.replace("/", "%2F").replace(" ", "%20")
+        
+        response = await api_client.get(f"/redis/{instance_id}/set/{encoded_key}/value", headers=headers)
+        assert response.status_code == 200
+        response = await api_client.post(f"/redis/{instance_id}", json=["GET", key], headers=headers)
+        assert response.json()["result"] == "value"
+        
+        response = await api_client.get(f"/redis/{instance_id}/get/{'k' * 2000}", headers=headers)
+        assert response.status_code == 400
+        response = await api_client.get(f"/redis/{instance_id}/get/%FF", headers=headers)
+        assert response.status_code == 400
+    
     @pytest.mark.redis
     async def test_redis_generic_command(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
         """Test generic Redis command execution via POST."""
@@ -243,6 +292,302 @@ class TestRedisHttpApi:
         get_data = get_response.json()
         assert get_data["result"] == value
     
+    @pytest.mark.redis
+    async def test_redis_publish(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test PUBLISH and PUBSUB introspection via the generic endpoint."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        channel = generate_test_key("channel")
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["PUBLISH", channel, "hello"], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == 0
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["PUBSUB", "NUMSUB", channel], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == {channel: 0}
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["SUBSCRIBE", channel], headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_lpos_sintercard(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test LPOS, SINTERCARD and SMISMEMBER handling via the generic endpoint."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        list_key = generate_test_key("lpos")
+        set_a = generate_test_key("set_a")
+        set_b = generate_test_key("set_b")
+        
+        await api_client.post(f"/redis/{instance_id}", json=["RPUSH", list_key, "a", "b", "a"], headers=headers)
+        response = await api_client.post(f"/redis/{instance_id}", json=["LPOS", list_key, "a", "RANK", "2"], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == 2
+        response = await api_client.post(f"/redis/{instance_id}", json=["LPOS", list_key, "a", "COUNT", "0"], headers=headers)
+        assert response.json()["result"] == [0, 2]
+        response = await api_client.post(f"/redis/{instance_id}", json=["LPOS", list_key, "a", "RANK", "0"], headers=headers)
+        assert response.status_code == 400
+        
+        await api_client.post(f"/redis/{instance_id}", json=["SADD", set_a, "x", "y", "z"], headers=headers)
+        await api_client.post(f"/redis/{instance_id}", json=["SADD", set_b, "x", "y"], headers=headers)
+        response = await api_client.post(f"/redis/{instance_id}", json=["SINTERCARD", "2", set_a, set_b], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == 2
+        response = await api_client.post(f"/redis/{instance_id}", json=["SINTERCARD", "2", set_a, set_b, "LIMIT", "1"], headers=headers)
+        assert response.json()["result"] == 1
+        response = await api_client.post(f"/redis/{instance_id}", json=["SINTERCARD", "3", set_a, set_b], headers=headers)
+        assert response.status_code == 400
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["SMISMEMBER", set_b, "y", "z", "x"], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == [True, False, True]
+        response = await api_client.post(f"/redis/{instance_id}", json=["SMISMEMBER", set_b], headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_zadd_flags(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test ZADD conditional flags, CH and INCR via the generic endpoint."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        board = generate_test_key("board")
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["ZADD", board, "10", "alice", "5", "bob"], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == 2
+        
+        # Only bob's score rises, and CH counts the update
+        response = await api_client.post(f"/redis/{instance_id}", json=["ZADD", board, "GT", "CH", "8", "alice", "8", "bob"], headers=headers)
+        assert response.json()["result"] == 1
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["ZADD", board, "INCR", "2.5", "bob"], headers=headers)
+        assert response.json()["result"] == 10.5
+        response = await api_client.post(f"/redis/{instance_id}", json=["ZADD", board, "NX", "INCR", "1", "bob"], headers=headers)
+        assert response.json()["result"] is None
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["ZADD", board, "NX", "XX", "1", "carol"], headers=headers)
+        assert response.status_code == 400
+        response = await api_client.post(f"/redis/{instance_id}", json=["ZADD", board, "GT", "LT", "1", "carol"], headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_collection_scans(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test HSCAN/SSCAN/ZSCAN via the generic endpoint and the paginating GET routes."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        hash_key = generate_test_key("hscan")
+        zset_key = generate_test_key("zscan")
+        
+        await api_client.post(f"/redis/{instance_id}", json=["HSET", hash_key, "name", "john"], headers=headers)
+        await api_client.post(f"/redis/{instance_id}", json=["HSET", hash_key, "age", "30"], headers=headers)
+        response = await api_client.post(f"/redis/{instance_id}", json=["HSCAN", hash_key, "0", "MATCH", "na*"], headers=headers)
+        assert response.status_code == 200
+        result = response.json()["result"]
+        assert result["cursor"] == "0"
+        assert result["items"] == [{"field": "name", "value": "john"}]
+        
+        await api_client.post(f"/redis/{instance_id}", json=["ZADD", zset_key, "1.5", "alice"], headers=headers)
+        response = await api_client.get(f"/redis/{instance_id}/zscan/{zset_key}", params={"count": 10}, headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"]["items"] == [{"member": "alice", "score": 1.5}]
+        
+        # Scanning with the wrong command for the key's type is a client error
+        response = await api_client.get(f"/redis/{instance_id}/sscan/{hash_key}", headers=headers)
+        assert response.status_code == 400
+        response = await api_client.get(f"/redis/{instance_id}/hscan/{hash_key}", params={"cursor": "abc"}, headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_dump_restore(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test copying a key with DUMP and RESTORE via the generic endpoint."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        source = generate_test_key("dump_source")
+        target = generate_test_key("dump_target")
+        value = generate_test_value()
+        
+        await api_client.post(f"/redis/{instance_id}", json=["SET", source, value], headers=headers)
+        response = await api_client.post(f"/redis/{instance_id}", json=["DUMP", source], headers=headers)
+        assert response.status_code == 200
+        payload = response.json()["result"]
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["RESTORE", target, "0", payload], headers=headers)
+        assert response.status_code == 200
+        response = await api_client.post(f"/redis/{instance_id}", json=["GET", target], headers=headers)
+        assert response.json()["result"] == value
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["RESTORE", target, "0", payload], headers=headers)
+        assert response.status_code == 409
+        response = await api_client.post(f"/redis/{instance_id}", json=["RESTORE", target, "0", payload, "REPLACE"], headers=headers)
+        assert response.status_code == 200
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["DUMP", generate_test_key("missing")], headers=headers)
+        assert response.json()["result"] is None
+    
+    @pytest.mark.redis
+    async def test_redis_copy(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test COPY within a database and into another one via the generic endpoint."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        source = generate_test_key("copy_source")
+        target = generate_test_key("copy_target")
+        value = generate_test_value()
+        
+        await api_client.post(f"/redis/{instance_id}", json=["SET", source, value], headers=headers)
+        response = await api_client.post(f"/redis/{instance_id}", json=["COPY", source, target], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == 1
+        response = await api_client.post(f"/redis/{instance_id}", json=["COPY", source, target], headers=headers)
+        assert response.json()["result"] == 0
+        response = await api_client.post(f"/redis/{instance_id}", json=["COPY", source, target, "REPLACE"], headers=headers)
+        assert response.json()["result"] == 1
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["COPY", source, target, "DB", "2"], headers=headers)
+        assert response.json()["result"] == 1
+        response = await api_client.post(f"/redis/{instance_id}", params={"db": 2}, json=["GET", target], headers=headers)
+        assert response.json()["result"] == value
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["COPY", source, target, "DB", "99"], headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_sort(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test SORT and SORT_RO with BY, GET, LIMIT and ALPHA via the generic endpoint."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        key = generate_test_key("sort")
+        target = generate_test_key("sort_target")
+        
+        await api_client.post(f"/redis/{instance_id}", json=["RPUSH", key, "3", "1", "2"], headers=headers)
+        response = await api_client.post(f"/redis/{instance_id}", json=["SORT", key], headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == ["1", "2", "3"]
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["SORT_RO", key, "DESC", "LIMIT", "0", "2"], headers=headers)
+        assert response.json()["result"] == ["3", "2"]
+        
+        for item, weight in [("1", "30"), ("2", "10"), ("3", "20")]:
+            await api_client.post(f"/redis/{instance_id}", json=["SET", f"{key}_weight_{item}", weight], headers=headers)
+            await api_client.post(f"/redis/{instance_id}", json=["SET", f"{key}_name_{item}", f"item{item}"], headers=headers)
+        response = await api_client.post(
+            f"/redis/{instance_id}",
+            json=["SORT", key, "BY", f"{key}_weight_*", "GET", "#", "GET", f"{key}_name_*"],
+            headers=headers
+        )
+        assert response.json()["result"] == ["2", "item2", "3", "item3", "1", "item1"]
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["SORT", key, "ALPHA", "STORE", target], headers=headers)
+        assert response.json()["result"] == 3
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["SORT_RO", key, "STORE", target], headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_mset_ex(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test setting a batch of keys with expiries through one pipeline."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        first = generate_test_key("mset_ex_1")
+        second = generate_test_key("mset_ex_2")
+        
+        response = await api_client.post(
+            f"/redis/{instance_id}/mset-ex",
+            json=[
+                {"key": first, "value": "one", "ttl": 60},
+                {"key": second, "value": "two", "ttl": 0},
+            ],
+            headers=headers,
+        )
+        assert response.status_code == 200
+        result = response.json()["result"]
+        assert result[0] == {"key": first, "ok": True}
+        assert result[1]["ok"] is False
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["TTL", first], headers=headers)
+        assert 0 < response.json()["result"] <= 60
+        response = await api_client.post(f"/redis/{instance_id}", json=["GET", second], headers=headers)
+        assert response.json()["result"] is None
+        
+        response = await api_client.post(f"/redis/{instance_id}/mset-ex", json=[], headers=headers)
+        assert response.status_code == 400
+    
+    @pytest.mark.redis
+    async def test_redis_cas_numeric(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test setting a key only when the new number is greater or less than the stored one."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        key = generate_test_key("cas_numeric")
+        url = f"/redis/{instance_id}/cas-numeric"
+        
+        response = await api_client.post(url, json={"key": key, "op": "gt", "value": 10}, headers=headers)
+        assert response.status_code == 200
+        assert response.json()["result"] == {"updated": True, "value": 10}
+        
+        response = await api_client.post(url, json={"key": key, "op": "gt", "value": 5}, headers=headers)
+        assert response.json()["result"] == {"updated": False, "value": 10}
+        response = await api_client.post(url, json={"key": key, "op": "gt", "value": 12.5}, headers=headers)
+        assert response.json()["result"] == {"updated": True, "value": 12.5}
+        response = await api_client.post(url, json={"key": key, "op": "lt", "value": 3}, headers=headers)
+        assert response.json()["result"] == {"updated": True, "value": 3}
+        
+        response = await api_client.post(url, json={"key": key, "op": "eq", "value": 3}, headers=headers)
+        assert response.status_code in [400, 422]
+        
+        await api_client.post(f"/redis/{instance_id}", json=["SET", key, "not-a-number"], headers=headers)
+        response = await api_client.post(url, json={"key": key, "op": "gt", "value": 1}, headers=headers)
+        assert response.status_code == 409
+    
+    @pytest.mark.redis
+    async def test_redis_geo_commands(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
+        """Test GEOADD, GEOSEARCH, GEODIST and GEOPOS via the generic endpoint."""
+        instance_id = test_redis_instance["id"]
+        headers = {"Authorization": f"Bearer {test_api_key['key']}"}
+        key = generate_test_key("geo")
+        
+        response = await api_client.post(
+            f"/redis/{instance_id}",
+            json=["GEOADD", key, "13.361389", "38.115556", "Palermo", "15.087269", "37.502669", "Catania"],
+            headers=headers,
+        )
+        assert response.status_code == 200
+        assert response.json()["result"] == 2
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["GEODIST", key, "Palermo", "Catania", "km"], headers=headers)
+        assert 166 < response.json()["result"] < 167
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["GEOPOS", key, "Palermo", "Nowhere"], headers=headers)
+        positions = response.json()["result"]
+        assert abs(positions[0]["longitude"] - 13.361389) < 1e-4
+        assert positions[1] is None
+        
+        response = await api_client.post(
+            f"/redis/{instance_id}",
+            json=["GEOSEARCH", key, "FROMLONLAT", "15", "37", "BYRADIUS", "200", "km", "ASC", "WITHDIST", "WITHCOORD"],
+            headers=headers,
+        )
+        assert response.status_code == 200
+        matches = response.json()["result"]
+        assert [match["member"] for match in matches] == ["Catania", "Palermo"]
+        assert all({"distance", "longitude", "latitude"} <= match.keys() for match in matches)
+        
+        response = await api_client.post(f"/redis/{instance_id}", json=["GEOSEARCH", key, "BYRADIUS", "200", "km"], headers=headers)
+        assert response.status_code == 400
+        response = await api_client.post(f"/redis/{instance_id}", json=["GEOADD", key, "200", "38", "Invalid"], headers=headers)
+        assert response.status_code == 400
+    
+    async def test_introspect_api_key(self, api_client: ApiClient, test_api_key: Dict[str, Any], wait_for_server):
+        """Test checking an API key and reading back its claims."""
+        response = await api_client.get("/redis/auth/introspect", headers={"Authorization": f"Bearer {test_api_key['key']}"})
+        assert response.status_code == 200
+        claims = response.json()
+        assert claims["key_id"] == test_api_key["id"]
+        assert claims["organization_id"] == test_api_key["organization_id"]
+        assert sorted(claims["scopes"]) == sorted(test_api_key["scopes"])
+        assert claims["active"] is True
+        
+        response = await api_client.get("/redis/auth/introspect", headers={"Authorization": "Bearer invalid-token"})
+        assert response.status_code == 401
+        assert response.json()["reason"] == "invalid"
+    
     @pytest.mark.redis
     async def test_redis_api_key_query_param(self, api_client: ApiClient, test_redis_instance: Dict[str, Any], test_api_key: Dict[str, Any], wait_for_server):
         """Test Redis API with API key as query parameter."""
@@ -274,6 +619,18 @@ class TestRedisHttpApi:
         )
         assert response.status_code == 401
     
+    @pytest.mark.redis
+    async def test_redis_list_commands(self, api_client: ApiClient, wait_for_server):
+        """Test listing the commands the generic endpoint supports."""
+        response = await api_client.get("/redis/commands")
+        assert response.status_code == 200
+        
+        commands = {command["name"]: command for command in response.json()["result"]}
+        assert commands["GET"]["access"] == "read"
+        assert commands["GET"]["required_scope"] == "read"
+        assert commands["SET"]["access"] == "write"
+        assert commands["SET"]["required_scope"] == "write"
+    
     @pytest.mark.redis
     async def test_redis_nonexistent_instance(self, api_client: ApiClient, test_api_key: Dict[str, Any], wait_for_server):
         """Test Redis operations with non-existent instance ID."""